@@ -0,0 +1,84 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use papers_cli_lib::{cli::SortBy, table::Table};
+use papers_core::{
+    author::Author,
+    paper::PaperMeta,
+    primitive::Primitive,
+    repo::{RegexFilters, Repo},
+    tag::Tag,
+};
+use tempfile::{tempdir, TempDir};
+
+const PAPERS: usize = 10_000;
+
+fn make_repo() -> (TempDir, Repo) {
+    let dir = tempdir().unwrap();
+    let mut repo = Repo::load(dir.path()).unwrap();
+    for i in 0..PAPERS {
+        let mut labels = BTreeMap::new();
+        labels.insert(
+            "year".to_owned(),
+            Primitive::String((2000 + i % 24).to_string()),
+        );
+        repo.import(PaperMeta {
+            title: format!("Paper number {i} about topic {}", i % 100),
+            url: Some(format!("https://example.com/{i}.pdf")),
+            tags: BTreeSet::from([Tag::new(&format!("tag{}", i % 10))]),
+            labels,
+            authors: vec![
+                Author::new(&format!("Author {}", i % 50)),
+                Author::new(&format!("Author {}", i % 7)),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+    }
+    (dir, repo)
+}
+
+fn bench_list(c: &mut Criterion) {
+    let (_dir, mut repo) = make_repo();
+
+    c.bench_function("all_papers", |b| {
+        b.iter(|| black_box(repo.all_papers().unwrap()))
+    });
+
+    c.bench_function("list_filtered", |b| {
+        b.iter(|| {
+            black_box(
+                repo.list(
+                    None,
+                    Some("topic 4".to_owned()),
+                    vec![Author::new("Author 3")],
+                    vec![Tag::new("tag4")],
+                    Vec::new(),
+                    &RegexFilters::default(),
+                )
+                .unwrap(),
+            )
+        })
+    });
+
+    let papers = repo.all_papers().unwrap();
+    c.bench_function("sort_title", |b| {
+        b.iter(|| {
+            let mut papers = papers.clone();
+            SortBy::Title.sort(&mut papers, repo.root());
+            black_box(papers)
+        })
+    });
+
+    let metas = papers.into_iter().map(|p| p.meta).collect::<Vec<_>>();
+    c.bench_function("table_render", |b| {
+        b.iter(|| black_box(Table::from(metas.clone()).to_string()))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_list
+}
+criterion_main!(benches);
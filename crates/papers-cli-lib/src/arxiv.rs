@@ -0,0 +1,361 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use papers_core::{author::Author, paper::PaperMeta, tag::Tag};
+use reqwest::Url;
+use tracing::{debug, info};
+
+use crate::http::HttpClient;
+
+/// Label that can hold the arXiv id of a paper whose url is elsewhere.
+pub const ARXIV_LABEL: &str = "arxiv";
+
+/// Field of the frontmatter the abstract of a paper added from arXiv is kept in.
+pub const ABSTRACT_FIELD: &str = "abstract";
+
+/// Maximum number of ids to ask the arXiv api about in one request.
+const BATCH_SIZE: usize = 100;
+
+/// Identifier of a paper on arXiv, e.g. `2101.00001v2` or `cs/0112017`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ArxivId {
+    /// The id without a version.
+    pub id: String,
+    /// The version, if a specific one is referred to.
+    pub version: Option<u32>,
+}
+
+impl FromStr for ArxivId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .trim()
+            .trim_start_matches("arXiv:")
+            .trim_end_matches(".pdf");
+        let (id, version) = match s.rsplit_once('v') {
+            Some((id, version)) if version.parse::<u32>().is_ok() => (id, version.parse().ok()),
+            _ => (s, None),
+        };
+        let (prefix, number) = id.split_once('/').unwrap_or(("", id));
+        let valid_number = match number.split_once('.') {
+            // new style, e.g. 2101.00001
+            Some((month, seq)) => {
+                month.len() == 4
+                    && (4..=5).contains(&seq.len())
+                    && month.chars().chain(seq.chars()).all(|c| c.is_ascii_digit())
+            }
+            // old style, e.g. cs/0112017
+            None => {
+                !prefix.is_empty()
+                    && number.len() == 7
+                    && number.chars().all(|c| c.is_ascii_digit())
+            }
+        };
+        if !valid_number {
+            return Err(format!("Invalid arXiv id {s:?}"));
+        }
+        Ok(Self {
+            id: id.to_owned(),
+            version,
+        })
+    }
+}
+
+impl Display for ArxivId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)?;
+        if let Some(version) = self.version {
+            write!(f, "v{version}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ArxivId {
+    /// Find the id in an arXiv abstract or pdf url.
+    pub fn from_url(url: &Url) -> Option<Self> {
+        let host = url.host_str()?;
+        if !(host == "arxiv.org" || host.ends_with(".arxiv.org")) {
+            return None;
+        }
+        let path = url.path();
+        let id = path
+            .strip_prefix("/abs/")
+            .or_else(|| path.strip_prefix("/pdf/"))?
+            .trim_end_matches('/');
+        id.parse().ok()
+    }
+
+    /// Find the arXiv id of a paper from its url or `arxiv` label.
+    pub fn from_paper(meta: &PaperMeta) -> Option<Self> {
+        meta.url
+            .as_deref()
+            .and_then(|u| Url::parse(u).ok())
+            .and_then(|u| Self::from_url(&u))
+            .or_else(|| meta.labels.get(ARXIV_LABEL)?.to_string().parse().ok())
+    }
+
+    /// Url of the abstract page for this id.
+    pub fn abs_url(&self) -> Url {
+        Url::parse(&format!("https://arxiv.org/abs/{self}")).unwrap()
+    }
+
+    /// Url of the pdf for this id.
+    pub fn pdf_url(&self) -> Url {
+        Url::parse(&format!("https://arxiv.org/pdf/{self}")).unwrap()
+    }
+}
+
+/// Ask the arXiv api for the latest version of each of the given ids.
+///
+/// Ids that arXiv doesn't know about are left out.
+pub fn latest_versions(
+    client: &HttpClient,
+    ids: &[String],
+) -> anyhow::Result<BTreeMap<String, u32>> {
+    let mut versions = BTreeMap::new();
+    for batch in ids.chunks(BATCH_SIZE) {
+        let mut url = Url::parse("https://export.arxiv.org/api/query")?;
+        url.query_pairs_mut()
+            .append_pair("id_list", &batch.join(","))
+            .append_pair("max_results", &batch.len().to_string());
+        info!(count = batch.len(), "Checking arXiv for new versions");
+        // not cached, the point is to find changes
+        let feed = client.get(&url)?.text()?;
+        versions.extend(parse_feed(&feed));
+    }
+    debug!(?versions, "Got latest arXiv versions");
+    Ok(versions)
+}
+
+/// Metadata of a paper from the arXiv api.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ArxivMeta {
+    /// Title of the paper.
+    pub title: Option<String>,
+    /// Authors of the paper, in order.
+    pub authors: Vec<Author>,
+    /// The abstract of the paper.
+    pub summary: Option<String>,
+    /// Subject categories of the paper, e.g. `cs.DC`, primary first.
+    pub categories: Vec<Tag>,
+}
+
+/// Ask the arXiv api for the metadata of a paper.
+pub fn fetch_meta(client: &HttpClient, id: &ArxivId) -> anyhow::Result<ArxivMeta> {
+    let mut url = Url::parse("https://export.arxiv.org/api/query")?;
+    url.query_pairs_mut()
+        .append_pair("id_list", &id.to_string());
+    info!(%id, "Fetching arXiv metadata");
+    let feed = client.get_text(&url)?;
+    let meta = parse_meta(&feed).ok_or_else(|| anyhow::anyhow!("arXiv has no paper {id}"))?;
+    debug!(?meta, "Got arXiv metadata");
+    Ok(meta)
+}
+
+/// Text of the first `tag` element in `xml`, with whitespace collapsed and entities decoded.
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    let (_, rest) = xml.split_once(&format!("<{tag}>"))?;
+    let (text, _) = rest.split_once(&format!("</{tag}>"))?;
+    let text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    Some(text).filter(|t| !t.is_empty())
+}
+
+/// Pull the metadata of the first entry out of an arXiv api feed.
+fn parse_meta(feed: &str) -> Option<ArxivMeta> {
+    let (_, entry) = feed.split_once("<entry>")?;
+    let entry = entry.split_once("</entry>").map_or(entry, |(e, _)| e);
+    let authors = entry
+        .split("<author>")
+        .skip(1)
+        .filter_map(|a| element_text(a, "name"))
+        .map(|name| Author::new(&name))
+        .collect();
+    let mut categories = Vec::<Tag>::new();
+    for category in entry.split("<category ").skip(1) {
+        let Some(term) = category
+            .split_once("term=\"")
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(term, _)| Tag::new(term))
+        else {
+            continue;
+        };
+        if !categories.contains(&term) {
+            categories.push(term);
+        }
+    }
+    Some(ArxivMeta {
+        title: element_text(entry, "title"),
+        authors,
+        summary: element_text(entry, "summary"),
+        categories,
+    })
+}
+
+/// Pull the versioned ids out of the entries of an arXiv api feed.
+fn parse_feed(feed: &str) -> BTreeMap<String, u32> {
+    let mut versions = BTreeMap::new();
+    for entry in feed.split("<entry>").skip(1) {
+        let Some(id_url) = entry
+            .split_once("<id>")
+            .and_then(|(_, rest)| rest.split_once("</id>"))
+            .map(|(id, _)| id.trim())
+        else {
+            continue;
+        };
+        let Some(id) = Url::parse(id_url).ok().and_then(|u| ArxivId::from_url(&u)) else {
+            continue;
+        };
+        if let Some(version) = id.version {
+            versions.insert(id.id, version);
+        }
+    }
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check_url(url: &str, expected: Expect) {
+        let id = ArxivId::from_url(&Url::parse(url).unwrap());
+        expected.assert_debug_eq(&id);
+    }
+
+    #[test]
+    fn test_abs_url() {
+        check_url(
+            "https://arxiv.org/abs/2101.00001v2",
+            expect![[r#"
+                Some(
+                    ArxivId {
+                        id: "2101.00001",
+                        version: Some(
+                            2,
+                        ),
+                    },
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_pdf_url() {
+        check_url(
+            "https://export.arxiv.org/pdf/cs/0112017.pdf",
+            expect![[r#"
+                Some(
+                    ArxivId {
+                        id: "cs/0112017",
+                        version: None,
+                    },
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_other_url() {
+        check_url(
+            "https://example.com/abs/2101.00001",
+            expect![[r#"
+                None
+            "#]],
+        );
+        check_url(
+            "https://arxiv.org/abs/not-an-id",
+            expect![[r#"
+                None
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_parse_meta() {
+        let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>ArXiv Query: id_list=2101.00001</title>
+  <entry>
+    <id>http://arxiv.org/abs/2101.00001v1</id>
+    <title>Consensus &amp; Replication:
+      A Survey</title>
+    <summary>  We survey
+      consensus.
+    </summary>
+    <author>
+      <name>Diego Ongaro</name>
+    </author>
+    <author>
+      <name>John Ousterhout</name>
+      <arxiv:affiliation>Stanford</arxiv:affiliation>
+    </author>
+    <arxiv:primary_category term="cs.DC" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.DC" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.OS" scheme="http://arxiv.org/schemas/atom"/>
+  </entry>
+</feed>"#;
+        expect![[r#"
+            Some(
+                ArxivMeta {
+                    title: Some(
+                        "Consensus & Replication: A Survey",
+                    ),
+                    authors: [
+                        Author {
+                            author: "Diego Ongaro",
+                        },
+                        Author {
+                            author: "John Ousterhout",
+                        },
+                    ],
+                    summary: Some(
+                        "We survey consensus.",
+                    ),
+                    categories: [
+                        Tag {
+                            key: "cs.DC",
+                        },
+                        Tag {
+                            key: "cs.OS",
+                        },
+                    ],
+                },
+            )
+        "#]]
+        .assert_debug_eq(&parse_meta(feed));
+        assert_eq!(parse_meta("<feed></feed>"), None);
+    }
+
+    #[test]
+    fn test_parse_feed() {
+        let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>ArXiv Query</title>
+  <id>http://arxiv.org/api/abc</id>
+  <entry>
+    <id>http://arxiv.org/abs/2101.00001v3</id>
+    <title>A Paper</title>
+  </entry>
+  <entry>
+    <id>http://arxiv.org/abs/cs/0112017v1</id>
+  </entry>
+</feed>"#;
+        expect![[r#"
+            {
+                "2101.00001": 3,
+                "cs/0112017": 1,
+            }
+        "#]]
+        .assert_debug_eq(&parse_feed(feed));
+    }
+}
@@ -0,0 +1,255 @@
+use std::{fmt::Display, path::PathBuf};
+
+use clap::ValueEnum;
+use papers_core::{author::Author, paper::LoadedPaper};
+use serde::Serialize;
+
+use crate::{
+    output::Render,
+    table::{truncate, PaperStatus},
+};
+
+/// What to put papers into columns by on the board.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BoardBy {
+    /// How far through reading each paper is.
+    #[default]
+    Status,
+}
+
+/// How far through reading a paper is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingState {
+    /// Nothing written about it and never reviewed.
+    Unread,
+    /// Notes written but not reviewed yet.
+    Reading,
+    /// Reviewed at least once.
+    Read,
+}
+
+impl ReadingState {
+    /// Every state, in the order of the columns.
+    pub const ALL: [Self; 3] = [Self::Unread, Self::Reading, Self::Read];
+
+    /// The state of a paper with the given status.
+    pub fn of(status: &PaperStatus) -> Self {
+        if status.read {
+            Self::Read
+        } else if status.has_notes {
+            Self::Reading
+        } else {
+            Self::Unread
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Unread => "unread",
+            Self::Reading => "reading",
+            Self::Read => "read",
+        }
+    }
+}
+
+/// A paper as shown on the board.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Card {
+    /// Path of the paper, relative to the root.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+    /// Authors of the paper.
+    pub authors: Vec<Author>,
+}
+
+impl Card {
+    fn lines(&self, width: usize) -> String {
+        let mut card = truncate(&self.title, width);
+        if !self.authors.is_empty() {
+            let authors = self
+                .authors
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            card.push('\n');
+            card.push_str(&truncate(&authors, width));
+        }
+        card
+    }
+}
+
+/// Papers in columns by how far through reading them is.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Board {
+    /// Papers that haven't been started.
+    pub unread: Vec<Card>,
+    /// Papers being read.
+    pub reading: Vec<Card>,
+    /// Papers that have been read.
+    pub read: Vec<Card>,
+}
+
+impl Board {
+    /// Put the papers on the board, each paper with its status, keeping their order within a
+    /// column.
+    pub fn of<'a>(papers: impl IntoIterator<Item = (&'a LoadedPaper, PaperStatus)>) -> Self {
+        let mut board = Self::default();
+        for (paper, status) in papers {
+            let card = Card {
+                path: paper.path.clone(),
+                title: paper.meta.title.clone(),
+                authors: paper.meta.authors.clone(),
+            };
+            board.column_mut(ReadingState::of(&status)).push(card);
+        }
+        board
+    }
+
+    /// The cards in the column for `state`.
+    pub fn column(&self, state: ReadingState) -> &[Card] {
+        match state {
+            ReadingState::Unread => &self.unread,
+            ReadingState::Reading => &self.reading,
+            ReadingState::Read => &self.read,
+        }
+    }
+
+    fn column_mut(&mut self, state: ReadingState) -> &mut Vec<Card> {
+        match state {
+            ReadingState::Unread => &mut self.unread,
+            ReadingState::Reading => &mut self.reading,
+            ReadingState::Read => &mut self.read,
+        }
+    }
+
+    /// The board as it shows in a terminal `width` columns wide, if known, with cards cut down to
+    /// fit.
+    fn render(&self, width: Option<u16>) -> comfy_table::Table {
+        let mut tab = comfy_table::Table::new();
+        // lines between rows keep cards apart
+        tab.load_preset(comfy_table::presets::UTF8_FULL)
+            .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS)
+            .set_content_arrangement(comfy_table::ContentArrangement::Disabled);
+
+        let columns = ReadingState::ALL.len();
+        // a border and a space either side for each column, and the border at the end
+        let card_width = width.map_or(usize::MAX, |w| {
+            usize::from(w).saturating_sub(3 * columns + 1) / columns
+        });
+        tab.set_header(ReadingState::ALL.map(|s| {
+            truncate(
+                &format!("{} ({})", s.name(), self.column(s).len()),
+                card_width,
+            )
+        }));
+        let depth = ReadingState::ALL
+            .map(|s| self.column(s).len())
+            .into_iter()
+            .max()
+            .unwrap_or_default();
+        for i in 0..depth {
+            tab.add_row(ReadingState::ALL.map(|s| {
+                self.column(s)
+                    .get(i)
+                    .map(|c| c.lines(card_width))
+                    .unwrap_or_default()
+            }));
+        }
+        tab
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = comfy_table::Table::new().width();
+        write!(f, "{}", self.render(width))
+    }
+}
+
+impl Render for Board {
+    fn header(&self) -> Vec<String> {
+        ["column", "path", "title"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        ReadingState::ALL
+            .into_iter()
+            .flat_map(|s| {
+                self.column(s).iter().map(move |c| {
+                    vec![
+                        s.name().to_owned(),
+                        c.path.display().to_string(),
+                        c.title.clone(),
+                    ]
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use papers_core::paper::PaperMeta;
+
+    use super::*;
+
+    fn paper(title: &str, authors: &[&str]) -> LoadedPaper {
+        LoadedPaper {
+            meta: PaperMeta {
+                title: title.to_owned(),
+                authors: authors.iter().map(|a| Author::new(a)).collect(),
+                ..Default::default()
+            },
+            path: PathBuf::from(format!("{title}.md")),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_board() {
+        let papers = [
+            paper("raft", &["Ongaro", "Ousterhout"]),
+            paper("paxos", &["Lamport"]),
+            paper("zab", &[]),
+            paper("a very long title for a paper about chain replication", &[]),
+        ];
+        let statuses = [
+            PaperStatus {
+                read: true,
+                has_notes: true,
+                ..Default::default()
+            },
+            PaperStatus {
+                has_notes: true,
+                ..Default::default()
+            },
+            PaperStatus::default(),
+            PaperStatus::default(),
+        ];
+        let board = Board::of(papers.iter().zip(statuses));
+        expect![[r#"
+            ╭───────────────────────────────────────────────────────┬─────────────┬────────────────────╮
+            │ unread (2)                                            ┆ reading (1) ┆ read (1)           │
+            ╞═══════════════════════════════════════════════════════╪═════════════╪════════════════════╡
+            │ zab                                                   ┆ paxos       ┆ raft               │
+            │                                                       ┆ Lamport     ┆ Ongaro, Ousterhout │
+            ├╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┼╌╌╌╌╌╌╌╌╌╌╌╌╌┼╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┤
+            │ a very long title for a paper about chain replication ┆             ┆                    │
+            ╰───────────────────────────────────────────────────────┴─────────────┴────────────────────╯"#]]
+        .assert_eq(&board.render(None).to_string());
+        expect![[r#"
+            ╭──────────────────┬─────────────┬──────────────────╮
+            │ unread (2)       ┆ reading (1) ┆ read (1)         │
+            ╞══════════════════╪═════════════╪══════════════════╡
+            │ zab              ┆ paxos       ┆ raft             │
+            │                  ┆ Lamport     ┆ Ongaro, Ousterh… │
+            ├╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┼╌╌╌╌╌╌╌╌╌╌╌╌╌┼╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┤
+            │ a very long tit… ┆             ┆                  │
+            ╰──────────────────┴─────────────┴──────────────────╯"#]]
+        .assert_eq(&board.render(Some(60)).to_string());
+    }
+}
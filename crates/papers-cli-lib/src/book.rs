@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use papers_core::{author::Author, label::Label, metadata::Isbn, primitive::Primitive};
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use crate::{http::HttpClient, reports::ResolvedDoc};
+
+/// Where Open Library describes books by their identifiers.
+const OPEN_LIBRARY_BOOKS_URL: &str = "https://openlibrary.org/api/books";
+
+/// Label holding the ISBN of a book.
+pub const ISBN_LABEL: &str = "isbn";
+
+/// Section appended to the notes of books for notes on each chapter.
+pub const BOOK_NOTES_SECTION: &str = "\n## Chapters\n\n### Chapter 1\n\n- \n";
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+/// A book as described by Open Library, keeping only the fields used.
+#[derive(Debug, Deserialize)]
+struct OpenLibraryBook {
+    title: String,
+    #[serde(default)]
+    authors: Vec<Named>,
+    #[serde(default)]
+    publishers: Vec<Named>,
+    /// Free form date, e.g. `2017` or `March 16, 2017`.
+    #[serde(default)]
+    publish_date: Option<String>,
+    /// Page of the book on Open Library.
+    #[serde(default)]
+    url: Option<String>,
+}
+
+/// Year in a free form date, the last run of four digits.
+fn year(date: &str) -> Option<u32> {
+    date.split(|c: char| !c.is_ascii_digit())
+        .rfind(|part| part.len() == 4)?
+        .parse()
+        .ok()
+}
+
+/// Metadata of the book in an Open Library books api response, none if it wasn't found.
+fn parse_book(body: &str, isbn: &Isbn) -> anyhow::Result<Option<ResolvedDoc>> {
+    let mut books: BTreeMap<String, OpenLibraryBook> =
+        serde_json::from_str(body).context("Reading Open Library book")?;
+    let Some(book) = books.pop_first().map(|(_, book)| book) else {
+        return Ok(None);
+    };
+    let mut labels = vec![Label::new(ISBN_LABEL, Primitive::String(isbn.to_string()))];
+    if let Some(year) = book.publish_date.as_deref().and_then(year) {
+        labels.push(Label::new("year", Primitive::Number(year.into())));
+    }
+    if let Some(publisher) = book.publishers.first() {
+        labels.push(Label::new(
+            "publisher",
+            Primitive::String(publisher.name.trim().to_owned()),
+        ));
+    }
+    let url = match book.url {
+        Some(url) => Url::parse(&url)?,
+        None => Url::parse(&format!("https://openlibrary.org/isbn/{isbn}"))?,
+    };
+    Ok(Some(ResolvedDoc {
+        title: Some(book.title.trim().to_owned()).filter(|t| !t.is_empty()),
+        authors: book
+            .authors
+            .iter()
+            .map(|a| a.name.trim())
+            .filter(|a| !a.is_empty())
+            .map(Author::new)
+            .collect(),
+        url,
+        labels,
+    }))
+}
+
+/// Resolve a book by its ISBN, using its metadata from Open Library.
+pub fn resolve_isbn(client: &HttpClient, isbn: &Isbn) -> anyhow::Result<ResolvedDoc> {
+    let url = Url::parse_with_params(
+        OPEN_LIBRARY_BOOKS_URL,
+        [
+            ("bibkeys", format!("ISBN:{isbn}")),
+            ("format", "json".to_owned()),
+            ("jscmd", "data".to_owned()),
+        ],
+    )?;
+    info!(%url, "Fetching book metadata");
+    let book = parse_book(&client.get_text(&url)?, isbn)?
+        .with_context(|| format!("No book with ISBN {isbn} on Open Library"))?;
+    debug!(?book, "Got book metadata");
+    Ok(book)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_book() {
+        let isbn: Isbn = "9781449373320".parse().unwrap();
+        let body = r#"{"ISBN:9781449373320": {
+            "url": "https://openlibrary.org/books/OL26470290M/Designing_Data-Intensive_Applications",
+            "key": "/books/OL26470290M",
+            "title": "Designing Data-Intensive Applications",
+            "subtitle": "The Big Ideas Behind Reliable, Scalable, and Maintainable Systems",
+            "authors": [{"url": "https://openlibrary.org/authors/OL7487181A", "name": "Martin Kleppmann"}],
+            "number_of_pages": 590,
+            "publishers": [{"name": "O'Reilly Media"}],
+            "publish_date": "March 16, 2017"
+        }}"#;
+        expect![[r#"
+            Some(
+                ResolvedDoc {
+                    title: Some(
+                        "Designing Data-Intensive Applications",
+                    ),
+                    authors: [
+                        Author {
+                            author: "Martin Kleppmann",
+                        },
+                    ],
+                    url: Url {
+                        scheme: "https",
+                        cannot_be_a_base: false,
+                        username: "",
+                        password: None,
+                        host: Some(
+                            Domain(
+                                "openlibrary.org",
+                            ),
+                        ),
+                        port: None,
+                        path: "/books/OL26470290M/Designing_Data-Intensive_Applications",
+                        query: None,
+                        fragment: None,
+                    },
+                    labels: [
+                        Label {
+                            key: "isbn",
+                            value: String(
+                                "9781449373320",
+                            ),
+                        },
+                        Label {
+                            key: "year",
+                            value: Number(
+                                Number(2017),
+                            ),
+                        },
+                        Label {
+                            key: "publisher",
+                            value: String(
+                                "O'Reilly Media",
+                            ),
+                        },
+                    ],
+                },
+            )
+        "#]]
+        .assert_debug_eq(&parse_book(body, &isbn).unwrap());
+
+        assert_eq!(parse_book("{}", &isbn).unwrap(), None);
+    }
+}
@@ -0,0 +1,359 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Context;
+use papers_core::{author::Author, paper::PaperMeta, primitive::Primitive, tag::Tag};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{arxiv::ABSTRACT_FIELD, citations::DOI_LABEL, snapshot::decode_entities};
+
+/// Tag of the books in a Calibre library that are papers, unless another is given.
+pub const DEFAULT_TAG: &str = "papers";
+
+/// Database of a Calibre library, in the library directory.
+const METADATA_DB: &str = "metadata.db";
+
+/// Name Calibre gives the metadata of a book, read when adding a directory of books.
+pub const OPF_FILE: &str = "metadata.opf";
+
+/// Label holding the id of the book in Calibre that a paper was imported from.
+const CALIBRE_LABEL: &str = "calibre_id";
+
+/// Books with the tag, with everything about them gathered into json columns.
+const BOOKS_QUERY: &str = "
+SELECT b.id, b.title, b.path, b.timestamp,
+    (SELECT json_group_array(name) FROM (
+        SELECT a.name FROM books_authors_link l JOIN authors a ON a.id = l.author
+        WHERE l.book = b.id ORDER BY l.id)) AS authors,
+    (SELECT json_group_array(t.name) FROM books_tags_link l JOIN tags t ON t.id = l.tag
+        WHERE l.book = b.id) AS tags,
+    (SELECT json_group_object(i.type, i.val) FROM identifiers i WHERE i.book = b.id)
+        AS identifiers,
+    (SELECT json_group_array(json_object('format', d.format, 'name', d.name)) FROM data d
+        WHERE d.book = b.id) AS formats,
+    (SELECT c.text FROM comments c WHERE c.book = b.id) AS comments
+FROM books b
+WHERE b.id IN (
+    SELECT l.book FROM books_tags_link l JOIN tags t ON t.id = l.tag WHERE t.name = '{tag}')
+ORDER BY b.id";
+
+/// A row of [`BOOKS_QUERY`], as printed by `sqlite3 -json`.
+#[derive(Debug, Deserialize)]
+struct Row {
+    id: u64,
+    title: String,
+    path: String,
+    timestamp: Option<String>,
+    authors: String,
+    tags: String,
+    identifiers: String,
+    formats: String,
+    comments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Format {
+    format: String,
+    name: String,
+}
+
+/// A book in a Calibre library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Book {
+    /// Id of the book in the library.
+    pub id: u64,
+    /// Title of the book.
+    pub title: String,
+    /// Authors of the book, in order.
+    pub authors: Vec<String>,
+    /// Tags of the book.
+    pub tags: Vec<String>,
+    /// Identifiers of the book by their type, e.g. `doi` or `isbn`.
+    pub identifiers: BTreeMap<String, String>,
+    /// When the book was added to the library.
+    pub added: Option<chrono::NaiveDateTime>,
+    /// Description of the book, in html.
+    pub comments: Option<String>,
+    /// Files of the book relative to the library, in the order Calibre lists them.
+    pub files: Vec<PathBuf>,
+}
+
+/// Parse a column holding json.
+fn json_column<T: DeserializeOwned>(column: &str) -> anyhow::Result<T> {
+    serde_json::from_str(column).with_context(|| format!("Reading Calibre column {column:?}"))
+}
+
+/// Parse a time as Calibre stores them, e.g. `2024-05-01 10:00:00.123456+00:00`.
+fn parse_timestamp(timestamp: &str) -> Option<chrono::NaiveDateTime> {
+    let time = chrono::DateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.f%:z")
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(timestamp))
+        .ok()?;
+    // times of papers are kept to the second
+    chrono::NaiveDateTime::from_timestamp_opt(time.timestamp(), 0)
+}
+
+/// Books from the output of `sqlite3 -json`, which is empty when there are none.
+fn parse_books(json: &str) -> anyhow::Result<Vec<Book>> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows: Vec<Row> = serde_json::from_str(json).context("Reading Calibre books")?;
+    rows.into_iter()
+        .map(|row| {
+            let formats: Vec<Format> = json_column(&row.formats)?;
+            let files = formats
+                .iter()
+                .map(|f| {
+                    Path::new(&row.path).join(format!("{}.{}", f.name, f.format.to_lowercase()))
+                })
+                .collect();
+            let identifiers = if row.identifiers.is_empty() {
+                BTreeMap::new()
+            } else {
+                json_column(&row.identifiers)?
+            };
+            Ok(Book {
+                id: row.id,
+                title: row.title,
+                authors: json_column(&row.authors)?,
+                tags: json_column(&row.tags)?,
+                identifiers,
+                added: row.timestamp.as_deref().and_then(parse_timestamp),
+                comments: row.comments.filter(|c| !c.trim().is_empty()),
+                files,
+            })
+        })
+        .collect()
+}
+
+/// The books in the Calibre `library` with the `tag`.
+///
+/// The database is read with the `sqlite3` command, which must be installed.
+pub fn read_library(library: &Path, tag: &str) -> anyhow::Result<Vec<Book>> {
+    let db = library.join(METADATA_DB);
+    if !db.is_file() {
+        anyhow::bail!("No Calibre library at {:?}, missing {METADATA_DB}", library);
+    }
+    let query = BOOKS_QUERY.replace("{tag}", &tag.replace('\'', "''"));
+    let output = Command::new("sqlite3")
+        .args(["-readonly", "-json"])
+        .arg(&db)
+        .arg(query)
+        .output()
+        .context("Running sqlite3 to read the Calibre library, is it installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to read {:?} with sqlite3: {}",
+            db,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    parse_books(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Text of some html, with tags removed and whitespace collapsed.
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+impl Book {
+    /// The file to use as the paper's document, preferring a pdf.
+    pub fn document(&self) -> Option<&Path> {
+        self.files
+            .iter()
+            .find(|f| f.extension().is_some_and(|e| e == "pdf"))
+            .or(self.files.first())
+            .map(PathBuf::as_path)
+    }
+
+    /// Metadata for a paper of the book, leaving out the tag it was selected by.
+    pub fn meta(&self, tag: &str) -> PaperMeta {
+        let mut meta = PaperMeta {
+            title: self.title.clone(),
+            authors: self.authors.iter().map(|a| Author::new(a)).collect(),
+            tags: self
+                .tags
+                .iter()
+                .filter(|t| *t != tag)
+                // tags in Calibre can have spaces, which ours can't
+                .map(|t| Tag::new(&t.split_whitespace().collect::<Vec<_>>().join("-")))
+                .collect(),
+            created_at: self.added.unwrap_or_else(|| {
+                chrono::NaiveDateTime::from_timestamp_opt(chrono::Utc::now().timestamp(), 0)
+                    .unwrap_or_default()
+            }),
+            ..Default::default()
+        };
+        meta.modified_at = meta.created_at;
+        meta.labels.insert(
+            CALIBRE_LABEL.to_owned(),
+            Primitive::String(self.id.to_string()),
+        );
+        for (kind, value) in &self.identifiers {
+            match kind.as_str() {
+                "url" | "uri" => meta.url = Some(value.clone()),
+                "arxiv" if meta.url.is_none() => {
+                    meta.url = Some(format!("https://arxiv.org/abs/{value}"));
+                }
+                _ => {
+                    meta.labels
+                        .insert(kind.clone(), Primitive::String(value.clone()));
+                }
+            }
+        }
+        if let Some(comments) = &self.comments {
+            meta.extra.insert(
+                ABSTRACT_FIELD.to_owned(),
+                serde_yaml::Value::String(html_text(comments)),
+            );
+        }
+        meta
+    }
+}
+
+/// Escape text for xml.
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// OPF metadata of a paper, which Calibre reads from `metadata.opf` beside a book when adding it.
+pub fn opf(meta: &PaperMeta) -> String {
+    let mut lines = vec![format!("<dc:title>{}</dc:title>", escape_xml(&meta.title))];
+    lines.extend(meta.authors.iter().map(|a| {
+        format!(
+            "<dc:creator opf:role=\"aut\">{}</dc:creator>",
+            escape_xml(&a.to_string())
+        )
+    }));
+    lines.extend(
+        meta.tags
+            .iter()
+            .map(|t| format!("<dc:subject>{}</dc:subject>", escape_xml(t.key()))),
+    );
+    if let Some(doi) = meta.labels.get(DOI_LABEL) {
+        lines.push(format!(
+            "<dc:identifier opf:scheme=\"DOI\">{}</dc:identifier>",
+            escape_xml(&doi.to_string())
+        ));
+    }
+    if let Some(url) = &meta.url {
+        lines.push(format!(
+            "<dc:identifier opf:scheme=\"URI\">{}</dc:identifier>",
+            escape_xml(url)
+        ));
+    }
+    if let Some(serde_yaml::Value::String(summary)) = meta.extra.get(ABSTRACT_FIELD) {
+        lines.push(format!(
+            "<dc:description>{}</dc:description>",
+            escape_xml(summary)
+        ));
+    }
+    lines.push(format!(
+        "<meta name=\"calibre:timestamp\" content=\"{}\"/>",
+        meta.created_at.and_utc().to_rfc3339()
+    ));
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    {}
+  </metadata>
+</package>
+"#,
+        lines.join("\n    ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_books() {
+        let json = r#"[{"id":3,"title":"In Search of an Understandable Consensus Algorithm","path":"Diego Ongaro/In Search of an Understandable (3)","timestamp":"2024-05-01 10:00:00.123456+00:00",
+            "authors":"[\"Diego Ongaro\",\"John Ousterhout\"]","tags":"[\"papers\",\"distributed systems\"]",
+            "identifiers":"{\"doi\":\"10.5555/2643634.2643666\",\"arxiv\":\"2101.00001\"}",
+            "formats":"[{\"format\":\"EPUB\",\"name\":\"In Search - Diego Ongaro\"},{\"format\":\"PDF\",\"name\":\"In Search - Diego Ongaro\"}]",
+            "comments":"<div><p>Raft is a consensus algorithm &amp; more.</p></div>"}]"#;
+        let books = parse_books(json).unwrap();
+        assert_eq!(books.len(), 1);
+        let book = &books[0];
+        assert_eq!(
+            book.document(),
+            Some(Path::new(
+                "Diego Ongaro/In Search of an Understandable (3)/In Search - Diego Ongaro.pdf"
+            ))
+        );
+        let meta = book.meta(DEFAULT_TAG);
+        assert_eq!(
+            meta.created_at,
+            NaiveDate::from_ymd_opt(2024, 5, 1)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap()
+        );
+        expect![[r#"
+            title: In Search of an Understandable Consensus Algorithm
+            url: https://arxiv.org/abs/2101.00001
+            filename: null
+            tags:
+            - distributed-systems
+            labels:
+              calibre_id: '3'
+              doi: 10.5555/2643634.2643666
+            authors:
+            - Diego Ongaro
+            - John Ousterhout
+            created_at: 2024-05-01T10:00:00
+            modified_at: 2024-05-01T10:00:00
+            last_review: null
+            next_review: null
+            extra:
+              abstract: Raft is a consensus algorithm & more.
+        "#]]
+        .assert_eq(&serde_yaml::to_string(&meta).unwrap());
+
+        assert_eq!(parse_books("").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_opf() {
+        let json = r#"[{"id":3,"title":"Raft & Paxos","path":"p","timestamp":"2024-05-01 10:00:00+00:00","authors":"[\"Diego Ongaro\"]","tags":"[\"papers\",\"consensus\"]","identifiers":"{\"doi\":\"10.1/2\"}","formats":"[]","comments":null}]"#;
+        let meta = parse_books(json).unwrap()[0].meta(DEFAULT_TAG);
+        expect![[r#"
+            <?xml version="1.0" encoding="utf-8"?>
+            <package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+              <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+                <dc:title>Raft &amp; Paxos</dc:title>
+                <dc:creator opf:role="aut">Diego Ongaro</dc:creator>
+                <dc:subject>consensus</dc:subject>
+                <dc:identifier opf:scheme="DOI">10.1/2</dc:identifier>
+                <meta name="calibre:timestamp" content="2024-05-01T10:00:00+00:00"/>
+              </metadata>
+            </package>
+        "#]]
+        .assert_eq(&opf(&meta));
+    }
+}
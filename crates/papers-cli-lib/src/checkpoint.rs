@@ -0,0 +1,193 @@
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, remove_file, File},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::debug;
+
+use crate::warning;
+
+/// Where checkpoints of bulk operations are kept, relative to the root of the repo.
+pub const CHECKPOINTS_DIR: &str = ".papers/checkpoints";
+
+/// First line of a checkpoint, saying what the operation was run on.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    input: String,
+}
+
+/// Line of a checkpoint for an item that was finished.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry<T> {
+    item: String,
+    value: T,
+}
+
+/// Progress of a bulk operation, saved as each item is finished so that an interrupted run can
+/// be resumed without doing the finished items again.
+///
+/// The checkpoint is a json object per line, the first describing the input and the rest the
+/// finished items, so a line cut short by the interruption only loses that item.
+#[derive(Debug)]
+pub struct Checkpoint<T> {
+    path: PathBuf,
+    done: BTreeMap<String, T>,
+    file: Mutex<File>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> Checkpoint<T> {
+    /// Start checkpointing `operation` on `input` in the repo at `root`.
+    ///
+    /// With `resume` the items finished by an interrupted run on the same input are kept,
+    /// otherwise the operation starts from the beginning.
+    pub fn start(
+        root: &Path,
+        operation: &str,
+        input: String,
+        resume: bool,
+    ) -> anyhow::Result<Self> {
+        let path = root
+            .join(CHECKPOINTS_DIR)
+            .join(format!("{operation}.jsonl"));
+        let done = match (Self::read(&path)?, resume) {
+            (Some((header, done)), true) if header.input == input => done,
+            (Some(_), true) => {
+                warning!("The interrupted {operation} was of a different input, starting over");
+                BTreeMap::new()
+            }
+            (None, true) => {
+                warning!("No interrupted {operation} to resume, starting over");
+                BTreeMap::new()
+            }
+            (Some(_), false) => {
+                warning!("Starting over an interrupted {operation}, pass --resume to continue it");
+                BTreeMap::new()
+            }
+            (None, false) => BTreeMap::new(),
+        };
+        debug!(?path, resumed = done.len(), "Starting checkpoint");
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut file = File::create(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&Header { input })?)?;
+        for (item, value) in &done {
+            writeln!(
+                file,
+                "{}",
+                serde_json::to_string(&Entry {
+                    item: item.clone(),
+                    value
+                })?
+            )?;
+        }
+        Ok(Self {
+            path,
+            done,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn read(path: &Path) -> anyhow::Result<Option<(Header, BTreeMap<String, T>)>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut lines = BufReader::new(file).lines();
+        let Some(header) = lines.next().transpose()? else {
+            return Ok(None);
+        };
+        let Ok(header) = serde_json::from_str(&header) else {
+            return Ok(None);
+        };
+        let mut done = BTreeMap::new();
+        for line in lines {
+            match serde_json::from_str::<Entry<T>>(&line?) {
+                Ok(entry) => {
+                    done.insert(entry.item, entry.value);
+                }
+                Err(err) => debug!(%err, "Ignoring unreadable checkpoint entry"),
+            }
+        }
+        Ok(Some((header, done)))
+    }
+
+    /// What was recorded for `item` by the run being resumed, if it finished it.
+    pub fn get(&self, item: &str) -> Option<T> {
+        self.done.get(item).cloned()
+    }
+
+    /// Record that `item` was finished.
+    ///
+    /// Failing to save only loses the chance to skip the item when resuming, so it isn't an
+    /// error.
+    pub fn record(&self, item: String, value: T) {
+        let line = match serde_json::to_string(&Entry { item, value }) {
+            Ok(line) => line,
+            Err(err) => {
+                warning!("Failed to save checkpoint: {err}");
+                return;
+            }
+        };
+        let mut file = self.file.lock().unwrap();
+        if let Err(err) = writeln!(file, "{line}").and_then(|()| file.sync_data()) {
+            warning!("Failed to save checkpoint: {err}");
+        }
+    }
+
+    /// The operation finished, so there is nothing left to resume.
+    pub fn finish(self) -> anyhow::Result<()> {
+        drop(self.file);
+        remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+
+    use super::*;
+
+    #[test]
+    fn test_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = Checkpoint::start(dir.path(), "import", "a".to_owned(), false).unwrap();
+        checkpoint.record("0".to_owned(), 1);
+        checkpoint.record("1".to_owned(), 2);
+        drop(checkpoint);
+
+        // an interruption part way through writing a line only loses that item
+        let path = dir.path().join(CHECKPOINTS_DIR).join("import.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"item\":\"2\",\"val").unwrap();
+        drop(file);
+
+        let checkpoint =
+            Checkpoint::<u32>::start(dir.path(), "import", "a".to_owned(), true).unwrap();
+        assert_eq!(checkpoint.get("0"), Some(1));
+        assert_eq!(checkpoint.get("1"), Some(2));
+        assert_eq!(checkpoint.get("2"), None);
+        checkpoint.record("2".to_owned(), 3);
+        drop(checkpoint);
+
+        // resuming again keeps the items finished by every run so far
+        let checkpoint =
+            Checkpoint::<u32>::start(dir.path(), "import", "a".to_owned(), true).unwrap();
+        assert_eq!(checkpoint.get("2"), Some(3));
+        drop(checkpoint);
+
+        // a different input, or not resuming, starts over
+        let checkpoint =
+            Checkpoint::<u32>::start(dir.path(), "import", "b".to_owned(), true).unwrap();
+        assert_eq!(checkpoint.get("0"), None);
+        checkpoint.finish().unwrap();
+        assert!(!path.exists());
+    }
+}
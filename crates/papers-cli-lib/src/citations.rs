@@ -0,0 +1,143 @@
+use papers_core::{paper::PaperMeta, primitive::Primitive};
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{arxiv::ArxivId, http::HttpClient};
+
+/// Label holding the citation count from the last refresh.
+pub const CITATIONS_LABEL: &str = "citations";
+
+/// Label holding the citation count from the refresh before that, for finding trending papers.
+pub const PREVIOUS_CITATIONS_LABEL: &str = "citations_previous";
+
+/// Label that can hold the DOI of a paper.
+pub const DOI_LABEL: &str = "doi";
+
+fn count_label(meta: &PaperMeta, key: &str) -> Option<u64> {
+    meta.labels.get(key)?.to_string().parse().ok()
+}
+
+/// Citation count from the last refresh.
+pub fn citations(meta: &PaperMeta) -> Option<u64> {
+    count_label(meta, CITATIONS_LABEL)
+}
+
+/// How much the citation count grew in the last refresh.
+pub fn citation_increase(meta: &PaperMeta) -> Option<i64> {
+    let current = citations(meta)?;
+    let previous = count_label(meta, PREVIOUS_CITATIONS_LABEL)?;
+    Some(current as i64 - previous as i64)
+}
+
+/// Store a new citation count, keeping the old one to compare against.
+pub fn set_citations(meta: &mut PaperMeta, count: u64) {
+    if let Some(current) = meta.labels.remove(CITATIONS_LABEL) {
+        meta.labels
+            .insert(PREVIOUS_CITATIONS_LABEL.to_owned(), current);
+    }
+    meta.labels
+        .insert(CITATIONS_LABEL.to_owned(), Primitive::Number(count.into()));
+}
+
+/// Identifier of the paper for the Semantic Scholar api.
+pub fn semantic_scholar_id(meta: &PaperMeta) -> Option<String> {
+    if let Some(id) = ArxivId::from_paper(meta) {
+        return Some(format!("ARXIV:{}", id.id));
+    }
+    if let Some(doi) = meta.labels.get(DOI_LABEL) {
+        return Some(format!("DOI:{doi}"));
+    }
+    let url = Url::parse(meta.url.as_deref()?).ok()?;
+    match url.host_str()? {
+        "doi.org" | "dx.doi.org" => Some(format!("DOI:{}", url.path().trim_start_matches('/'))),
+        _ => Some(format!("URL:{url}")),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PaperResponse {
+    citation_count: u64,
+}
+
+/// Get the current citation count of a paper from Semantic Scholar.
+pub fn fetch_citations(client: &HttpClient, id: &str) -> anyhow::Result<u64> {
+    let mut url = Url::parse(&format!(
+        "https://api.semanticscholar.org/graph/v1/paper/{id}"
+    ))?;
+    url.query_pairs_mut().append_pair("fields", "citationCount");
+    // not cached, counts change over time
+    let res: PaperResponse = serde_json::from_str(&client.get(&url)?.text()?)?;
+    debug!(id, count = res.citation_count, "Got citation count");
+    Ok(res.citation_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check_id(url: Option<&str>, labels: &[(&str, &str)], expected: Expect) {
+        let meta = PaperMeta {
+            url: url.map(str::to_owned),
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), Primitive::String(v.to_string())))
+                .collect::<BTreeMap<_, _>>(),
+            ..Default::default()
+        };
+        expected.assert_debug_eq(&semantic_scholar_id(&meta));
+    }
+
+    #[test]
+    fn test_ids() {
+        check_id(
+            Some("https://arxiv.org/abs/2101.00001v2"),
+            &[],
+            expect![[r#"
+                Some(
+                    "ARXIV:2101.00001",
+                )
+            "#]],
+        );
+        check_id(
+            Some("https://doi.org/10.1145/3132747.3132790"),
+            &[],
+            expect![[r#"
+                Some(
+                    "DOI:10.1145/3132747.3132790",
+                )
+            "#]],
+        );
+        check_id(
+            Some("https://example.com/paper.pdf"),
+            &[("doi", "10.1/abc")],
+            expect![[r#"
+                Some(
+                    "DOI:10.1/abc",
+                )
+            "#]],
+        );
+        check_id(
+            None,
+            &[],
+            expect![[r#"
+                None
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_increase() {
+        let mut meta = PaperMeta::default();
+        set_citations(&mut meta, 10);
+        assert_eq!(citations(&meta), Some(10));
+        assert_eq!(citation_increase(&meta), None);
+        set_citations(&mut meta, 25);
+        assert_eq!(citation_increase(&meta), Some(15));
+    }
+}
@@ -1,34 +1,122 @@
 use std::{
+    cmp::Reverse,
     collections::{BTreeMap, BTreeSet},
-    fs::{read_dir, rename, File},
-    io::{stdin, stdout},
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, File},
+    io::{stdin, stdout, Read, Write},
     path::{Path, PathBuf},
-    process::Command,
+    time::Instant,
 };
 
 use anyhow::Context;
+use chrono::{Days, NaiveDate, NaiveTime};
 use clap::{CommandFactory, ValueEnum};
 use clap_complete::{generate_to, Generator, Shell};
-use papers_core::{author::Author, paper::LoadedPaper, paper::PaperMeta, repo::Repo, tag::Tag};
+use papers_core::{
+    audit,
+    author::Author,
+    cancel::CancellationToken,
+    dir_defaults::DirDefaults,
+    doc_type::DocType,
+    filename::{collision_key, unique_path},
+    frontmatter::render,
+    hashes::{hash_bytes, hash_file},
+    journal::history,
+    lock::PaperLock,
+    metadata::{Doi, Isbn},
+    paper::LoadedPaper,
+    paper::{PaperMeta, Provenance, Source},
+    repo::{RegexFilters, Repo},
+    review::Rating,
+    tag::Tag,
+    tag_registry::TagRegistry,
+};
 use pdf::file::FileOptions;
+use regex::Regex;
 use reqwest::Url;
 use tracing::{debug, info, warn};
 
-use papers_core::label::Label;
+use papers_core::{label::Label, primitive::Primitive};
 
 use crate::{
-    config::Config,
-    fuzzy::select_paper,
-    interactive::{input, input_bool, input_default, input_opt, input_vec, input_vec_default},
-    table::{Table, TableCount},
+    arxiv::{
+        fetch_meta as fetch_arxiv_meta, latest_versions, ArxivId, ABSTRACT_FIELD, ARXIV_LABEL,
+    },
+    board::{Board, BoardBy},
+    book::{resolve_isbn, BOOK_NOTES_SECTION},
+    calibre,
+    checkpoint::Checkpoint,
+    citations::{
+        citation_increase, citations, fetch_citations, semantic_scholar_id, set_citations,
+        CITATIONS_LABEL,
+    },
+    completions::{detect_shell, install_completions, write_completions},
+    config::{Config, ExistingFile, PathOrString},
+    dblp::author_publications,
+    deadlines::{days_left, today, ReadBy},
+    dedupe::{first_added, merge_duplicates},
+    doctor::{find_relink, Candidate, Fixer},
+    du::UsageBy,
+    duration::HumanDuration,
+    edit::edit_notes,
+    export::{
+        attachments, read_bundle, BundleDifferences, BundlePaper, ExportFormat, ImportFormat,
+    },
+    fetch_missing::{is_missing, map_concurrently, FetchedFile, DEFAULT_JOBS, TO_FETCH_TAG},
+    fuzzy::{fuzzy_filter, resolve_paper, select_paper, select_repo_paper},
+    help_topics::render_help,
+    highlights::{highlights, merge_highlights, paper_uris, SyncedPaper, SyncedPapers},
+    http::{HttpClient, MIRROR_LABEL},
+    init::{copy_template, fetch_template, TEMPLATE_CONFIG},
+    integrity::check_file,
+    interactive::{
+        input, input_bool, input_default, input_key, input_opt, input_selection, input_vec,
+        input_vec_default, Cancelled,
+    },
+    man::gen_man_pages,
+    monthly::{Month, MonthlyReport},
+    notes::NotesTemplate,
+    optimize::{optimize_pdf, DEFAULT_OPTIMIZE_COMMAND},
+    output::{
+        Added, Deadline, Deadlines, DocumentThumbnail, DocumentThumbnails, FetchedDocument,
+        FetchedDocuments, FieldChange, FieldChanges, FileUsage, FileUsages, History, Imported,
+        ImportedPaper, Optimized, OptimizedFile, OutputStyle, Problem, ProblemKind, Problems,
+        Rename, Renames, RestoredPaper, RestoredPapers, SnapshotSummary, Snapshots, TagDescription,
+        TagDescriptions, TrashedPapers, Trending, TrendingPapers, Update, Updates, UsageGroup,
+        UsageGroups,
+    },
+    paper_format::PaperFormat,
+    progress::{Progress, ProgressStyle},
+    publish::{SiteGenerator, PUBLIC_TAG},
+    publishers::resolve_download_url,
+    readwise::{is_pulled, Book},
+    recent::Recent,
+    remap::LabelMap,
+    reports::{resolve_doi, resolve_rfc, resolve_tech_report, TechReport},
+    review_stats::{ReviewQueue, ReviewStats},
+    schema::SchemaKind,
+    scholar,
+    sed::{SedField, Substitution},
+    share::render_shared,
+    since::{Since, TimeFilters},
+    snapshot::{fetch_page_meta, snapshot_url},
+    status::Status,
+    table::{PaperStatus, Table, TableCount},
+    talk::{fetch_talk_meta, oembed_endpoint, TALK_NOTES_SECTION},
+    thumbnails::Thumbnails,
+    title::extract_title,
+    tree::{Tree, TreeBy},
+    triage::{clear_inbox, TriageAction, TriageCard, INBOX_TAG},
+    url_path::UrlOrPath,
+    urls::{normalise_url, upgrade_https},
+    vocab::Vocabulary,
+    watch::{pdfs, Inbox},
 };
-use crate::{error, rename_files};
+use crate::{error, rename_files, warning};
 use crate::{file_or_stdin::FileOrStdin, ids::Ids};
 
-static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
-
 /// A paper management program.
 #[derive(Debug, clap::Parser)]
+#[clap(disable_help_subcommand = true)]
 pub struct Cli {
     /// Config file path to load.
     #[clap(long, short, global = true)]
@@ -38,6 +126,18 @@ pub struct Cli {
     #[clap(long, global = true)]
     pub default_repo: Option<PathBuf>,
 
+    /// Don't access the network, skipping anything that needs it.
+    #[clap(long, global = true)]
+    pub offline: bool,
+
+    /// Style to print the results of commands in.
+    #[clap(long, short, global = true, value_enum, default_value_t)]
+    pub output: OutputStyle,
+
+    /// Style to report the progress of long operations in.
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub progress: ProgressStyle,
+
     /// Commands.
     #[clap(subcommand)]
     pub cmd: SubCommand,
@@ -46,11 +146,21 @@ pub struct Cli {
 /// Subcommands for the cli.
 #[derive(Debug, clap::Subcommand)]
 pub enum SubCommand {
+    /// Create a repo in the default repo directory.
+    ///
+    /// A template, a directory or a git repository, can seed the repo with shared notes templates,
+    /// example papers and a `.papersignore`. A `config.yaml` in the template is used for the
+    /// config file if there isn't one yet.
+    Init {
+        /// Directory or git url of a template to copy into the new repo.
+        #[clap(long)]
+        template: Option<UrlOrPath>,
+    },
     /// Add a paper to the repo.
     Add {
-        /// Url to fetch from.
+        /// Url to fetch from. A `file://` url or local path is copied into the repo instead.
         #[clap(long, short)]
-        url: Option<Url>,
+        url: Option<UrlOrPath>,
 
         /// Whether to fetch the document from URL or not.
         #[clap(long)]
@@ -75,6 +185,51 @@ pub enum SubCommand {
         /// Labels to associate with these files. Labels take the form `key=value`.
         #[clap(name = "label", long, short)]
         labels: Vec<Label>,
+
+        /// Save a snapshot of the web page at the url as the document, rather than fetching it
+        /// directly.
+        #[clap(long, requires = "url")]
+        snapshot: bool,
+
+        /// Type of document, detected from the url if not given.
+        #[clap(long)]
+        doc_type: Option<DocType>,
+
+        /// Number of an IETF RFC to add, filling in its metadata and url.
+        #[clap(long, conflicts_with_all = ["url", "tech_report"])]
+        rfc: Option<u32>,
+
+        /// Id of a technical report to add, e.g. UCAM-CL-TR-123 or EECS-2009-28, filling in its
+        /// metadata and url.
+        #[clap(long, conflicts_with = "url")]
+        tech_report: Option<TechReport>,
+
+        /// DOI of a paper to add, e.g. 10.1145/3132747.3132784, filling in its title, authors,
+        /// year and journal from Crossref.
+        #[clap(long, conflicts_with_all = ["url", "rfc", "tech_report"])]
+        doi: Option<Doi>,
+
+        /// ISBN of a book to add, e.g. 978-1-4493-7332-0, filling in its title, authors, year and
+        /// publisher from Open Library. The document type defaults to book.
+        #[clap(long, conflicts_with_all = ["url", "rfc", "tech_report", "doi"])]
+        isbn: Option<Isbn>,
+
+        /// Date to read the paper by, e.g. 2023-08-01 or 2w from today.
+        #[clap(long)]
+        read_by: Option<ReadBy>,
+
+        /// Copy a file from outside the repo into it, rather than asking.
+        #[clap(long, conflicts_with = "move_file")]
+        copy: bool,
+
+        /// Move a file from outside the repo into it, rather than asking.
+        #[clap(long = "move")]
+        move_file: bool,
+
+        /// Replace an existing file when fetching the document, rather than asking or saving it
+        /// under a new name.
+        #[clap(long)]
+        force: bool,
     },
     /// List the papers stored with this repo.
     List {
@@ -98,13 +253,52 @@ pub enum SubCommand {
         #[clap(name = "label", long, short)]
         labels: Vec<Label>,
 
-        /// Output the filtered selection of papers in different formats.
-        #[clap(long, short, value_enum, default_value_t)]
-        output: OutputStyle,
+        /// Filter down to papers that entered the repo this way: add or import.
+        #[clap(long)]
+        source: Option<Source>,
 
-        /// Sort entries by a criterion.
-        #[clap(long, value_enum, default_value_t)]
-        sort: SortBy,
+        /// Filters on when papers were added, changed and last reviewed.
+        #[clap(flatten)]
+        time: TimeFilters,
+
+        /// Filter down to papers whose titles match this regular expression.
+        #[clap(long)]
+        title_re: Option<Regex>,
+
+        /// Filter down to papers whose document filenames match this regular expression.
+        #[clap(long)]
+        file_re: Option<Regex>,
+
+        /// Filter down to papers with an author whose name matches this regular expression.
+        #[clap(long)]
+        author_re: Option<Regex>,
+
+        /// Filter down to papers with notes written beyond the notes template.
+        #[clap(long, conflicts_with = "no_notes")]
+        has_notes: bool,
+
+        /// Filter down to papers with nothing written in their notes beyond the notes template.
+        #[clap(long)]
+        no_notes: bool,
+
+        /// Filter down to papers that fuzzy match this across their titles, authors, tags and
+        /// labels, as in the fuzzy picker, best matches first.
+        #[clap(long, short)]
+        query: Option<String>,
+
+        /// Sort entries by a criterion, by title if not given unless ranking matches of
+        /// `--query`.
+        #[clap(long, value_enum)]
+        sort: Option<SortBy>,
+
+        /// Print each paper on a line using a template of fields, e.g. '{title}\t{url}'. See
+        /// `papers help query-syntax` for the available fields.
+        #[clap(long, conflicts_with = "output")]
+        format: Option<PaperFormat>,
+
+        /// Exit with status 1 if no papers match.
+        #[clap(long)]
+        fail_if_empty: bool,
     },
     /// Automatically rename files to match their entry in the database.
     RenameFiles {
@@ -118,7 +312,8 @@ pub enum SubCommand {
     },
     /// Edit the notes file for a paper.
     Edit {
-        /// Path of the paper to edit, fuzzy selected if not given.
+        /// Path of the paper to edit, part of its title, a glob, or `@last-added` or
+        /// `@last-edited`. Fuzzy selected if not given.
         #[clap()]
         path: Option<PathBuf>,
 
@@ -128,116 +323,783 @@ pub enum SubCommand {
     },
     /// Open the pdf file for the given paper.
     Open {
-        /// Path of the paper to open, fuzzy selected if not given.
+        /// Path of the paper to open, part of its title, a glob, or `@last-added` or
+        /// `@last-edited`. Fuzzy selected if not given.
         #[clap()]
         path: Option<PathBuf>,
     },
     /// Review papers that have been unseen too long.
+    #[clap(args_conflicts_with_subcommands = true)]
     Review {
-        /// Path of the paper to review, fuzzy selected if not given.
+        /// Review command to run, reviewing papers if not given.
+        #[clap(subcommand)]
+        command: Option<ReviewCommands>,
+
+        /// Path of the paper to review, part of its title, a glob, or `@last-added` or
+        /// `@last-edited`. Fuzzy selected if not given.
         #[clap()]
         path: Option<PathBuf>,
 
         /// Open the pdf file too.
         #[clap(long)]
         open: bool,
+
+        /// Exit with status 1 if no papers are due for review.
+        #[clap(long)]
+        fail_if_empty: bool,
+
+        /// Stop the session after reviewing this many papers.
+        #[clap(long, conflicts_with = "path")]
+        limit: Option<usize>,
+
+        /// Stop the session once this long has passed, e.g. 30m or 1h, finishing the paper being
+        /// reviewed.
+        #[clap(long, conflicts_with = "path")]
+        time_box: Option<HumanDuration>,
+
+        /// How well the paper was remembered: again, hard, good or easy. Asked for after editing
+        /// the notes if not given.
+        #[clap(long)]
+        rating: Option<Rating>,
+
+        /// Filters on when papers were added, changed and last reviewed.
+        #[clap(flatten)]
+        time: TimeFilters,
+    },
+    /// List the papers in the inbox, those imported but not yet triaged or reviewed.
+    Inbox {
+        /// Triage the papers in the inbox rather than listing them.
+        #[clap(long)]
+        triage: bool,
+    },
+    /// Count the papers due for review, in the inbox, queued to read and due to be read soon.
+    Status {
+        /// Print the counts on one line for a shell prompt or status bar, e.g. `r3 i2 q5 d1`,
+        /// leaving out counts of zero.
+        #[clap(long)]
+        short: bool,
+    },
+    /// Go through papers one at a time, tagging, queueing or archiving each with a single key.
+    ///
+    /// Queueing tags the paper `to-read` and archiving tags it `archived`.
+    Triage {
+        /// Only triage papers whose titles match this (case-insensitive).
+        #[clap(long)]
+        title: Option<String>,
+
+        /// Only triage papers that have all of the given authors.
+        #[clap(name = "author", long, short)]
+        authors: Vec<Author>,
+
+        /// Only triage papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+
+        /// Only triage papers that have all of the given labels. Labels take the form
+        /// `key=value`.
+        #[clap(name = "label", long, short)]
+        labels: Vec<Label>,
     },
     /// Generate cli completion files.
     Completions {
-        /// Shell to generate for.
+        /// Shell to generate for, detected from the environment if not given.
         #[clap()]
-        shell: Shell,
+        shell: Option<Shell>,
         /// Directory to save completion files to.
         #[clap(default_value = ".")]
         dir: PathBuf,
+        /// Install the completions into the standard location for the shell.
+        #[clap(long, conflicts_with_all = ["stdout", "dir"])]
+        install: bool,
+        /// Write the completions to stdout, e.g. for packaging.
+        #[clap(long, conflicts_with = "dir")]
+        stdout: bool,
+    },
+    /// Generate man pages for papers and each of its subcommands.
+    Man {
+        /// Directory to save man pages to.
+        #[clap(default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Print help for a subcommand or a topic, see `papers help topics`.
+    Help {
+        /// Subcommand or topic to show help for.
+        #[clap()]
+        name: Option<String>,
     },
-    /// Import a list of tasks in json format.
+    /// Import a list of tasks in json format, or yaml for files ending in `.yaml` or `.yml`.
+    ///
+    /// The format can be exported from a `list` command using the `-o json` argument, or with
+    /// notes from `export-json --full`. Imported papers are tagged `inbox` until they are triaged
+    /// or reviewed.
     ///
-    /// The format can be exported from a `list` command using the `-o json` argument.
+    /// With `--format calibre` the books of a Calibre library tagged `papers` are imported
+    /// instead, copying in their documents. The library is read with the `sqlite3` command.
     Import {
-        /// File to import from, or '-' for stdin.
+        /// File to import from, or '-' for stdin. The library directory for Calibre.
+        #[clap(value_parser = FileOrStdin::parse_allowing_dirs)]
+        file: FileOrStdin,
+
+        /// Format to import from.
+        #[clap(long, value_enum, default_value_t)]
+        format: ImportFormat,
+
+        /// Tag of the books in a Calibre library to import as papers.
+        #[clap(long, default_value = calibre::DEFAULT_TAG)]
+        calibre_tag: String,
+
+        /// Don't tag the imported papers `inbox`, e.g. when restoring a backup.
+        #[clap(long)]
+        no_inbox: bool,
+
+        /// Continue an interrupted import of the same file, skipping the papers it already added.
+        #[clap(long)]
+        resume: bool,
+    },
+    /// Watch a directory, such as downloads, adding each new PDF that appears in it.
+    ///
+    /// New PDFs are moved into the repo and go through `add`, asking for their details at a
+    /// terminal. PDFs already in the directory when the watch starts are left alone. Stop
+    /// watching with Ctrl-C.
+    Watch {
+        /// Directory to watch.
+        #[clap()]
+        dir: PathBuf,
+
+        /// Add papers with the title and authors extracted from their documents, without asking.
+        /// Always the case when not run at a terminal.
+        #[clap(long)]
+        auto: bool,
+
+        /// Copy new PDFs into the repo, leaving them in the directory, rather than moving them.
+        #[clap(long)]
+        copy: bool,
+
+        /// Tags to give the added papers.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+
+        /// How often to look for new PDFs, e.g. 2s or 1m.
+        #[clap(long, default_value = "2s")]
+        interval: HumanDuration,
+
+        /// Add the PDFs already in the directory then stop, rather than watching for new ones.
+        #[clap(long)]
+        once: bool,
+    },
+    /// Print a JSON Schema for the frontmatter of notes files, or the json read by `import`, for
+    /// editors and other tools to check them against.
+    Schema {
+        /// What to print the schema of.
+        #[clap(long, value_enum, default_value_t)]
+        kind: SchemaKind,
+    },
+    /// Export the notes files of papers, with their metadata, to a directory for sharing.
+    ///
+    /// With `--format calibre` each paper gets a directory with its document and a
+    /// `metadata.opf` instead, for Calibre to add as books from directories.
+    Export {
+        /// Directory to write the notes files to, created if it doesn't exist.
+        #[clap()]
+        dir: PathBuf,
+
+        /// Only export papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+
+        /// Strip personal labels, tags and notes sections set in the `anonymise` config, along
+        /// with review and read by dates.
+        #[clap(long)]
+        anonymise: bool,
+
+        /// What to write for each paper.
+        #[clap(long, value_enum, default_value_t)]
+        format: ExportFormat,
+    },
+    /// Write the papers tagged `public` as pages of a static site, e.g. the content directory of a
+    /// blog.
+    Publish {
+        /// Directory to write the pages to, created if it doesn't exist.
+        #[clap()]
+        dir: PathBuf,
+
+        /// Static site generator to map the metadata to the frontmatter of.
+        #[clap(long, value_enum, default_value_t)]
+        generator: SiteGenerator,
+
+        /// Strip personal labels, tags and notes sections set in the `anonymise` config.
+        #[clap(long)]
+        anonymise: bool,
+    },
+    /// Upload the notes of a paper to the gist or paste service in the `share` config, printing
+    /// the url they can be seen at.
+    Share {
+        /// Path of the paper to share, selected interactively if not given.
+        #[clap()]
+        path: Option<PathBuf>,
+
+        /// Put the title, authors, url and tags of the paper above the notes.
+        #[clap(long)]
+        with_meta: bool,
+
+        /// Strip personal labels, tags and notes sections set in the `anonymise` config.
+        #[clap(long)]
+        anonymise: bool,
+    },
+    /// Print the papers as a json array, for external processing and backups.
+    ExportJson {
+        /// Include the body of the notes and the paths of the files of each paper.
+        #[clap(long)]
+        full: bool,
+
+        /// Only export papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+
+        /// Strip personal labels, tags and notes sections set in the `anonymise` config, along
+        /// with review and read by dates.
+        #[clap(long)]
+        anonymise: bool,
+    },
+    /// Check that an export has everything in the repo, by reading it as `import` would and
+    /// comparing each paper with the repo.
+    ///
+    /// Exits with an error if anything differs, notes and documents are only compared if the
+    /// export has them.
+    VerifyExport {
+        /// Export to check, json or yaml for files ending in `.yaml` or `.yml`.
         #[clap()]
         file: FileOrStdin,
+
+        /// Only compare with papers that have all of the given tags, as exported with `--tag`.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
     },
     /// Check consistency of things in the repo.
     Doctor {
-        /// Try and fix the problems
+        /// Try and fix the problems, only those of the given kinds if any are given, e.g.
+        /// `--fix notes-wrong-path,file-wrong-path`.
+        /// Duplicates are only merged when `duplicate-file` is given or with `--interactive`.
+        #[clap(long, value_enum, num_args = 0.., value_delimiter = ',')]
+        fix: Option<Vec<ProblemKind>>,
+
+        /// Ask before fixing each problem, with the choice to fix or leave all later problems of
+        /// the same kind.
+        #[clap(long, short)]
+        interactive: bool,
+
+        /// Fetch documents that are empty or corrupt again from the paper's url.
+        #[clap(long)]
+        refetch: bool,
+
+        /// Also warn about tags that aren't in the tag registry, `.paperstags.yaml` in the root of
+        /// the repo.
+        #[clap(long)]
+        strict: bool,
+
+        /// Also warn about papers added at least this many days ago whose notes are still just
+        /// the notes template.
+        #[clap(long)]
+        untouched_days: Option<u64>,
+    },
+    /// Merge papers whose documents have the same contents, moving all but the one kept in each
+    /// group to the trash.
+    Dedupe {
+        /// Keep the paper added first in each group without asking.
+        #[clap(long, short)]
+        yes: bool,
+    },
+    /// Check arXiv for newer versions of papers than the ones stored.
+    ///
+    /// Papers are found by an arXiv url or an `arxiv` label. The stored version is taken from the
+    /// url, e.g. https://arxiv.org/abs/2101.00001v2, and is unknown otherwise.
+    CheckUpdates {
+        /// Offer to fetch the latest version of each paper, replacing the stored document.
+        #[clap(long)]
+        fetch: bool,
+
+        /// Fetch without asking for confirmation.
+        #[clap(long, short, requires = "fetch")]
+        yes: bool,
+    },
+    /// Fetch the documents of papers that have a url but no document, such as those added with
+    /// `--fetch false`.
+    ///
+    /// Each document is saved under the paper's name and attached to it, and the `to-fetch` tag
+    /// removed.
+    FetchMissing {
+        /// Filter down to papers whose titles match this (case-insensitive).
+        #[clap(long)]
+        title: Option<String>,
+
+        /// Filter down to papers that have all of the given authors.
+        #[clap(name = "author", long, short)]
+        authors: Vec<Author>,
+
+        /// Filter down to papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+
+        /// Filter down to papers that have all of the given labels. Labels take the form `key=value`.
+        #[clap(name = "label", long, short)]
+        labels: Vec<Label>,
+
+        /// Number of documents to fetch at once.
+        #[clap(long, short, default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+
+        /// Attach the documents fetched by an interrupted run instead of fetching them again.
+        #[clap(long)]
+        resume: bool,
+    },
+    /// List papers with a read by date, most urgent first.
+    ///
+    /// Overdue papers are always shown.
+    Deadlines {
+        /// Only show papers due within this many days.
+        #[clap(long, default_value = "14")]
+        within: i64,
+    },
+    /// Track citation counts of papers from Semantic Scholar.
+    Citations {
+        /// Citations command to run.
+        #[clap(subcommand)]
+        command: CitationsCommands,
+    },
+    /// Show the history of changes made to the repo.
+    Log {
+        /// Only show changes to this paper, following it back through renames.
+        #[clap(long)]
+        paper: Option<PathBuf>,
+
+        /// Only show changes since this time, a date like 2023-08-01 or an age like 3d.
+        #[clap(long)]
+        since: Option<Since>,
+    },
+    /// Find and replace in a metadata field of papers with a sed style expression.
+    ///
+    /// Notes files aren't renamed when titles change, use `papers rename-files` or `papers doctor
+    /// --fix` after.
+    Sed {
+        /// Field to edit.
+        #[clap(long, value_enum)]
+        field: SedField,
+
+        /// Substitution to apply, e.g. 's/Tehcnique/Technique/g'. The flags g (every match) and i
+        /// (ignore case) are supported.
+        #[clap()]
+        expression: Substitution,
+
+        /// Only edit papers that have all of the given authors.
+        #[clap(name = "author", long, short)]
+        authors: Vec<Author>,
+
+        /// Only edit papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+
+        /// Only edit papers that have all of the given labels. Labels take the form `key=value`.
+        #[clap(name = "label", long, short)]
+        labels: Vec<Label>,
+
+        /// Print the changes but don't save them.
         #[clap(long)]
-        fix: bool,
+        dry_run: bool,
+    },
+    /// Save and restore the metadata of all papers.
+    Snapshot {
+        /// Snapshot command to run.
+        #[clap(subcommand)]
+        command: SnapshotCommands,
+    },
+    /// Shrink pdf documents and linearise them for fast opening, using `optimize_command` from the
+    /// config.
+    ///
+    /// A document is only replaced when the optimized version is smaller.
+    OptimizePdfs {
+        /// Paths of the papers whose documents to optimize, all papers if not given.
+        #[clap()]
+        paths: Vec<PathBuf>,
+    },
+    /// Render thumbnails of the first pages of documents, using `thumbnails.command` from the
+    /// config.
+    ///
+    /// Thumbnails are named by the hash of the document so they are rendered again when it
+    /// changes. When run over all papers, thumbnails of documents that are gone are removed.
+    Thumbnails {
+        /// Paths of the papers whose documents to render, all papers if not given.
+        #[clap()]
+        paths: Vec<PathBuf>,
+    },
+    /// Show how much disk space documents use, listing the largest.
+    Du {
+        /// Total the sizes of documents by a group instead of listing the largest.
+        #[clap(long, value_enum)]
+        by: Option<UsageBy>,
+
+        /// Number of documents to list.
+        #[clap(long, short = 'n', default_value = "10")]
+        count: usize,
     },
     /// List stats about tags.
     Tags {
-        /// Output the filtered selection of papers in different formats.
-        #[clap(long, short, value_enum, default_value_t)]
-        output: OutputStyle,
         /// Sort the output by count.
         #[clap(long, short, default_value = "false")]
         sort: bool,
+
+        /// Show what each tag is for from the tag registry, `.paperstags.yaml` in the root of the
+        /// repo, including registered tags that no paper uses yet.
+        #[clap(long)]
+        describe: bool,
     },
     /// List stats about labels.
+    #[clap(args_conflicts_with_subcommands = true)]
     Labels {
-        /// Output the filtered selection of papers in different formats.
-        #[clap(long, short, value_enum, default_value_t)]
-        output: OutputStyle,
         /// Sort the output by count.
         #[clap(long, short, default_value = "false")]
         sort: bool,
+
+        /// Label command to run instead of listing stats.
+        #[clap(subcommand)]
+        command: Option<LabelsCommands>,
+    },
+    /// Share settings between machines.
+    Config {
+        /// Config command to run.
+        #[clap(subcommand)]
+        command: ConfigCommands,
     },
     /// List stats about authors.
     Authors {
-        /// Output the filtered selection of papers in different formats.
-        #[clap(long, short, value_enum, default_value_t)]
-        output: OutputStyle,
         /// Sort the output by count.
         #[clap(long, short, default_value = "false")]
         sort: bool,
     },
+    /// Work with the tags, labels and authors used across the repo.
+    Vocab {
+        /// Vocab command to run.
+        #[clap(subcommand)]
+        command: VocabCommands,
+    },
+    /// Show papers as cards in columns, such as how far through reading them is.
+    Board {
+        /// What to put papers into columns by.
+        #[clap(long, value_enum, default_value_t)]
+        by: BoardBy,
+    },
+    /// Show papers in a tree of groups, such as nested tags.
+    Tree {
+        /// What to group papers by, tags are nested by their `/`-separated levels.
+        #[clap(long, value_enum, default_value_t)]
+        by: TreeBy,
+    },
+    /// Report what was added and read in a month, e.g. as markdown to share with `-o markdown`.
+    Report {
+        /// Month to report on, e.g. 2024-05, defaults to this month.
+        #[clap(long)]
+        month: Option<Month>,
+    },
+    /// Move a paper to the trash, from where `papers trash restore` can put it back.
+    Remove {
+        /// Path of the paper to remove, part of its title, a glob, or `@last-added` or
+        /// `@last-edited`. Fuzzy selected if not given.
+        #[clap()]
+        path: Option<PathBuf>,
+
+        /// Move the paper's document to the trash too.
+        #[clap(long)]
+        with_file: bool,
+    },
+    /// Work with papers that have been removed.
+    Trash {
+        /// Trash command to run.
+        #[clap(subcommand)]
+        command: TrashCommands,
+    },
+    /// Work with annotations made on Hypothes.is.
+    Hypothesis {
+        /// Hypothesis command to run.
+        #[clap(subcommand)]
+        command: HypothesisCommands,
+    },
+    /// Send highlights to Readwise and get back those made elsewhere.
+    Readwise {
+        /// Readwise command to run.
+        #[clap(subcommand)]
+        command: ReadwiseCommands,
+    },
+    /// Work with publications listed on DBLP.
+    Dblp {
+        /// DBLP command to run.
+        #[clap(subcommand)]
+        command: DblpCommands,
+    },
 }
 
 impl SubCommand {
     /// Execute a subcommand.
-    pub fn execute(self, config: &Config) -> anyhow::Result<()> {
+    pub fn execute(
+        self,
+        config: &Config,
+        config_file: &Path,
+        output: OutputStyle,
+        progress: ProgressStyle,
+        cancel: &CancellationToken,
+    ) -> anyhow::Result<()> {
         match self {
+            Self::Init { template } => {
+                let root = &config.default_repo;
+                create_dir_all(root)?;
+                let repo = load_repo(config, cancel)?;
+                if let Some(template) = template {
+                    let scratch = tempfile::tempdir()?;
+                    let template = fetch_template(&template, scratch.path())?;
+                    let (copied, skipped) = copy_template(&template, repo.root())?;
+                    for path in copied {
+                        println!("Copied {}", path.display());
+                    }
+                    for path in skipped {
+                        warning!("Not overwriting {}", path.display());
+                    }
+
+                    let template_config = template.join(TEMPLATE_CONFIG);
+                    if template_config.is_file() {
+                        if config_file.exists() {
+                            warning!(
+                                "Not overwriting the existing config file with the template's config"
+                            );
+                        } else {
+                            let mut new_config = Config::load(&template_config)?;
+                            new_config.default_repo = repo.root().to_owned();
+                            if let Some(parent) = config_file.parent() {
+                                create_dir_all(parent)?;
+                            }
+                            serde_yaml::to_writer(File::create(config_file)?, &new_config)?;
+                            println!("Wrote the template's config");
+                        }
+                    }
+                }
+                info!(root=?repo.root(), "Initialised repo");
+            }
             Self::Add {
-                mut url,
+                url: url_or_path,
                 mut fetch,
                 mut file,
-                title,
+                mut title,
                 mut authors,
                 mut tags,
                 mut labels,
+                snapshot,
+                doc_type,
+                rfc,
+                tech_report,
+                doi,
+                isbn,
+                read_by,
+                copy,
+                move_file,
+                force,
             } => {
-                let mut repo = load_repo(config)?;
-                let mut new_title;
-                if atty::is(atty::Stream::Stdout) {
-                    if let Some(url) = &url {
-                        println!("Using url {}", url);
-                    } else {
-                        url = input_opt::<Url>("Url for document");
-                    }
-
-                    if let Some(fetch) = fetch {
-                        if fetch {
-                            println!("Will fetch url");
-                        } else {
-                            println!("Will not fetch url");
-                        }
-                    } else {
-                        if let Some(url) = &url {
-                            fetch = Some(input_bool(&format!("Fetch {}", url), true));
+                let existing = if force {
+                    ExistingFile::Overwrite
+                } else {
+                    config.existing_file
+                };
+                let mut repo = load_repo(config, cancel)?;
+                let http = HttpClient::new(&config.http)?;
+                let mut url = match url_or_path {
+                    Some(UrlOrPath::Url(url)) => Some(url),
+                    Some(UrlOrPath::Path(path)) => {
+                        if file.is_some() {
+                            anyhow::bail!("Can't add both a local url and a file");
                         }
+                        file = Some(copy_local(&path, &repo, move_file, cancel)?);
+                        fetch = Some(false);
+                        None
                     }
-
+                    None => None,
+                };
+                let resolved = if let Some(rfc) = rfc {
+                    Some(resolve_rfc(&http, rfc)?)
+                } else if let Some(report) = &tech_report {
+                    Some(resolve_tech_report(&http, report)?)
+                } else if let Some(doi) = &doi {
+                    Some(resolve_doi(&http, doi)?)
+                } else if let Some(isbn) = &isbn {
+                    Some(resolve_isbn(&http, isbn)?)
+                } else {
+                    None
+                };
+                if let Some(resolved) = resolved {
+                    url = Some(resolved.url);
+                    if title.is_none() {
+                        title = resolved.title;
+                    }
+                    if authors.is_empty() {
+                        authors = resolved.authors;
+                    }
+                    labels.extend(resolved.labels);
+                }
+                let mut extra = BTreeMap::new();
+                if let Some(id) = url.as_ref().and_then(ArxivId::from_url) {
+                    // the abstract page isn't the document, so fetch the pdf instead
+                    url = Some(id.pdf_url());
+                    if fetch.is_none() {
+                        fetch = Some(true);
+                    }
+                    if file.is_none() && fetch == Some(true) && !http.is_offline() {
+                        let name = format!("{}.pdf", id.to_string().replace('/', "_"));
+                        file = Some(repo.root().join(name));
+                    }
+                    if !skip_offline(&http, &format!("arXiv metadata for {id}")) {
+                        match fetch_arxiv_meta(&http, &id) {
+                            Ok(meta) => {
+                                if title.is_none() {
+                                    title = meta.title;
+                                }
+                                if authors.is_empty() {
+                                    authors = meta.authors;
+                                }
+                                if tags.is_empty() {
+                                    tags = meta.categories;
+                                }
+                                if let Some(summary) = meta.summary {
+                                    extra.insert(
+                                        ABSTRACT_FIELD.to_owned(),
+                                        serde_yaml::Value::String(summary),
+                                    );
+                                }
+                            }
+                            Err(err) => {
+                                warn!(%err, %id, "Failed to fetch arXiv metadata");
+                            }
+                        }
+                    }
+                }
+                let doc_type = doc_type.unwrap_or_else(|| {
+                    if isbn.is_some() {
+                        DocType::Book
+                    } else if url.as_ref().and_then(oembed_endpoint).is_some() {
+                        DocType::Talk
+                    } else {
+                        DocType::Paper
+                    }
+                });
+                if doc_type == DocType::Talk {
+                    // talks are opened by their url rather than downloaded
+                    fetch = Some(false);
+                    if let Some(url) = url
+                        .as_ref()
+                        .filter(|u| oembed_endpoint(u).is_some())
+                        .filter(|u| !skip_offline(&http, &format!("talk metadata for {u}")))
+                    {
+                        match fetch_talk_meta(&http, url) {
+                            Ok(talk) => {
+                                if title.is_none() {
+                                    title = talk.title;
+                                }
+                                if authors.is_empty() {
+                                    authors.extend(talk.channel);
+                                }
+                            }
+                            Err(err) => {
+                                warn!(%err, %url, "Failed to fetch talk metadata");
+                            }
+                        }
+                    }
+                }
+                if doc_type == DocType::Book && file.is_none() {
+                    // the url of a book is its catalogue page, not something to read
+                    fetch = Some(false);
+                }
+                // whether the document was downloaded rather than given, so says nothing about
+                // where the paper belongs
+                let mut fetched = false;
+                if snapshot {
+                    if let Some(url) = url
+                        .as_ref()
+                        .filter(|u| !skip_offline(&http, &format!("snapshot of {u}")))
+                    {
+                        let (path, page) = snapshot_url(
+                            &http,
+                            url,
+                            repo.root(),
+                            config.snapshot_command.as_deref(),
+                        )?;
+                        file = Some(path);
+                        fetched = true;
+                        fetch = Some(false);
+                        if title.is_none() {
+                            title = page.title;
+                        }
+                        if authors.is_empty() {
+                            authors = page.authors;
+                        }
+                    }
+                }
+                // kept to fetch later, so the metadata comes from the page rather than the document
+                let bookmark = fetch == Some(false)
+                    && file.is_none()
+                    && !snapshot
+                    && doc_type != DocType::Talk
+                    && doc_type != DocType::Book
+                    && url.is_some();
+                if bookmark {
+                    if let Some(url) = url
+                        .as_ref()
+                        .filter(|_| title.is_none())
+                        .filter(|u| !skip_offline(&http, &format!("page metadata for {u}")))
+                    {
+                        match fetch_page_meta(&http, url) {
+                            Ok(page) => {
+                                title = page.title;
+                                if authors.is_empty() {
+                                    authors = page.authors;
+                                }
+                            }
+                            Err(err) => {
+                                warn!(%err, %url, "Failed to fetch page metadata");
+                            }
+                        }
+                    }
+                    tags.push(Tag::new(TO_FETCH_TAG));
+                }
+                if fetch != Some(false) && skip_offline(&http, "fetching documents") {
+                    fetch = Some(false);
+                }
+                let mut new_title;
+                // whether the file was made by this command, so can be removed again
+                let mut new_file = false;
+                let mut mirror = None;
+                if atty::is(atty::Stream::Stdout) {
+                    if let Some(url) = &url {
+                        println!("Using url {}", url);
+                    } else {
+                        url = input_opt::<Url>("Url for document")?;
+                    }
+
+                    if let Some(fetch) = fetch {
+                        if fetch {
+                            println!("Will fetch url");
+                        } else {
+                            println!("Will not fetch url");
+                        }
+                    } else {
+                        if let Some(url) = &url {
+                            fetch = Some(input_bool(&format!("Fetch {}", url), true)?);
+                        }
+                    }
+
                     if let Some(file) = &file {
                         println!("Using file {:?}", file);
                     } else {
                         if let Some((url, true)) = url.as_ref().zip(fetch) {
                             // try and get the default filename to use
                             let default_file =
-                                url.path_segments().unwrap().last().unwrap().to_owned();
-                            file = Some(input_default::<PathBuf>("Path to file", &default_file));
+                                url.path_segments().unwrap().next_back().unwrap().to_owned();
+                            file = Some(input_default::<PathBuf>("Path to file", &default_file)?);
                         } else {
-                            file = input_opt::<PathBuf>("Path to file");
+                            file = input_opt::<PathBuf>("Path to file")?;
                         };
                     }
 
@@ -246,7 +1108,18 @@ impl SubCommand {
                             if let Some(f) = &file {
                                 let name = f.file_name().unwrap();
                                 let path = repo.root().join(name);
-                                file = Some(fetch_url(&url, &path)?);
+                                let (path, fetched_from) = fetch_url(
+                                    &http,
+                                    url,
+                                    &path,
+                                    config.institutional_proxy.as_deref(),
+                                    existing,
+                                    cancel,
+                                )?;
+                                file = Some(path);
+                                mirror = fetched_from;
+                                new_file = true;
+                                fetched = true;
                             } else {
                                 anyhow::bail!("No file to downlod to");
                             }
@@ -263,9 +1136,14 @@ impl SubCommand {
                             None
                         };
                         if let Some(extracted_title) = extracted_title {
-                            input_default("Title", &extracted_title)
+                            let prompt = if extracted_title.confident {
+                                "Title"
+                            } else {
+                                "Title (guessed from first page, please check)"
+                            };
+                            input_default(prompt, &extracted_title.title)?
                         } else {
-                            input("Title")
+                            input("Title")?
                         }
                     };
 
@@ -276,14 +1154,14 @@ impl SubCommand {
                             BTreeSet::new()
                         };
                         if extracted_authors.is_empty() {
-                            authors = input_vec("Authors", ",");
+                            authors = input_vec("Authors", ",")?;
                         } else {
                             let extracted_authors_str = extracted_authors
                                 .iter()
                                 .map(|a| a.to_string())
                                 .collect::<Vec<String>>()
                                 .join(",");
-                            authors = input_vec_default("Authors", ",", &extracted_authors_str);
+                            authors = input_vec_default("Authors", ",", &extracted_authors_str)?;
                         }
                     } else {
                         let authors_string = authors
@@ -301,7 +1179,7 @@ impl SubCommand {
                             .map(|t| t.to_string())
                             .collect::<Vec<String>>()
                             .join(",");
-                        tags = input_vec(&format!("Tags (default: {})", default_tags_str), " ");
+                        tags = input_vec(&format!("Tags (default: {})", default_tags_str), " ")?;
                     } else {
                         let tags_string = tags
                             .iter()
@@ -322,7 +1200,7 @@ impl SubCommand {
                         labels = input_vec(
                             &format!("Labels (key=value) (default: {})", default_labels_str),
                             " ",
-                        );
+                        )?;
                     } else {
                         let labels_string = labels
                             .iter()
@@ -335,14 +1213,25 @@ impl SubCommand {
                 } else {
                     if let Some(true) = fetch {
                         if let Some(url) = &url {
-                            file = Some(fetch_url(&url, &file.unwrap())?);
+                            let (path, fetched_from) = fetch_url(
+                                &http,
+                                url,
+                                &file.unwrap(),
+                                config.institutional_proxy.as_deref(),
+                                existing,
+                                cancel,
+                            )?;
+                            file = Some(path);
+                            mirror = fetched_from;
+                            new_file = true;
+                            fetched = true;
                         }
                     }
                     new_title = title.unwrap_or_default();
 
                     if let Some(file) = &file {
                         if new_title.is_empty() {
-                            new_title = extract_title(file).unwrap_or_default();
+                            new_title = extract_title(file).map(|t| t.title).unwrap_or_default();
                         }
 
                         if authors.is_empty() {
@@ -351,8 +1240,63 @@ impl SubCommand {
                     }
                 }
 
-                let tags = BTreeSet::from_iter(tags);
-                let labels = BTreeSet::from_iter(labels);
+                if let Some(path) = file.as_ref().filter(|f| f.is_file()) {
+                    let outside = repo.in_root(path).is_err();
+                    let bring_in = if !outside {
+                        None
+                    } else if copy || move_file {
+                        Some(move_file)
+                    } else if atty::is(atty::Stream::Stdout) {
+                        if input_bool(&format!("Copy {path:?} into the repo"), true)? {
+                            Some(false)
+                        } else if input_bool(&format!("Move {path:?} into the repo"), false)? {
+                            Some(true)
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    };
+                    if let Some(move_file) = bring_in {
+                        let path = copy_local(path, &repo, move_file, cancel)?;
+                        new_file |= !move_file;
+                        file = Some(path);
+                    }
+                }
+
+                if let Some(path) = file.as_ref().filter(|f| f.is_file()) {
+                    let duplicate = repo.find_duplicate(path).unwrap_or_else(|err| {
+                        warn!(%err, ?path, "Failed to check for duplicate documents");
+                        None
+                    });
+                    if let Some((existing, filename)) =
+                        duplicate.and_then(|p| Some((p.path, p.meta.filename?)))
+                    {
+                        warning!("{path:?} is a duplicate of {filename:?} from {existing:?}");
+                        if atty::is(atty::Stream::Stdout)
+                            && input_bool(&format!("Use {filename:?} instead"), true)?
+                        {
+                            if new_file {
+                                remove_file(path)?;
+                            }
+                            file = Some(repo.root().join(filename));
+                        }
+                    }
+                }
+
+                labels.extend(
+                    mirror.map(|m| Label::new(MIRROR_LABEL, Primitive::String(m.to_string()))),
+                );
+                // papers added into a directory, by their local document or the current
+                // directory, get its defaults and those of the directories above it
+                let dir = file
+                    .as_deref()
+                    .filter(|_| !fetched)
+                    .and_then(|f| repo.in_root(f).ok())
+                    .and_then(|f| f.parent().map(Path::to_owned))
+                    .unwrap_or_else(|| current_dir_in(&repo));
+                let (tags, labels, notes) =
+                    with_dir_defaults(config, &repo, &dir, doc_type, tags, labels)?;
 
                 let url = url.map(|u| u.to_string());
 
@@ -361,12 +1305,17 @@ impl SubCommand {
                     file,
                     url,
                     new_title,
+                    doc_type,
                     authors.clone(),
                     tags.clone(),
                     labels.clone(),
+                    read_by.map(|ReadBy(date)| date),
+                    Provenance::now(Source::Add, None),
+                    extra,
+                    &notes,
                 ) {
                     Ok(paper) => {
-                        println!("Added paper {}", paper.title);
+                        output.print(&Added(vec![paper]))?;
                     }
                     Err(err) => {
                         warn!(%err, "Failed to add paper");
@@ -380,39 +1329,95 @@ impl SubCommand {
                 authors,
                 tags,
                 labels,
-                output,
+                source,
+                time,
+                title_re,
+                file_re,
+                author_re,
+                has_notes,
+                no_notes,
+                query,
                 sort,
+                format,
+                fail_if_empty,
             } => {
-                let mut repo = load_repo(config)?;
-                let mut papers = repo.list(file, title, authors, tags, labels)?;
-
-                papers.sort_by_key(|p| match sort {
-                    SortBy::Title => p.meta.title.clone(),
-                    SortBy::CreatedAt => p.meta.created_at.to_string(),
-                    SortBy::ModifiedAt => p.meta.modified_at.to_string(),
-                });
-
-                let paper_metas = papers.into_iter().map(|p| p.meta).collect::<Vec<_>>();
-                match output {
-                    OutputStyle::Table => {
-                        let table = Table::from(paper_metas);
-                        println!("{table}");
+                let mut repo = load_repo(config, cancel)?;
+                let regexes = RegexFilters {
+                    title: title_re,
+                    file: file_re,
+                    author: author_re,
+                };
+                let mut papers = repo.list(file, title, authors, tags, labels, &regexes)?;
+                if let Some(source) = source {
+                    papers.retain(|p| p.meta.provenance.as_ref().map(|p| p.source) == Some(source));
+                }
+                papers.retain(|p| time.matches(&p.meta));
+                let mut template = NotesTemplate::default();
+                if has_notes || no_notes || config.status_column || config.notes_column {
+                    template = load_notes_template(config)?;
+                    // listing only loads the metadata of papers
+                    for paper in &mut papers {
+                        paper.notes = repo.get_paper(&paper.path)?.notes;
                     }
-                    OutputStyle::Json => {
-                        serde_json::to_writer(stdout(), &paper_metas)?;
+                }
+                if has_notes || no_notes {
+                    papers.retain(|p| template.has_notes(&p.notes) == has_notes);
+                }
+
+                // equally good matches stay in this order
+                sort.clone()
+                    .unwrap_or_default()
+                    .sort(&mut papers, repo.root());
+                if let Some(query) = query {
+                    papers = fuzzy_filter(papers, &query);
+                    if let Some(sort) = sort {
+                        sort.sort(&mut papers, repo.root());
                     }
-                    OutputStyle::Yaml => {
-                        serde_yaml::to_writer(stdout(), &paper_metas)?;
+                }
+
+                if fail_if_empty && papers.is_empty() {
+                    return Err(NoResults.into());
+                }
+
+                if let Some(format) = format {
+                    for paper in &papers {
+                        println!("{}", format.render(paper));
                     }
+                    return Ok(());
                 }
+
+                let statuses = if config.status_column {
+                    let now = chrono::Utc::now().naive_utc();
+                    papers
+                        .iter()
+                        .map(|p| PaperStatus::of(p, &template, now))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                let notes_words = if config.notes_column {
+                    papers.iter().map(|p| template.words(&p.notes)).collect()
+                } else {
+                    Vec::new()
+                };
+                let paper_metas = papers.into_iter().map(|p| p.meta).collect::<Vec<_>>();
+                output.print(
+                    &Table::from(paper_metas)
+                        .with_label_columns(config.label_columns.clone())
+                        .with_statuses(statuses)
+                        .with_notes_words(notes_words),
+                )?;
             }
             Self::RenameFiles {
                 strategies,
                 dry_run,
             } => {
-                let repo = load_repo(config)?;
+                let repo = load_repo(config, cancel)?;
                 let root = repo.root().to_owned();
-                for paper in repo.all_papers() {
+                // names already taken, compared case-insensitively for windows and macos
+                let mut claimed = BTreeSet::new();
+                let mut renames = Renames::default();
+                for paper in repo.all_papers()? {
                     let new_name = strategies.iter().find_map(|s| s.rename(&paper.meta).ok());
                     let new_name = if let Some(new_name) = new_name {
                         new_name
@@ -442,15 +1447,19 @@ impl SubCommand {
                                 PathBuf::from(&new_name).with_extension(new_extension)
                             };
 
-                            if new_path != path {
-                                if !new_path.exists() {
-                                    // old exists, new doesn't exist, do the rename
-                                    println!("Renaming {path:?} to {new_path:?}");
-                                    if !dry_run {
-                                        rename(&path, &new_path).unwrap();
-                                        repo.update(&paper, Some(&new_path)).unwrap();
-                                    }
+                            if new_path != path
+                                && (!new_path.exists() || is_case_only_rename(&path, &new_path))
+                                && claimed.insert(collision_key(&new_path))
+                            {
+                                // old exists, new doesn't exist, do the rename
+                                if !dry_run {
+                                    repo.rename(&path, &new_path).unwrap();
+                                    repo.update(&paper, Some(&new_path)).unwrap();
                                 }
+                                renames.0.push(Rename {
+                                    from: path,
+                                    to: new_path,
+                                });
                             }
                         }
                     } else {
@@ -459,18 +1468,24 @@ impl SubCommand {
 
                     let new_paper_path = root.join(new_name).with_extension("md");
                     let paper_path = root.join(paper.path);
-                    if !new_paper_path.exists() {
-                        if paper_path != new_paper_path {
-                            println!("Renaming {paper_path:?} to {new_paper_path:?}");
-                            if !dry_run {
-                                rename(&paper_path, new_paper_path).unwrap();
-                            }
+                    if paper_path != new_paper_path
+                        && (!new_paper_path.exists()
+                            || is_case_only_rename(&paper_path, &new_paper_path))
+                        && claimed.insert(collision_key(&new_paper_path))
+                    {
+                        if !dry_run {
+                            repo.rename(&paper_path, &new_paper_path).unwrap();
                         }
+                        renames.0.push(Rename {
+                            from: paper_path,
+                            to: new_paper_path,
+                        });
                     }
                 }
+                output.print(&renames)?;
             }
             Self::Edit { path, open } => {
-                let repo = load_repo(config)?;
+                let repo = load_repo(config, cancel)?;
                 let root = repo.root().to_owned();
 
                 let original_paper = get_or_select_paper(&repo, path.as_deref())?;
@@ -478,7 +1493,8 @@ impl SubCommand {
                 if open {
                     open_file(&original_paper.meta, &root)?;
                 }
-                edit(&root.join(&original_paper.path))?;
+                edit_notes(&root, &original_paper.path)?;
+                Recent::record(&root, &original_paper.path);
 
                 // now set the modified time
                 let updated_paper = repo.get_paper(&original_paper.path)?;
@@ -491,26 +1507,96 @@ impl SubCommand {
                 }
             }
             Self::Open { path } => {
-                let repo = load_repo(config)?;
+                let repo = load_repo(config, cancel)?;
                 let root = repo.root().to_owned();
 
                 let paper = get_or_select_paper(&repo, path.as_deref())?;
 
                 open_file(&paper.meta, &root)?;
+                Recent::record(&root, &paper.path);
             }
-            Self::Review { open, path } => {
+            Self::Review {
+                command: Some(command),
+                ..
+            } => match command {
+                ReviewCommands::List {
+                    within,
+                    time,
+                    fail_if_empty,
+                } => {
+                    let repo = load_repo(config, cancel)?;
+                    let mut papers = repo.all_papers()?;
+                    papers.retain(|p| time.matches(&p.meta));
+                    let queue = ReviewQueue::of(papers, today(), within);
+                    output.print(&queue)?;
+                    if fail_if_empty && queue.0.is_empty() {
+                        return Err(NoResults.into());
+                    }
+                }
+                ReviewCommands::Stats { weeks, time } => {
+                    let repo = load_repo(config, cancel)?;
+                    let entries = repo.journal().entries()?;
+                    let mut papers = repo.all_papers()?;
+                    papers.retain(|p| time.matches(&p.meta));
+                    output.print(&ReviewStats::of(&papers, &entries, today(), weeks))?;
+                }
+                ReviewCommands::Schedule { paths, on, never } => {
+                    let repo = load_repo(config, cancel)?;
+                    let papers = paths
+                        .iter()
+                        .map(|path| resolve_paper(&repo, path))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    for mut paper in papers {
+                        if never {
+                            paper.meta.no_review = true;
+                            println!("{:?} won't come up for review", paper.path);
+                        } else if let Some(ReadBy(date)) = on {
+                            paper.meta.no_review = false;
+                            paper.meta.next_review = Some(date.and_time(NaiveTime::MIN));
+                            println!("Next review of {:?} on {date}", paper.path);
+                        }
+                        repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                    }
+                }
+            },
+            Self::Review {
+                command: None,
+                open,
+                path,
+                fail_if_empty,
+                limit,
+                time_box,
+                rating,
+                time,
+            } => {
                 // get the list of papers ready for review
-                let repo = load_repo(config)?;
+                let repo = load_repo(config, cancel)?;
                 let root = repo.root().to_owned();
 
                 let review = |paper: LoadedPaper| -> anyhow::Result<()> {
                     if open {
                         open_file(&paper.meta, &root)?;
                     }
-                    edit(&root.join(&paper.path))?;
+                    edit_notes(&root, &paper.path)?;
+                    let rating = match rating {
+                        Some(rating) => Some(rating),
+                        None => loop {
+                            let key = input_key(Rating::PROMPT);
+                            match key {
+                                // closed input, leave the paper unreviewed
+                                None => return Err(Cancelled.into()),
+                                Some(' ') => break None,
+                                _ => {}
+                            }
+                            if let Some(rating) = Rating::from_key(key) {
+                                break Some(rating);
+                            }
+                        },
+                    };
                     // now set the modified time
                     let mut updated_paper = repo.get_paper(&paper.path)?;
-                    updated_paper.meta.update_review();
+                    updated_paper.meta.update_review(&config.review, rating);
+                    clear_inbox(&mut updated_paper.meta.tags);
                     println!(
                         "Review complete, next review on {}",
                         updated_paper.meta.next_review.unwrap()
@@ -525,223 +1611,2095 @@ impl SubCommand {
 
                 match path {
                     Some(path) => {
-                        let paper = repo.get_paper(&path)?;
+                        let paper = resolve_paper(&repo, &path)?;
                         review(paper)?;
                     }
-                    None => loop {
-                        let all_papers = repo.all_papers();
-                        let reviewable_papers = all_papers
-                            .iter()
-                            .filter(|p| p.meta.is_reviewable())
-                            .cloned()
-                            .collect::<Vec<_>>();
-                        if reviewable_papers.is_empty() {
-                            break;
-                        }
-                        match select_paper(&reviewable_papers) {
-                            Some(p) => review(p)?,
-                            None => {
-                                anyhow::bail!("No paper selected");
+                    None => {
+                        let started = Instant::now();
+                        let mut reviewed = 0;
+                        let remaining = loop {
+                            cancel.check()?;
+                            let all_papers = repo.all_papers()?;
+                            let reviewable_papers = all_papers
+                                .iter()
+                                .filter(|p| p.meta.is_reviewable() && time.matches(&p.meta))
+                                .cloned()
+                                .collect::<Vec<_>>();
+                            if reviewable_papers.is_empty() {
+                                if fail_if_empty && reviewed == 0 {
+                                    return Err(NoResults.into());
+                                }
+                                break 0;
                             }
+                            let out_of_time = time_box.is_some_and(|HumanDuration(t)| {
+                                started.elapsed() >= t.to_std().unwrap_or_default()
+                            });
+                            if limit.is_some_and(|l| reviewed >= l) || out_of_time {
+                                break reviewable_papers.len();
+                            }
+                            match select_paper(&repo, &reviewable_papers) {
+                                Some(p) => review(p)?,
+                                None => {
+                                    anyhow::bail!("No paper selected");
+                                }
+                            }
+                            reviewed += 1;
+                        };
+                        if reviewed > 0 || remaining > 0 {
+                            let took =
+                                chrono::Duration::from_std(started.elapsed()).unwrap_or_default();
+                            println!(
+                                "Reviewed {reviewed} papers in {}, {remaining} still due",
+                                HumanDuration(took)
+                            );
                         }
-                    },
+                    }
+                };
+            }
+            Self::Completions {
+                shell,
+                dir,
+                install,
+                stdout: to_stdout,
+            } => {
+                let shell = match shell {
+                    Some(shell) => shell,
+                    None => detect_shell()?,
+                };
+                if to_stdout {
+                    write_completions(shell, &mut stdout());
+                } else if install {
+                    let path = install_completions(shell)?;
+                    println!("Installed {shell} completions to {path:?}");
+                    if shell == Shell::Zsh {
+                        if let Some(parent) = path.parent() {
+                            println!("Make sure {parent:?} is in your fpath");
+                        }
+                    }
+                } else {
+                    let path = gen_completions(shell, &dir)?;
+                    info!(?path, ?shell, "Generated completions");
+                }
+            }
+            Self::Man { dir } => {
+                for path in gen_man_pages(&dir)? {
+                    info!(?path, "Generated man page");
+                }
+            }
+            Self::Help { name } => println!("{}", render_help(name.as_deref())?),
+            Self::Inbox { triage } => {
+                let tags = vec![Tag::new(INBOX_TAG)];
+                let command = if triage {
+                    Self::Triage {
+                        title: None,
+                        authors: Vec::new(),
+                        tags,
+                        labels: Vec::new(),
+                    }
+                } else {
+                    Self::List {
+                        file: None,
+                        title: None,
+                        authors: Vec::new(),
+                        tags,
+                        labels: Vec::new(),
+                        source: None,
+                        time: TimeFilters::default(),
+                        title_re: None,
+                        file_re: None,
+                        author_re: None,
+                        has_notes: false,
+                        no_notes: false,
+                        query: None,
+                        sort: Some(SortBy::CreatedAt),
+                        format: None,
+                        fail_if_empty: false,
+                    }
                 };
+                command.execute(config, config_file, output, progress, cancel)?;
             }
-            Self::Completions { shell, dir } => {
-                let path = gen_completions(shell, &dir);
-                info!(?path, ?shell, "Generated completions");
+            Self::Status { short } => {
+                let repo = load_repo(config, cancel)?;
+                let papers = repo.all_papers()?;
+                let status = Status::of(papers.iter().map(|p| &p.meta), today());
+                if short {
+                    println!("{}", status.short());
+                } else {
+                    output.print(&status)?;
+                }
             }
-            Self::Import { file } => {
-                let papers = match file {
+            Self::Triage {
+                title,
+                authors,
+                tags,
+                labels,
+            } => {
+                let mut repo = load_repo(config, cancel)?;
+                let mut papers =
+                    repo.list(None, title, authors, tags, labels, &RegexFilters::default())?;
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let total = papers.len();
+                'papers: for (i, paper) in papers.into_iter().enumerate() {
+                    cancel.check()?;
+                    let mut paper = repo.get_paper(&paper.path)?;
+                    print!("\n({}/{total}) {}", i + 1, TriageCard(&paper));
+                    loop {
+                        let Some(action) = TriageAction::from_key(input_key(TriageAction::PROMPT))
+                        else {
+                            continue;
+                        };
+                        match action {
+                            TriageAction::Tag => {
+                                let new_tags = input_vec::<Tag>("Tags", " ")?;
+                                clear_inbox(&mut paper.meta.tags);
+                                paper.meta.tags.extend(new_tags);
+                                repo.write_paper(&paper.path, paper.meta.clone(), &paper.notes)?;
+                            }
+                            TriageAction::Open => open_file(&paper.meta, repo.root())?,
+                            TriageAction::Quit => break 'papers,
+                            TriageAction::Skip => break,
+                            TriageAction::Queue | TriageAction::Archive => {
+                                if action.retag(&mut paper.meta.tags) {
+                                    repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            Self::Import {
+                file,
+                format,
+                calibre_tag,
+                no_inbox,
+                resume,
+            } => {
+                let detail = match &file {
                     FileOrStdin::File(path) => {
-                        let reader = File::open(path)?;
-                        let papers: Vec<PaperMeta> = serde_json::from_reader(reader)?;
-                        papers
+                        // only a Calibre library is a directory
+                        if path.is_dir() && !matches!(format, ImportFormat::Calibre) {
+                            anyhow::bail!("Path was not a file: {:?}", path);
+                        }
+                        path.to_string_lossy().into_owned()
+                    }
+                    FileOrStdin::Stdin => "stdin".to_owned(),
+                };
+                let repo = load_repo(config, cancel)?;
+                let now =
+                    chrono::NaiveDateTime::from_timestamp_opt(chrono::Utc::now().timestamp(), 0)
+                        .unwrap_or_default();
+                // existing papers to fill in from entries for the same work
+                let mut merges = Vec::new();
+                // documents to copy into the repo, by the position of their paper
+                let (papers, documents): (Vec<_>, Vec<_>) = match format {
+                    ImportFormat::Bundle => read_bundle(&file)?
+                        .into_iter()
+                        .map(|paper| (paper, None))
+                        .unzip(),
+                    ImportFormat::Calibre => {
+                        let FileOrStdin::File(library) = &file else {
+                            anyhow::bail!("Can't read a Calibre library from stdin");
+                        };
+                        calibre::read_library(library, &calibre_tag)?
+                            .into_iter()
+                            .map(|book| {
+                                let paper = BundlePaper {
+                                    path: None,
+                                    meta: book.meta(&calibre_tag),
+                                    notes: None,
+                                    attachments: None,
+                                };
+                                (paper, book.document().map(|d| library.join(d)))
+                            })
+                            .unzip()
                     }
-                    FileOrStdin::Stdin => {
-                        let reader = stdin();
-                        let papers: Vec<PaperMeta> = serde_json::from_reader(reader)?;
-                        papers
+                    ImportFormat::ScholarBibtex => {
+                        let mut bibtex = String::new();
+                        match &file {
+                            FileOrStdin::File(path) => {
+                                File::open(path)?.read_to_string(&mut bibtex)
+                            }
+                            FileOrStdin::Stdin => stdin().read_to_string(&mut bibtex),
+                        }
+                        .context("Reading BibTeX")?;
+                        let existing = repo.all_papers()?;
+                        let mut new = Vec::new();
+                        for entry in scholar::parse_bibtex(&bibtex) {
+                            let Some(paper) = existing.iter().find(|p| entry.is_paper(&p.meta))
+                            else {
+                                if let Some(similar) =
+                                    existing.iter().find(|p| entry.may_be_paper(&p.meta))
+                                {
+                                    warning!(
+                                        "{:?} may be the same work as {:?}, adding it separately",
+                                        entry.title,
+                                        similar.path
+                                    );
+                                }
+                                let paper = BundlePaper {
+                                    path: None,
+                                    meta: entry.meta(now),
+                                    notes: None,
+                                    attachments: None,
+                                };
+                                new.push((paper, None));
+                                continue;
+                            };
+                            if entry.merge_into(&mut paper.meta.clone(), now) {
+                                merges.push((paper.path.clone(), entry));
+                            } else {
+                                debug!(title = entry.title, path = ?paper.path, "Already have paper");
+                            }
+                        }
+                        new.into_iter().unzip()
                     }
                 };
-                let mut repo = load_repo(config)?;
-                for paper in papers {
-                    repo.import(paper)?;
+                // papers are identified by their position, so only resume on the same papers
+                let merging = merges.iter().map(|(path, _)| path).collect::<Vec<_>>();
+                let input = hash_bytes(&serde_json::to_vec(&(&papers, merging))?);
+                let checkpoint = Checkpoint::<()>::start(repo.root(), "import", input, resume)?;
+                let mut imported = Imported::default();
+                for (
+                    i,
+                    (
+                        BundlePaper {
+                            meta: mut paper,
+                            notes,
+                            ..
+                        },
+                        document,
+                    ),
+                ) in papers.into_iter().zip(documents).enumerate()
+                {
+                    cancel.check()?;
+                    if checkpoint.get(&i.to_string()).is_some() {
+                        debug!(i, "Skipping paper imported before being interrupted");
+                        continue;
+                    }
+                    if let Some(document) = document {
+                        match copy_local(&document, &repo, false, cancel) {
+                            Ok(path) => paper.filename = Some(repo.relative(&path)),
+                            Err(err) => {
+                                warning!("Failed to copy {:?} into the repo: {err}", document)
+                            }
+                        }
+                    }
+                    if !no_inbox {
+                        paper.tags.insert(Tag::new(INBOX_TAG));
+                    }
+                    paper.provenance = Some(Provenance::now(Source::Import, Some(detail.clone())));
+                    let path = repo.get_path(&paper);
+                    repo.write_paper(&path, paper.clone(), notes.as_deref().unwrap_or_default())?;
+                    checkpoint.record(i.to_string(), ());
                     info!("Added paper");
+                    imported.0.push(ImportedPaper {
+                        meta: paper,
+                        merged: false,
+                    });
+                }
+                for (path, entry) in merges {
+                    cancel.check()?;
+                    let key = format!("merge {}", path.display());
+                    if checkpoint.get(&key).is_some() {
+                        debug!(?path, "Skipping paper merged before being interrupted");
+                        continue;
+                    }
+                    let _lock = PaperLock::acquire(repo.root(), &path)?;
+                    let mut paper = repo.get_paper(&path)?;
+                    if entry.merge_into(&mut paper.meta, now) {
+                        repo.write_paper(&paper.path, paper.meta.clone(), &paper.notes)?;
+                        imported.0.push(ImportedPaper {
+                            meta: paper.meta,
+                            merged: true,
+                        });
+                    }
+                    checkpoint.record(key, ());
+                }
+                checkpoint.finish()?;
+                output.print(&imported)?;
+            }
+            Self::Watch {
+                dir,
+                auto,
+                copy,
+                tags,
+                interval: HumanDuration(interval),
+                once,
+            } => {
+                if !dir.is_dir() {
+                    anyhow::bail!("Path was not a directory: {:?}", dir);
+                }
+                // add only asks for the details it doesn't have at a terminal
+                let auto = auto || !atty::is(atty::Stream::Stdout);
+                let mut inbox = Inbox::new(&dir)?;
+                if !once {
+                    println!("Watching {dir:?} for new PDFs, press Ctrl-C to stop");
+                }
+                loop {
+                    let new = if once {
+                        pdfs(&dir)?.into_keys().collect()
+                    } else {
+                        match inbox.wait(interval.to_std()?, cancel) {
+                            Ok(new) => new,
+                            // stopping the watch is how it finishes
+                            Err(_) if cancel.is_cancelled() => break,
+                            Err(err) => return Err(err),
+                        }
+                    };
+                    for pdf in new {
+                        debug!(?pdf, "Found new pdf");
+                        let duplicate =
+                            load_repo(config, cancel).and_then(|repo| repo.find_duplicate(&pdf));
+                        let result = match duplicate {
+                            Ok(Some(existing)) => {
+                                warning!(
+                                    "{pdf:?} is a duplicate of {:?}, not adding it",
+                                    existing.path
+                                );
+                                Ok(())
+                            }
+                            Err(err) => Err(err),
+                            Ok(None) if auto => auto_add(config, &pdf, !copy, &tags, cancel)
+                                .and_then(|paper| output.print(&Added(vec![paper]))),
+                            Ok(None) => Self::Add {
+                                url: Some(UrlOrPath::Path(pdf.clone())),
+                                fetch: None,
+                                file: None,
+                                title: None,
+                                authors: Vec::new(),
+                                tags: tags.clone(),
+                                labels: Vec::new(),
+                                snapshot: false,
+                                doc_type: None,
+                                rfc: None,
+                                tech_report: None,
+                                doi: None,
+                                isbn: None,
+                                read_by: None,
+                                copy,
+                                move_file: !copy,
+                                force: false,
+                            }
+                            .execute(
+                                config,
+                                config_file,
+                                output,
+                                progress,
+                                cancel,
+                            ),
+                        };
+                        match result {
+                            Ok(()) => {}
+                            Err(err) if err.is::<Cancelled>() || cancel.is_cancelled() => {
+                                return Err(err)
+                            }
+                            Err(err) => warning!("Failed to add {:?}: {err}", pdf),
+                        }
+                    }
+                    if once {
+                        break;
+                    }
+                }
+            }
+            Self::Schema { kind } => {
+                println!("{}", serde_json::to_string_pretty(&kind.schema())?);
+            }
+            Self::Export {
+                dir,
+                tags,
+                anonymise,
+                format,
+            } => {
+                let mut repo = load_repo(config, cancel)?;
+                let papers = repo.list(
+                    None,
+                    None,
+                    Vec::new(),
+                    tags,
+                    Vec::new(),
+                    &RegexFilters::default(),
+                )?;
+                ensure_outside_repo(&repo, &dir)?;
+                create_dir_all(&dir)?;
+                let mut exported = 0;
+                for paper in papers {
+                    cancel.check()?;
+                    let LoadedPaper {
+                        path,
+                        mut meta,
+                        mut notes,
+                    } = repo.get_paper(&paper.path)?;
+                    if anonymise {
+                        notes = config.anonymise.anonymise(&mut meta, &notes);
+                    }
+                    let path = dir.join(repo.relative(&path));
+                    match format {
+                        ExportFormat::Notes => {
+                            if let Some(parent) = path.parent() {
+                                create_dir_all(parent)?;
+                            }
+                            std::fs::write(&path, render(&meta, &notes)?)?;
+                        }
+                        ExportFormat::Calibre => {
+                            // Calibre adds a directory as a book, reading its metadata.opf
+                            let book = path.with_extension("");
+                            create_dir_all(&book)?;
+                            std::fs::write(book.join(calibre::OPF_FILE), calibre::opf(&meta))?;
+                            let document = meta.filename.as_ref().map(|f| repo.root().join(f));
+                            if let Some(document) = document.filter(|d| d.is_file()) {
+                                if let Some(name) = document.file_name() {
+                                    std::fs::copy(&document, book.join(name))?;
+                                }
+                            }
+                        }
+                    }
+                    debug!(?path, "Exported paper");
+                    exported += 1;
+                }
+                println!("Exported {exported} papers to {dir:?}");
+            }
+            Self::Publish {
+                dir,
+                generator,
+                anonymise,
+            } => {
+                let mut repo = load_repo(config, cancel)?;
+                let papers = repo.list(
+                    None,
+                    None,
+                    Vec::new(),
+                    vec![Tag::new(PUBLIC_TAG)],
+                    Vec::new(),
+                    &RegexFilters::default(),
+                )?;
+                ensure_outside_repo(&repo, &dir)?;
+                create_dir_all(&dir)?;
+                // pages that would overwrite each other, such as on a case-insensitive filesystem
+                let mut pages = BTreeMap::new();
+                for paper in &papers {
+                    if let Some(other) = pages.insert(collision_key(&paper.path), &paper.path) {
+                        anyhow::bail!(
+                            "Not publishing, {:?} and {other:?} would be the same page",
+                            paper.path
+                        );
+                    }
+                }
+                let mut published = 0;
+                for paper in papers {
+                    cancel.check()?;
+                    let LoadedPaper {
+                        path,
+                        mut meta,
+                        mut notes,
+                    } = repo.get_paper(&paper.path)?;
+                    if anonymise {
+                        notes = config.anonymise.anonymise(&mut meta, &notes);
+                    }
+                    // laid out as in the repo so that notes with the same name don't collide
+                    let path = dir.join(path);
+                    if let Some(parent) = path.parent() {
+                        create_dir_all(parent)?;
+                    }
+                    std::fs::write(&path, generator.page(&meta, &notes)?)?;
+                    debug!(?path, "Published paper");
+                    published += 1;
                 }
+                println!("Published {published} papers to {dir:?}");
             }
-            Self::Doctor { fix } => {
-                let repo = load_repo(config)?;
+            Self::Share {
+                path,
+                with_meta,
+                anonymise,
+            } => {
+                let repo = load_repo(config, cancel)?;
+                let paper = get_or_select_paper(&repo, path.as_deref())?;
+                let LoadedPaper {
+                    path,
+                    mut meta,
+                    mut notes,
+                } = repo.get_paper(&paper.path)?;
+                if anonymise {
+                    notes = config.anonymise.anonymise(&mut meta, &notes);
+                }
+                let content = render_shared(&meta, &notes, with_meta);
+                let http = HttpClient::new(&config.http)?;
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                let url = config.share.upload(&http, &name, &content)?;
+                println!("{url}");
+            }
+            Self::ExportJson {
+                full,
+                tags,
+                anonymise,
+            } => {
+                let mut repo = load_repo(config, cancel)?;
+                let mut papers = repo.list(
+                    None,
+                    None,
+                    Vec::new(),
+                    tags,
+                    Vec::new(),
+                    &RegexFilters::default(),
+                )?;
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let mut exported = Vec::new();
+                for paper in papers {
+                    cancel.check()?;
+                    let LoadedPaper {
+                        path,
+                        mut meta,
+                        mut notes,
+                    } = if full {
+                        repo.get_paper(&paper.path)?
+                    } else {
+                        paper
+                    };
+                    if anonymise {
+                        notes = config.anonymise.anonymise(&mut meta, &notes);
+                    }
+                    let attachments = full.then(|| attachments(&meta, repo.root()));
+                    exported.push(BundlePaper {
+                        path: Some(path),
+                        meta,
+                        notes: full.then_some(notes),
+                        attachments,
+                    });
+                }
+                serde_json::to_writer_pretty(stdout().lock(), &exported)?;
+                println!();
+            }
+            Self::VerifyExport { file, tags } => {
+                let bundle = read_bundle(&file)?;
+                let mut repo = load_repo(config, cancel)?;
+                let papers = repo.list(
+                    None,
+                    None,
+                    Vec::new(),
+                    tags,
+                    Vec::new(),
+                    &RegexFilters::default(),
+                )?;
+                // notes are only loaded when there are some to compare with
+                let papers = if bundle.iter().any(|p| p.notes.is_some()) {
+                    papers
+                        .into_iter()
+                        .map(|p| repo.get_paper(&p.path))
+                        .collect::<anyhow::Result<Vec<_>>>()?
+                } else {
+                    papers
+                };
+                let differences =
+                    BundleDifferences::of(&bundle, &papers, repo.root(), |m| repo.get_path(m));
+                if differences.0.is_empty() {
+                    println!("Export matches the repo, {} papers", papers.len());
+                } else {
+                    output.print(&differences)?;
+                    error!(
+                        "Export differs from the repo in {} places",
+                        differences.0.len()
+                    );
+                    return Err(NoResults.into());
+                }
+            }
+            Self::Doctor {
+                fix,
+                interactive,
+                refetch,
+                strict,
+                untouched_days,
+            } => {
+                let mut fixer = Fixer::new(fix, interactive);
+                let repo = load_repo(config, cancel)?;
+                let template = load_notes_template(config)?;
+                let now = chrono::Utc::now().naive_utc();
+                let registry = if strict {
+                    Some(TagRegistry::load(repo.root())?)
+                } else {
+                    None
+                };
+                let http = HttpClient::new(&config.http)?;
                 let root = repo.root();
-                let entries = read_dir(&root)?;
+                let entries = read_dir(root)?;
                 let mut other_files = BTreeMap::new();
+                let mut expected_paths = BTreeMap::new();
+                let mut problems = Problems::default();
+                // papers missing their documents, by the index of their problem
+                let mut missing = Vec::new();
                 let mut paths = Vec::new();
                 for entry in entries {
                     let entry = entry?;
                     let path = entry.path();
-                    if path.is_file() {
+                    if path.is_file() && !repo.is_ignored(&path) {
                         paths.push(path);
                     }
                 }
                 paths.sort();
 
+                let report = Progress::start(progress, "doctor", paths.len());
                 for path in paths {
+                    cancel.check()?;
+                    report.item(path.strip_prefix(root).unwrap_or(&path).display());
                     if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                        if !repo.is_paper(&path) {
+                            debug!(?path, "Skipping notes that aren't a paper");
+                            continue;
+                        }
                         let paper = repo
                             .get_paper(&path)
                             .with_context(|| format!("Loading paper at {:?}", path))?;
                         let expected_path = repo.get_path(&paper.meta);
-                        let current_path = path.strip_prefix(&root).unwrap();
+                        let current_path = path.strip_prefix(root).unwrap();
                         debug!(?expected_path, ?current_path, "Checking paper path");
+                        // papers that would share a file on a case-insensitive filesystem
+                        if let Some(other) = expected_paths
+                            .insert(collision_key(&expected_path), current_path.to_owned())
+                        {
+                            problems.0.push(Problem {
+                                kind: ProblemKind::NotesCollision,
+                                path: current_path.to_owned(),
+                                other: Some(other),
+                                detail: None,
+                                fixed: false,
+                            });
+                            // its document still belongs to it, not an unmatched file
+                            if let Some(filename) = paper.meta.filename.as_ref() {
+                                if root.join(filename).is_file() {
+                                    other_files.insert(filename.clone(), true);
+                                }
+                            }
+                            continue;
+                        }
+                        // check that the url is valid and in its canonical form
+                        if let Some(url) = paper.meta.url.as_deref() {
+                            match normalise_url(url) {
+                                Err(err) => problems.0.push(Problem {
+                                    kind: ProblemKind::InvalidUrl,
+                                    path: current_path.to_owned(),
+                                    other: None,
+                                    detail: Some(format!("url={url:?}, error={err:?}")),
+                                    fixed: false,
+                                }),
+                                Ok(normal) => {
+                                    let normal = upgrade_https(&http, &normal).unwrap_or(normal);
+                                    if Url::parse(url).ok().as_ref() != Some(&normal) {
+                                        let mut problem = Problem {
+                                            kind: ProblemKind::UrlNotNormalised,
+                                            path: current_path.to_owned(),
+                                            other: None,
+                                            detail: Some(normal.to_string()),
+                                            fixed: false,
+                                        };
+                                        if fixer.should_fix(&problem) {
+                                            let mut paper = paper.clone();
+                                            paper.meta.url = Some(normal.to_string());
+                                            repo.write_paper(
+                                                current_path,
+                                                paper.meta,
+                                                &paper.notes,
+                                            )?;
+                                            problem.fixed = true;
+                                        }
+                                        problems.0.push(problem);
+                                    }
+                                }
+                            }
+                        }
+
+                        // check that the tags are ones meant to be used
+                        if let Some(registry) = &registry {
+                            for tag in &paper.meta.tags {
+                                if !registry.contains(tag) {
+                                    problems.0.push(Problem {
+                                        kind: ProblemKind::UnregisteredTag,
+                                        path: current_path.to_owned(),
+                                        other: None,
+                                        detail: Some(tag.key().to_owned()),
+                                        fixed: false,
+                                    });
+                                }
+                            }
+                        }
+
+                        // check that something has been written since the paper was added
+                        let age = now - paper.meta.created_at;
+                        if untouched_days
+                            .is_some_and(|d| paper.meta.created_at + Days::new(d) <= now)
+                            && !template.has_notes(&paper.notes)
+                        {
+                            problems.0.push(Problem {
+                                kind: ProblemKind::UntouchedNotes,
+                                path: current_path.to_owned(),
+                                other: None,
+                                detail: Some(age.num_days().to_string()),
+                                fixed: false,
+                            });
+                        }
+
                         // check that the paper notes are at the right location
                         if expected_path != current_path {
-                            println!(
-                                "Paper notes at wrong path. current={:?}, expected={:?}",
-                                current_path, expected_path
-                            );
-                            if fix {
-                                println!(
-                                    "Moving paper notes. current={:?}, expected={:?}",
-                                    current_path, expected_path
-                                );
-                                rename(root.join(current_path), root.join(&expected_path))?;
+                            let mut problem = Problem {
+                                kind: ProblemKind::NotesWrongPath,
+                                path: current_path.to_owned(),
+                                other: Some(expected_path.clone()),
+                                detail: None,
+                                fixed: false,
+                            };
+                            if fixer.should_fix(&problem) {
+                                repo.rename(&root.join(current_path), &root.join(&expected_path))?;
+                                problem.fixed = true;
                             }
+                            problems.0.push(problem);
                         }
 
                         // check that the paper's file exists
                         if let Some(filename) = paper.meta.filename.as_ref() {
-                            let abs_filename = root.join(&filename);
+                            let abs_filename = root.join(filename);
                             if !abs_filename.is_file() {
-                                println!(
-                                    "File is not at the named location. current={:?}, filename={:?}",
-                                    current_path,
-                                    filename,
-                                );
+                                missing.push((problems.0.len(), paper.clone()));
+                                problems.0.push(Problem {
+                                    kind: ProblemKind::MissingFile,
+                                    path: current_path.to_owned(),
+                                    other: Some(filename.clone()),
+                                    detail: None,
+                                    fixed: false,
+                                });
                             } else {
                                 other_files.insert(filename.clone(), true);
 
+                                // check that the contents look like a complete document
+                                match check_file(&abs_filename) {
+                                    Ok(Some(file_problem)) => {
+                                        let url = paper
+                                            .meta
+                                            .url
+                                            .as_deref()
+                                            .and_then(|u| Url::parse(u).ok());
+                                        let mut refetched = None;
+                                        if let (true, Some(url)) = (refetch, url) {
+                                            match fetch_url(
+                                                &http,
+                                                &url,
+                                                &abs_filename,
+                                                config.institutional_proxy.as_deref(),
+                                                // replacing the corrupt document
+                                                ExistingFile::Overwrite,
+                                                cancel,
+                                            ) {
+                                                Ok((path, mirror)) => {
+                                                    if mirror.is_some() {
+                                                        let mut paper =
+                                                            repo.get_paper(&paper.path)?;
+                                                        record_mirror(
+                                                            &mut paper.meta.labels,
+                                                            mirror,
+                                                        );
+                                                        repo.write_paper(
+                                                            &paper.path,
+                                                            paper.meta,
+                                                            &paper.notes,
+                                                        )?;
+                                                    }
+                                                    refetched = Some(path)
+                                                }
+                                                Err(err) => {
+                                                    warn!(%err, %url, "Failed to fetch document again")
+                                                }
+                                            }
+                                        }
+                                        problems.0.push(Problem {
+                                            kind: ProblemKind::CorruptFile,
+                                            path: filename.clone(),
+                                            other: None,
+                                            detail: Some(file_problem.to_string()),
+                                            fixed: refetched.is_some(),
+                                        });
+                                        // fetching may have given the document a new extension
+                                        if let Some(path) = refetched.filter(|p| p != &abs_filename)
+                                        {
+                                            repo.update(&paper, Some(&path))?;
+                                            continue;
+                                        }
+                                    }
+                                    Ok(None) => {}
+                                    Err(err) => {
+                                        warn!(%err, ?abs_filename, "Failed to check document")
+                                    }
+                                }
+
                                 // check if it should be renamed
                                 let expected_path_document = expected_path
                                     .with_extension(abs_filename.extension().unwrap_or_default());
                                 if filename != &expected_path_document {
-                                    println!(
-                                        "File at wrong path. current={:?}, expected={:?}",
-                                        filename, expected_path_document
-                                    );
-                                    if fix {
-                                        println!(
-                                            "Moving file. current={:?}, expected={:?}",
-                                            filename, expected_path_document
-                                        );
+                                    let mut problem = Problem {
+                                        kind: ProblemKind::FileWrongPath,
+                                        path: filename.clone(),
+                                        other: Some(expected_path_document.clone()),
+                                        detail: None,
+                                        fixed: false,
+                                    };
+                                    if fixer.should_fix(&problem) {
                                         let expected_path_document =
-                                            root.join(expected_path_document);
+                                            root.join(&expected_path_document);
                                         // logic from rename-files
-                                        rename(
-                                            root.join(filename),
-                                            root.join(&expected_path_document),
+                                        repo.rename(
+                                            &root.join(filename),
+                                            &root.join(&expected_path_document),
                                         )?;
                                         repo.update(&paper, Some(&expected_path_document))?;
+                                        problem.fixed = true;
                                     }
+                                    problems.0.push(problem);
                                 }
                             }
                         }
-                    } else {
-                        other_files
-                            .entry(path.strip_prefix(root).unwrap().to_owned())
-                            .or_default();
+                    } else {
+                        other_files
+                            .entry(path.strip_prefix(root).unwrap().to_owned())
+                            .or_default();
+                    }
+                }
+                report.finish();
+
+                // unmatched files may be the documents of papers missing them, moved or renamed
+                let mut candidates = other_files
+                    .iter()
+                    .filter(|(_, matched)| !**matched)
+                    .map(|(path, _)| Candidate {
+                        path: path.clone(),
+                        title: extract_title(&root.join(path)).map(|t| t.title),
+                    })
+                    .collect::<Vec<_>>();
+                for (index, paper) in missing {
+                    let Some(found) = find_relink(&paper.meta, &candidates).map(Path::to_owned)
+                    else {
+                        continue;
+                    };
+                    let problem = &mut problems.0[index];
+                    problem.detail = Some(found.to_string_lossy().into_owned());
+                    if fixer.should_fix(problem) {
+                        repo.update(&paper, Some(&root.join(&found)))?;
+                        problem.fixed = true;
+                        other_files.insert(found.clone(), true);
+                        candidates.retain(|c| c.path != found);
+                    }
+                }
+
+                for (path, matched) in other_files {
+                    if !matched {
+                        problems.0.push(Problem {
+                            kind: ProblemKind::UnmatchedFile,
+                            path,
+                            other: None,
+                            detail: None,
+                            fixed: false,
+                        });
+                    }
+                }
+
+                // papers added twice under different names, merged into the first added
+                for group in repo.find_duplicates()? {
+                    let keep = group[first_added(&group)].path.clone();
+                    let start = problems.0.len();
+                    let mut merge = Vec::new();
+                    for paper in group.into_iter().filter(|p| p.path != keep) {
+                        let problem = Problem {
+                            kind: ProblemKind::DuplicateFile,
+                            path: paper.path.clone(),
+                            other: Some(keep.clone()),
+                            detail: None,
+                            fixed: false,
+                        };
+                        if fixer.should_fix(&problem) {
+                            merge.push(paper.path);
+                        }
+                        problems.0.push(problem);
+                    }
+                    if !merge.is_empty() {
+                        merge_duplicates(&repo, &keep, &merge, &template)?;
+                        for problem in &mut problems.0[start..] {
+                            problem.fixed = merge.contains(&problem.path);
+                        }
+                    }
+                }
+                output.print(&problems)?;
+            }
+            Self::Dedupe { yes } => {
+                let repo = load_repo(config, cancel)?;
+                let template = load_notes_template(config)?;
+                let groups = repo.find_duplicates()?;
+                if groups.is_empty() {
+                    println!("No duplicate documents found");
+                }
+                for group in groups {
+                    cancel.check()?;
+                    let keep = if yes {
+                        first_added(&group)
+                    } else {
+                        println!("Papers with the same document:");
+                        for (i, paper) in group.iter().enumerate() {
+                            println!(
+                                "  {}. {} {:?} added {}",
+                                i + 1,
+                                paper.meta.title,
+                                paper.path,
+                                paper.meta.created_at.date()
+                            );
+                        }
+                        let default = (first_added(&group) + 1).to_string();
+                        match input_default::<usize>(
+                            "Number of the paper to keep, 0 to skip",
+                            &default,
+                        )? {
+                            n if (1..=group.len()).contains(&n) => n - 1,
+                            _ => continue,
+                        }
+                    };
+                    let keep = &group[keep].path;
+                    let others = group
+                        .iter()
+                        .map(|p| p.path.clone())
+                        .filter(|p| p != keep)
+                        .collect::<Vec<_>>();
+                    for trashed in merge_duplicates(&repo, keep, &others, &template)? {
+                        println!(
+                            "Merged {:?} into {keep:?}, moved to the trash as {}",
+                            trashed.path, trashed.name
+                        );
+                    }
+                }
+            }
+            Self::CheckUpdates { fetch, yes } => {
+                let repo = load_repo(config, cancel)?;
+                let http = HttpClient::new(&config.http)?;
+                let mut papers = repo
+                    .all_papers()?
+                    .into_iter()
+                    .filter_map(|p| Some((ArxivId::from_paper(&p.meta)?, p)))
+                    .collect::<Vec<_>>();
+                papers.sort_by(|a, b| a.1.path.cmp(&b.1.path));
+                let ids = papers
+                    .iter()
+                    .map(|(id, _)| id.id.clone())
+                    .collect::<Vec<_>>();
+                let latest = latest_versions(&http, &ids)?;
+
+                let mut updates = Updates::default();
+                let report = Progress::start(progress, "check-updates", papers.len());
+                for (id, paper) in papers {
+                    cancel.check()?;
+                    report.item(paper.path.display());
+                    let Some(&latest) = latest.get(&id.id) else {
+                        continue;
+                    };
+                    let latest_id = ArxivId {
+                        id: id.id.clone(),
+                        version: Some(latest),
+                    };
+                    // an unversioned url gives no version, so fall back to the one recorded
+                    let current = id.version.or_else(|| {
+                        let label = paper.meta.labels.get(ARXIV_LABEL)?.to_string();
+                        label.parse::<ArxivId>().ok()?.version
+                    });
+                    let Some(current) = current else {
+                        // nothing to compare with yet, so take the latest as what was fetched and
+                        // record it to compare with next time
+                        let mut paper = repo.get_paper(&paper.path)?;
+                        paper.meta.labels.insert(
+                            ARXIV_LABEL.to_owned(),
+                            Primitive::String(latest_id.to_string()),
+                        );
+                        repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                        continue;
+                    };
+                    if current >= latest {
+                        continue;
+                    }
+                    let mut fetched = false;
+                    if fetch
+                        && (yes
+                            || input_bool(
+                                &format!("Fetch {latest_id} for {}", paper.meta.title),
+                                true,
+                            )?)
+                    {
+                        let mut paper = repo.get_paper(&paper.path)?;
+                        let path = match &paper.meta.filename {
+                            Some(filename) => repo.root().join(filename),
+                            None => repo
+                                .root()
+                                .join(repo.get_path(&paper.meta).with_extension("pdf")),
+                        };
+                        let (path, mirror) = fetch_url(
+                            &http,
+                            &latest_id.pdf_url(),
+                            &path,
+                            config.institutional_proxy.as_deref(),
+                            // replacing the older version
+                            ExistingFile::Overwrite,
+                            cancel,
+                        )?;
+                        record_mirror(&mut paper.meta.labels, mirror);
+                        paper.meta.filename = Some(repo.relative(&path));
+                        paper.meta.url = Some(latest_id.abs_url().to_string());
+                        repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                        fetched = true;
+                    }
+                    updates.0.push(Update {
+                        path: paper.path,
+                        id: id.id,
+                        current: Some(current),
+                        latest,
+                        fetched,
+                    });
+                }
+                report.finish();
+                output.print(&updates)?;
+            }
+            Self::FetchMissing {
+                title,
+                authors,
+                tags,
+                labels,
+                jobs,
+                resume,
+            } => {
+                let mut repo = load_repo(config, cancel)?;
+                let http = HttpClient::new(&config.http)?;
+                if skip_offline(&http, "fetching documents") {
+                    return Ok(());
+                }
+                let mut papers = repo
+                    .list(None, title, authors, tags, labels, &RegexFilters::default())?
+                    .into_iter()
+                    .filter(is_missing)
+                    .collect::<Vec<_>>();
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let to_fetch = papers
+                    .iter()
+                    .map(|p| (&p.path, &p.meta.url))
+                    .collect::<Vec<_>>();
+                let input = hash_bytes(&serde_json::to_vec(&to_fetch)?);
+                let checkpoint =
+                    Checkpoint::<FetchedFile>::start(repo.root(), "fetch-missing", input, resume)?;
+                let targets = papers
+                    .iter()
+                    .map(|p| {
+                        repo.root()
+                            .join(repo.get_path(&p.meta).with_extension("pdf"))
+                    })
+                    .collect::<Vec<_>>();
+                let report = Progress::start(progress, "fetch-missing", papers.len());
+                let results = map_concurrently(
+                    papers.iter().zip(targets).collect(),
+                    jobs,
+                    |(paper, path)| -> anyhow::Result<_> {
+                        cancel.check()?;
+                        report.item(paper.path.display());
+                        let item = paper.path.to_string_lossy().into_owned();
+                        if let Some(done) = checkpoint.get(&item) {
+                            let path = repo.root().join(&done.filename);
+                            if path.is_file() {
+                                debug!(?path, "Using document fetched before being interrupted");
+                                let mirror =
+                                    done.mirror.as_deref().and_then(|m| Url::parse(m).ok());
+                                return Ok((path, mirror));
+                            }
+                        }
+                        let url = paper.meta.url.as_deref().unwrap_or_default();
+                        let url = Url::parse(url).with_context(|| format!("Invalid url {url}"))?;
+                        let _permit = http.acquire(&url);
+                        let (path, mirror) = fetch_url(
+                            &http,
+                            &url,
+                            &path,
+                            config.institutional_proxy.as_deref(),
+                            // never replacing another paper's document
+                            ExistingFile::Rename,
+                            cancel,
+                        )?;
+                        checkpoint.record(
+                            item,
+                            FetchedFile {
+                                filename: repo.relative(&path),
+                                mirror: mirror.as_ref().map(Url::to_string),
+                            },
+                        );
+                        Ok((path, mirror))
+                    },
+                );
+                report.finish();
+                // leave the papers as they were so that resuming fetches the same list, keeping
+                // the checkpoint of the documents fetched so far
+                cancel.check()?;
+
+                let mut fetched = FetchedDocuments::default();
+                for (mut paper, result) in papers.into_iter().zip(results) {
+                    let url = paper.meta.url.clone().unwrap_or_default();
+                    match result {
+                        Ok((path, mirror)) => {
+                            let filename = repo.relative(&path);
+                            record_mirror(&mut paper.meta.labels, mirror);
+                            paper.meta.filename = Some(filename.clone());
+                            paper.meta.tags.remove(&Tag::new(TO_FETCH_TAG));
+                            repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                            fetched.0.push(FetchedDocument {
+                                path: paper.path,
+                                url,
+                                filename: Some(filename),
+                                error: None,
+                            });
+                        }
+                        Err(err) => fetched.0.push(FetchedDocument {
+                            path: paper.path,
+                            url,
+                            filename: None,
+                            error: Some(err.to_string()),
+                        }),
+                    }
+                }
+                // every fetched document is attached now
+                checkpoint.finish()?;
+                output.print(&fetched)?;
+            }
+            Self::Deadlines { within } => {
+                let repo = load_repo(config, cancel)?;
+                let today = today();
+                let mut deadlines = repo
+                    .all_papers()?
+                    .into_iter()
+                    .filter_map(|p| {
+                        Some(Deadline {
+                            days_left: days_left(&p.meta, today)?,
+                            read_by: p.meta.read_by?,
+                            title: p.meta.title,
+                            path: p.path,
+                        })
+                    })
+                    .filter(|d| d.days_left <= within)
+                    .collect::<Vec<_>>();
+                deadlines
+                    .sort_by(|a, b| a.read_by.cmp(&b.read_by).then_with(|| a.path.cmp(&b.path)));
+                output.print(&Deadlines(deadlines))?;
+            }
+            Self::Citations { command } => {
+                let mut repo = load_repo(config, cancel)?;
+                match command {
+                    CitationsCommands::Update { tags } => {
+                        let http = HttpClient::new(&config.http)?;
+                        if skip_offline(&http, "updating citations") {
+                            return Ok(());
+                        }
+                        let mut papers = repo.list(
+                            None,
+                            None,
+                            Vec::new(),
+                            tags,
+                            Vec::new(),
+                            &RegexFilters::default(),
+                        )?;
+                        papers.sort_by(|a, b| a.path.cmp(&b.path));
+                        let mut changes = FieldChanges::default();
+                        let report = Progress::start(progress, "citations-update", papers.len());
+                        for paper in papers {
+                            cancel.check()?;
+                            report.item(paper.path.display());
+                            let Some(id) = semantic_scholar_id(&paper.meta) else {
+                                continue;
+                            };
+                            let count = match fetch_citations(&http, &id) {
+                                Ok(count) => count,
+                                Err(err) => {
+                                    warning!("Failed to get citations for {:?}: {err}", paper.path);
+                                    continue;
+                                }
+                            };
+                            let mut paper = repo.get_paper(&paper.path)?;
+                            let from = citations(&paper.meta)
+                                .map(|c| c.to_string())
+                                .unwrap_or_default();
+                            set_citations(&mut paper.meta, count);
+                            repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                            changes.0.push(FieldChange {
+                                path: paper.path,
+                                field: format!("label.{CITATIONS_LABEL}"),
+                                from,
+                                to: count.to_string(),
+                            });
+                        }
+                        report.finish();
+                        output.print(&changes)?;
+                    }
+                    CitationsCommands::Trending { count } => {
+                        let mut trending = repo
+                            .all_papers()?
+                            .into_iter()
+                            .filter_map(|p| {
+                                Some(Trending {
+                                    increase: citation_increase(&p.meta)?,
+                                    citations: citations(&p.meta)?,
+                                    title: p.meta.title,
+                                    path: p.path,
+                                })
+                            })
+                            .filter(|t| t.increase > 0)
+                            .collect::<Vec<_>>();
+                        trending.sort_by(|a, b| {
+                            b.increase
+                                .cmp(&a.increase)
+                                .then_with(|| a.path.cmp(&b.path))
+                        });
+                        trending.truncate(count);
+                        output.print(&TrendingPapers(trending))?;
+                    }
+                }
+            }
+            Self::Log { paper, since } => {
+                let repo = load_repo(config, cancel)?;
+                let mut entries = repo.journal().entries()?;
+                if let Some(paper) = paper {
+                    entries = history(&entries, &repo.relative(&paper))
+                        .into_iter()
+                        .cloned()
+                        .collect();
+                }
+                if let Some(Since(since)) = since {
+                    entries.retain(|e| e.at >= since);
+                }
+                output.print(&History(entries))?;
+            }
+            Self::Sed {
+                field,
+                expression,
+                authors,
+                tags,
+                labels,
+                dry_run,
+            } => {
+                let mut repo = load_repo(config, cancel)?;
+                let mut papers =
+                    repo.list(None, None, authors, tags, labels, &RegexFilters::default())?;
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let field_name = field.to_possible_value().unwrap().get_name().to_owned();
+                let mut changes = FieldChanges::default();
+                for paper in papers {
+                    cancel.check()?;
+                    let mut paper = repo.get_paper(&paper.path)?;
+                    if let Some((from, to)) = field.apply(&expression, &mut paper.meta) {
+                        if !dry_run {
+                            repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                        }
+                        changes.0.push(FieldChange {
+                            path: paper.path,
+                            field: field_name.clone(),
+                            from,
+                            to,
+                        });
+                    }
+                }
+                output.print(&changes)?;
+            }
+            Self::Snapshot { command } => {
+                let repo = load_repo(config, cancel)?;
+                match command {
+                    SnapshotCommands::Create { name } => {
+                        let snapshot = repo.create_snapshot(name)?;
+                        output.print(&Snapshots(vec![SnapshotSummary::from(&snapshot)]))?;
+                    }
+                    SnapshotCommands::List => {
+                        let snapshots = repo.snapshots()?;
+                        output.print(&Snapshots(snapshots.iter().map(Into::into).collect()))?;
+                    }
+                    SnapshotCommands::Restore { name } => {
+                        let restored = repo
+                            .restore_snapshot(&name)?
+                            .into_iter()
+                            .map(|(path, action)| RestoredPaper { path, action })
+                            .collect();
+                        output.print(&RestoredPapers(restored))?;
+                    }
+                }
+            }
+            Self::OptimizePdfs { paths } => {
+                let repo = load_repo(config, cancel)?;
+                let mut papers = if paths.is_empty() {
+                    repo.all_papers()?
+                } else {
+                    paths
+                        .iter()
+                        .map(|p| repo.get_paper(p))
+                        .collect::<anyhow::Result<Vec<_>>>()?
+                };
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let command = config
+                    .optimize_command
+                    .as_deref()
+                    .unwrap_or(DEFAULT_OPTIMIZE_COMMAND);
+                let mut optimized = Optimized::default();
+                let report = Progress::start(progress, "optimize-pdfs", papers.len());
+                for paper in papers {
+                    cancel.check()?;
+                    report.item(paper.path.display());
+                    let Some(filename) = paper.meta.filename else {
+                        continue;
+                    };
+                    let path = repo.root().join(&filename);
+                    let is_pdf = path
+                        .extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+                    if !is_pdf {
+                        continue;
+                    }
+                    match optimize_pdf(command, &path) {
+                        Ok((before, after)) => {
+                            if after < before {
+                                let hash = repo.update_hash(&filename)?;
+                                let mut paper = repo.get_paper(&paper.path)?;
+                                paper.meta.sha256 = Some(hash);
+                                repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                            }
+                            optimized.0.push(OptimizedFile {
+                                filename,
+                                before,
+                                after,
+                            });
+                        }
+                        Err(err) => error!("Failed to optimize {filename:?}: {err}"),
+                    }
+                }
+                report.finish();
+                output.print(&optimized)?;
+            }
+            Self::Thumbnails { paths } => {
+                let repo = load_repo(config, cancel)?;
+                let all = paths.is_empty();
+                let mut papers = if all {
+                    repo.all_papers()?
+                } else {
+                    paths
+                        .iter()
+                        .map(|p| repo.get_paper(p))
+                        .collect::<anyhow::Result<Vec<_>>>()?
+                };
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let thumbnails = Thumbnails::new(&config.thumbnails, repo.root());
+                let mut rendered = DocumentThumbnails::default();
+                let mut keep = BTreeSet::new();
+                let mut unhashed = false;
+                let report = Progress::start(progress, "thumbnails", papers.len());
+                for paper in papers {
+                    cancel.check()?;
+                    report.item(paper.path.display());
+                    let Some(filename) = paper.meta.filename else {
+                        continue;
+                    };
+                    let path = repo.root().join(&filename);
+                    let is_pdf = path
+                        .extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+                    if !is_pdf {
+                        continue;
+                    }
+                    let hash = match hash_file(&path) {
+                        Ok(hash) => hash,
+                        Err(err) => {
+                            error!("Failed to hash {filename:?}: {err}");
+                            // its thumbnail can't be told apart from a stale one
+                            unhashed = true;
+                            continue;
+                        }
+                    };
+                    let result = thumbnails.get(&path, &hash);
+                    // keep the thumbnail of a document that failed to render rather than pruning it
+                    keep.insert(hash);
+                    match result {
+                        Ok((thumbnail, fresh)) => {
+                            rendered.0.push(DocumentThumbnail {
+                                filename,
+                                thumbnail,
+                                rendered: fresh,
+                            });
+                        }
+                        Err(err) => error!("Failed to render thumbnail of {filename:?}: {err}"),
+                    }
+                }
+                report.finish();
+                if all && !unhashed {
+                    let removed = thumbnails.prune(&keep)?;
+                    debug!(removed, "Removed stale thumbnails");
+                }
+                output.print(&rendered)?;
+            }
+            Self::Du { by, count } => {
+                let repo = load_repo(config, cancel)?;
+                let mut usages = Vec::new();
+                for paper in repo.all_papers()? {
+                    let Some(filename) = &paper.meta.filename else {
+                        continue;
+                    };
+                    match repo.root().join(filename).metadata() {
+                        Ok(metadata) => usages.push((paper, metadata.len())),
+                        Err(err) => debug!(?filename, %err, "Failed to get size of document"),
+                    }
+                }
+                match by {
+                    Some(by) => {
+                        let mut groups = BTreeMap::<String, UsageGroup>::new();
+                        for (paper, bytes) in &usages {
+                            for group in by.groups(&paper.meta) {
+                                let usage = groups.entry(group.clone()).or_insert(UsageGroup {
+                                    group,
+                                    papers: 0,
+                                    bytes: 0,
+                                });
+                                usage.papers += 1;
+                                usage.bytes += bytes;
+                            }
+                        }
+                        let mut groups = groups.into_values().collect::<Vec<_>>();
+                        groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.group.cmp(&b.group)));
+                        output.print(&UsageGroups(groups))?;
                     }
-                }
-
-                for (path, matched) in other_files {
-                    if !matched {
-                        println!("Found unmatched file {:?}", path);
+                    None => {
+                        let mut files = usages
+                            .into_iter()
+                            .filter_map(|(paper, bytes)| {
+                                Some(FileUsage {
+                                    filename: paper.meta.filename?,
+                                    path: paper.path,
+                                    bytes,
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        files.sort_by(|a, b| {
+                            b.bytes.cmp(&a.bytes).then(a.filename.cmp(&b.filename))
+                        });
+                        files.truncate(count);
+                        output.print(&FileUsages(files))?;
                     }
                 }
             }
-            Self::Tags { output, sort } => {
-                let repo = load_repo(config)?;
+            Self::Tags {
+                sort,
+                describe: true,
+            } => {
+                let repo = load_repo(config, cancel)?;
+                let registry = TagRegistry::load(repo.root())?;
+                let mut counts = registry
+                    .0
+                    .keys()
+                    .map(|tag| (tag.clone(), 0))
+                    .collect::<BTreeMap<_, _>>();
+                for tag in repo.all_papers()?.into_iter().flat_map(|p| p.meta.tags) {
+                    *counts.entry(tag.key().to_owned()).or_default() += 1;
+                }
+                let mut tags = counts
+                    .into_iter()
+                    .map(|(tag, count)| {
+                        let info = registry.0.get(&tag).cloned();
+                        TagDescription {
+                            registered: info.is_some(),
+                            description: info.as_ref().and_then(|i| i.description.clone()),
+                            usage: info.and_then(|i| i.usage),
+                            tag,
+                            count,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if sort {
+                    tags.sort_by_key(|t| t.count);
+                }
+                output.print(&TagDescriptions(tags))?;
+            }
+            Self::Tags { sort, .. } => {
+                let repo = load_repo(config, cancel)?;
                 let mut tag_counts = repo
-                    .all_papers()
+                    .all_papers()?
                     .into_iter()
-                    .map(|p| p.meta.tags)
-                    .flatten()
+                    .flat_map(|p| p.meta.tags)
                     .map(|t| t.key().to_owned())
                     .fold(TableCount::default(), |acc, t| acc.add(t));
                 if sort {
                     tag_counts.sort_by_count();
                 }
-                match output {
-                    OutputStyle::Table => {
-                        println!("{tag_counts}");
-                    }
-                    OutputStyle::Json => {
-                        serde_json::to_writer(stdout(), &tag_counts)?;
-                    }
-                    OutputStyle::Yaml => {
-                        serde_yaml::to_writer(stdout(), &tag_counts)?;
+                output.print(&tag_counts)?;
+            }
+            Self::Config {
+                command: ConfigCommands::Export { diff },
+            } => {
+                print!("{}", serde_yaml::to_string(&config.export(diff)?)?);
+            }
+            Self::Config {
+                command: ConfigCommands::Import { file },
+            } => {
+                let profile = match file {
+                    FileOrStdin::File(path) => serde_yaml::from_reader(File::open(path)?)?,
+                    FileOrStdin::Stdin => serde_yaml::from_reader(stdin())?,
+                };
+                // the config as saved, without any overrides from the command line
+                let new_config = Config::load(config_file)?.import(profile)?;
+                if let Some(parent) = config_file.parent() {
+                    create_dir_all(parent)?;
+                }
+                serde_yaml::to_writer(File::create(config_file)?, &new_config)?;
+                println!("Imported the config profile");
+            }
+            Self::Labels {
+                command: Some(LabelsCommands::Remap { key, map, dry_run }),
+                ..
+            } => {
+                let map = LabelMap::load(&map)?;
+                let repo = load_repo(config, cancel)?;
+                let mut paths = repo.paper_paths();
+                paths.sort();
+                let mut changes = FieldChanges::default();
+                let mut unmapped = BTreeMap::<String, usize>::new();
+                for paper in repo.load_papers(paths) {
+                    let mut paper = repo.get_paper(&paper.path)?;
+                    match map.remap(&key, &mut paper.meta) {
+                        Ok(Some((from, to))) => {
+                            if !dry_run {
+                                repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                            }
+                            changes.0.push(FieldChange {
+                                path: paper.path,
+                                field: format!("label.{key}"),
+                                from,
+                                to,
+                            });
+                        }
+                        Ok(None) => {}
+                        Err(value) => *unmapped.entry(value).or_default() += 1,
                     }
                 }
+                cancel.check()?;
+                output.print(&changes)?;
+                if !unmapped.is_empty() {
+                    let values = unmapped
+                        .iter()
+                        .map(|(value, count)| format!("{value:?} ({count})"))
+                        .collect::<Vec<_>>();
+                    warning!("Unmapped values for {key}: {}", values.join(", "));
+                }
             }
-            Self::Labels { output, sort } => {
-                let repo = load_repo(config)?;
+            Self::Labels { sort, .. } => {
+                let repo = load_repo(config, cancel)?;
                 let mut label_counts = repo
-                    .all_papers()
+                    .all_papers()?
                     .into_iter()
-                    .map(|p| p.meta.labels)
-                    .flatten()
+                    .flat_map(|p| p.meta.labels)
                     .map(|(k, v)| Label::new(&k, v).to_string())
                     .fold(TableCount::default(), |acc, t| acc.add(t.to_owned()));
                 if sort {
                     label_counts.sort_by_count();
                 }
-                match output {
-                    OutputStyle::Table => {
-                        println!("{label_counts}");
-                    }
-                    OutputStyle::Json => {
-                        serde_json::to_writer(stdout(), &label_counts)?;
-                    }
-                    OutputStyle::Yaml => {
-                        serde_yaml::to_writer(stdout(), &label_counts)?;
-                    }
-                }
+                output.print(&label_counts)?;
             }
-            Self::Authors { output, sort } => {
-                let repo = load_repo(config)?;
+            Self::Authors { sort } => {
+                let repo = load_repo(config, cancel)?;
                 let mut author_counts = repo
-                    .all_papers()
+                    .all_papers()?
                     .into_iter()
-                    .map(|p| p.meta.authors)
-                    .flatten()
+                    .flat_map(|p| p.meta.authors)
                     .map(|t| t.to_string())
                     .fold(TableCount::default(), |acc, t| acc.add(t.to_owned()));
                 if sort {
                     author_counts.sort_by_count();
                 }
-                match output {
-                    OutputStyle::Table => {
-                        println!("{author_counts}");
+                output.print(&author_counts)?;
+            }
+            Self::Vocab {
+                command: VocabCommands::Export,
+            } => {
+                let repo = load_repo(config, cancel)?;
+                let papers = repo.all_papers()?;
+                output.print(&Vocabulary::of(papers.iter().map(|p| &p.meta)))?;
+            }
+            Self::Board { by } => match by {
+                BoardBy::Status => {
+                    let repo = load_repo(config, cancel)?;
+                    let template = load_notes_template(config)?;
+                    let now = chrono::Utc::now().naive_utc();
+                    let mut papers = repo.all_papers()?;
+                    // notes tell papers being read apart from unread ones
+                    for paper in &mut papers {
+                        paper.notes = repo.get_paper(&paper.path)?.notes;
+                    }
+                    SortBy::default().sort(&mut papers, repo.root());
+                    let statuses = papers.iter().map(|p| PaperStatus::of(p, &template, now));
+                    output.print(&Board::of(papers.iter().zip(statuses)))?;
+                }
+            },
+            Self::Tree { by } => {
+                let repo = load_repo(config, cancel)?;
+                let mut papers = repo.all_papers()?;
+                SortBy::default().sort(&mut papers, repo.root());
+                output.print(&Tree::of(&papers, by))?;
+            }
+            Self::Report { month } => {
+                let repo = load_repo(config, cancel)?;
+                let template = load_notes_template(config)?;
+                let month = month.unwrap_or_else(|| Month::of(today()));
+                let mut papers = repo.all_papers()?;
+                // summaries come from the notes
+                for paper in &mut papers {
+                    paper.notes = repo.get_paper(&paper.path)?.notes;
+                }
+                SortBy::default().sort(&mut papers, repo.root());
+                let entries = repo.journal().entries()?;
+                output.print(&MonthlyReport::of(month, &papers, &entries, &template))?;
+            }
+            Self::Remove { path, with_file } => {
+                let repo = load_repo(config, cancel)?;
+                let paper = get_or_select_paper(&repo, path.as_deref())?;
+                // don't pull the notes out from under an editor
+                let _lock = PaperLock::acquire(repo.root(), &paper.path)?;
+                let trashed = repo.trash(&paper.path, with_file)?;
+                match &trashed.file {
+                    Some(file) => println!(
+                        "Moved {:?} and {:?} to the trash as {}",
+                        trashed.path, file, trashed.name
+                    ),
+                    None => println!("Moved {:?} to the trash as {}", trashed.path, trashed.name),
+                }
+            }
+            Self::Trash { command } => {
+                let repo = load_repo(config, cancel)?;
+                match command {
+                    TrashCommands::List => output.print(&TrashedPapers(repo.trashed()?))?,
+                    TrashCommands::Restore { name } => {
+                        let trashed = repo.restore_trashed(&name)?;
+                        println!("Restored {:?}", trashed.path);
+                    }
+                }
+            }
+            Self::Hypothesis { command } => {
+                let mut repo = load_repo(config, cancel)?;
+                match command {
+                    HypothesisCommands::Sync { tags } => {
+                        config.hypothesis.user()?;
+                        let http = HttpClient::new(&config.http)?;
+                        if skip_offline(&http, "syncing annotations") {
+                            return Ok(());
+                        }
+                        let mut papers = repo.list(
+                            None,
+                            None,
+                            Vec::new(),
+                            tags,
+                            Vec::new(),
+                            &RegexFilters::default(),
+                        )?;
+                        papers.sort_by(|a, b| a.path.cmp(&b.path));
+                        let mut synced = SyncedPapers::default();
+                        let report = Progress::start(progress, "hypothesis-sync", papers.len());
+                        for paper in papers {
+                            cancel.check()?;
+                            report.item(paper.path.display());
+                            let uris = paper_uris(&paper.meta);
+                            if uris.is_empty() {
+                                continue;
+                            }
+                            let annotations = match config.hypothesis.annotations(&http, &uris) {
+                                Ok(annotations) => annotations,
+                                Err(err) => {
+                                    warning!(
+                                        "Failed to get annotations for {:?}: {err}",
+                                        paper.path
+                                    );
+                                    continue;
+                                }
+                            };
+                            if annotations.is_empty() {
+                                continue;
+                            }
+                            // don't change the notes out from under an editor
+                            let _lock = PaperLock::acquire(repo.root(), &paper.path)?;
+                            let mut paper = repo.get_paper(&paper.path)?;
+                            let added = merge_highlights(&mut paper.notes, &annotations);
+                            if added > 0 {
+                                repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                                synced.0.push(SyncedPaper {
+                                    path: paper.path,
+                                    added,
+                                });
+                            }
+                        }
+                        report.finish();
+                        output.print(&synced)?;
                     }
-                    OutputStyle::Json => {
-                        serde_json::to_writer(stdout(), &author_counts)?;
+                }
+            }
+            Self::Readwise { command } => {
+                let mut repo = load_repo(config, cancel)?;
+                let (ReadwiseCommands::Push { tags } | ReadwiseCommands::Pull { tags }) = &command;
+                let mut papers = repo.list(
+                    None,
+                    None,
+                    Vec::new(),
+                    tags.clone(),
+                    Vec::new(),
+                    &RegexFilters::default(),
+                )?;
+                papers.sort_by(|a, b| a.path.cmp(&b.path));
+                let http = HttpClient::new(&config.http)?;
+                match command {
+                    ReadwiseCommands::Push { .. } => {
+                        if skip_offline(&http, "pushing highlights") {
+                            return Ok(());
+                        }
+                        let (mut pushed, mut from, mut failed) = (0, 0, 0);
+                        let report = Progress::start(progress, "readwise-push", papers.len());
+                        for paper in papers {
+                            cancel.check()?;
+                            report.item(paper.path.display());
+                            let paper = repo.get_paper(&paper.path)?;
+                            let highlights = highlights(&paper.notes)
+                                .into_iter()
+                                .filter(|h| !is_pulled(h))
+                                .collect::<Vec<_>>();
+                            if highlights.is_empty() {
+                                continue;
+                            }
+                            // one paper failing, e.g. being rate limited, doesn't stop the others
+                            if let Err(err) = config.readwise.push(&http, &paper.meta, &highlights)
+                            {
+                                warning!("Failed to push highlights from {:?}: {err}", paper.path);
+                                failed += 1;
+                                continue;
+                            }
+                            pushed += highlights.len();
+                            from += 1;
+                        }
+                        report.finish();
+                        println!("Pushed {pushed} highlights from {from} papers to Readwise");
+                        if failed > 0 {
+                            anyhow::bail!("Failed to push highlights from {failed} papers");
+                        }
                     }
-                    OutputStyle::Yaml => {
-                        serde_yaml::to_writer(stdout(), &author_counts)?;
+                    ReadwiseCommands::Pull { .. } => {
+                        if skip_offline(&http, "pulling highlights") {
+                            return Ok(());
+                        }
+                        let books = config.readwise.export(&http)?;
+                        let mut synced = SyncedPapers::default();
+                        for paper in papers {
+                            cancel.check()?;
+                            let pulled = books
+                                .iter()
+                                .filter(|b| b.is_paper(&paper.meta))
+                                .flat_map(Book::highlights)
+                                .collect::<Vec<_>>();
+                            if pulled.is_empty() {
+                                continue;
+                            }
+                            // don't change the notes out from under an editor
+                            let _lock = PaperLock::acquire(repo.root(), &paper.path)?;
+                            let mut paper = repo.get_paper(&paper.path)?;
+                            // passages already in the notes, such as those pushed from them
+                            let pulled = pulled
+                                .into_iter()
+                                .filter(|h| {
+                                    h.quote.as_deref().is_none_or(|q| !paper.notes.contains(q))
+                                })
+                                .collect::<Vec<_>>();
+                            let added = merge_highlights(&mut paper.notes, &pulled);
+                            if added > 0 {
+                                repo.write_paper(&paper.path, paper.meta, &paper.notes)?;
+                                synced.0.push(SyncedPaper {
+                                    path: paper.path,
+                                    added,
+                                });
+                            }
+                        }
+                        output.print(&synced)?;
                     }
                 }
             }
+            Self::Dblp { command } => match command {
+                DblpCommands::Import { author, tags } => {
+                    let mut repo = load_repo(config, cancel)?;
+                    let http = HttpClient::new(&config.http)?;
+                    if skip_offline(&http, "searching DBLP") {
+                        return Ok(());
+                    }
+                    let publications = author_publications(&http, &author)?;
+                    if publications.is_empty() {
+                        println!("No publications found for {author:?}");
+                        return Ok(());
+                    }
+                    let existing = repo.all_papers()?;
+                    for (i, publication) in publications.iter().enumerate() {
+                        let added = existing.iter().any(|p| publication.is_paper(&p.meta));
+                        println!(
+                            "{:>3}. {}{}",
+                            i + 1,
+                            publication,
+                            if added { " (already added)" } else { "" }
+                        );
+                    }
+                    let selected = input_selection("Publications to add", publications.len())?;
+                    // papers get the defaults of the current directory, as with add
+                    let dir = current_dir_in(&repo);
+                    let mut added = Vec::new();
+                    for publication in selected.into_iter().map(|i| &publications[i]) {
+                        let (mut tags, labels, notes) = with_dir_defaults(
+                            config,
+                            &repo,
+                            &dir,
+                            DocType::Paper,
+                            tags.iter().cloned(),
+                            publication.labels(),
+                        )?;
+                        tags.insert(Tag::new(INBOX_TAG));
+                        added.push(add(
+                            &mut repo,
+                            None::<PathBuf>,
+                            publication.url.clone(),
+                            publication.title.clone(),
+                            DocType::Paper,
+                            publication.authors.clone(),
+                            tags,
+                            labels,
+                            None,
+                            Provenance::now(
+                                Source::Import,
+                                Some(format!("dblp:{}", publication.key)),
+                            ),
+                            BTreeMap::new(),
+                            &notes,
+                        )?);
+                    }
+                    output.print(&Added(added))?;
+                }
+            },
         }
         Ok(())
     }
 }
 
-fn load_repo(config: &Config) -> anyhow::Result<Repo> {
+/// Error for when a command found nothing, or a check didn't pass, so scripts can tell it apart
+/// from success.
+#[derive(Debug)]
+pub struct NoResults;
+
+impl std::fmt::Display for NoResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No results")
+    }
+}
+
+impl std::error::Error for NoResults {}
+
+fn load_repo(config: &Config, cancel: &CancellationToken) -> anyhow::Result<Repo> {
     debug!(repo_dir=?config.default_repo, "Using default repo.");
     let repo_dir = config.default_repo.to_owned();
-    let repo = Repo::load(&repo_dir)?;
+    let mut repo = Repo::load(&repo_dir)?;
+    repo.set_cancellation(cancel.clone());
+    repo.set_require_marker(config.require_marker);
+    repo.set_fold_filters(!config.exact_filters);
     Ok(repo)
 }
 
+/// Look into how reviewing is going.
+#[derive(Debug, clap::Parser)]
+pub enum ReviewCommands {
+    /// List the papers due for review, most overdue first.
+    ///
+    /// Papers that have never been reviewed are always shown.
+    List {
+        /// Also show papers coming up for review within this many days.
+        #[clap(long, default_value = "0")]
+        within: i64,
+
+        /// Filters on when papers were added, changed and last reviewed.
+        #[clap(flatten)]
+        time: TimeFilters,
+
+        /// Exit with status 1 if no papers are due.
+        #[clap(long)]
+        fail_if_empty: bool,
+    },
+    /// Show how many times each paper has been reviewed, how the gaps between reviews grow, and
+    /// which papers are stuck at short intervals, along with how many reviews are due today and
+    /// in the weeks after.
+    ///
+    /// Review times come from the journal, so only reviews since it was started are counted.
+    Stats {
+        /// Number of weeks to count upcoming reviews for.
+        #[clap(long, default_value = "4")]
+        weeks: usize,
+
+        /// Filters on when papers were added, changed and last reviewed.
+        #[clap(flatten)]
+        time: TimeFilters,
+    },
+    /// Set when papers next come up for review, or stop them coming up at all.
+    #[clap(group = clap::ArgGroup::new("when").required(true))]
+    Schedule {
+        /// Papers to schedule, each a path, part of a title, a glob, or `@last-added` or
+        /// `@last-edited`.
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Date of the next review, e.g. 2023-08-01 or 2w from today.
+        #[clap(long, group = "when")]
+        on: Option<ReadBy>,
+
+        /// Never bring the papers up for review, e.g. for reference manuals.
+        #[clap(long, group = "when")]
+        never: bool,
+    },
+}
+
+/// Share settings between machines.
+#[derive(Debug, clap::Parser)]
+pub enum ConfigCommands {
+    /// Print the config as yaml to use on another machine, leaving out settings that are paths on
+    /// this machine, such as `default_repo`.
+    Export {
+        /// Only print the settings that differ from the defaults.
+        #[clap(long)]
+        diff: bool,
+    },
+    /// Apply settings exported from another machine to the config file, keeping the settings that
+    /// are paths on this machine.
+    Import {
+        /// Yaml file to read the settings from, or `-` for stdin.
+        file: FileOrStdin,
+    },
+}
+
+/// Manage labels.
+#[derive(Debug, clap::Parser)]
+pub enum LabelsCommands {
+    /// Rewrite the values of a label across the repo using a mapping file.
+    ///
+    /// The file is a yaml mapping from old values to new ones. Values that aren't in the mapping
+    /// are reported so they can be added.
+    Remap {
+        /// Key of the label to rewrite.
+        #[clap(long)]
+        key: String,
+
+        /// Yaml file mapping old values to new ones.
+        #[clap(long)]
+        map: PathBuf,
+
+        /// Print the changes but don't save them.
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+/// Track citation counts.
+#[derive(Debug, clap::Parser)]
+pub enum CitationsCommands {
+    /// Refresh citation counts into the `citations` label, keeping the previous count.
+    ///
+    /// Papers are looked up by their arXiv id, a `doi` label, a doi.org url, or their url.
+    Update {
+        /// Only update papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+    },
+    /// Show the papers whose citation counts grew the most in the last update.
+    Trending {
+        /// Number of papers to show.
+        #[clap(long, short = 'n', default_value = "10")]
+        count: usize,
+    },
+}
+
+/// Manage snapshots of the metadata of all papers.
+#[derive(Debug, clap::Parser)]
+pub enum SnapshotCommands {
+    /// Save the metadata of all papers, e.g. before running a bulk command.
+    Create {
+        /// Name for the snapshot, defaults to the current time.
+        #[clap()]
+        name: Option<String>,
+    },
+    /// List the saved snapshots.
+    List,
+    /// Put the metadata of papers back to how it was in a snapshot, keeping their notes.
+    Restore {
+        /// Name of the snapshot to restore.
+        #[clap()]
+        name: String,
+    },
+}
+
+/// Work with papers in the trash.
+#[derive(Debug, clap::Parser)]
+pub enum TrashCommands {
+    /// List the papers in the trash, oldest first.
+    List,
+    /// Put a paper in the trash back where it was, along with its document if it was removed too.
+    Restore {
+        /// Name of the paper in the trash, as given by `papers trash list`.
+        #[clap()]
+        name: String,
+    },
+}
+
+/// Work with annotations made on Hypothes.is.
+#[derive(Debug, clap::Parser)]
+pub enum HypothesisCommands {
+    /// Add the annotations made by the configured user on the urls and DOIs of papers to the
+    /// Highlights section of their notes, skipping those added before.
+    Sync {
+        /// Only sync papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+    },
+}
+
+/// Send highlights to Readwise and get back those made elsewhere.
+#[derive(Debug, clap::Parser)]
+pub enum ReadwiseCommands {
+    /// Send the highlights in the Highlights section of the notes of papers to Readwise, with
+    /// their comments as notes, leaving out those pulled from it.
+    Push {
+        /// Only push papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+    },
+    /// Add highlights made in Readwise, or the apps it syncs with, to the Highlights section of the
+    /// notes of the papers they match by url, DOI or title, skipping those added before.
+    Pull {
+        /// Only pull into papers that have all of the given tags.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+    },
+}
+
+/// Work with publications listed on DBLP.
+#[derive(Debug, clap::Parser)]
+pub enum DblpCommands {
+    /// List the publications of an author on DBLP, newest first, and add those picked as papers
+    /// without documents, with their venue, year and DOI as labels.
+    ///
+    /// Added papers are tagged `inbox` until they are triaged or reviewed.
+    Import {
+        /// Full name of the author, as DBLP lists them.
+        #[clap(long)]
+        author: String,
+
+        /// Tags to give the added papers.
+        #[clap(name = "tag", long, short)]
+        tags: Vec<Tag>,
+    },
+}
+
+/// Work with the vocabulary of the repo.
+#[derive(Debug, clap::Parser)]
+pub enum VocabCommands {
+    /// Print every tag, label key, label value and author with how many papers use them and when
+    /// the first and last of those papers were added, e.g. for dashboards or spotting drift.
+    Export,
+}
+
 /// Manage authors.
 #[derive(Debug, clap::Parser)]
 pub enum AuthorsCommands {
@@ -767,38 +3725,36 @@ pub enum AuthorsCommands {
     },
 }
 
-/// Fetch a url to a local file, returning the path to the fetch file.
-fn fetch_url(url: &Url, path: &Path) -> anyhow::Result<PathBuf> {
+/// Fetch a url to a local file, returning the path to the fetch file and the mirror it was
+/// fetched from if the url itself failed.
+///
+/// Publisher landing pages are mapped to their pdfs, going through the proxy if given.
+fn fetch_url(
+    client: &HttpClient,
+    url: &Url,
+    path: &Path,
+    proxy: Option<&str>,
+    existing: ExistingFile,
+    cancel: &CancellationToken,
+) -> anyhow::Result<(PathBuf, Option<Url>)> {
     let mut filename = path.to_owned();
 
-    if filename.exists() {
-        warn!(?filename, "Path already exists, try moving it");
-    }
-
-    debug!(user_agent = APP_USER_AGENT, "Building http client");
-    let client = match reqwest::blocking::Client::builder()
-        .user_agent(APP_USER_AGENT)
-        .build()
-    {
-        Ok(client) => client,
-        Err(err) => {
-            warn!(%err,"Failed to create http client.");
-            return Err(err.into());
-        }
-    };
-    info!(%url, "Fetching");
-    let mut res = match client
-        .get(url.clone())
-        .send()
-        .expect("Failed to get url")
-        .error_for_status()
-    {
-        Ok(res) => res,
-        Err(err) => {
-            warn!(%err, %url, "Failed to get resource.");
-            return Err(err.into());
+    let resolved = resolve_download_url(client, url, proxy)?;
+    let mut fetched = Err(anyhow::anyhow!("Nowhere to fetch {} from", resolved));
+    for url in client.with_mirrors(&resolved) {
+        info!(%url, "Fetching");
+        match client.get(&url) {
+            Ok(res) => {
+                fetched = Ok((url, res));
+                break;
+            }
+            Err(err) => {
+                warn!(%err, %url, "Failed to get resource.");
+                fetched = Err(err);
+            }
         }
-    };
+    }
+    let (url, mut res) = fetched?;
     let headers = res.headers();
     if let Some(content_type) = headers.get(reqwest::header::CONTENT_TYPE) {
         if content_type == "application/pdf" {
@@ -817,33 +3773,163 @@ fn fetch_url(url: &Url, path: &Path) -> anyhow::Result<PathBuf> {
         }
     }
 
-    let mut file = match File::create(&filename) {
-        Ok(file) => file,
-        Err(err) => {
-            warn!(%err, ?filename,"Failed to create file");
-            return Err(err.into());
-        }
-    };
+    let filename = download_target(&filename, existing)?;
     debug!(%url, ?filename, "Saving");
-    match std::io::copy(&mut res, &mut file) {
-        Ok(_) => {}
-        Err(err) => {
-            warn!(%err, ?filename, "Failed to copy from http response to file");
-            return Err(err.into());
-        }
-    };
+    if let Err(err) = copy_cancellable(&mut res, &filename, cancel) {
+        warn!(%err, ?filename, "Failed to copy from http response to file");
+        return Err(err);
+    }
     info!(%url, ?filename, "Fetched");
+    audit::record("download", &filename, None);
+    let mirror = (url != resolved).then_some(url);
+    Ok((filename, mirror))
+}
+
+/// Note in a paper's labels the mirror its document was fetched from, if any.
+fn record_mirror(labels: &mut BTreeMap<String, Primitive>, mirror: Option<Url>) {
+    if let Some(mirror) = mirror {
+        labels.insert(
+            MIRROR_LABEL.to_owned(),
+            Primitive::String(mirror.to_string()),
+        );
+    }
+}
+
+/// Where to save a download meant for `path`, deciding what to do if a file is already there.
+fn download_target(path: &Path, existing: ExistingFile) -> anyhow::Result<PathBuf> {
+    if !path.exists() {
+        return Ok(path.to_owned());
+    }
+    let existing = match existing {
+        ExistingFile::Ask if atty::is(atty::Stream::Stdin) => loop {
+            let prompt = format!("{path:?} already exists, [o]verwrite, [r]ename or [c]ancel");
+            match input_key(&prompt).map(|k| k.to_ascii_lowercase()) {
+                Some('o') => break ExistingFile::Overwrite,
+                Some('r') => break ExistingFile::Rename,
+                Some('c') | None => return Err(Cancelled.into()),
+                Some(_) => {}
+            }
+        },
+        ExistingFile::Ask => ExistingFile::Rename,
+        existing => existing,
+    };
+    if existing == ExistingFile::Overwrite {
+        warn!(?path, "Overwriting existing file");
+        Ok(path.to_owned())
+    } else {
+        let renamed = unique_path(path);
+        warning!("{path:?} already exists, saving to {renamed:?} instead");
+        Ok(renamed)
+    }
+}
+
+/// Fail if `dir`, which may not exist yet, is the repo root or inside it, so writing papers out
+/// there can't overwrite the repo's own files.
+fn ensure_outside_repo(repo: &Repo, dir: &Path) -> anyhow::Result<()> {
+    let absolute = std::path::absolute(dir)?;
+    let existing = absolute
+        .ancestors()
+        .find(|a| a.exists())
+        .unwrap_or(&absolute);
+    let resolved = existing
+        .canonicalize()?
+        .join(absolute.strip_prefix(existing)?);
+    let root = repo.root().canonicalize()?;
+    if resolved.starts_with(&root) {
+        anyhow::bail!("{dir:?} is inside the repo at {root:?}, choose a directory outside of it");
+    }
+    Ok(())
+}
+
+/// Copy, or move, a local file into the repo root, returning its new path.
+///
+/// Files already in the repo are used where they are.
+fn copy_local(
+    path: &Path,
+    repo: &Repo,
+    move_file: bool,
+    cancel: &CancellationToken,
+) -> anyhow::Result<PathBuf> {
+    if !path.exists() {
+        anyhow::bail!("Failed to find local file {path:?}");
+    }
+    if repo.in_root(path).is_ok() {
+        debug!(?path, "Local file is already in the repo");
+        return Ok(path.to_owned());
+    }
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Local file {path:?} has no name"))?;
+    let filename = repo.root().join(name);
+    if filename.exists() {
+        anyhow::bail!("Path {filename:?} already exists, try moving it");
+    }
+    if move_file {
+        debug!(?path, ?filename, "Moving local file into the repo");
+        if let Err(err) = rename(path, &filename) {
+            // renaming fails across filesystems, so fall back to copying
+            debug!(%err, "Failed to rename, copying instead");
+            copy_cancellable(&mut File::open(path)?, &filename, cancel)?;
+            remove_file(path)?;
+        }
+        audit::record("move", &filename, Some(path));
+    } else {
+        debug!(?path, ?filename, "Copying local file into the repo");
+        copy_cancellable(&mut File::open(path)?, &filename, cancel)?;
+        audit::record("copy", &filename, Some(path));
+    }
     Ok(filename)
 }
 
+/// Copy a reader into a file, stopping early if cancelled.
+///
+/// Data is written to a `.part` file first which is removed if the copy doesn't complete.
+fn copy_cancellable<R: Read>(
+    reader: &mut R,
+    path: &Path,
+    cancel: &CancellationToken,
+) -> anyhow::Result<()> {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+
+    let mut copy = || -> anyhow::Result<()> {
+        let mut file = File::create(&part)?;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            cancel.check()?;
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buf[..n])?;
+        }
+        file.sync_all()?;
+        rename(&part, path)?;
+        Ok(())
+    };
+    let result = copy();
+    if result.is_err() {
+        debug!(?part, "Removing partial file");
+        let _ = remove_file(&part);
+    }
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
 fn add<P: AsRef<Path>>(
     repo: &mut Repo,
     file: Option<P>,
     url: Option<String>,
     title: String,
+    doc_type: DocType,
     authors: Vec<Author>,
     tags: BTreeSet<Tag>,
     labels: BTreeSet<Label>,
+    read_by: Option<NaiveDate>,
+    provenance: Provenance,
+    extra: BTreeMap<String, serde_yaml::Value>,
+    notes: &str,
 ) -> anyhow::Result<PaperMeta> {
     if let Some(file) = file.as_ref() {
         let file = file.as_ref();
@@ -857,31 +3943,85 @@ fn add<P: AsRef<Path>>(
         labels_map.insert(label.key().to_owned(), label.value().to_owned());
     }
 
-    let paper = repo.add(file, url, title, authors, tags, labels_map)?;
+    let paper = repo.add(
+        file, url, title, doc_type, authors, tags, labels_map, read_by, provenance, extra, notes,
+    )?;
     info!(filename = ?paper.filename, "Added paper");
 
     Ok(paper)
 }
 
-fn extract_title(file: &Path) -> Option<String> {
-    if let Ok(pdf_file) = FileOptions::cached().open(file) {
-        debug!(?file, "Loaded pdf file");
-        if let Some(info) = pdf_file.trailer.info_dict.as_ref() {
-            debug!(?file, ?info, "Found the info dict");
-            // try and extract the title
-            if let Some(found_title) = &info.title {
-                debug!(?file, "Found title");
-                if let Ok(found_title) = found_title.to_string() {
-                    if !found_title.is_empty() {
-                        debug!(?file, title = found_title, "Setting auto title");
-                        return Some(found_title.trim().to_owned());
-                    }
-                }
-            }
-        }
-    }
-    warn!("Couldn't find a title in pdf metadata");
-    None
+/// Add the PDF at `pdf` without asking for its details, taking its title and authors from the
+/// document, moving it into the repo or copying it.
+fn auto_add(
+    config: &Config,
+    pdf: &Path,
+    move_file: bool,
+    tags: &[Tag],
+    cancel: &CancellationToken,
+) -> anyhow::Result<PaperMeta> {
+    let mut repo = load_repo(config, cancel)?;
+    let title = extract_title(pdf)
+        .map(|t| t.title)
+        .or_else(|| Some(pdf.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    let authors = extract_authors(pdf).into_iter().collect();
+    let file = copy_local(pdf, &repo, move_file, cancel)?;
+    // the document is added to the root of the repo
+    let (tags, labels, notes) = with_dir_defaults(
+        config,
+        &repo,
+        Path::new(""),
+        DocType::Paper,
+        tags.iter().chain(&config.paper_defaults.tags).cloned(),
+        config.paper_defaults.labels.iter().cloned(),
+    )?;
+    add(
+        &mut repo,
+        Some(file),
+        None,
+        title,
+        DocType::Paper,
+        authors,
+        tags,
+        labels,
+        None,
+        Provenance::now(Source::Add, Some(pdf.to_string_lossy().into_owned())),
+        BTreeMap::new(),
+        &notes,
+    )
+}
+
+/// The current directory relative to the root of the repo, the root if it is outside the repo.
+fn current_dir_in(repo: &Repo) -> PathBuf {
+    std::env::current_dir()
+        .ok()
+        .and_then(|d| repo.in_root(&d).ok())
+        .unwrap_or_default()
+}
+
+/// Tags, labels and notes for a new paper added into `dir` of the repo, with the defaults of that
+/// directory and those above it.
+fn with_dir_defaults(
+    config: &Config,
+    repo: &Repo,
+    dir: &Path,
+    doc_type: DocType,
+    tags: impl IntoIterator<Item = Tag>,
+    labels: impl IntoIterator<Item = Label>,
+) -> anyhow::Result<(BTreeSet<Tag>, BTreeSet<Label>, String)> {
+    let dir_defaults = DirDefaults::load(repo.root(), dir)?;
+    debug!(?dir, ?dir_defaults, "Loaded directory defaults");
+    let tags = tags.into_iter().chain(dir_defaults.tags).collect();
+    // the last of labels with the same key is kept, so given labels win over defaults
+    let labels = dir_defaults
+        .labels
+        .iter()
+        .map(|(key, value)| Label::new(key, value.clone()))
+        .chain(labels)
+        .collect();
+    let notes = notes_template(config, doc_type, dir_defaults.notes_template.as_deref())?;
+    Ok((tags, labels, notes))
 }
 
 fn extract_authors(file: &Path) -> BTreeSet<Author> {
@@ -936,18 +4076,27 @@ pub enum SortBy {
     CreatedAt,
     /// Sort by modification.
     ModifiedAt,
+    /// Sort by citation count, most cited first.
+    Citations,
+    /// Sort by how often and how recently papers were opened or edited, most first.
+    Frecency,
 }
 
-/// Output style for lists.
-#[derive(Debug, Default, Clone, ValueEnum)]
-pub enum OutputStyle {
-    /// Pretty table format.
-    #[default]
-    Table,
-    /// Json format.
-    Json,
-    /// Yaml format.
-    Yaml,
+impl SortBy {
+    /// Sort papers from the repo at `root` by this criterion.
+    pub fn sort(&self, papers: &mut [LoadedPaper], root: &Path) {
+        match self {
+            Self::Title => papers.sort_by(|a, b| a.meta.title.cmp(&b.meta.title)),
+            Self::CreatedAt => papers.sort_by_key(|p| p.meta.created_at),
+            Self::ModifiedAt => papers.sort_by_key(|p| p.meta.modified_at),
+            Self::Citations => papers.sort_by_key(|p| Reverse(citations(&p.meta))),
+            Self::Frecency => {
+                // papers never used stay in order of title
+                papers.sort_by(|a, b| a.meta.title.cmp(&b.meta.title));
+                Recent::load(root).order_by_frecency(papers, |p| p.path.clone());
+            }
+        }
+    }
 }
 
 /// Generate completions.
@@ -965,14 +4114,56 @@ where
     Ok(path)
 }
 
-fn edit(path: &Path) -> anyhow::Result<()> {
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_owned());
-    Command::new(editor).args([path.to_owned()]).status()?;
-    Ok(())
+/// Report that something is skipped because we are offline, returning whether it was.
+fn skip_offline(http: &HttpClient, what: &str) -> bool {
+    if http.is_offline() {
+        println!("Offline, skipping {what}");
+        true
+    } else {
+        false
+    }
+}
+
+/// Get the initial notes for a new document of the given type, from the template of its
+/// directory if it has one.
+fn notes_template(
+    config: &Config,
+    doc_type: DocType,
+    dir_template: Option<&Path>,
+) -> anyhow::Result<String> {
+    let mut notes = if let Some(path) = dir_template {
+        read_to_string(path).with_context(|| format!("Reading notes template {:?}", path))?
+    } else {
+        match &config.notes_template {
+            PathOrString::File(path) => {
+                let path = config.default_repo.join(path);
+                read_to_string(&path)
+                    .with_context(|| format!("Reading notes template {:?}", path))?
+            }
+            PathOrString::Content(content) => content.clone(),
+        }
+    };
+    match doc_type {
+        DocType::Talk => notes.push_str(TALK_NOTES_SECTION),
+        DocType::Book => notes.push_str(BOOK_NOTES_SECTION),
+        DocType::Paper => {}
+    }
+    Ok(notes)
+}
+
+/// The lines of the notes templates, to tell notes that have been written in apart from new ones.
+fn load_notes_template(config: &Config) -> anyhow::Result<NotesTemplate> {
+    // the template for talks has every line of the one for papers
+    let mut template = notes_template(config, DocType::Talk, None)?;
+    template.push_str(BOOK_NOTES_SECTION);
+    Ok(NotesTemplate::new(&template))
 }
 
 fn open_file(meta: &PaperMeta, root: &Path) -> anyhow::Result<()> {
-    if let Some(filename) = &meta.filename {
+    if let (DocType::Talk, Some(url)) = (meta.doc_type, &meta.url) {
+        info!(url, "Opening");
+        open::that_detached(url)?;
+    } else if let Some(filename) = &meta.filename {
         let path = root.join(filename);
         info!(?path, "Opening");
         open::that_detached(path)?;
@@ -982,18 +4173,21 @@ fn open_file(meta: &PaperMeta, root: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Whether a rename only changes the case of the name, which looks like the target already exists
+/// on case-insensitive filesystems.
+fn is_case_only_rename(from: &Path, to: &Path) -> bool {
+    from != to && collision_key(from) == collision_key(to)
+}
+
 fn get_or_select_paper(repo: &Repo, path: Option<&Path>) -> anyhow::Result<LoadedPaper> {
     match path {
-        Some(path) => repo.get_paper(path),
-        None => {
-            let all_papers = repo.all_papers();
-            match select_paper(&all_papers) {
-                Some(p) => Ok(p),
-                None => {
-                    anyhow::bail!("No paper selected");
-                }
+        Some(path) => resolve_paper(repo, path),
+        None => match select_repo_paper(repo) {
+            Some(p) => Ok(p),
+            None => {
+                anyhow::bail!("No paper selected");
             }
-        }
+        },
     }
 }
 
@@ -0,0 +1,65 @@
+use std::{
+    env,
+    fs::{create_dir_all, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use directories::BaseDirs;
+
+use crate::cli::Cli;
+
+/// Name of the binary that completions are generated for.
+const BIN_NAME: &str = "papers";
+
+/// Find the shell the user is running from the environment.
+pub fn detect_shell() -> anyhow::Result<Shell> {
+    Shell::from_env()
+        .ok_or_else(|| anyhow::anyhow!("Couldn't detect your shell, please give it explicitly"))
+}
+
+/// Write the completions for a shell to the given writer.
+pub fn write_completions(shell: Shell, writer: &mut dyn Write) {
+    generate(shell, &mut Cli::command(), BIN_NAME, writer);
+}
+
+/// Install completions for a shell into the standard per-user location, returning the path
+/// written.
+pub fn install_completions(shell: Shell) -> anyhow::Result<PathBuf> {
+    let path = install_path(shell)?;
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    let mut file = File::create(&path)?;
+    write_completions(shell, &mut file);
+    Ok(path)
+}
+
+/// The standard per-user location that a shell loads completions from.
+pub fn install_path(shell: Shell) -> anyhow::Result<PathBuf> {
+    let dirs =
+        BaseDirs::new().ok_or_else(|| anyhow::anyhow!("Couldn't find your home directory"))?;
+    let home = dirs.home_dir();
+    let xdg = |var: &str, default: &str| {
+        env::var_os(var)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(default))
+    };
+    let path = match shell {
+        Shell::Bash => match env::var_os("BASH_COMPLETION_USER_DIR") {
+            Some(dir) => PathBuf::from(dir).join("completions"),
+            None => xdg("XDG_DATA_HOME", ".local/share").join("bash-completion/completions"),
+        }
+        .join(BIN_NAME),
+        Shell::Fish => xdg("XDG_CONFIG_HOME", ".config")
+            .join("fish/completions")
+            .join(format!("{BIN_NAME}.fish")),
+        Shell::Zsh => home.join(".zfunc").join(format!("_{BIN_NAME}")),
+        shell => anyhow::bail!(
+            "Don't know where to install completions for {shell}, use --stdout instead"
+        ),
+    };
+    Ok(path)
+}
@@ -6,11 +6,21 @@ use std::path::PathBuf;
 
 use directories::ProjectDirs;
 use papers_core::label::Label;
+use papers_core::review::ReviewConfig;
 use papers_core::tag::Tag;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_yaml::{Mapping, Value};
 use tracing::debug;
 
+use crate::export::AnonymiseConfig;
+use crate::http::HttpConfig;
+use crate::hypothesis::HypothesisConfig;
+use crate::logging::LogConfig;
+use crate::readwise::ReadwiseConfig;
+use crate::share::ShareConfig;
+use crate::thumbnails::ThumbnailConfig;
+
 /// Default values for a paper.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PaperDefaults {
@@ -38,6 +48,245 @@ impl Default for PathOrString {
     }
 }
 
+/// What to do when a document being fetched would replace an existing file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingFile {
+    /// Ask at a terminal, otherwise save under a new name.
+    #[default]
+    Ask,
+    /// Save under a new name, e.g. `paper-1.pdf`.
+    Rename,
+    /// Replace the existing file.
+    Overwrite,
+}
+
+/// Documentation for each config key, nested keys are joined with a `.`.
+pub const CONFIG_KEYS: &[(&str, &str)] = &[
+    (
+        "default_repo",
+        "Directory of the default repo, if no db found in the parent directories.",
+    ),
+    (
+        "notes_template",
+        "Template for new notes, either `file: <path>` (absolute or relative to the default repo) or `content: <text>`.",
+    ),
+    ("paper_defaults.tags", "Tags to give new papers."),
+    ("paper_defaults.labels", "Labels to give new papers."),
+    (
+        "snapshot_command",
+        "Command used to convert web pages to documents when snapshotting, with `{url}` and `{output}` substituted.",
+    ),
+    (
+        "institutional_proxy",
+        "Proxy to fetch publisher pdfs through, with `{url}` substituted for the target.",
+    ),
+    (
+        "optimize_command",
+        "Command used to optimize pdfs, with `{input}` and `{output}` substituted, defaults to qpdf.",
+    ),
+    (
+        "require_marker",
+        "Only treat notes files with `papers: true` in their frontmatter as papers, so the repo can share a directory with other notes.",
+    ),
+    (
+        "exact_filters",
+        "Match tags, labels and authors in filters exactly, rather than ignoring case and accents.",
+    ),
+    (
+        "existing_file",
+        "What to do when a fetched document would replace an existing file: ask, rename or overwrite.",
+    ),
+    (
+        "label_columns",
+        "Labels or extra fields to show in their own columns when listing papers, e.g. `[year, venue]`.",
+    ),
+    (
+        "status_column",
+        "Show a status column when listing papers with glyphs for read, has a document, has notes and overdue for review.",
+    ),
+    (
+        "notes_column",
+        "Show a column with the number of words written in the notes of each paper, beyond the notes template, when listing papers.",
+    ),
+    (
+        "http.rate_limit_ms",
+        "Minimum time between requests to the same host, in milliseconds.",
+    ),
+    (
+        "http.max_concurrent_per_host",
+        "Most documents fetched from the same host at once when fetching in bulk.",
+    ),
+    (
+        "http.hosts",
+        "Limits for particular hosts by domain, overriding `rate_limit_ms` and `max_concurrent_per_host`, e.g. `{arxiv.org: {rate_limit_ms: 3000, max_concurrent: 1}}`.",
+    ),
+    (
+        "http.mirrors",
+        "Alternate hosts to fetch documents from, tried in order when a host fails, e.g. `{arxiv.org: [export.arxiv.org]}`. Only the hosts listed are ever tried.",
+    ),
+    ("http.cache", "Whether to cache metadata responses on disk."),
+    (
+        "http.cache_ttl_secs",
+        "How long cached responses stay fresh, in seconds.",
+    ),
+    (
+        "http.cache_dir",
+        "Directory to cache responses in, defaults to the user's cache directory.",
+    ),
+    (
+        "http.offline",
+        "Don't make any requests, only using cached responses.",
+    ),
+    (
+        "http.connect_timeout_secs",
+        "Timeout for establishing connections, in seconds.",
+    ),
+    (
+        "http.timeout_secs",
+        "Timeout for whole requests, including downloading the body, in seconds.",
+    ),
+    (
+        "logging.dir",
+        "Directory to write json log files to, file logging is disabled if not set.",
+    ),
+    (
+        "logging.rotation",
+        "How often to start a new log file: hourly, daily or never.",
+    ),
+    (
+        "logging.max_files",
+        "Number of log files to keep, older ones are deleted.",
+    ),
+    (
+        "logging.level",
+        "Filter for what gets logged to the file, in the same form as `RUST_LOG`.",
+    ),
+    (
+        "thumbnails.command",
+        "Command to render a png of the first page of a document, with `{input}` and `{output}` substituted, defaults to ghostscript.",
+    ),
+    (
+        "thumbnails.dir",
+        "Directory to keep thumbnails in, defaults to the user's cache directory.",
+    ),
+    (
+        "anonymise.labels",
+        "Keys of labels to remove when exporting with `--anonymise`.",
+    ),
+    (
+        "anonymise.tags",
+        "Tags to remove when exporting with `--anonymise`.",
+    ),
+    (
+        "anonymise.sections",
+        "Headings of notes sections to remove when exporting with `--anonymise`.",
+    ),
+    (
+        "anonymise.notes",
+        "Remove the notes entirely when exporting with `--anonymise`.",
+    ),
+    (
+        "share.service",
+        "Where `papers share` uploads notes to, `gist` or `paste`.",
+    ),
+    (
+        "share.url",
+        "Url to post notes to for the `paste` share service, which replies with their url.",
+    ),
+    (
+        "share.token_env",
+        "Environment variable holding the token to upload notes with, `GITHUB_TOKEN` for gists if not set.",
+    ),
+    (
+        "share.public",
+        "Whether shared gists are listed publicly rather than only visible with the link.",
+    ),
+    (
+        "hypothesis.user",
+        "Hypothes.is username whose annotations `papers hypothesis sync` adds to the notes.",
+    ),
+    (
+        "hypothesis.token_env",
+        "Environment variable holding the Hypothes.is api token, for private annotations, `HYPOTHESIS_TOKEN` if not set.",
+    ),
+    (
+        "readwise.token_env",
+        "Environment variable holding the Readwise api token, `READWISE_TOKEN` if not set.",
+    ),
+    (
+        "review.power",
+        "Power the days between two reviews are raised to for the wait until the next, 2 if not set.",
+    ),
+    (
+        "review.initial_interval_days",
+        "Days from reviewing a paper for the first time to its next review, 1 if not set.",
+    ),
+    (
+        "review.max_interval_days",
+        "Most days to wait between reviews, unlimited if not set.",
+    ),
+];
+
+/// Keys of the config that only make sense on the machine they were set on, left out of exported
+/// profiles and kept when importing them.
+pub const MACHINE_KEYS: &[&str] = &[
+    "default_repo",
+    "http.cache_dir",
+    "logging.dir",
+    "thumbnails.dir",
+];
+
+/// Remove a key, with nested keys joined with a `.`, from a config value.
+fn remove_key(value: &mut Value, key: &str) {
+    match key.split_once('.') {
+        Some((section, rest)) => {
+            if let Some(nested) = value.get_mut(section) {
+                remove_key(nested, rest);
+            }
+        }
+        None => {
+            if let Some(mapping) = value.as_mapping_mut() {
+                mapping.remove(key);
+            }
+        }
+    }
+}
+
+/// The parts of `value` that differ from `default`, `None` if there are none.
+fn diff(value: &Value, default: &Value) -> Option<Value> {
+    match (value, default) {
+        (Value::Mapping(value), Value::Mapping(default)) => {
+            let changed = value
+                .iter()
+                .filter_map(|(k, v)| match default.get(k) {
+                    Some(d) => diff(v, d).map(|v| (k.clone(), v)),
+                    None => Some((k.clone(), v.clone())),
+                })
+                .collect::<Mapping>();
+            (!changed.is_empty()).then_some(Value::Mapping(changed))
+        }
+        (value, default) => (value != default).then(|| value.clone()),
+    }
+}
+
+/// Write `other` over `value`, merging mappings key by key.
+fn merge(value: &mut Value, other: Value) {
+    match (value, other) {
+        (Value::Mapping(value), Value::Mapping(other)) => {
+            for (k, v) in other {
+                match value.get_mut(&k) {
+                    Some(existing) => merge(existing, v),
+                    None => {
+                        value.insert(k, v);
+                    }
+                }
+            }
+        }
+        (value, other) => *value = other,
+    }
+}
+
 /// The config to be loaded.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -52,6 +301,83 @@ pub struct Config {
     /// Defaults for paper fields on entry
     #[serde(default)]
     pub paper_defaults: PaperDefaults,
+
+    /// Command used to convert web pages to documents when snapshotting, e.g.
+    /// `wkhtmltopdf {url} {output}`. The html is saved directly if not set.
+    #[serde(default)]
+    pub snapshot_command: Option<String>,
+
+    /// Institutional proxy to fetch publisher pdfs through, with `{url}` substituted for the
+    /// target, e.g. `https://proxy.example.edu/login?url={url}`.
+    #[serde(default)]
+    pub institutional_proxy: Option<String>,
+
+    /// Command used by `papers optimize-pdfs`, with `{input}` and `{output}` substituted, e.g.
+    /// `gs -sDEVICE=pdfwrite -dPDFSETTINGS=/ebook -o {output} {input}`. Uses qpdf if not set.
+    #[serde(default)]
+    pub optimize_command: Option<String>,
+
+    /// Only treat notes files with `papers: true` in their frontmatter as papers, adding it to
+    /// papers as they are written. Lets the repo live in a directory of other notes, such as an
+    /// Obsidian vault.
+    #[serde(default)]
+    pub require_marker: bool,
+
+    /// Match tags, labels and authors in filters exactly, rather than ignoring case and accents
+    /// so that `--author muller` finds `Müller`.
+    #[serde(default)]
+    pub exact_filters: bool,
+
+    /// What to do when a fetched document would replace an existing file, asking by default.
+    #[serde(default)]
+    pub existing_file: ExistingFile,
+
+    /// Labels, or extra fields, to show in their own columns when listing papers rather than in
+    /// the labels column with the rest.
+    #[serde(default)]
+    pub label_columns: Vec<String>,
+
+    /// Whether to show a compact status column when listing papers, with a glyph each for being
+    /// read, having a document, having notes and being overdue for review.
+    #[serde(default)]
+    pub status_column: bool,
+
+    /// Whether to show the number of words written in the notes of each paper, beyond the notes
+    /// template, when listing papers.
+    #[serde(default)]
+    pub notes_column: bool,
+
+    /// Settings for requests to external services.
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Settings for writing logs to files.
+    #[serde(default)]
+    pub logging: LogConfig,
+
+    /// Settings for rendering thumbnails of documents.
+    #[serde(default)]
+    pub thumbnails: ThumbnailConfig,
+
+    /// What to strip from papers when exporting with `--anonymise`.
+    #[serde(default)]
+    pub anonymise: AnonymiseConfig,
+
+    /// Where to upload notes with `papers share`.
+    #[serde(default)]
+    pub share: ShareConfig,
+
+    /// Where to sync annotations from with `papers hypothesis sync`.
+    #[serde(default)]
+    pub hypothesis: HypothesisConfig,
+
+    /// How to reach Readwise for `papers readwise`.
+    #[serde(default)]
+    pub readwise: ReadwiseConfig,
+
+    /// How `papers review` spaces out the reviews of a paper.
+    #[serde(default)]
+    pub review: ReviewConfig,
 }
 
 fn default_repo() -> PathBuf {
@@ -64,7 +390,14 @@ impl Config {
     /// Returns a default config if the file doesn't exist.
     pub fn load(filename: &Path) -> anyhow::Result<Self> {
         debug!(?filename, "Trying to load config");
-        let file = File::open(filename)?;
+        let file = match File::open(filename) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                debug!(?filename, "No config file, using the defaults");
+                return Self::load_str("{}");
+            }
+            Err(err) => return Err(err.into()),
+        };
         let config = Self::load_reader(file)?;
         Ok(config)
     }
@@ -80,6 +413,36 @@ impl Config {
         let config = Self::load_reader(s.as_bytes())?;
         Ok(config)
     }
+
+    /// The config as a profile to share between machines, without the [`MACHINE_KEYS`].
+    ///
+    /// With `only_changed` just the settings that differ from the defaults are included.
+    pub fn export(&self, only_changed: bool) -> anyhow::Result<Value> {
+        let mut value = serde_yaml::to_value(self)?;
+        let mut default = serde_yaml::to_value(Self::load_str("")?)?;
+        for key in MACHINE_KEYS {
+            remove_key(&mut value, key);
+            remove_key(&mut default, key);
+        }
+        if only_changed {
+            value = diff(&value, &default).unwrap_or_else(|| Value::Mapping(Mapping::new()));
+        }
+        Ok(value)
+    }
+
+    /// Apply a profile from [`Config::export`] over this config, ignoring any [`MACHINE_KEYS`] in
+    /// it.
+    pub fn import(&self, mut profile: Value) -> anyhow::Result<Self> {
+        if !profile.is_mapping() {
+            anyhow::bail!("Config profile should be a mapping of settings");
+        }
+        for key in MACHINE_KEYS {
+            remove_key(&mut profile, key);
+        }
+        let mut value = serde_yaml::to_value(self)?;
+        merge(&mut value, profile);
+        Ok(serde_yaml::from_value(value)?)
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +464,68 @@ mod tests {
         expected.assert_debug_eq(&conf);
     }
 
+    #[test]
+    fn test_export_import() {
+        let config = Config::load_str(
+            "default_repo: /home/me/papers\nrequire_marker: true\nhttp:\n  cache_dir: /tmp/cache\n  timeout_secs: 60\n",
+        )
+        .unwrap();
+        let profile = config.export(true).unwrap();
+        expect![[r#"
+            require_marker: true
+            http:
+              timeout_secs: 60
+        "#]]
+        .assert_eq(&serde_yaml::to_string(&profile).unwrap());
+
+        let other = Config::load_str("default_repo: /papers\nhttp:\n  offline: true\n").unwrap();
+        let imported = other
+            .import(
+                serde_yaml::from_str("default_repo: /elsewhere\nhttp:\n  timeout_secs: 60\n")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(imported.default_repo, PathBuf::from("/papers"));
+        assert_eq!(imported.http.timeout_secs, 60);
+        assert!(imported.http.offline);
+        assert!(other.import(Value::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn test_config_keys_documented() {
+        let config = serde_yaml::to_value(Config::load_str("").unwrap()).unwrap();
+        let mut keys = Vec::new();
+        for (key, value) in config.as_mapping().unwrap() {
+            let key = key.as_str().unwrap();
+            match value {
+                serde_yaml::Value::Mapping(nested)
+                    if [
+                        "paper_defaults",
+                        "http",
+                        "logging",
+                        "thumbnails",
+                        "anonymise",
+                        "share",
+                        "hypothesis",
+                        "readwise",
+                        "review",
+                    ]
+                    .contains(&key) =>
+                {
+                    for nested_key in nested.keys() {
+                        keys.push(format!("{key}.{}", nested_key.as_str().unwrap()));
+                    }
+                }
+                _ => keys.push(key.to_owned()),
+            }
+        }
+        let documented = CONFIG_KEYS
+            .iter()
+            .map(|(k, _)| k.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, documented);
+    }
+
     #[test]
     fn test_config_empty() {
         check(
@@ -115,6 +540,61 @@ mod tests {
                         tags: {},
                         labels: {},
                     },
+                    snapshot_command: None,
+                    institutional_proxy: None,
+                    optimize_command: None,
+                    require_marker: false,
+                    exact_filters: false,
+                    existing_file: Ask,
+                    label_columns: [],
+                    status_column: false,
+                    notes_column: false,
+                    http: HttpConfig {
+                        rate_limit_ms: 1000,
+                        max_concurrent_per_host: 2,
+                        hosts: {},
+                        mirrors: {},
+                        cache: true,
+                        cache_ttl_secs: 604800,
+                        cache_dir: None,
+                        offline: false,
+                        connect_timeout_secs: 30,
+                        timeout_secs: 600,
+                    },
+                    logging: LogConfig {
+                        dir: None,
+                        rotation: Daily,
+                        max_files: 7,
+                        level: "info",
+                    },
+                    thumbnails: ThumbnailConfig {
+                        command: None,
+                        dir: None,
+                    },
+                    anonymise: AnonymiseConfig {
+                        labels: {},
+                        tags: {},
+                        sections: [],
+                        notes: false,
+                    },
+                    share: ShareConfig {
+                        service: Gist,
+                        url: None,
+                        token_env: None,
+                        public: false,
+                    },
+                    hypothesis: HypothesisConfig {
+                        user: None,
+                        token_env: None,
+                    },
+                    readwise: ReadwiseConfig {
+                        token_env: None,
+                    },
+                    review: ReviewConfig {
+                        power: 2.0,
+                        initial_interval_days: 1,
+                        max_interval_days: None,
+                    },
                 }
             "#]],
         );
@@ -136,6 +616,61 @@ mod tests {
                         tags: {},
                         labels: {},
                     },
+                    snapshot_command: None,
+                    institutional_proxy: None,
+                    optimize_command: None,
+                    require_marker: false,
+                    exact_filters: false,
+                    existing_file: Ask,
+                    label_columns: [],
+                    status_column: false,
+                    notes_column: false,
+                    http: HttpConfig {
+                        rate_limit_ms: 1000,
+                        max_concurrent_per_host: 2,
+                        hosts: {},
+                        mirrors: {},
+                        cache: true,
+                        cache_ttl_secs: 604800,
+                        cache_dir: None,
+                        offline: false,
+                        connect_timeout_secs: 30,
+                        timeout_secs: 600,
+                    },
+                    logging: LogConfig {
+                        dir: None,
+                        rotation: Daily,
+                        max_files: 7,
+                        level: "info",
+                    },
+                    thumbnails: ThumbnailConfig {
+                        command: None,
+                        dir: None,
+                    },
+                    anonymise: AnonymiseConfig {
+                        labels: {},
+                        tags: {},
+                        sections: [],
+                        notes: false,
+                    },
+                    share: ShareConfig {
+                        service: Gist,
+                        url: None,
+                        token_env: None,
+                        public: false,
+                    },
+                    hypothesis: HypothesisConfig {
+                        user: None,
+                        token_env: None,
+                    },
+                    readwise: ReadwiseConfig {
+                        token_env: None,
+                    },
+                    review: ReviewConfig {
+                        power: 2.0,
+                        initial_interval_days: 1,
+                        max_interval_days: None,
+                    },
                 }
             "#]],
         );
@@ -157,6 +692,61 @@ mod tests {
                         tags: {},
                         labels: {},
                     },
+                    snapshot_command: None,
+                    institutional_proxy: None,
+                    optimize_command: None,
+                    require_marker: false,
+                    exact_filters: false,
+                    existing_file: Ask,
+                    label_columns: [],
+                    status_column: false,
+                    notes_column: false,
+                    http: HttpConfig {
+                        rate_limit_ms: 1000,
+                        max_concurrent_per_host: 2,
+                        hosts: {},
+                        mirrors: {},
+                        cache: true,
+                        cache_ttl_secs: 604800,
+                        cache_dir: None,
+                        offline: false,
+                        connect_timeout_secs: 30,
+                        timeout_secs: 600,
+                    },
+                    logging: LogConfig {
+                        dir: None,
+                        rotation: Daily,
+                        max_files: 7,
+                        level: "info",
+                    },
+                    thumbnails: ThumbnailConfig {
+                        command: None,
+                        dir: None,
+                    },
+                    anonymise: AnonymiseConfig {
+                        labels: {},
+                        tags: {},
+                        sections: [],
+                        notes: false,
+                    },
+                    share: ShareConfig {
+                        service: Gist,
+                        url: None,
+                        token_env: None,
+                        public: false,
+                    },
+                    hypothesis: HypothesisConfig {
+                        user: None,
+                        token_env: None,
+                    },
+                    readwise: ReadwiseConfig {
+                        token_env: None,
+                    },
+                    review: ReviewConfig {
+                        power: 2.0,
+                        initial_interval_days: 1,
+                        max_interval_days: None,
+                    },
                 }
             "#]],
         );
@@ -184,6 +774,61 @@ mod tests {
                         tags: {},
                         labels: {},
                     },
+                    snapshot_command: None,
+                    institutional_proxy: None,
+                    optimize_command: None,
+                    require_marker: false,
+                    exact_filters: false,
+                    existing_file: Ask,
+                    label_columns: [],
+                    status_column: false,
+                    notes_column: false,
+                    http: HttpConfig {
+                        rate_limit_ms: 1000,
+                        max_concurrent_per_host: 2,
+                        hosts: {},
+                        mirrors: {},
+                        cache: true,
+                        cache_ttl_secs: 604800,
+                        cache_dir: None,
+                        offline: false,
+                        connect_timeout_secs: 30,
+                        timeout_secs: 600,
+                    },
+                    logging: LogConfig {
+                        dir: None,
+                        rotation: Daily,
+                        max_files: 7,
+                        level: "info",
+                    },
+                    thumbnails: ThumbnailConfig {
+                        command: None,
+                        dir: None,
+                    },
+                    anonymise: AnonymiseConfig {
+                        labels: {},
+                        tags: {},
+                        sections: [],
+                        notes: false,
+                    },
+                    share: ShareConfig {
+                        service: Gist,
+                        url: None,
+                        token_env: None,
+                        public: false,
+                    },
+                    hypothesis: HypothesisConfig {
+                        user: None,
+                        token_env: None,
+                    },
+                    readwise: ReadwiseConfig {
+                        token_env: None,
+                    },
+                    review: ReviewConfig {
+                        power: 2.0,
+                        initial_interval_days: 1,
+                        max_interval_days: None,
+                    },
                 }
             "#]],
         );
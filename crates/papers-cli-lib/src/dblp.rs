@@ -0,0 +1,304 @@
+use std::{cmp::Reverse, fmt::Display};
+
+use anyhow::Context;
+use papers_core::{author::Author, label::Label, paper::PaperMeta, primitive::Primitive};
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{citations::DOI_LABEL, http::HttpClient};
+
+/// Where publications are searched for.
+const SEARCH_URL: &str = "https://dblp.org/search/publ/api";
+
+/// Most publications the api returns for a single search.
+const SEARCH_LIMIT: usize = 1000;
+
+/// Label holding the venue a publication appeared at.
+pub const VENUE_LABEL: &str = "venue";
+
+/// Label holding the key of a publication in DBLP.
+pub const DBLP_LABEL: &str = "dblp";
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    result: SearchResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    hits: Hits,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hits {
+    #[serde(default)]
+    hit: Vec<Hit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hit {
+    info: Info,
+}
+
+/// A value DBLP gives alone when there is one, or in a list when there are more.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(one) => vec![one],
+            Self::Many(many) => many,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Authors {
+    author: OneOrMany<InfoAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InfoAuthor {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Info {
+    title: String,
+    #[serde(default)]
+    authors: Option<Authors>,
+    #[serde(default)]
+    venue: Option<OneOrMany<String>>,
+    #[serde(default)]
+    year: Option<String>,
+    key: String,
+    #[serde(default)]
+    doi: Option<String>,
+    /// Electronic edition, a link to the publication.
+    #[serde(default)]
+    ee: Option<OneOrMany<String>>,
+    /// Page of the publication on DBLP.
+    #[serde(default)]
+    url: Option<String>,
+}
+
+/// A publication listed on DBLP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Publication {
+    /// Key of the publication, e.g. `conf/usenix/OngaroO14`.
+    pub key: String,
+    /// Title of the publication.
+    pub title: String,
+    /// Authors of the publication, in order.
+    pub authors: Vec<Author>,
+    /// Where it was published, e.g. `USENIX ATC`.
+    pub venue: Option<String>,
+    /// Year it was published.
+    pub year: Option<u32>,
+    /// DOI of the publication.
+    pub doi: Option<String>,
+    /// Link to the publication, or failing that its page on DBLP.
+    pub url: Option<String>,
+}
+
+/// Name of an author without the number DBLP gives to tell people of the same name apart, e.g.
+/// `Jane Doe 0002`.
+fn author_name(name: &str) -> &str {
+    match name.rsplit_once(' ') {
+        Some((name, number)) if number.len() == 4 && number.chars().all(|c| c.is_ascii_digit()) => {
+            name
+        }
+        _ => name,
+    }
+}
+
+/// Publications from a search response, in the order given.
+fn parse_publications(body: &str) -> anyhow::Result<Vec<Publication>> {
+    let response: SearchResponse =
+        serde_json::from_str(body).context("Reading DBLP publications")?;
+    Ok(response
+        .result
+        .hits
+        .hit
+        .into_iter()
+        .map(|Hit { info }| Publication {
+            // titles of publications end with a full stop
+            title: info
+                .title
+                .strip_suffix('.')
+                .unwrap_or(&info.title)
+                .to_owned(),
+            authors: info
+                .authors
+                .map(|a| a.author.into_vec())
+                .unwrap_or_default()
+                .iter()
+                .map(|a| Author::new(author_name(&a.text)))
+                .collect(),
+            venue: info.venue.map(|v| v.into_vec().join(", ")),
+            year: info.year.and_then(|y| y.parse().ok()),
+            doi: info.doi,
+            url: info
+                .ee
+                .and_then(|ee| ee.into_vec().into_iter().next())
+                .or(info.url),
+            key: info.key,
+        })
+        .collect())
+}
+
+/// Publications of the author with the exact name, newest first.
+pub fn author_publications(http: &HttpClient, author: &str) -> anyhow::Result<Vec<Publication>> {
+    // names in author queries are joined by underscores and ended with a colon to match exactly
+    let query = format!(
+        "author:{}:",
+        author.split_whitespace().collect::<Vec<_>>().join("_")
+    );
+    let url = Url::parse_with_params(
+        SEARCH_URL,
+        [
+            ("q", query),
+            ("format", "json".to_owned()),
+            ("h", SEARCH_LIMIT.to_string()),
+        ],
+    )?;
+    info!(%url, "Searching DBLP");
+    let mut publications = parse_publications(&http.get_text(&url)?)?;
+    publications.sort_by_key(|p| Reverse(p.year));
+    Ok(publications)
+}
+
+impl Display for Publication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)?;
+        let place = self
+            .venue
+            .iter()
+            .cloned()
+            .chain(self.year.map(|y| y.to_string()))
+            .collect::<Vec<_>>();
+        if !place.is_empty() {
+            write!(f, " ({})", place.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Publication {
+    /// Whether the paper is this publication, by its DBLP key, DOI or title.
+    pub fn is_paper(&self, meta: &PaperMeta) -> bool {
+        let label = |key| meta.labels.get(key).map(|v| v.to_string());
+        label(DBLP_LABEL).as_ref() == Some(&self.key)
+            || (self.doi.is_some() && label(DOI_LABEL) == self.doi)
+            || meta.title.trim().eq_ignore_ascii_case(&self.title)
+    }
+
+    /// Labels for the venue, year, DOI and DBLP key of the publication.
+    pub fn labels(&self) -> Vec<Label> {
+        let mut labels = vec![Label::new(DBLP_LABEL, Primitive::String(self.key.clone()))];
+        if let Some(venue) = &self.venue {
+            labels.push(Label::new(VENUE_LABEL, Primitive::String(venue.clone())));
+        }
+        if let Some(year) = self.year {
+            labels.push(Label::new("year", Primitive::Number(year.into())));
+        }
+        if let Some(doi) = &self.doi {
+            labels.push(Label::new(DOI_LABEL, Primitive::String(doi.clone())));
+        }
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_publications() {
+        let body = r#"{"result": {"hits": {"@total": "2", "hit": [
+            {"@score": "1", "info": {
+                "authors": {"author": [{"@pid": "1", "text": "Diego Ongaro"}, {"@pid": "2", "text": "John K. Ousterhout 0001"}]},
+                "title": "In Search of an Understandable Consensus Algorithm.",
+                "venue": "USENIX ATC", "year": "2014", "type": "Conference and Workshop Papers",
+                "key": "conf/usenix/OngaroO14",
+                "ee": "https://www.usenix.org/conference/atc14/technical-sessions/presentation/ongaro",
+                "url": "https://dblp.org/rec/conf/usenix/OngaroO14"}},
+            {"@score": "1", "info": {
+                "authors": {"author": {"@pid": "1", "text": "Diego Ongaro"}},
+                "title": "Consensus: Bridging Theory and Practice.",
+                "venue": ["Stanford", "PhD"], "year": "2014", "key": "phd/Ongaro14",
+                "doi": "10.5555/2643634", "url": "https://dblp.org/rec/phd/Ongaro14"}}
+        ]}}}"#;
+        let publications = parse_publications(body).unwrap();
+        expect![[r#"
+            [
+                Publication {
+                    key: "conf/usenix/OngaroO14",
+                    title: "In Search of an Understandable Consensus Algorithm",
+                    authors: [
+                        Author {
+                            author: "Diego Ongaro",
+                        },
+                        Author {
+                            author: "John K. Ousterhout",
+                        },
+                    ],
+                    venue: Some(
+                        "USENIX ATC",
+                    ),
+                    year: Some(
+                        2014,
+                    ),
+                    doi: None,
+                    url: Some(
+                        "https://www.usenix.org/conference/atc14/technical-sessions/presentation/ongaro",
+                    ),
+                },
+                Publication {
+                    key: "phd/Ongaro14",
+                    title: "Consensus: Bridging Theory and Practice",
+                    authors: [
+                        Author {
+                            author: "Diego Ongaro",
+                        },
+                    ],
+                    venue: Some(
+                        "Stanford, PhD",
+                    ),
+                    year: Some(
+                        2014,
+                    ),
+                    doi: Some(
+                        "10.5555/2643634",
+                    ),
+                    url: Some(
+                        "https://dblp.org/rec/phd/Ongaro14",
+                    ),
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&publications);
+
+        assert_eq!(
+            publications[1].to_string(),
+            "Consensus: Bridging Theory and Practice (Stanford, PhD, 2014)"
+        );
+        let meta = PaperMeta {
+            title: "in search of an understandable consensus algorithm".to_owned(),
+            ..Default::default()
+        };
+        assert!(publications[0].is_paper(&meta));
+        assert!(!publications[1].is_paper(&meta));
+
+        let empty = r#"{"result": {"hits": {"@total": "0"}}}"#;
+        assert_eq!(parse_publications(empty).unwrap(), Vec::new());
+    }
+}
@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use chrono::{Days, NaiveDate, Utc};
+use papers_core::paper::PaperMeta;
+
+use crate::duration::{parse_length, unit_names};
+
+/// Papers due to be read within this many days are flagged in listings.
+pub const DUE_SOON_DAYS: i64 = 7;
+
+/// A date to read a paper by, supporting nice parsing from cli.
+///
+/// - 2023-08-01
+/// - 3d, 2w or 1mo from today, rounded down to whole days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadBy(pub NaiveDate);
+
+impl FromStr for ReadBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Self(date));
+        }
+        parse_length(s)
+            .and_then(|wait| today().checked_add_days(Days::new(wait.num_days().try_into().ok()?)))
+            .map(Self)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid date {s:?}, expected a date like 2023-08-01 or a wait like 3d in {}",
+                    unit_names()
+                )
+            })
+    }
+}
+
+/// The current date.
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// Days left until the paper should be read by, negative once it is overdue.
+pub fn days_left(meta: &PaperMeta, today: NaiveDate) -> Option<i64> {
+    Some((meta.read_by? - today).num_days())
+}
+
+/// Short description of how urgent a deadline is.
+pub fn describe(days_left: i64) -> String {
+    match days_left {
+        0 => "due today".to_owned(),
+        d if d < 0 => format!("overdue by {}d", -d),
+        d => format!("due in {d}d"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(read_by: &str, expected: Expect) {
+        let today = NaiveDate::from_ymd_opt(2023, 8, 10).unwrap();
+        let meta = PaperMeta {
+            read_by: Some(read_by.parse::<ReadBy>().unwrap().0),
+            ..Default::default()
+        };
+        let days = days_left(&meta, today).unwrap();
+        expected.assert_eq(&describe(days));
+    }
+
+    #[test]
+    fn test_describe() {
+        check("2023-08-10", expect!["due today"]);
+        check("2023-08-07", expect!["overdue by 3d"]);
+        check("2023-08-17", expect!["due in 7d"]);
+        check("2023-09-01", expect!["due in 22d"]);
+    }
+
+    #[test]
+    fn test_relative() {
+        let ReadBy(date) = "2w".parse().unwrap();
+        assert_eq!((date - today()).num_days(), 14);
+        assert!("2x".parse::<ReadBy>().is_err());
+    }
+}
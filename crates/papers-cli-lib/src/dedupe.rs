@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use papers_core::{lock::PaperLock, paper::LoadedPaper, repo::Repo, trash::Trashed};
+
+use crate::notes::NotesTemplate;
+
+/// Index of the paper added first in a group of duplicates, the one kept by default.
+///
+/// Papers added in the same second are told apart by their paths.
+pub fn first_added(group: &[LoadedPaper]) -> usize {
+    group
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, p)| (p.meta.created_at, &p.path))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Merge the metadata and notes of `other`, a duplicate of `paper`, into `paper`.
+///
+/// Anything `paper` already has is kept, with tags, labels and authors from `other` added to
+/// them and the earliest date added of the two.
+pub fn merge(paper: &mut LoadedPaper, other: &LoadedPaper, template: &NotesTemplate) {
+    let (meta, theirs) = (&mut paper.meta, &other.meta);
+    if meta.url.is_none() {
+        meta.url = theirs.url.clone();
+    }
+    meta.tags.extend(theirs.tags.iter().cloned());
+    for (key, value) in &theirs.labels {
+        meta.labels
+            .entry(key.clone())
+            .or_insert_with(|| value.clone());
+    }
+    for author in &theirs.authors {
+        if !meta.authors.contains(author) {
+            meta.authors.push(author.clone());
+        }
+    }
+    for (key, value) in &theirs.extra {
+        meta.extra
+            .entry(key.clone())
+            .or_insert_with(|| value.clone());
+    }
+    meta.created_at = meta.created_at.min(theirs.created_at);
+    meta.last_review = meta.last_review.max(theirs.last_review);
+    meta.next_review = meta.next_review.or(theirs.next_review);
+    meta.read_by = match (meta.read_by, theirs.read_by) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    if !template.has_notes(&other.notes) || other.notes.trim() == paper.notes.trim() {
+        return;
+    }
+    if template.has_notes(&paper.notes) {
+        paper.notes = format!(
+            "{}\n\n## Merged from {}\n\n{}\n",
+            paper.notes.trim_end(),
+            other.meta.title,
+            other.notes.trim()
+        );
+    } else {
+        paper.notes = other.notes.clone();
+    }
+}
+
+/// Merge the papers at `others` into the one at `keep`, then move them to the trash along with
+/// their documents, unless shared with the kept paper.
+pub fn merge_duplicates(
+    repo: &Repo,
+    keep: &Path,
+    others: &[PathBuf],
+    template: &NotesTemplate,
+) -> anyhow::Result<Vec<Trashed>> {
+    // don't change any notes out from under an editor
+    let _locks = std::iter::once(keep)
+        .chain(others.iter().map(PathBuf::as_path))
+        .map(|p| PaperLock::acquire(repo.root(), p))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut paper = repo.get_paper(keep)?;
+    let others = others
+        .iter()
+        .map(|p| repo.get_paper(p))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    for other in &others {
+        merge(&mut paper, other, template);
+    }
+    repo.write_paper(&paper.path, paper.meta.clone(), &paper.notes)?;
+    others
+        .iter()
+        .map(|other| {
+            let with_file = other.meta.filename != paper.meta.filename;
+            repo.trash(&other.path, with_file)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use expect_test::expect;
+    use papers_core::{author::Author, paper::PaperMeta, tag::Tag};
+
+    use super::*;
+
+    fn paper(title: &str, day: u32, notes: &str) -> LoadedPaper {
+        LoadedPaper {
+            meta: PaperMeta {
+                title: title.to_owned(),
+                created_at: NaiveDate::from_ymd_opt(2024, 5, day)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+                ..Default::default()
+            },
+            path: PathBuf::from(format!("{title}.md")),
+            notes: notes.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_first_added() {
+        let group = [paper("b", 3, ""), paper("c", 1, ""), paper("a", 1, "")];
+        assert_eq!(first_added(&group), 2);
+    }
+
+    #[test]
+    fn test_merge() {
+        let template = NotesTemplate::new("## Summary\n");
+        let mut kept = paper("raft", 3, "## Summary\nLeader based.\n");
+        kept.meta.tags = [Tag::new("consensus")].into();
+        kept.meta.authors = vec![Author::new("Diego Ongaro")];
+        let mut other = paper("raft-extended", 1, "## Summary\nWith membership changes.\n");
+        other.meta.url = Some("https://raft.github.io/raft.pdf".to_owned());
+        other.meta.tags = [Tag::new("distributed")].into();
+        other.meta.authors = vec![Author::new("Diego Ongaro"), Author::new("John Ousterhout")];
+
+        merge(&mut kept, &other, &template);
+        assert_eq!(kept.meta.title, "raft");
+        assert_eq!(kept.meta.url, other.meta.url);
+        assert_eq!(kept.meta.created_at, other.meta.created_at);
+        assert_eq!(kept.meta.tags.len(), 2);
+        assert_eq!(kept.meta.authors, other.meta.authors);
+        expect![[r#"
+            ## Summary
+            Leader based.
+
+            ## Merged from raft-extended
+
+            ## Summary
+            With membership changes.
+        "#]]
+        .assert_eq(&kept.notes);
+
+        // notes that are just the template are replaced rather than added to
+        let mut empty = paper("raft", 3, "## Summary\n");
+        merge(&mut empty, &other, &template);
+        assert_eq!(empty.notes, other.notes);
+        merge(&mut empty, &paper("copy", 1, "## Summary\n"), &template);
+        assert_eq!(empty.notes, other.notes);
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use papers_core::paper::PaperMeta;
+
+use crate::{interactive::input_key, output::Problem, output::ProblemKind};
+
+/// How alike an unmatched file needs to be to a paper to be proposed as its missing document.
+const RELINK_THRESHOLD: f64 = 0.5;
+
+/// A file that isn't the document of any paper, which may be one whose document is missing.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Path of the file, relative to the root.
+    pub path: PathBuf,
+    /// Title extracted from the file, if it is a pdf that has one.
+    pub title: Option<String>,
+}
+
+/// Lowercase words of a name or title.
+fn words(s: &str) -> BTreeSet<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// How alike two names or titles are, from 0 to 1, by the share of their words in common.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The candidate most likely to be the missing document of a paper, comparing its name to the
+/// missing filename and title, and the title extracted from it to the paper's title.
+pub fn find_relink<'a>(meta: &PaperMeta, candidates: &'a [Candidate]) -> Option<&'a Path> {
+    let stem = |p: &Path| {
+        p.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let missing = meta.filename.as_deref().map(stem).unwrap_or_default();
+    let score = |c: &Candidate| {
+        let name = stem(&c.path);
+        let title = c
+            .title
+            .as_deref()
+            .map_or(0., |t| similarity(t, &meta.title));
+        similarity(&name, &missing)
+            .max(similarity(&name, &meta.title))
+            .max(title)
+    };
+    candidates
+        .iter()
+        .map(|c| (score(c), c))
+        .filter(|(score, _)| *score >= RELINK_THRESHOLD)
+        // the first of equally good candidates
+        .fold(
+            None,
+            |best: Option<(f64, &Candidate)>, (score, c)| match best {
+                Some((best_score, _)) if best_score >= score => best,
+                _ => Some((score, c)),
+            },
+        )
+        .map(|(_, c)| c.path.as_path())
+}
+
+/// Answer to whether to fix a problem, chosen with a single key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixChoice {
+    /// Fix this problem.
+    Yes,
+    /// Leave this problem.
+    No,
+    /// Fix this and every later problem of the same kind.
+    Always,
+    /// Leave this and every later problem of the same kind.
+    Never,
+}
+
+impl FixChoice {
+    /// Prompt listing the keys for each choice.
+    pub const PROMPT: &'static str = "Fix? [y]es, [n]o, [a]lways, ne[v]er";
+
+    /// The choice for a key, `None` for a key with no choice.
+    pub fn from_key(key: Option<char>) -> Option<Self> {
+        match key.map(|k| k.to_ascii_lowercase()) {
+            Some('y') => Some(Self::Yes),
+            Some('n' | ' ') => Some(Self::No),
+            Some('a') => Some(Self::Always),
+            // nothing more to read so leave the rest alone
+            Some('v') | None => Some(Self::Never),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Decides which problems the doctor fixes, remembering the choices made for each kind.
+#[derive(Debug, Default)]
+pub struct Fixer {
+    /// Kinds of problems to fix, none if not fixing and empty for all of them.
+    kinds: Option<BTreeSet<ProblemKind>>,
+    /// Whether to ask before fixing each problem.
+    interactive: bool,
+    /// Choices made for all problems of a kind.
+    decided: BTreeMap<ProblemKind, bool>,
+}
+
+impl Fixer {
+    /// Fix the given kinds of problems, all of them if empty, asking first if `interactive`.
+    pub fn new(kinds: Option<Vec<ProblemKind>>, interactive: bool) -> Self {
+        let kinds = match kinds {
+            None if interactive => Some(BTreeSet::new()),
+            kinds => kinds.map(BTreeSet::from_iter),
+        };
+        Self {
+            kinds,
+            interactive,
+            decided: BTreeMap::new(),
+        }
+    }
+
+    /// Whether to fix the problem, asking if interactive.
+    pub fn should_fix(&mut self, problem: &Problem) -> bool {
+        let Some(kinds) = &self.kinds else {
+            return false;
+        };
+        if !kinds.is_empty() && !kinds.contains(&problem.kind) {
+            return false;
+        }
+        // merging duplicates moves papers to the trash, so is only done when asked for by kind
+        // or after asking about each
+        if kinds.is_empty() && !self.interactive && problem.kind == ProblemKind::DuplicateFile {
+            return false;
+        }
+        if !self.interactive {
+            return true;
+        }
+        if let Some(fix) = self.decided.get(&problem.kind) {
+            return *fix;
+        }
+        println!("{problem}");
+        let choice = loop {
+            if let Some(choice) = FixChoice::from_key(input_key(FixChoice::PROMPT)) {
+                break choice;
+            }
+        };
+        match choice {
+            FixChoice::Yes => true,
+            FixChoice::No => false,
+            FixChoice::Always => *self.decided.entry(problem.kind).or_insert(true),
+            FixChoice::Never => *self.decided.entry(problem.kind).or_insert(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(kind: ProblemKind) -> Problem {
+        Problem {
+            kind,
+            path: "a.md".into(),
+            other: None,
+            detail: None,
+            fixed: false,
+        }
+    }
+
+    #[test]
+    fn test_should_fix() {
+        let mut fixer = Fixer::new(None, false);
+        assert!(!fixer.should_fix(&problem(ProblemKind::NotesWrongPath)));
+
+        let mut fixer = Fixer::new(Some(Vec::new()), false);
+        assert!(fixer.should_fix(&problem(ProblemKind::NotesWrongPath)));
+        assert!(fixer.should_fix(&problem(ProblemKind::UrlNotNormalised)));
+
+        let mut fixer = Fixer::new(Some(vec![ProblemKind::FileWrongPath]), false);
+        assert!(!fixer.should_fix(&problem(ProblemKind::NotesWrongPath)));
+        assert!(fixer.should_fix(&problem(ProblemKind::FileWrongPath)));
+    }
+
+    fn check_relink(filename: &str, title: &str, candidates: &[(&str, Option<&str>)]) -> String {
+        let meta = PaperMeta {
+            title: title.to_owned(),
+            filename: Some(filename.into()),
+            ..Default::default()
+        };
+        let candidates = candidates
+            .iter()
+            .map(|(path, title)| Candidate {
+                path: path.into(),
+                title: title.map(String::from),
+            })
+            .collect::<Vec<_>>();
+        find_relink(&meta, &candidates)
+            .map(|p| p.display().to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_find_relink() {
+        // by the old filename
+        assert_eq!(
+            check_relink(
+                "raft.pdf",
+                "Consensus",
+                &[("paxos.pdf", None), ("raft (1).pdf", None)]
+            ),
+            "raft (1).pdf"
+        );
+        // by the title of the paper
+        assert_eq!(
+            check_relink(
+                "1234.pdf",
+                "In Search of an Understandable Consensus Algorithm",
+                &[(
+                    "in-search-of-an-understandable-consensus-algorithm.pdf",
+                    None
+                )]
+            ),
+            "in-search-of-an-understandable-consensus-algorithm.pdf"
+        );
+        // by the title in the document
+        assert_eq!(
+            check_relink(
+                "1234.pdf",
+                "Dynamo: Amazon's Highly Available Key-value Store",
+                &[(
+                    "download.pdf",
+                    Some("Dynamo: Amazon's highly available key-value store")
+                )]
+            ),
+            "download.pdf"
+        );
+        assert_eq!(
+            check_relink("raft.pdf", "Raft", &[("unrelated.pdf", None)]),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_from_key() {
+        assert_eq!(FixChoice::from_key(Some('Y')), Some(FixChoice::Yes));
+        assert_eq!(FixChoice::from_key(Some(' ')), Some(FixChoice::No));
+        assert_eq!(FixChoice::from_key(Some('a')), Some(FixChoice::Always));
+        assert_eq!(FixChoice::from_key(None), Some(FixChoice::Never));
+        assert_eq!(FixChoice::from_key(Some('z')), None);
+    }
+}
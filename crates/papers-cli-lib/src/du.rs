@@ -0,0 +1,115 @@
+use chrono::Datelike;
+use clap::ValueEnum;
+use papers_core::{paper::PaperMeta, tag::Tag};
+
+use crate::triage::{ARCHIVE_TAG, INBOX_TAG, QUEUE_TAG};
+
+/// Label that can hold the year a paper was published.
+pub const YEAR_LABEL: &str = "year";
+
+/// Group used for papers that don't have anything to group them by.
+pub const NO_GROUP: &str = "(none)";
+
+/// How to group papers when reporting disk usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UsageBy {
+    /// Each tag of the paper.
+    Tag,
+    /// The `year` label of the paper, or the year it was added.
+    Year,
+    /// Whether the paper is in the inbox, queued to read or archived.
+    Status,
+}
+
+impl UsageBy {
+    /// Groups that the paper counts towards.
+    pub fn groups(&self, meta: &PaperMeta) -> Vec<String> {
+        match self {
+            Self::Tag if meta.tags.is_empty() => vec![NO_GROUP.to_owned()],
+            Self::Tag => meta.tags.iter().map(|t| t.to_string()).collect(),
+            Self::Year => {
+                let year = meta
+                    .labels
+                    .get(YEAR_LABEL)
+                    .map(|y| y.to_string())
+                    .unwrap_or_else(|| meta.created_at.year().to_string());
+                vec![year]
+            }
+            Self::Status => {
+                let status = [INBOX_TAG, QUEUE_TAG, ARCHIVE_TAG]
+                    .into_iter()
+                    .find(|t| meta.tags.contains(&Tag::new(t)))
+                    .unwrap_or(NO_GROUP);
+                vec![status.to_owned()]
+            }
+        }
+    }
+}
+
+/// Size in bytes in a short human readable form, e.g. `1.5 MiB`.
+pub fn display_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use expect_test::{expect, Expect};
+    use papers_core::primitive::Primitive;
+
+    use super::*;
+
+    fn check_size(bytes: u64, expected: Expect) {
+        expected.assert_eq(&display_size(bytes));
+    }
+
+    #[test]
+    fn test_display_size() {
+        check_size(0, expect!["0 B"]);
+        check_size(1023, expect!["1023 B"]);
+        check_size(1536, expect!["1.5 KiB"]);
+        check_size(5 * 1024 * 1024, expect!["5.0 MiB"]);
+        check_size(3 << 40, expect!["3.0 TiB"]);
+    }
+
+    #[test]
+    fn test_groups() {
+        let meta = PaperMeta {
+            tags: BTreeSet::from([Tag::new("db"), Tag::new("to-read")]),
+            labels: BTreeMap::from([("year".to_owned(), Primitive::Number(2019.into()))]),
+            ..Default::default()
+        };
+        expect![[r#"
+            [
+                [
+                    "db",
+                    "to-read",
+                ],
+                [
+                    "2019",
+                ],
+                [
+                    "to-read",
+                ],
+            ]
+        "#]]
+        .assert_debug_eq(&[UsageBy::Tag, UsageBy::Year, UsageBy::Status].map(|b| b.groups(&meta)));
+        expect![[r#"
+            [
+                "(none)",
+            ]
+        "#]]
+        .assert_debug_eq(&UsageBy::Status.groups(&PaperMeta::default()));
+    }
+}
@@ -0,0 +1,122 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::Duration;
+
+/// Units a length of time can be given in, with how long each is.
+///
+/// Months and years are taken to be 30 and 365 days.
+pub const UNITS: &[(&str, Duration)] = &[
+    ("s", Duration::seconds(1)),
+    ("m", Duration::minutes(1)),
+    ("h", Duration::hours(1)),
+    ("d", Duration::days(1)),
+    ("w", Duration::weeks(1)),
+    ("mo", Duration::days(30)),
+    ("y", Duration::days(365)),
+];
+
+/// Parse a length of time like `30m` or `2w`, a whole number followed by one of [`UNITS`].
+pub fn parse_length(s: &str) -> Option<Duration> {
+    let unit_start = s.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = s.split_at(unit_start);
+    let count = count.parse::<i32>().ok()?;
+    let (_, length) = UNITS.iter().find(|(u, _)| *u == unit)?;
+    Some(*length * count)
+}
+
+/// The units of [`UNITS`] for error messages.
+pub fn unit_names() -> String {
+    UNITS.iter().map(|(u, _)| *u).collect::<Vec<_>>().join(", ")
+}
+
+/// A length of time, supporting nice parsing from cli.
+///
+/// - 90s
+/// - 30m
+/// - 2h
+/// - 1w
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanDuration(pub Duration);
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        parse_length(s).map(Self).ok_or_else(|| {
+            format!(
+                "Invalid duration {s:?}, expected a length like 30m or 2h in {}",
+                unit_names()
+            )
+        })
+    }
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds = self.0.num_seconds();
+        if seconds < 60 {
+            write!(f, "{seconds}s")
+        } else if seconds < 60 * 60 {
+            write!(f, "{}m", seconds / 60)
+        } else {
+            write!(f, "{}h{}m", seconds / (60 * 60), seconds % (60 * 60) / 60)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(s: &str, expected: Expect) {
+        let parsed = s.parse::<HumanDuration>().map(|d| d.to_string());
+        expected.assert_debug_eq(&parsed);
+    }
+
+    #[test]
+    fn test_parse() {
+        check(
+            "30m",
+            expect![[r#"
+                Ok(
+                    "30m",
+                )
+            "#]],
+        );
+        check(
+            "90m",
+            expect![[r#"
+                Ok(
+                    "1h30m",
+                )
+            "#]],
+        );
+        check(
+            "45s",
+            expect![[r#"
+                Ok(
+                    "45s",
+                )
+            "#]],
+        );
+        check(
+            "2w",
+            expect![[r#"
+                Ok(
+                    "336h0m",
+                )
+            "#]],
+        );
+        check(
+            "3x",
+            expect![[r#"
+                Err(
+                    "Invalid duration \"3x\", expected a length like 30m or 2h in s, m, h, d, w, mo, y",
+                )
+            "#]],
+        );
+    }
+}
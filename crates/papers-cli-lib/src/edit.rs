@@ -0,0 +1,191 @@
+use std::{
+    fs::{create_dir_all, read_to_string, remove_file, rename, write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use papers_core::lock::{Locked, PaperLock};
+use tracing::debug;
+
+use crate::{
+    interactive::{input_bool, input_key, Cancelled},
+    warning,
+};
+
+/// Where the copies of notes being edited are kept, relative to the root of the repo.
+pub const EDITS_DIR: &str = ".papers/edits";
+
+/// What to do with notes that changed on disk while they were being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    /// Edit both versions together by hand.
+    Merge,
+    /// Keep the edits, replacing the changes on disk.
+    Yours,
+    /// Keep the changes on disk, dropping the edits.
+    Theirs,
+}
+
+impl Resolution {
+    const PROMPT: &'static str = "[m]erge by hand, keep [y]ours or keep [t]heirs";
+
+    fn from_key(key: Option<char>) -> Option<Self> {
+        match key?.to_ascii_lowercase() {
+            'm' => Some(Self::Merge),
+            'y' => Some(Self::Yours),
+            't' => Some(Self::Theirs),
+            _ => None,
+        }
+    }
+
+    /// Ask how to resolve the edits with the keys from `read_key`, asking again for keys that
+    /// aren't understood.
+    ///
+    /// Cancelled when there are no more keys, e.g. on escape or the end of input.
+    fn ask(mut read_key: impl FnMut() -> Option<char>) -> Result<Self, Cancelled> {
+        loop {
+            let key = read_key().ok_or(Cancelled)?;
+            if let Some(resolution) = Self::from_key(Some(key)) {
+                return Ok(resolution);
+            }
+        }
+    }
+}
+
+/// Open `path` in the user's editor, waiting for it to close.
+fn edit(path: &Path) -> anyhow::Result<()> {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_owned());
+    Command::new(editor).args([path.to_owned()]).status()?;
+    Ok(())
+}
+
+/// Both versions of some notes, marked like a git conflict for merging by hand.
+fn conflict(theirs: &str, yours: &str) -> String {
+    let line = |s: &str| {
+        if s.ends_with('\n') || s.is_empty() {
+            s.to_owned()
+        } else {
+            format!("{s}\n")
+        }
+    };
+    format!(
+        "<<<<<<< on disk\n{}=======\n{}>>>>>>> yours\n",
+        line(theirs),
+        line(yours)
+    )
+}
+
+/// Lock the paper at `path` for editing, asking whether to edit it anyway if it is locked and
+/// there is someone to ask.
+fn lock(root: &Path, path: &Path) -> anyhow::Result<PaperLock> {
+    match PaperLock::acquire(root, path) {
+        Err(err) if atty::is(atty::Stream::Stdin) => {
+            let Some(locked) = err.downcast_ref::<Locked>() else {
+                return Err(err);
+            };
+            if input_bool(&format!("{locked}. Edit anyway?"), false)? {
+                PaperLock::force(root, path)
+            } else {
+                Err(Cancelled.into())
+            }
+        }
+        res => res,
+    }
+}
+
+/// Edit the notes of the paper at `path`, relative to `root`, in the user's editor.
+///
+/// The paper is locked while it is edited, and the editor works on a copy so that changes made
+/// to the notes on disk in the meantime, such as by a sync, are noticed rather than overwritten.
+pub fn edit_notes(root: &Path, path: &Path) -> anyhow::Result<()> {
+    let _lock = lock(root, path)?;
+    let file = root.join(path);
+    let copy = root.join(EDITS_DIR).join(path);
+    if let Some(parent) = copy.parent() {
+        create_dir_all(parent)?;
+    }
+    let mut base = read_to_string(&file)?;
+    write(&copy, &base)?;
+    loop {
+        edit(&copy)?;
+        let yours = read_to_string(&copy)?;
+        let theirs = read_to_string(&file)?;
+        if theirs == base || yours == base || theirs == yours {
+            if yours != base {
+                replace(&file, &yours)?;
+            }
+            break;
+        }
+        warning!("{path:?} changed on disk while it was being edited");
+        if !atty::is(atty::Stream::Stdin) {
+            anyhow::bail!("Not overwriting {path:?}, the edits are kept in {copy:?}");
+        }
+        let Ok(resolution) = Resolution::ask(|| input_key(Resolution::PROMPT)) else {
+            warning!("Not overwriting {path:?}, the edits are kept in {copy:?}");
+            return Err(Cancelled.into());
+        };
+        debug!(
+            ?resolution,
+            ?path,
+            "Resolving edits of notes changed on disk"
+        );
+        match resolution {
+            Resolution::Merge => {
+                write(&copy, conflict(&theirs, &yours))?;
+                // the merged notes are compared against what is on disk now
+                base = theirs;
+            }
+            Resolution::Yours => {
+                replace(&file, &yours)?;
+                break;
+            }
+            Resolution::Theirs => break,
+        }
+    }
+    remove_file(&copy)?;
+    Ok(())
+}
+
+/// Replace the contents of `file`, through a temporary file so that it is never left partly
+/// written.
+fn replace(file: &Path, contents: &str) -> anyhow::Result<()> {
+    let mut tmp = file.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    write(&tmp, contents)?;
+    rename(&tmp, file)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_conflict() {
+        expect![[r#"
+            <<<<<<< on disk
+            # Summary
+            From sync.
+            =======
+            # Summary
+            Mine.
+            >>>>>>> yours
+        "#]]
+        .assert_eq(&conflict("# Summary\nFrom sync.\n", "# Summary\nMine."));
+    }
+
+    #[test]
+    fn test_ask_resolution() {
+        let mut keys = "xT".chars();
+        assert!(matches!(
+            Resolution::ask(|| keys.next()),
+            Ok(Resolution::Theirs)
+        ));
+        // the end of input cancels rather than asking forever
+        let mut keys = "x".chars();
+        assert!(matches!(Resolution::ask(|| keys.next()), Err(Cancelled)));
+    }
+}
@@ -5,3 +5,11 @@ macro_rules! error {
         eprintln!("error: {}", format_args!($($arg)*))
     }};
 }
+
+/// Print a warning.
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => {{
+        eprintln!("warning: {}", format_args!($($arg)*))
+    }};
+}
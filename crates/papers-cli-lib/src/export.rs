@@ -0,0 +1,383 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs::File,
+    io::{stdin, BufReader},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use papers_core::{
+    paper::{LoadedPaper, PaperMeta},
+    tag::Tag,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{file_or_stdin::FileOrStdin, output::Render};
+
+/// A paper in an export bundle, as printed by `papers export-json` or `papers list -o json` and
+/// read by `papers import`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundlePaper {
+    /// Path of the notes file, relative to the root of the repo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Metadata from the frontmatter.
+    #[serde(flatten)]
+    pub meta: PaperMeta,
+    /// Body of the notes, only in full exports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Files belonging to the paper that exist, relative to the root of the repo, only in full
+    /// exports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<PathBuf>>,
+}
+
+/// What `papers import` reads papers from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// A bundle of papers in json or yaml.
+    #[default]
+    Bundle,
+    /// A Calibre library directory, copying in the documents of its books with the
+    /// `--calibre-tag` tag.
+    Calibre,
+    /// A BibTeX export from Google Scholar. Entries with the title of a paper already in the
+    /// repo fill in what it is missing rather than being added again.
+    ScholarBibtex,
+}
+
+/// What `papers export` writes for each paper.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The notes file, with the metadata in its frontmatter.
+    #[default]
+    Notes,
+    /// A directory with the document and a `metadata.opf`, for Calibre to add as a book.
+    Calibre,
+}
+
+/// Read a bundle of papers, as yaml if the file ends in `.yaml` or `.yml` and as json otherwise.
+pub fn read_bundle(file: &FileOrStdin) -> anyhow::Result<Vec<BundlePaper>> {
+    let papers = match file {
+        FileOrStdin::File(path) => {
+            let reader = BufReader::new(File::open(path)?);
+            let yaml = path.extension().is_some_and(|e| e == "yaml" || e == "yml");
+            if yaml {
+                serde_yaml::from_reader(reader)?
+            } else {
+                serde_json::from_reader(reader)?
+            }
+        }
+        FileOrStdin::Stdin => serde_json::from_reader(stdin())?,
+    };
+    Ok(papers)
+}
+
+/// Files belonging to a paper that exist, relative to the `root` of the repo.
+pub fn attachments(meta: &PaperMeta, root: &Path) -> Vec<PathBuf> {
+    meta.filename
+        .iter()
+        .filter(|f| root.join(f).is_file())
+        .cloned()
+        .collect()
+}
+
+/// Somewhere an export doesn't match the repo.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BundleDifference {
+    /// Path of the paper.
+    pub path: PathBuf,
+    /// Field that differs, or `paper` if the paper is missing from one side.
+    pub field: String,
+    /// The value in the repo, as json, if there is one.
+    pub repo: Option<String>,
+    /// The value in the export, as json, if there is one.
+    pub export: Option<String>,
+}
+
+/// Everywhere an export doesn't match the repo.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct BundleDifferences(pub Vec<BundleDifference>);
+
+/// Fields of a paper as json values, for comparing them one by one.
+fn fields(meta: &PaperMeta) -> BTreeMap<String, serde_json::Value> {
+    match serde_json::to_value(meta) {
+        Ok(serde_json::Value::Object(fields)) => fields.into_iter().collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+impl BundleDifferences {
+    /// Compare the papers in an export with those in the repo, in `root`.
+    ///
+    /// Papers in the export without a path are matched to the path `path_of` gives them, as
+    /// importing them would. Notes and attachments are only compared if the export has them.
+    pub fn of(
+        bundle: &[BundlePaper],
+        papers: &[LoadedPaper],
+        root: &Path,
+        path_of: impl Fn(&PaperMeta) -> PathBuf,
+    ) -> Self {
+        let mut exported = bundle
+            .iter()
+            .map(|b| (b.path.clone().unwrap_or_else(|| path_of(&b.meta)), b))
+            .collect::<BTreeMap<_, _>>();
+        let mut differences = Vec::new();
+        let mut papers = papers.iter().collect::<Vec<_>>();
+        papers.sort_by(|a, b| a.path.cmp(&b.path));
+        for paper in papers {
+            let Some(export) = exported.remove(&paper.path) else {
+                differences.push(BundleDifference {
+                    path: paper.path.clone(),
+                    field: "paper".to_owned(),
+                    repo: Some(paper.meta.title.clone()),
+                    export: None,
+                });
+                continue;
+            };
+            let mut difference = |field: &str, repo: Option<String>, export: Option<String>| {
+                if repo != export {
+                    differences.push(BundleDifference {
+                        path: paper.path.clone(),
+                        field: field.to_owned(),
+                        repo,
+                        export,
+                    });
+                }
+            };
+            let (repo_fields, export_fields) = (fields(&paper.meta), fields(&export.meta));
+            let keys = repo_fields
+                .keys()
+                .chain(export_fields.keys())
+                .collect::<BTreeSet<_>>();
+            for key in keys {
+                let value =
+                    |f: &BTreeMap<String, serde_json::Value>| f.get(key).map(|v| v.to_string());
+                difference(key, value(&repo_fields), value(&export_fields));
+            }
+            if let Some(notes) = &export.notes {
+                let json = |n: &str| serde_json::Value::from(n).to_string();
+                difference("notes", Some(json(&paper.notes)), Some(json(notes)));
+            }
+            if let Some(files) = &export.attachments {
+                let json = |f: &[PathBuf]| serde_json::to_string(f).unwrap_or_default();
+                let repo_files = attachments(&paper.meta, root);
+                difference("attachments", Some(json(&repo_files)), Some(json(files)));
+            }
+        }
+        for (path, export) in exported {
+            differences.push(BundleDifference {
+                path,
+                field: "paper".to_owned(),
+                repo: None,
+                export: Some(export.meta.title.clone()),
+            });
+        }
+        Self(differences)
+    }
+}
+
+impl Display for BundleDifferences {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|d| match (d.field.as_str(), &d.repo, &d.export) {
+                ("paper", Some(_), None) => {
+                    format!("Paper missing from the export. path={:?}", d.path)
+                }
+                ("paper", None, Some(_)) => format!("Paper only in the export. path={:?}", d.path),
+                (field, repo, export) => format!(
+                    "Field differs. path={:?}, field={field}, repo={}, export={}",
+                    d.path,
+                    repo.as_deref().unwrap_or("missing"),
+                    export.as_deref().unwrap_or("missing")
+                ),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for BundleDifferences {
+    fn header(&self) -> Vec<String> {
+        ["path", "field", "repo", "export"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|d| {
+                vec![
+                    d.path.to_string_lossy().into_owned(),
+                    d.field.clone(),
+                    d.repo.clone().unwrap_or_default(),
+                    d.export.clone().unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// What to strip from papers when exporting them for sharing.
+///
+/// Review dates and read by dates are always removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnonymiseConfig {
+    /// Keys of labels to remove.
+    #[serde(default)]
+    pub labels: BTreeSet<String>,
+
+    /// Tags to remove.
+    #[serde(default)]
+    pub tags: BTreeSet<Tag>,
+
+    /// Headings of sections of the notes to remove, along with everything under them.
+    #[serde(default)]
+    pub sections: Vec<String>,
+
+    /// Remove the notes entirely.
+    #[serde(default)]
+    pub notes: bool,
+}
+
+impl AnonymiseConfig {
+    /// Strip the configured fields from a paper, returning the notes that are left.
+    pub fn anonymise(&self, meta: &mut PaperMeta, notes: &str) -> String {
+        meta.labels.retain(|k, _| !self.labels.contains(k));
+        meta.tags.retain(|t| !self.tags.contains(t));
+        meta.last_review = None;
+        meta.next_review = None;
+        meta.read_by = None;
+        meta.provenance = None;
+        if self.notes {
+            String::new()
+        } else {
+            strip_sections(notes, &self.sections)
+        }
+    }
+}
+
+/// Level and text of a markdown heading line.
+pub fn heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_end();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = &line[level..];
+    if level == 0 || !(text.is_empty() || text.starts_with(' ')) {
+        return None;
+    }
+    Some((level, text.trim()))
+}
+
+/// Remove the sections with the given headings, up to the next heading at the same or a higher
+/// level.
+fn strip_sections(notes: &str, sections: &[String]) -> String {
+    let mut out = String::new();
+    let mut skipping = None;
+    for line in notes.split_inclusive('\n') {
+        match heading(line) {
+            Some((level, _)) if skipping.is_some_and(|s| level > s) => continue,
+            Some((level, text)) => {
+                skipping = sections
+                    .iter()
+                    .any(|s| s.eq_ignore_ascii_case(text))
+                    .then_some(level);
+                if skipping.is_some() {
+                    continue;
+                }
+            }
+            None if skipping.is_some() => continue,
+            None => {}
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use expect_test::{expect, Expect};
+    use papers_core::primitive::Primitive;
+
+    use super::*;
+
+    fn check(notes: &str, sections: &[&str], expected: Expect) {
+        let sections = sections.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        expected.assert_eq(&strip_sections(notes, &sections));
+    }
+
+    #[test]
+    fn test_strip_sections() {
+        check(
+            "# Summary\nGood.\n## Private\nMeh.\n### Detail\nMore meh.\n## Questions\nWhy?\n",
+            &["private"],
+            expect![[r#"
+                # Summary
+                Good.
+                ## Questions
+                Why?
+            "#]],
+        );
+        check(
+            "Intro\n# Private\nSecret\n",
+            &["Private"],
+            expect![[r#"
+                Intro
+            "#]],
+        );
+        check(
+            "#hashtag\n# Private notes\nKept\n",
+            &["Private"],
+            expect![[r#"
+                #hashtag
+                # Private notes
+                Kept
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_anonymise() {
+        let config = AnonymiseConfig {
+            labels: BTreeSet::from(["rating".to_owned()]),
+            tags: BTreeSet::from([Tag::new("reviewer")]),
+            sections: Vec::new(),
+            notes: true,
+        };
+        let mut meta = PaperMeta {
+            title: "A Paper".to_owned(),
+            tags: BTreeSet::from([Tag::new("reviewer"), Tag::new("db")]),
+            labels: BTreeMap::from([
+                ("rating".to_owned(), Primitive::Number(3.into())),
+                ("venue".to_owned(), Primitive::String("osdi".to_owned())),
+            ]),
+            read_by: chrono::NaiveDate::from_ymd_opt(2023, 8, 1),
+            ..Default::default()
+        };
+        let notes = config.anonymise(&mut meta, "my notes");
+        assert_eq!(notes, "");
+        expect![[r#"
+            (
+                {
+                    Tag {
+                        key: "db",
+                    },
+                },
+                {
+                    "venue": String(
+                        "osdi",
+                    ),
+                },
+                None,
+            )
+        "#]]
+        .assert_debug_eq(&(&meta.tags, &meta.labels, meta.read_by));
+    }
+}
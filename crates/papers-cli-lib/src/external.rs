@@ -0,0 +1,33 @@
+use std::{
+    path::Path,
+    process::{Command, ExitStatus},
+};
+
+use tracing::debug;
+
+/// Run a configured command template, split on whitespace with `{input}` and `{output}`
+/// substituted in each part, waiting for it to exit.
+///
+/// `name` describes the command in errors, e.g. `Optimize`.
+pub fn run_template(
+    name: &str,
+    command: &str,
+    input: &Path,
+    output: &Path,
+) -> anyhow::Result<ExitStatus> {
+    let mut parts = command.split_whitespace().map(|part| {
+        part.replace("{input}", &input.to_string_lossy())
+            .replace("{output}", &output.to_string_lossy())
+    });
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{name} command is empty"))?;
+    debug!(
+        program,
+        ?input,
+        ?output,
+        "Running {} command",
+        name.to_lowercase()
+    );
+    Ok(Command::new(program).args(parts).status()?)
+}
@@ -0,0 +1,77 @@
+use std::{path::PathBuf, sync::Mutex, thread};
+
+use papers_core::{doc_type::DocType, paper::LoadedPaper};
+use serde::{Deserialize, Serialize};
+
+/// Tag for papers added without their document, to be fetched later with `papers fetch-missing`.
+pub const TO_FETCH_TAG: &str = "to-fetch";
+
+/// Number of documents fetched at once when not configured.
+pub const DEFAULT_JOBS: usize = 4;
+
+/// A document that was fetched, kept in the checkpoint until it is attached to its paper.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchedFile {
+    /// Where the document was saved, relative to the root of the repo.
+    pub filename: PathBuf,
+    /// Mirror the document came from, if not the paper's url.
+    pub mirror: Option<String>,
+}
+
+/// Whether a paper has a url to fetch its document from but no document.
+///
+/// Talks are opened by their url so never need one.
+pub fn is_missing(paper: &LoadedPaper) -> bool {
+    paper.meta.url.is_some()
+        && paper.meta.filename.is_none()
+        && paper.meta.doc_type != DocType::Talk
+}
+
+/// Apply `f` to each item on up to `jobs` threads, returning the results in the order of the
+/// items.
+pub fn map_concurrently<T, R, F>(items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let count = items.len();
+    let items = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new((0..count).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, count.max(1)) {
+            scope.spawn(|| loop {
+                // the lock is only held to take the next item, not while working on it
+                let next = items.lock().unwrap().next();
+                let Some((i, item)) = next else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_concurrently() {
+        let items = (0..20).collect::<Vec<u64>>();
+        let results = map_concurrently(items, 4, |i| {
+            // finish out of order
+            thread::sleep(std::time::Duration::from_millis(20 - i));
+            i * 2
+        });
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+        assert!(map_concurrently(Vec::<u64>::new(), 4, |i| i).is_empty());
+    }
+}
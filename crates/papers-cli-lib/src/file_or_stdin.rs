@@ -3,7 +3,7 @@ use std::{path::PathBuf, str::FromStr};
 /// A filename or stdin.
 #[derive(Debug, Clone)]
 pub enum FileOrStdin {
-    /// A filename.
+    /// A filename, or a directory when parsed with [`FileOrStdin::parse_allowing_dirs`].
     File(PathBuf),
     /// stdin.
     Stdin,
@@ -25,3 +25,15 @@ impl FromStr for FileOrStdin {
         }
     }
 }
+
+impl FileOrStdin {
+    /// Parse a filename or stdin, also accepting directories such as a Calibre library.
+    pub fn parse_allowing_dirs(s: &str) -> Result<Self, String> {
+        let path = PathBuf::from(s);
+        if path.is_dir() {
+            Ok(Self::File(path))
+        } else {
+            s.parse()
+        }
+    }
+}
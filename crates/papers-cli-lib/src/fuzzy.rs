@@ -1,49 +1,291 @@
-use papers_core::paper::{LoadedPaper, PaperMeta};
+use glob::{MatchOptions, Pattern};
+use papers_core::{
+    paper::{LoadedPaper, PaperMeta},
+    repo::Repo,
+    tag::Tag,
+};
 use skim::prelude::*;
-use std::sync::Arc;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
+
+use crate::{line_editor::read_line, recent::Recent, warning};
 
 struct FuzzyPaper(LoadedPaper);
 
+/// Something to do from the picker, which then opens again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerAction {
+    /// Open the document of the highlighted paper.
+    OpenDocument,
+    /// Open the url of the highlighted paper in the browser.
+    OpenUrl,
+    /// Ask for a tag and add it to the highlighted paper, or remove it if the paper already has
+    /// it.
+    ToggleTag,
+    /// Switch to the next [`PickerOrder`].
+    ToggleOrder,
+}
+
+/// Order of the papers in the picker.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PickerOrder {
+    /// Papers opened or edited often and recently first.
+    #[default]
+    Frecency,
+    /// Papers opened or edited most recently first.
+    Recent,
+    /// The order the papers were given in.
+    Given,
+}
+
+impl PickerOrder {
+    fn next(self) -> Self {
+        match self {
+            Self::Frecency => Self::Recent,
+            Self::Recent => Self::Given,
+            Self::Given => Self::Frecency,
+        }
+    }
+}
+
+/// Keys bound to actions in the picker, along with the name skim gives back for each.
+pub const PICKER_BINDINGS: &[(&str, &str, PickerAction)] = &[
+    ("alt-o", "open", PickerAction::OpenDocument),
+    ("alt-u", "url", PickerAction::OpenUrl),
+    ("alt-t", "tag", PickerAction::ToggleTag),
+    ("alt-r", "order", PickerAction::ToggleOrder),
+];
+
 /// Select a paper by fuzzy searching them.
-pub fn select_paper(papers: &[LoadedPaper]) -> Option<LoadedPaper> {
-    select_papers_inner(papers, false).first().cloned()
+pub fn select_paper(repo: &Repo, papers: &[LoadedPaper]) -> Option<LoadedPaper> {
+    select_papers_inner(repo, papers, false).first().cloned()
 }
 
 /// Select multiple papers by fuzzy searching them.
-pub fn select_papers(papers: &[LoadedPaper]) -> Vec<LoadedPaper> {
-    select_papers_inner(papers, true)
+pub fn select_papers(repo: &Repo, papers: &[LoadedPaper]) -> Vec<LoadedPaper> {
+    select_papers_inner(repo, papers, true)
+}
+
+fn select_papers_inner(repo: &Repo, papers: &[LoadedPaper], multi: bool) -> Vec<LoadedPaper> {
+    let by_path = papers
+        .iter()
+        .map(|p| (p.path.clone(), p))
+        .collect::<BTreeMap<_, _>>();
+    let paths = papers.iter().map(|p| p.path.clone()).collect();
+    pick(
+        repo,
+        paths,
+        |paths| {
+            paths
+                .into_iter()
+                .filter_map(|p| by_path.get(&p).map(|p| (*p).clone()))
+        },
+        multi,
+    )
+}
+
+/// Select a paper from the repo, showing the picker straight away and streaming papers into it as
+/// they are loaded.
+pub fn select_repo_paper(repo: &Repo) -> Option<LoadedPaper> {
+    pick(
+        repo,
+        repo.paper_paths(),
+        |paths| repo.load_papers(paths),
+        false,
+    )
+    .first()
+    .cloned()
+}
+
+/// Papers that fuzzy match `query` in the same way as the picker, best matches first.
+pub fn fuzzy_filter(papers: Vec<LoadedPaper>, query: &str) -> Vec<LoadedPaper> {
+    let engine = AndOrEngineFactory::new(ExactOrFuzzyEngineFactory::builder().build())
+        .create_engine_with_case(query, CaseMatching::Smart);
+    let mut matches = papers
+        .into_iter()
+        .filter_map(|paper| {
+            let item: Arc<dyn SkimItem> = Arc::new(FuzzyPaper(paper.clone()));
+            engine.match_item(item).map(|m| (m.rank, paper))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(rank, _)| *rank);
+    matches.into_iter().map(|(_, paper)| paper).collect()
 }
 
-fn select_papers_inner(papers: &[LoadedPaper], multi: bool) -> Vec<LoadedPaper> {
+/// Shortcut for the paper that was added most recently.
+pub const LAST_ADDED: &str = "@last-added";
+
+/// Shortcut for the paper that was changed most recently.
+pub const LAST_EDITED: &str = "@last-edited";
+
+/// Find the paper meant by a path, which may instead be part of a title or path, or a glob over
+/// them, failing if it matches no papers or several.
+///
+/// [`LAST_ADDED`] and [`LAST_EDITED`] pick out the most recently added and changed papers.
+pub fn resolve_paper(repo: &Repo, query: &Path) -> anyhow::Result<LoadedPaper> {
+    if repo.root().join(query).is_file() {
+        return repo.get_paper(query);
+    }
+    let query = query.to_string_lossy();
+    let mut papers = repo.all_papers()?;
+    papers.sort_by(|a, b| a.path.cmp(&b.path));
+    let latest = match query.as_ref() {
+        LAST_ADDED => Some(papers.iter().max_by_key(|p| p.meta.created_at)),
+        LAST_EDITED => Some(papers.iter().max_by_key(|p| p.meta.modified_at)),
+        _ => None,
+    };
+    if let Some(latest) = latest {
+        let latest = latest.ok_or_else(|| anyhow::anyhow!("There are no papers"))?;
+        return repo.get_paper(&latest.path);
+    }
+    let matches = if query.contains(['*', '?', '[']) {
+        let pattern = Pattern::new(&query)?;
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        papers
+            .into_iter()
+            .filter(|p| {
+                pattern.matches_path_with(&p.path, options)
+                    || pattern.matches_with(&p.meta.title, options)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        let query = query.to_lowercase();
+        let matches = papers
+            .into_iter()
+            .filter(|p| {
+                p.meta.title.to_lowercase().contains(&query)
+                    || p.path.to_string_lossy().to_lowercase().contains(&query)
+            })
+            .collect::<Vec<_>>();
+        // a whole title wins over the longer titles it is part of
+        let exact = matches
+            .iter()
+            .filter(|p| p.meta.title.to_lowercase() == query)
+            .cloned()
+            .collect::<Vec<_>>();
+        if exact.len() == 1 {
+            exact
+        } else {
+            matches
+        }
+    };
+    match <[LoadedPaper; 1]>::try_from(matches) {
+        Ok([paper]) => repo.get_paper(&paper.path),
+        Err(matches) if matches.is_empty() => anyhow::bail!("No paper matches {query:?}"),
+        Err(matches) => {
+            let candidates = matches
+                .iter()
+                .map(|p| format!("\n  {}", p.path.display()))
+                .collect::<String>();
+            anyhow::bail!("Several papers match {query:?}:{candidates}")
+        }
+    }
+}
+
+/// Run the picker over the papers at `paths`, loaded by `load`, until papers are selected or it is
+/// aborted, running any actions picked along the way.
+fn pick<I: Iterator<Item = LoadedPaper> + Send>(
+    repo: &Repo,
+    paths: Vec<PathBuf>,
+    load: impl Fn(Vec<PathBuf>) -> I,
+    multi: bool,
+) -> Vec<LoadedPaper> {
     // lines skim adds
     let ui_lines = 2;
-    let height = papers.len() + ui_lines;
+    let height = paths.len() + ui_lines;
     let height = height.to_string();
+    let bindings = PICKER_BINDINGS
+        .iter()
+        .map(|(key, name, _)| format!("{key}:accept({name})"))
+        .collect::<Vec<_>>();
+
+    // papers changed by actions, in place of the versions we were given
+    let mut changed = BTreeMap::<PathBuf, LoadedPaper>::new();
+    let mut query = String::new();
+    let mut order = PickerOrder::default();
+    loop {
+        let mut paths = paths.clone();
+        let recent = Recent::load(repo.root());
+        match order {
+            PickerOrder::Frecency => recent.order_by_frecency(&mut paths, |p| repo.relative(p)),
+            PickerOrder::Recent => recent.order(&mut paths, |p| repo.relative(p)),
+            PickerOrder::Given => {}
+        }
+        let options = SkimOptionsBuilder::default()
+            .height(Some(&height))
+            .multi(multi)
+            .case(CaseMatching::Smart)
+            .bind(bindings.iter().map(String::as_str).collect())
+            .query(Some(&query))
+            // keep the order papers are given in for equally good matches
+            .tiebreak(Some("score".to_owned()))
+            .build()
+            .unwrap();
 
-    let options = SkimOptionsBuilder::default()
-        .height(Some(&height))
-        .multi(multi)
-        .case(CaseMatching::Smart)
-        .build()
-        .unwrap();
-
-    let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
-    for paper in papers {
-        let p = FuzzyPaper(paper.clone());
-        tx_item.send(Arc::new(p)).unwrap();
+        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+        let papers = load(paths).map(|p| changed.get(&p.path).cloned().unwrap_or(p));
+        let (action, selected, last_query) = thread::scope(|scope| {
+            scope.spawn(move || {
+                for paper in papers {
+                    // the picker has closed so nobody wants the rest
+                    if tx_item.send(Arc::new(FuzzyPaper(paper))).is_err() {
+                        break;
+                    }
+                }
+            });
+            run_skim(&options, rx_item)
+        });
+        let Some(action) = action else {
+            return selected;
+        };
+        query = last_query;
+        if action == PickerAction::ToggleOrder {
+            order = order.next();
+            continue;
+        }
+        let Some(paper) = selected.first() else {
+            continue;
+        };
+        match run_action(repo, action, paper.clone()) {
+            Ok(Some(paper)) => {
+                changed.insert(paper.path.clone(), paper);
+            }
+            Ok(None) => {}
+            Err(err) => warning!("{err:#}"),
+        }
     }
-    drop(tx_item);
+}
 
-    let skim_result = match Skim::run_with(&options, Some(rx_item)) {
+/// Run the picker, giving back the action chosen if any, the selected papers and the query.
+fn run_skim(
+    options: &SkimOptions,
+    rx_item: SkimItemReceiver,
+) -> (Option<PickerAction>, Vec<LoadedPaper>, String) {
+    let skim_result = match Skim::run_with(options, Some(rx_item)) {
         Some(result) => result,
-        None => return Vec::new(),
+        None => return (None, Vec::new(), String::new()),
     };
 
     // don't continue if the user actually aborted rather than selecting
     if skim_result.is_abort {
-        return Vec::new();
+        return (None, Vec::new(), String::new());
     }
 
+    let action = match &skim_result.final_event {
+        Event::EvActAccept(Some(name)) => PICKER_BINDINGS
+            .iter()
+            .find(|(_, n, _)| n == name)
+            .map(|(_, _, action)| *action),
+        _ => None,
+    };
+
     let selected_papers = skim_result.selected_items.iter().map(|item| {
         (**item)
             .as_any()
@@ -52,15 +294,62 @@ fn select_papers_inner(papers: &[LoadedPaper], multi: bool) -> Vec<LoadedPaper>
             .to_owned()
     });
 
-    selected_papers.map(|p| p.0.clone()).collect()
+    (
+        action,
+        selected_papers.map(|p| p.0.clone()).collect(),
+        skim_result.query,
+    )
+}
+
+/// Do an action to a paper, giving back the paper if the action changed it.
+fn run_action(
+    repo: &Repo,
+    action: PickerAction,
+    mut paper: LoadedPaper,
+) -> anyhow::Result<Option<LoadedPaper>> {
+    match action {
+        PickerAction::OpenDocument => match &paper.meta.filename {
+            Some(filename) => {
+                open::that_detached(repo.root().join(filename))?;
+                Recent::record(repo.root(), &paper.path);
+            }
+            None => warning!("{:?} has no document", paper.path),
+        },
+        PickerAction::OpenUrl => match &paper.meta.url {
+            Some(url) => {
+                open::that_detached(url)?;
+                Recent::record(repo.root(), &paper.path);
+            }
+            None => warning!("{:?} has no url", paper.path),
+        },
+        PickerAction::ToggleTag => {
+            let Some(tag) = read_line("Toggle tag: ")? else {
+                return Ok(None);
+            };
+            let tag = tag.trim();
+            if tag.is_empty() || tag.contains(char::is_whitespace) {
+                anyhow::bail!("Tags can't be empty or contain whitespace");
+            }
+            let tag = Tag::new(tag);
+            if !paper.meta.tags.remove(&tag) {
+                paper.meta.tags.insert(tag);
+            }
+            repo.write_paper(&paper.path, paper.meta.clone(), &paper.notes)?;
+            return Ok(Some(repo.get_paper(&paper.path)?));
+        }
+        PickerAction::ToggleOrder => {}
+    }
+    Ok(None)
 }
 
 impl SkimItem for FuzzyPaper {
-    fn text(&self) -> Cow<str> {
+    fn text(&self) -> Cow<'_, str> {
         let PaperMeta {
             title,
+            doc_type: _,
             url: _,
             filename: _,
+            sha256: _,
             tags,
             labels,
             authors,
@@ -68,6 +357,11 @@ impl SkimItem for FuzzyPaper {
             modified_at: _,
             last_review: _,
             next_review: _,
+            no_review: _,
+            ratings: _,
+            read_by: _,
+            provenance: _,
+            extra: _,
         } = &self.0.meta;
         let authors = authors
             .iter()
@@ -91,3 +385,86 @@ impl SkimItem for FuzzyPaper {
         .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use expect_test::{expect, Expect};
+    use papers_core::{
+        doc_type::DocType,
+        paper::{Provenance, Source},
+    };
+
+    use super::*;
+
+    fn check_resolve(repo: &Repo, query: &str, expected: Expect) {
+        let resolved = resolve_paper(repo, Path::new(query))
+            .map(|p| p.path.display().to_string())
+            .unwrap_or_else(|e| e.to_string());
+        expected.assert_eq(&resolved);
+    }
+
+    #[test]
+    fn test_resolve_paper() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repo::load(dir.path()).unwrap();
+        for title in ["Raft", "Raft Extended", "Paxos Made Simple"] {
+            repo.add::<&Path>(
+                None,
+                None,
+                title.to_owned(),
+                DocType::Paper,
+                Vec::new(),
+                BTreeSet::new(),
+                BTreeMap::new(),
+                None,
+                Provenance::now(Source::Add, None),
+                BTreeMap::new(),
+                "",
+            )
+            .unwrap();
+        }
+
+        check_resolve(&repo, "Raft Extended.md", expect!["Raft Extended.md"]);
+        check_resolve(&repo, "paxos", expect!["Paxos Made Simple.md"]);
+        check_resolve(&repo, "raft", expect!["Raft.md"]);
+        check_resolve(&repo, "Paxos*", expect!["Paxos Made Simple.md"]);
+        check_resolve(&repo, "zab", expect![[r#"No paper matches "zab""#]]);
+        check_resolve(
+            &repo,
+            "*a*",
+            expect![[r#"
+            Several papers match "*a*":
+              Paxos Made Simple.md
+              Raft Extended.md
+              Raft.md"#]],
+        );
+    }
+
+    #[test]
+    fn test_resolve_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repo::load(dir.path()).unwrap();
+        check_resolve(&repo, LAST_ADDED, expect!["There are no papers"]);
+        let date = |day| {
+            chrono::NaiveDate::from_ymd_opt(2023, 8, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        for (title, created, modified) in [("old", 1, 9), ("new", 5, 6)] {
+            let meta = PaperMeta {
+                title: title.to_owned(),
+                created_at: date(created),
+                modified_at: date(modified),
+                ..Default::default()
+            };
+            let contents = papers_core::frontmatter::render(&meta, "").unwrap();
+            std::fs::write(dir.path().join(format!("{title}.md")), contents).unwrap();
+        }
+
+        check_resolve(&repo, LAST_ADDED, expect!["new.md"]);
+        check_resolve(&repo, LAST_EDITED, expect!["old.md"]);
+    }
+}
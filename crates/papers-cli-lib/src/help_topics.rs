@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use clap::CommandFactory;
+
+use crate::cli::Cli;
+
+/// A long-form help page about a concept rather than a single subcommand.
+#[derive(Debug, Clone, Copy)]
+pub struct HelpTopic {
+    /// Name used to look up the topic.
+    pub name: &'static str,
+    /// One line description shown in the topic list.
+    pub summary: &'static str,
+    /// Full text of the topic.
+    pub body: &'static str,
+}
+
+/// All of the available help topics.
+pub const TOPICS: &[HelpTopic] = &[
+    HelpTopic {
+        name: "reviews",
+        summary: "Spaced repetition of papers with `papers review`",
+        body: "\
+Papers can be reviewed periodically so that their contents stay fresh.
+
+Running `papers review` lets you pick from the papers that are due for review and opens their
+notes in $EDITOR (add `--open` to open the document too). Once the editor closes the review is
+recorded in the paper's `last_review` and `next_review` fields.
+
+After editing you are asked how well you remembered the paper: again, hard, good or easy. Again
+starts the waits over, hard makes the next wait only a little longer, good lengthens it as usual
+and easy a further third. Press enter to skip rating, which counts as good. The ratings are kept,
+with when they were given, in the paper's `ratings` field.
+
+A paper that has never been reviewed is always due. After each review the wait until the next one
+grows: the first waits are one and two days, after that the number of days between the last two
+reviews is raised to the configured `review.power`. With the default power of 2 reviews happen
+after 1, 2, 4, 16, 256, ... days.
+
+The review dates are plain frontmatter fields, so they can be edited by hand to bring a review
+forward or push it back.",
+    },
+    HelpTopic {
+        name: "query-syntax",
+        summary: "Filtering papers in `papers list`",
+        body: "\
+`papers list` shows every paper, filters narrow it down and are all combined with AND.
+
+  --title <TEXT>          title contains TEXT, ignoring case
+  -f, --file <TEXT>       document filename contains TEXT, ignoring case
+  -a, --author <AUTHOR>   has this author, repeat to require several
+  -t, --tag <TAG>         has this tag, repeat to require several
+  -l, --label <KEY=VALUE> has a label KEY with VALUE, repeat to require several
+  --title-re <REGEX>      title matches the regular expression REGEX
+  --file-re <REGEX>       document filename matches REGEX
+  --author-re <REGEX>     some author's name matches REGEX
+  --has-notes             has notes written beyond the notes template, or --no-notes for none
+  -q, --query <TEXT>      fuzzy matches TEXT like the picker does, best matches first
+  --added-since <TIME>    added since TIME, a date like 2023-08-01 or an age like 2w, also
+                          --added-before, --modified-since, --modified-before, --read-since
+                          and --read-before
+
+Tags, authors and label values match ignoring case and accents unless `exact_filters` is set in
+the config. Regular expressions are case sensitive, start them with `(?i)` to ignore case:
+
+  papers list --title-re '(?i)^(raft|paxos)$' --author-re 'Lamport$'
+
+Results can be ordered with `--sort title|created-at|modified-at|citations|frecency` and printed
+as a table, json, yaml, csv or plain text with `-o`. For example:
+
+  papers list --tag to-read --label venue=osdi --sort created-at -o json
+
+For scripts, `--format` prints chosen fields of each paper on a line instead. Fields are written in
+braces and `\\t` and `\\n` give tabs and newlines:
+
+  papers list --tag to-read --format '{title}\\t{url}'
+
+The fields are title, path, url, filename, doc_type, authors, tags, labels, created_at,
+modified_at, last_review, next_review, read_by, source and source_detail (how the paper entered the
+repo, e.g. import and the file imported), label.<key> for a single label and extra.<key> for a field
+kept under `extra` in the frontmatter. Fields with multiple values are joined with commas and `{{`
+or `}}` give literal braces.
+
+`--label key=value` also matches fields under `extra` when the paper has no such label, and
+`--source add` or `--source import` keeps the papers that entered the repo that way.
+
+With `--fail-if-empty` the exit status is 1 when nothing matches, for use in shell conditionals:
+
+  if papers list --tag to-read --fail-if-empty --format '{title}'; then ...",
+    },
+    HelpTopic {
+        name: "templates",
+        summary: "Templates for the notes of new papers",
+        body: "\
+New papers start their notes from the `notes_template` in the config file. It takes one of two
+forms:
+
+  notes_template:
+    file: template.md       # absolute, or relative to default_repo
+
+  notes_template:
+    content: |
+      ## Summary
+
+      ## Thoughts
+
+The template is copied verbatim below the frontmatter. Talks (`papers add --doc-type talk`) get an
+extra section appended for timestamps.
+
+Default tags and labels for new papers can be set with `paper_defaults.tags` and
+`paper_defaults.labels`.",
+    },
+];
+
+/// Find a help topic by name.
+pub fn find_topic(name: &str) -> Option<&'static HelpTopic> {
+    TOPICS.iter().find(|t| t.name == name)
+}
+
+/// List the available topics, one per line.
+pub fn topic_list() -> String {
+    let width = TOPICS
+        .iter()
+        .map(|t| t.name.len())
+        .max()
+        .unwrap_or_default();
+    let mut list = String::from("Help topics:\n");
+    for topic in TOPICS {
+        writeln!(list, "  {:width$}  {}", topic.name, topic.summary).unwrap();
+    }
+    list
+}
+
+/// Render the help for a subcommand or a topic, or the general help if neither is given.
+pub fn render_help(name: Option<&str>) -> anyhow::Result<String> {
+    let mut cmd = Cli::command().name("papers");
+    cmd.build();
+    match name {
+        None => Ok(format!(
+            "{}\n{}\nUse `papers help <TOPIC>` to read one.",
+            cmd.render_long_help(),
+            topic_list()
+        )),
+        Some("topics") => Ok(topic_list()),
+        Some(name) => {
+            if let Some(topic) = find_topic(name) {
+                Ok(topic.body.to_owned())
+            } else if let Some(subcommand) = cmd.find_subcommand_mut(name) {
+                Ok(subcommand.render_long_help().to_string())
+            } else {
+                anyhow::bail!(
+                    "No subcommand or help topic called {name:?}, see `papers help topics`"
+                )
+            }
+        }
+    }
+}
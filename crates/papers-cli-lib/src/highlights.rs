@@ -0,0 +1,347 @@
+use std::{fmt::Display, path::PathBuf};
+
+use papers_core::paper::PaperMeta;
+use serde::Serialize;
+
+use crate::{citations::DOI_LABEL, export::heading, output::Render};
+
+/// Heading of the section of the notes that highlights are kept in.
+pub const HIGHLIGHTS_SECTION: &str = "Highlights";
+
+/// A highlighted passage of a paper, or a comment on it, as kept in the notes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    /// Where the highlight was synced from and its id there, e.g. `hypothesis:a1`, so it is only
+    /// added once.
+    pub marker: Option<String>,
+    /// Text that was highlighted, if any.
+    pub quote: Option<String>,
+    /// Comment on the highlight, or the whole of one written by hand.
+    pub text: String,
+}
+
+/// Uris a paper may be known by elsewhere, its url and DOI.
+pub fn paper_uris(meta: &PaperMeta) -> Vec<String> {
+    let mut uris = Vec::new();
+    if let Some(url) = &meta.url {
+        uris.push(url.clone());
+    }
+    if let Some(doi) = meta.labels.get(DOI_LABEL) {
+        uris.push(format!("https://doi.org/{doi}"));
+        uris.push(format!("doi:{doi}"));
+    }
+    uris
+}
+
+/// Comment marking where a highlight was synced from.
+fn marker_comment(marker: &str) -> String {
+    format!("<!-- {marker} -->")
+}
+
+/// Split the marker comment off the end of a line.
+fn split_marker(line: &str) -> (&str, Option<&str>) {
+    let line = line.trim_end();
+    let Some(rest) = line.strip_suffix("-->") else {
+        return (line, None);
+    };
+    match rest.rfind("<!--") {
+        Some(start) => (
+            line[..start].trim_end(),
+            Some(rest[start + "<!--".len()..].trim()),
+        ),
+        None => (line, None),
+    }
+}
+
+/// Collapse runs of whitespace, including newlines, to single spaces.
+fn flatten(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl Highlight {
+    /// Markdown list item for the highlight, ending with its marker.
+    fn item(&self) -> String {
+        let text = flatten(&self.text);
+        let mut item = match (&self.quote, text.is_empty()) {
+            (Some(quote), true) => format!("- > {}", flatten(quote)),
+            (Some(quote), false) => format!("- > {}\n  {text}", flatten(quote)),
+            (None, _) => format!("- {text}"),
+        };
+        if let Some(marker) = &self.marker {
+            item.push(' ');
+            item.push_str(&marker_comment(marker));
+        }
+        item
+    }
+
+    /// Parse a list item written by [`Self::item`], or by hand.
+    fn parse(item: &[&str]) -> Option<Self> {
+        let (first, rest) = item.split_first()?;
+        let first = first.trim_start().strip_prefix(['-', '*'])?.trim_start();
+        let mut marker = None;
+        let mut lines = std::iter::once(first)
+            .chain(rest.iter().map(|l| l.trim()))
+            .map(|l| {
+                let (l, m) = split_marker(l);
+                marker = marker.or(m);
+                l
+            })
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>();
+        let quote = match lines.first().and_then(|l| l.strip_prefix('>')) {
+            Some(quote) => {
+                let quote = quote.trim().to_owned();
+                lines.remove(0);
+                Some(quote)
+            }
+            None => None,
+        };
+        let text = lines.join(" ");
+        if quote.is_none() && text.is_empty() {
+            return None;
+        }
+        Some(Self {
+            marker: marker.map(str::to_owned),
+            quote,
+            text,
+        })
+    }
+}
+
+/// Whether the line starts a list item.
+fn is_item_start(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("- ") || line.starts_with("* ")
+}
+
+/// Whether the line starts a list item or continues one.
+fn is_list_line(line: &str) -> bool {
+    is_item_start(line) || (line.starts_with([' ', '\t']) && !line.trim().is_empty())
+}
+
+/// Range of lines of the highlights section, after its heading, if the notes have one.
+fn section(lines: &[&str]) -> Option<std::ops::Range<usize>> {
+    let mut start = None;
+    for (i, line) in lines.iter().enumerate() {
+        match (heading(line), start) {
+            (Some((level, text)), None) if text.eq_ignore_ascii_case(HIGHLIGHTS_SECTION) => {
+                start = Some((i + 1, level));
+            }
+            (Some((level, _)), Some((start, section))) if level <= section => {
+                return Some(start..i);
+            }
+            _ => {}
+        }
+    }
+    start.map(|(start, _)| start..lines.len())
+}
+
+/// The highlights listed in the highlights section of the notes.
+pub fn highlights(notes: &str) -> Vec<Highlight> {
+    let lines = notes.lines().collect::<Vec<_>>();
+    let Some(range) = section(&lines) else {
+        return Vec::new();
+    };
+    let mut items: Vec<Vec<&str>> = Vec::new();
+    for line in &lines[range] {
+        if is_item_start(line) {
+            items.push(vec![line]);
+        } else if is_list_line(line) {
+            // continuing the last item
+            if let Some(item) = items.last_mut() {
+                item.push(line);
+            }
+        }
+    }
+    items.iter().filter_map(|i| Highlight::parse(i)).collect()
+}
+
+/// Add the highlights whose markers aren't already in the notes to the end of their highlights
+/// section, creating it if needed, returning how many were added.
+pub fn merge_highlights(notes: &mut String, highlights: &[Highlight]) -> usize {
+    let new = highlights
+        .iter()
+        .filter(|h| {
+            h.marker
+                .as_deref()
+                .is_none_or(|m| !notes.contains(&marker_comment(m)))
+        })
+        .map(Highlight::item)
+        .collect::<Vec<_>>();
+    if new.is_empty() {
+        return 0;
+    }
+
+    let mut lines = notes.lines().map(str::to_owned).collect::<Vec<_>>();
+    let range = section(&lines.iter().map(String::as_str).collect::<Vec<_>>());
+    let mut end = match range {
+        Some(range) => range.end,
+        None => {
+            lines.extend([String::new(), format!("## {HIGHLIGHTS_SECTION}")]);
+            lines.len()
+        }
+    };
+    // after the last thing written in the section
+    while end > 0 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    // continuing the list of earlier highlights, otherwise set apart from what comes before
+    let mut insert = Vec::new();
+    if end == 0 || !is_list_line(&lines[end - 1]) {
+        insert.push(String::new());
+    }
+    insert.extend(new.iter().cloned());
+    if lines.get(end).is_some_and(|l| !l.trim().is_empty()) {
+        insert.push(String::new());
+    }
+    lines.splice(end..end, insert);
+    *notes = lines.join("\n") + "\n";
+    new.len()
+}
+
+/// Highlights added to the notes of a paper.
+#[derive(Debug, Serialize)]
+pub struct SyncedPaper {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Number of highlights added.
+    pub added: usize,
+}
+
+/// Papers that had highlights added.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct SyncedPapers(pub Vec<SyncedPaper>);
+
+impl Display for SyncedPapers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|p| format!("Added {} highlights to {:?}", p.added, p.path))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for SyncedPapers {
+    fn header(&self) -> Vec<String> {
+        ["path", "added"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|p| vec![p.path.to_string_lossy().into_owned(), p.added.to_string()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    fn highlight(marker: &str, quote: Option<&str>, text: &str) -> Highlight {
+        Highlight {
+            marker: Some(marker.to_owned()),
+            quote: quote.map(str::to_owned),
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_paper_uris() {
+        let mut meta = PaperMeta {
+            url: Some("https://raft.github.io/raft.pdf".to_owned()),
+            ..Default::default()
+        };
+        meta.labels.insert(
+            DOI_LABEL.to_owned(),
+            "10.5555/2643634.2643666".parse().unwrap(),
+        );
+        assert_eq!(
+            paper_uris(&meta),
+            vec![
+                "https://raft.github.io/raft.pdf",
+                "https://doi.org/10.5555/2643634.2643666",
+                "doi:10.5555/2643634.2643666",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_highlights() {
+        let highlights = [
+            highlight("hypothesis:a1", Some("a consensus\n algorithm"), "Key idea"),
+            highlight("hypothesis:a2", Some("understandable"), ""),
+        ];
+        let mut notes = "## Summary\n\nLeader based.\n".to_owned();
+        assert_eq!(merge_highlights(&mut notes, &highlights[..1]), 1);
+        expect![[r#"
+            ## Summary
+
+            Leader based.
+
+            ## Highlights
+
+            - > a consensus algorithm
+              Key idea <!-- hypothesis:a1 -->
+        "#]]
+        .assert_eq(&notes);
+
+        // only new highlights are added, to the end of the existing section
+        notes.push_str("\n## Thoughts\n\nNice.\n");
+        assert_eq!(merge_highlights(&mut notes, &highlights), 1);
+        assert_eq!(merge_highlights(&mut notes, &highlights), 0);
+        expect![[r#"
+            ## Summary
+
+            Leader based.
+
+            ## Highlights
+
+            - > a consensus algorithm
+              Key idea <!-- hypothesis:a1 -->
+            - > understandable <!-- hypothesis:a2 -->
+
+            ## Thoughts
+
+            Nice.
+        "#]]
+        .assert_eq(&notes);
+
+        // a highlights section from the template is filled in
+        let mut notes = "## Highlights\n\n## Thoughts\n".to_owned();
+        merge_highlights(&mut notes, &highlights[1..]);
+        assert_eq!(
+            notes,
+            "## Highlights\n\n- > understandable <!-- hypothesis:a2 -->\n\n## Thoughts\n"
+        );
+    }
+
+    #[test]
+    fn test_highlights() {
+        let notes = "## Summary\n\n- Not a highlight\n\n## Highlights\n\n- > a consensus algorithm\n  Key idea <!-- hypothesis:a1 -->\n- > understandable <!-- readwise:12 -->\n* Written by hand,\n  over two lines\n\n### Detail\n\n- In a subsection\n\n## Thoughts\n\n- Not a highlight either\n";
+        let expected = [
+            highlight("hypothesis:a1", Some("a consensus algorithm"), "Key idea"),
+            highlight("readwise:12", Some("understandable"), ""),
+            Highlight {
+                marker: None,
+                quote: None,
+                text: "Written by hand, over two lines".to_owned(),
+            },
+            Highlight {
+                marker: None,
+                quote: None,
+                text: "In a subsection".to_owned(),
+            },
+        ];
+        assert_eq!(highlights(notes), expected);
+        // reading back what was written
+        let mut notes = String::new();
+        merge_highlights(&mut notes, &expected[..2]);
+        assert_eq!(highlights(&notes), expected[..2]);
+    }
+}
@@ -0,0 +1,474 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{create_dir_all, metadata, read_to_string, write},
+    path::PathBuf,
+    sync::{Condvar, Mutex},
+    thread::sleep,
+    time::{Duration, Instant, SystemTime},
+};
+
+use directories::ProjectDirs;
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    Url,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Label recording the url a document was fetched from when it came from a mirror.
+pub const MIRROR_LABEL: &str = "mirror";
+
+static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
+
+/// Configuration for requests to external services.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// Minimum time between requests to the same host, in milliseconds.
+    #[serde(default = "default_rate_limit_ms")]
+    pub rate_limit_ms: u64,
+
+    /// Most documents fetched from the same host at once when fetching in bulk.
+    #[serde(default = "default_max_concurrent_per_host")]
+    pub max_concurrent_per_host: usize,
+
+    /// Limits for particular hosts, overriding the ones above. A domain also covers its
+    /// subdomains, with the most specific domain taking precedence.
+    #[serde(default)]
+    pub hosts: BTreeMap<String, HostConfig>,
+
+    /// Alternate hosts to fetch documents from, tried in order when fetching from a host fails,
+    /// e.g. `{arxiv.org: [export.arxiv.org]}`. Only the hosts listed here are ever tried.
+    #[serde(default)]
+    pub mirrors: BTreeMap<String, Vec<String>>,
+
+    /// Whether to cache metadata responses on disk.
+    #[serde(default = "default_cache")]
+    pub cache: bool,
+
+    /// How long cached responses stay fresh, in seconds.
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+
+    /// Directory to cache responses in, defaults to the user's cache directory.
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Don't make any requests, only using cached responses.
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Timeout for establishing connections, in seconds.
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+
+    /// Timeout for whole requests, including downloading the body, in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_rate_limit_ms() -> u64 {
+    1000
+}
+
+fn default_max_concurrent_per_host() -> usize {
+    2
+}
+
+fn default_cache() -> bool {
+    true
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    60 * 60 * 24 * 7
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    30
+}
+
+fn default_timeout_secs() -> u64 {
+    10 * 60
+}
+
+/// Limits for requests to one host, e.g. a publisher that throttles clients.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostConfig {
+    /// Minimum time between requests to the host, in milliseconds.
+    #[serde(default)]
+    pub rate_limit_ms: Option<u64>,
+
+    /// Most documents fetched from the host at once when fetching in bulk.
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit_ms: default_rate_limit_ms(),
+            max_concurrent_per_host: default_max_concurrent_per_host(),
+            hosts: BTreeMap::new(),
+            mirrors: BTreeMap::new(),
+            cache: default_cache(),
+            cache_ttl_secs: default_cache_ttl_secs(),
+            cache_dir: None,
+            offline: false,
+            connect_timeout_secs: default_connect_timeout_secs(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+/// Http client shared by everything talking to external services.
+///
+/// Requests are rate limited per host and metadata responses are cached on disk.
+pub struct HttpClient {
+    client: Client,
+    config: HttpConfig,
+    cache_dir: Option<PathBuf>,
+    /// When the next request to each host may be made.
+    next_requests: Mutex<HashMap<String, Instant>>,
+    /// Number of permits held for each host.
+    in_flight: Mutex<HashMap<String, usize>>,
+    released: Condvar,
+}
+
+/// A slot for fetching from a host, given back when dropped.
+pub struct HostPermit<'a> {
+    client: &'a HttpClient,
+    host: String,
+}
+
+impl Drop for HostPermit<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.client.in_flight.lock().unwrap();
+        if let Some(count) = in_flight.get_mut(&self.host) {
+            *count = count.saturating_sub(1);
+        }
+        self.client.released.notify_all();
+    }
+}
+
+impl HttpClient {
+    /// Build a new client from the config.
+    pub fn new(config: &HttpConfig) -> anyhow::Result<Self> {
+        debug!(user_agent = APP_USER_AGENT, "Building http client");
+        let client = match Client::builder()
+            .user_agent(APP_USER_AGENT)
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                warn!(%err, "Failed to create http client.");
+                return Err(err.into());
+            }
+        };
+        let cache_dir = if config.cache {
+            config.cache_dir.clone().or_else(|| {
+                ProjectDirs::from("io", "jeffas", "papers").map(|d| d.cache_dir().join("http"))
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            client,
+            config: config.clone(),
+            cache_dir,
+            next_requests: Mutex::default(),
+            in_flight: Mutex::default(),
+            released: Condvar::new(),
+        })
+    }
+
+    /// Whether the client is in offline mode.
+    pub fn is_offline(&self) -> bool {
+        self.config.offline
+    }
+
+    /// Make a GET request, failing on error statuses.
+    pub fn get(&self, url: &Url) -> anyhow::Result<Response> {
+        self.get_with(url, |r| r)
+    }
+
+    /// Make a GET request with the headers set by `request`, failing on error statuses.
+    pub fn get_with(
+        &self,
+        url: &Url,
+        request: impl FnOnce(RequestBuilder) -> RequestBuilder,
+    ) -> anyhow::Result<Response> {
+        self.check_online(url)?;
+        self.wait_for_host(url);
+        debug!(%url, "GET");
+        Ok(request(self.client.get(url.clone()))
+            .send()?
+            .error_for_status()?)
+    }
+
+    /// Make a HEAD request, following redirects.
+    pub fn head(&self, url: &Url) -> anyhow::Result<Response> {
+        self.check_online(url)?;
+        self.wait_for_host(url);
+        debug!(%url, "HEAD");
+        Ok(self.client.head(url.clone()).send()?)
+    }
+
+    /// Make a POST request with the body and headers set by `request`, failing on error statuses.
+    pub fn post(
+        &self,
+        url: &Url,
+        request: impl FnOnce(RequestBuilder) -> RequestBuilder,
+    ) -> anyhow::Result<Response> {
+        self.check_online(url)?;
+        self.wait_for_host(url);
+        debug!(%url, "POST");
+        Ok(request(self.client.post(url.clone()))
+            .send()?
+            .error_for_status()?)
+    }
+
+    /// Get the body of a url as text, using the cache if possible.
+    pub fn get_text(&self, url: &Url) -> anyhow::Result<String> {
+        let cache_path = self.cache_path(url);
+        if let Some(cache_path) = &cache_path {
+            if let Some(body) = self.load_cached(cache_path) {
+                debug!(%url, ?cache_path, "Using cached response");
+                return Ok(body);
+            }
+        }
+
+        let body = self.get(url)?.text()?;
+
+        if let Some(cache_path) = &cache_path {
+            if let Err(err) = store_cached(cache_path, &body) {
+                warn!(%err, ?cache_path, "Failed to cache response");
+            }
+        }
+        Ok(body)
+    }
+
+    /// Wait until fewer than the allowed number of fetches from the host of `url` are running,
+    /// holding a slot until the permit is dropped.
+    pub fn acquire(&self, url: &Url) -> HostPermit<'_> {
+        let host = url.host_str().unwrap_or_default().to_owned();
+        let max = self
+            .host_config(&host)
+            .and_then(|c| c.max_concurrent)
+            .unwrap_or(self.config.max_concurrent_per_host)
+            .max(1);
+        let mut in_flight = self.in_flight.lock().unwrap();
+        while in_flight.get(&host).is_some_and(|&n| n >= max) {
+            debug!(host, max, "Waiting for a free slot");
+            in_flight = self.released.wait(in_flight).unwrap();
+        }
+        *in_flight.entry(host.clone()).or_default() += 1;
+        HostPermit { client: self, host }
+    }
+
+    /// The url followed by the same url on each configured mirror of its host, in order.
+    pub fn with_mirrors(&self, url: &Url) -> Vec<Url> {
+        let mut urls = vec![url.clone()];
+        let mirrors = url.host_str().and_then(|h| self.config.mirrors.get(h));
+        for mirror in mirrors.into_iter().flatten() {
+            let mut alternate = url.clone();
+            match alternate.set_host(Some(mirror)) {
+                Ok(()) => urls.push(alternate),
+                Err(err) => warn!(%err, mirror, "Invalid mirror host"),
+            }
+        }
+        urls
+    }
+
+    /// The limits for a host from the most specific matching domain in the config.
+    fn host_config(&self, host: &str) -> Option<&HostConfig> {
+        self.config
+            .hosts
+            .iter()
+            .filter(|(domain, _)| {
+                host == domain.as_str()
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|sub| sub.ends_with('.'))
+            })
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(_, config)| config)
+    }
+
+    fn check_online(&self, url: &Url) -> anyhow::Result<()> {
+        if self.config.offline {
+            anyhow::bail!("Offline, not fetching {}", url);
+        }
+        Ok(())
+    }
+
+    fn wait_for_host(&self, url: &Url) {
+        let host = url.host_str().unwrap_or_default().to_owned();
+        let interval = Duration::from_millis(
+            self.host_config(&host)
+                .and_then(|c| c.rate_limit_ms)
+                .unwrap_or(self.config.rate_limit_ms),
+        );
+        // reserve the next slot, so that concurrent requests to the same host are spaced out
+        // without holding the lock while waiting
+        let wait = {
+            let mut next_requests = self.next_requests.lock().unwrap();
+            let now = Instant::now();
+            let start = next_requests.get(&host).map_or(now, |&next| next.max(now));
+            next_requests.insert(host.clone(), start + interval);
+            start - now
+        };
+        if !wait.is_zero() {
+            debug!(host, ?wait, "Rate limiting");
+            sleep(wait);
+        }
+    }
+
+    fn cache_path(&self, url: &Url) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}", cache_key(url.as_str()))))
+    }
+
+    fn load_cached(&self, path: &PathBuf) -> Option<String> {
+        let modified = metadata(path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        // stale entries are still better than nothing when offline
+        if !self.config.offline && age > Duration::from_secs(self.config.cache_ttl_secs) {
+            return None;
+        }
+        read_to_string(path).ok()
+    }
+}
+
+fn store_cached(path: &PathBuf, body: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    write(path, body)?;
+    Ok(())
+}
+
+/// Stable hash of a url for naming cache entries (64-bit FNV-1a).
+fn cache_key(url: &str) -> u64 {
+    url.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_cache_key_stable() {
+        expect!["cbf29ce484222325"].assert_eq(&format!("{:016x}", cache_key("")));
+        expect!["046a4934057f51cf"]
+            .assert_eq(&format!("{:016x}", cache_key("https://example.com/a")));
+    }
+
+    #[test]
+    fn test_host_config() {
+        let config = HttpConfig {
+            hosts: BTreeMap::from([
+                (
+                    "arxiv.org".to_owned(),
+                    HostConfig {
+                        rate_limit_ms: Some(3000),
+                        max_concurrent: Some(1),
+                    },
+                ),
+                (
+                    "export.arxiv.org".to_owned(),
+                    HostConfig {
+                        rate_limit_ms: Some(5000),
+                        max_concurrent: None,
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+        let client = HttpClient::new(&config).unwrap();
+        let limit = |host| client.host_config(host).and_then(|c| c.rate_limit_ms);
+        assert_eq!(limit("arxiv.org"), Some(3000));
+        assert_eq!(limit("www.arxiv.org"), Some(3000));
+        assert_eq!(limit("export.arxiv.org"), Some(5000));
+        assert_eq!(limit("notarxiv.org"), None);
+    }
+
+    #[test]
+    fn test_with_mirrors() {
+        let config = HttpConfig {
+            mirrors: BTreeMap::from([(
+                "arxiv.org".to_owned(),
+                vec!["export.arxiv.org".to_owned(), "not a host".to_owned()],
+            )]),
+            ..Default::default()
+        };
+        let client = HttpClient::new(&config).unwrap();
+        let urls = |url| {
+            client
+                .with_mirrors(&Url::parse(url).unwrap())
+                .iter()
+                .map(Url::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            urls("https://arxiv.org/pdf/2101.00001v1"),
+            [
+                "https://arxiv.org/pdf/2101.00001v1",
+                "https://export.arxiv.org/pdf/2101.00001v1"
+            ]
+        );
+        assert_eq!(
+            urls("https://www.arxiv.org/pdf/2101.00001v1"),
+            ["https://www.arxiv.org/pdf/2101.00001v1"]
+        );
+    }
+
+    #[test]
+    fn test_acquire_limits_per_host() {
+        let config = HttpConfig {
+            max_concurrent_per_host: 1,
+            ..Default::default()
+        };
+        let client = HttpClient::new(&config).unwrap();
+        let a = Url::parse("https://a.example.com/1.pdf").unwrap();
+        let b = Url::parse("https://b.example.com/2.pdf").unwrap();
+        let first = client.acquire(&a);
+        // other hosts aren't held up
+        let _other = client.acquire(&b);
+        std::thread::scope(|scope| {
+            let waiting = scope.spawn(|| {
+                let _second = client.acquire(&a);
+            });
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!waiting.is_finished());
+            drop(first);
+            waiting.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn test_offline_uses_cache() {
+        let dir = tempdir().unwrap();
+        let config = HttpConfig {
+            cache_dir: Some(dir.path().to_owned()),
+            offline: true,
+            ..Default::default()
+        };
+        let client = HttpClient::new(&config).unwrap();
+        let url = Url::parse("https://example.com/meta.json").unwrap();
+
+        let err = client.get_text(&url).unwrap_err().to_string();
+        expect!["Offline, not fetching https://example.com/meta.json"].assert_eq(&err);
+
+        store_cached(&client.cache_path(&url).unwrap(), "cached").unwrap();
+        expect!["cached"].assert_eq(&client.get_text(&url).unwrap());
+    }
+}
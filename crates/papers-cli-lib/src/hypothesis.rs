@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{highlights::Highlight, http::HttpClient};
+
+/// Where annotations are searched for.
+const SEARCH_URL: &str = "https://api.hypothes.is/api/search";
+
+/// Most annotations the api returns for a single search.
+const SEARCH_LIMIT: usize = 200;
+
+/// Environment variable holding the api token, unless another is configured.
+const DEFAULT_TOKEN_ENV: &str = "HYPOTHESIS_TOKEN";
+
+/// Source of the markers of synced annotations.
+const MARKER_SOURCE: &str = "hypothesis";
+
+/// Settings for `papers hypothesis`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HypothesisConfig {
+    /// Hypothes.is username whose annotations are synced.
+    #[serde(default)]
+    pub user: Option<String>,
+
+    /// Environment variable holding the api token, `HYPOTHESIS_TOKEN` if not set.
+    #[serde(default)]
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    rows: Vec<Row>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Row {
+    id: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    target: Vec<Target>,
+    /// Annotations this one replies to.
+    #[serde(default)]
+    references: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Target {
+    #[serde(default)]
+    selector: Vec<Selector>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum Selector {
+    TextQuoteSelector {
+        exact: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Annotations from a search response, leaving out replies.
+fn parse_annotations(body: &str) -> anyhow::Result<Vec<Highlight>> {
+    let response: SearchResponse =
+        serde_json::from_str(body).context("Reading Hypothes.is annotations")?;
+    Ok(response
+        .rows
+        .into_iter()
+        .filter(|r| r.references.is_empty())
+        .map(|r| {
+            let quote = r
+                .target
+                .iter()
+                .flat_map(|t| &t.selector)
+                .find_map(|s| match s {
+                    Selector::TextQuoteSelector { exact } => Some(exact.clone()),
+                    Selector::Other => None,
+                });
+            Highlight {
+                marker: Some(format!("{MARKER_SOURCE}:{}", r.id)),
+                quote,
+                text: r.text,
+            }
+        })
+        .collect())
+}
+
+impl HypothesisConfig {
+    /// The user whose annotations are synced.
+    pub fn user(&self) -> anyhow::Result<&str> {
+        self.user
+            .as_deref()
+            .context("No Hypothes.is user, set hypothesis.user in the config")
+    }
+
+    /// The user's annotations on any of the `uris`, in the order they were made.
+    pub fn annotations(
+        &self,
+        http: &HttpClient,
+        uris: &[String],
+    ) -> anyhow::Result<Vec<Highlight>> {
+        let user = self.user()?;
+        let var = self.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+        // public annotations can be read without a token
+        let token = std::env::var(var).ok();
+        let mut seen = BTreeSet::new();
+        let mut annotations = Vec::new();
+        for uri in uris {
+            let url = Url::parse_with_params(
+                SEARCH_URL,
+                [
+                    ("user", format!("acct:{user}@hypothes.is")),
+                    ("uri", uri.clone()),
+                    ("limit", SEARCH_LIMIT.to_string()),
+                    ("sort", "created".to_owned()),
+                    ("order", "asc".to_owned()),
+                ],
+            )?;
+            let body = http
+                .get_with(&url, |r| match &token {
+                    Some(token) => r.bearer_auth(token),
+                    None => r,
+                })?
+                .text()?;
+            for annotation in parse_annotations(&body)? {
+                if seen.insert(annotation.marker.clone()) {
+                    annotations.push(annotation);
+                }
+            }
+        }
+        Ok(annotations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotations() {
+        let body = r#"{"total": 3, "rows": [
+            {"id": "a1", "text": "Key idea", "target": [{"source": "https://raft.github.io/raft.pdf", "selector": [
+                {"type": "RangeSelector", "startContainer": "/div[1]"},
+                {"type": "TextQuoteSelector", "exact": "a consensus\n algorithm", "prefix": "", "suffix": ""}
+            ]}]},
+            {"id": "a2", "text": "Page note", "target": [{"source": "https://raft.github.io/raft.pdf"}]},
+            {"id": "a3", "text": "Agreed", "references": ["a1"], "target": [{"source": "https://raft.github.io/raft.pdf"}]}
+        ]}"#;
+        let annotations = parse_annotations(body).unwrap();
+        assert_eq!(
+            annotations,
+            vec![
+                Highlight {
+                    marker: Some("hypothesis:a1".to_owned()),
+                    quote: Some("a consensus\n algorithm".to_owned()),
+                    text: "Key idea".to_owned(),
+                },
+                Highlight {
+                    marker: Some("hypothesis:a2".to_owned()),
+                    quote: None,
+                    text: "Page note".to_owned(),
+                },
+            ]
+        );
+    }
+}
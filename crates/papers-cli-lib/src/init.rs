@@ -0,0 +1,114 @@
+use std::{
+    fs::{copy, create_dir_all, read_dir},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tracing::{debug, info};
+
+use crate::url_path::UrlOrPath;
+
+/// Name of the config in a template, which is used for the config file rather than being copied
+/// into the repo.
+pub const TEMPLATE_CONFIG: &str = "config.yaml";
+
+/// Entries of a template that are never copied into the repo.
+const SKIPPED: &[&str] = &[".git", TEMPLATE_CONFIG];
+
+/// Directory holding the template, cloning it with git into `scratch` if it is a url.
+pub fn fetch_template(template: &UrlOrPath, scratch: &Path) -> anyhow::Result<PathBuf> {
+    match template {
+        UrlOrPath::Path(path) => {
+            if !path.is_dir() {
+                anyhow::bail!("Template {:?} is not a directory", path);
+            }
+            Ok(path.clone())
+        }
+        UrlOrPath::Url(url) => {
+            let dir = scratch.join("template");
+            info!(%url, ?dir, "Cloning template");
+            let status = Command::new("git")
+                .args(["clone", "--quiet", "--depth", "1", url.as_str()])
+                .arg(&dir)
+                .status()?;
+            if !status.success() {
+                anyhow::bail!("Failed to clone template {} with {}", url, status);
+            }
+            Ok(dir)
+        }
+    }
+}
+
+/// Copy the files of a template into the repo at `root`, leaving any that already exist alone.
+///
+/// Returns the paths copied and the paths skipped because they existed, relative to `root`.
+pub fn copy_template(template: &Path, root: &Path) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut copied = Vec::new();
+    let mut skipped = Vec::new();
+    copy_dir(template, root, Path::new(""), &mut copied, &mut skipped)?;
+    Ok((copied, skipped))
+}
+
+fn copy_dir(
+    template: &Path,
+    root: &Path,
+    relative: &Path,
+    copied: &mut Vec<PathBuf>,
+    skipped: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut entries = read_dir(template.join(relative))?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        if relative.as_os_str().is_empty() && SKIPPED.iter().any(|s| name == *s) {
+            continue;
+        }
+        let path = relative.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_dir(template, root, &path, copied, skipped)?;
+            continue;
+        }
+        let target = root.join(&path);
+        if target.exists() {
+            debug!(?target, "Not overwriting existing file from template");
+            skipped.push(path);
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent)?;
+        }
+        copy(entry.path(), &target)?;
+        copied.push(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, read_to_string, write};
+
+    use super::*;
+
+    #[test]
+    fn test_copy_template() {
+        let template = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        create_dir_all(template.path().join(".git")).unwrap();
+        write(template.path().join(".git/HEAD"), "ref").unwrap();
+        create_dir_all(template.path().join("templates")).unwrap();
+        write(template.path().join("templates/notes.md"), "# Notes").unwrap();
+        write(template.path().join(TEMPLATE_CONFIG), "{}").unwrap();
+        write(template.path().join("example.md"), "template").unwrap();
+        write(root.path().join("example.md"), "mine").unwrap();
+
+        let (copied, skipped) = copy_template(template.path(), root.path()).unwrap();
+        assert_eq!(copied, vec![PathBuf::from("templates/notes.md")]);
+        assert_eq!(skipped, vec![PathBuf::from("example.md")]);
+        assert_eq!(
+            read_to_string(root.path().join("example.md")).unwrap(),
+            "mine"
+        );
+        assert!(!root.path().join(".git").exists());
+        assert!(!root.path().join(TEMPLATE_CONFIG).exists());
+    }
+}
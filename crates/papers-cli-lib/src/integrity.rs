@@ -0,0 +1,166 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// How far from the end of a pdf the `%%EOF` marker is looked for, some writers add trailing
+/// whitespace or junk after it.
+const PDF_TRAILER_WINDOW: u64 = 1024;
+
+/// Something wrong with the contents of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileProblem {
+    /// The file has no contents, e.g. from a failed download.
+    Empty,
+    /// The file is named as a pdf but doesn't start like one, e.g. an html error page.
+    NotPdf,
+    /// The pdf has no end marker so the download probably stopped early.
+    Truncated,
+    /// The contents are of a different type to the extension.
+    WrongType {
+        /// Type from the extension.
+        expected: String,
+        /// Type detected from the contents.
+        detected: String,
+    },
+}
+
+impl Display for FileProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "file is empty"),
+            Self::NotPdf => write!(f, "file is not a pdf"),
+            Self::Truncated => write!(f, "pdf is missing its end marker, it may be truncated"),
+            Self::WrongType { expected, detected } => {
+                write!(f, "file is named as {expected} but contains {detected}")
+            }
+        }
+    }
+}
+
+/// Check that the contents of a document look valid for its extension.
+pub fn check_file(path: &Path) -> std::io::Result<Option<FileProblem>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(Some(FileProblem::Empty));
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if extension == "pdf" {
+        let mut header = [0; 5];
+        let read = file.read(&mut header)?;
+        if &header[..read] != b"%PDF-" {
+            return Ok(Some(FileProblem::NotPdf));
+        }
+        file.seek(SeekFrom::Start(len.saturating_sub(PDF_TRAILER_WINDOW)))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        if !tail.windows(5).any(|w| w == b"%%EOF") {
+            return Ok(Some(FileProblem::Truncated));
+        }
+        return Ok(None);
+    }
+
+    if let Some(kind) = infer::get_from_path(path)? {
+        let detected = kind.extension();
+        let equivalent = detected == extension
+            || matches!(
+                (detected, extension.as_str()),
+                ("jpg", "jpeg") | ("html", "htm")
+            );
+        if !extension.is_empty() && !equivalent {
+            return Ok(Some(FileProblem::WrongType {
+                expected: extension,
+                detected: detected.to_owned(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(name: &str, contents: &[u8], expected: Expect) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        expected.assert_debug_eq(&check_file(&path).unwrap());
+    }
+
+    #[test]
+    fn test_valid_pdf() {
+        check(
+            "a.pdf",
+            b"%PDF-1.4\n...\n%%EOF\n",
+            expect![[r#"
+                None
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        check(
+            "a.pdf",
+            b"",
+            expect![[r#"
+                Some(
+                    Empty,
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_html_as_pdf() {
+        check(
+            "a.pdf",
+            b"<!DOCTYPE html><html></html>",
+            expect![[r#"
+                Some(
+                    NotPdf,
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        check(
+            "a.pdf",
+            b"%PDF-1.4\n1 0 obj\n",
+            expect![[r#"
+                Some(
+                    Truncated,
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        check(
+            "a.html",
+            b"%PDF-1.4\n%%EOF\n",
+            expect![[r#"
+                Some(
+                    WrongType {
+                        expected: "html",
+                        detected: "pdf",
+                    },
+                )
+            "#]],
+        );
+    }
+}
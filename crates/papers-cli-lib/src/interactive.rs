@@ -1,112 +1,429 @@
 use std::{
-    fmt::Debug,
+    collections::BTreeSet,
+    fmt::Display,
     io::{self, stdout, BufRead, Write},
     str::FromStr,
 };
 
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal,
+};
+
+use crate::{line_editor::read_line, warning};
+
+/// Error for when input at a prompt was cancelled with ctrl-c or escape, so the command can stop
+/// without changing anything.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
 /// Get a line of input as provided.
-pub fn input_string(prompt: &str) -> String {
+///
+/// At a terminal the line can be edited, with history from previous prompts.
+pub fn input_string(prompt: &str) -> Result<String, Cancelled> {
+    if atty::is(atty::Stream::Stdin) {
+        return match read_line(&format!("{}: ", prompt)) {
+            Ok(Some(input)) => Ok(input.trim().to_owned()),
+            Ok(None) | Err(_) => Err(Cancelled),
+        };
+    }
+
     let mut stdin = io::stdin().lock();
 
     print!("{}: ", prompt);
     stdout().flush().unwrap();
 
     let mut input = String::new();
-    stdin.read_line(&mut input).unwrap();
-    input.trim().to_owned()
+    if stdin.read_line(&mut input).map_err(|_| Cancelled)? == 0 {
+        // nothing more to read, so asking again would never get an answer
+        println!();
+        return Err(Cancelled);
+    }
+    Ok(input.trim().to_owned())
 }
 
-/// Get a line of input converted to a FromStr type.
-pub fn input<T: FromStr + Debug>(prompt: &str) -> T
+/// Read lines with `read` until `parse` accepts one, saying why the others were rejected.
+fn prompt_until<T>(
+    mut read: impl FnMut() -> Result<String, Cancelled>,
+    mut parse: impl FnMut(&str) -> Result<T, String>,
+) -> Result<T, Cancelled> {
+    loop {
+        match parse(&read()?) {
+            Ok(value) => return Ok(value),
+            Err(err) => warning!("{}, try again", err),
+        }
+    }
+}
+
+fn parse<T: FromStr>(input: &str) -> Result<T, String>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    let input = input_string(prompt);
-    T::from_str(&input).unwrap()
+    T::from_str(input).map_err(|e| format!("Invalid value {input:?}: {e}"))
 }
 
-/// Get a line of input converted to a FromStr type, or the default value.
-pub fn input_default<T: FromStr + Debug + Default + PartialEq>(prompt: &str, default: &str) -> T
+fn parse_default<T: FromStr + Default + PartialEq>(input: &str, default: &str) -> Result<T, String>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    let input = input_string(&format!("{} [{}]", prompt, default));
-    match T::from_str(&input) {
-        Ok(res) => {
-            if res != T::default() {
-                res
-            } else {
-                T::from_str(default).unwrap()
-            }
-        }
-        Err(_) => T::from_str(default).unwrap(),
+    if input.is_empty() {
+        return parse(default);
+    }
+    let res = parse(input)?;
+    if res != T::default() {
+        Ok(res)
+    } else {
+        parse(default)
     }
 }
 
-/// Get a line of input converted to a FromStr type if there was any.
-pub fn input_opt<T: FromStr + Debug>(prompt: &str) -> Option<T>
+fn parse_opt<T: FromStr>(input: &str) -> Result<Option<T>, String>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    let input = input_string(&format!("{} (optional)", prompt));
     if input.is_empty() {
-        None
+        Ok(None)
     } else {
-        Some(T::from_str(&input).unwrap())
+        parse(input).map(Some)
     }
 }
 
+fn parse_vec<T: FromStr>(input: &str, sep: &str) -> Result<Vec<T>, String>
+where
+    <T as FromStr>::Err: Display,
+{
+    input
+        .split(sep)
+        .filter(|s| !s.is_empty())
+        .map(parse)
+        .collect()
+}
+
+fn parse_bool(input: &str, default: bool) -> Result<bool, String> {
+    match input.to_lowercase().as_str() {
+        "" => Ok(default),
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        _ => Err(format!("Expected y or n, not {input:?}")),
+    }
+}
+
+/// Positions, from 0, picked from `count` numbered items by their numbers and ranges of them,
+/// e.g. `1 3-5`, or `all`.
+fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    if input.eq_ignore_ascii_case("all") {
+        return Ok((0..count).collect());
+    }
+    let number = |s: &str| match s.trim().parse::<usize>() {
+        Ok(n) if (1..=count).contains(&n) => Ok(n - 1),
+        _ => Err(format!("Expected a number from 1 to {count}, not {s:?}")),
+    };
+    let mut selected = BTreeSet::new();
+    for part in input.split([' ', ',']).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => selected.extend(number(start)?..=number(end)?),
+            None => {
+                selected.insert(number(part)?);
+            }
+        }
+    }
+    Ok(selected.into_iter().collect())
+}
+
+/// Get a line of input converted to a FromStr type, asking again until it is valid.
+pub fn input<T: FromStr>(prompt: &str) -> Result<T, Cancelled>
+where
+    <T as FromStr>::Err: Display,
+{
+    prompt_until(|| input_string(prompt), parse)
+}
+
+/// Get a line of input converted to a FromStr type, or the default value if there wasn't any.
+pub fn input_default<T: FromStr + Default + PartialEq>(
+    prompt: &str,
+    default: &str,
+) -> Result<T, Cancelled>
+where
+    <T as FromStr>::Err: Display,
+{
+    let prompt = format!("{} [{}]", prompt, default);
+    prompt_until(|| input_string(&prompt), |i| parse_default(i, default))
+}
+
 /// Get a line of input converted to a FromStr type if there was any.
-pub fn input_vec_default<T: FromStr + Debug>(prompt: &str, sep: &str, default: &str) -> Vec<T>
+pub fn input_opt<T: FromStr>(prompt: &str) -> Result<Option<T>, Cancelled>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    let input = input_vec(&format!("{} [{}]", prompt, default), sep);
+    let prompt = format!("{} (optional)", prompt);
+    prompt_until(|| input_string(&prompt), parse_opt)
+}
+
+/// Get a separated list of values, or the default list if there weren't any.
+pub fn input_vec_default<T: FromStr>(
+    prompt: &str,
+    sep: &str,
+    default: &str,
+) -> Result<Vec<T>, Cancelled>
+where
+    <T as FromStr>::Err: Display,
+{
+    let input = input_vec(&format!("{} [{}]", prompt, default), sep)?;
     if input.is_empty() {
-        default
+        Ok(default
             .split(sep)
-            .filter_map(|s| {
-                if s.is_empty() {
-                    None
-                } else {
-                    Some(T::from_str(s).unwrap())
-                }
-            })
-            .collect()
+            .filter_map(|s| T::from_str(s).ok())
+            .collect())
     } else {
-        input
+        Ok(input)
     }
 }
 
-/// Get a line of input converted to a FromStr type if there was any.
-pub fn input_vec<T: FromStr + Debug>(prompt: &str, sep: &str) -> Vec<T>
+/// Get a separated list of values, asking again until they are all valid.
+pub fn input_vec<T: FromStr>(prompt: &str, sep: &str) -> Result<Vec<T>, Cancelled>
 where
-    <T as FromStr>::Err: Debug,
+    <T as FromStr>::Err: Display,
 {
-    let input = input_string(&format!("{} (separated by '{}')", prompt, sep));
-    input
-        .split(sep)
-        .filter_map(|s| {
-            if s.is_empty() {
-                None
-            } else {
-                Some(T::from_str(s).unwrap())
-            }
-        })
-        .collect()
+    let prompt = format!("{} (separated by '{}')", prompt, sep);
+    prompt_until(|| input_string(&prompt), |i| parse_vec(i, sep))
 }
 
-/// Get a line of input converted to a FromStr type if there was any.
-pub fn input_bool(prompt: &str, default: bool) -> bool {
-    let input = input_string(&format!(
+/// Get a yes or no answer, or the default if there wasn't any.
+pub fn input_bool(prompt: &str, default: bool) -> Result<bool, Cancelled> {
+    let prompt = format!(
         "{} [{}/{}]",
         prompt,
         if default { "Y" } else { "y" },
         if default { "n" } else { "N" }
-    ));
-    match input.to_lowercase().as_str() {
-        "y" | "yes" => true,
-        "n" | "no" => false,
-        _ => default,
+    );
+    prompt_until(|| input_string(&prompt), |i| parse_bool(i, default))
+}
+
+/// Pick some of `count` numbered items, by their numbers and ranges of them or `all`, returning
+/// their positions from 0. Nothing picks none.
+pub fn input_selection(prompt: &str, count: usize) -> Result<Vec<usize>, Cancelled> {
+    let prompt = format!("{} (e.g. 1 3-5, or all)", prompt);
+    prompt_until(|| input_string(&prompt), |i| parse_selection(i, count))
+}
+
+/// Get a single key press, without waiting for enter when attached to a terminal.
+///
+/// Returns `None` on escape, ctrl-c or the end of input.
+pub fn input_key(prompt: &str) -> Option<char> {
+    print!("{}: ", prompt);
+    stdout().flush().unwrap();
+
+    if !atty::is(atty::Stream::Stdin) {
+        // take the first character of each line so keys can be piped in
+        let mut input = String::new();
+        // unreadable input is treated like its end
+        if io::stdin().lock().read_line(&mut input).is_err() || input.is_empty() {
+            return None;
+        }
+        return Some(input.trim().chars().next().unwrap_or(' '));
+    }
+
+    terminal::enable_raw_mode().unwrap();
+    let key = loop {
+        match event::read() {
+            Ok(Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            })) => match code {
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break None,
+                KeyCode::Char(c) => break Some(c),
+                KeyCode::Enter => break Some(' '),
+                KeyCode::Esc => break None,
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(_) => break None,
+        }
+    };
+    terminal::disable_raw_mode().unwrap();
+    match key {
+        Some(c) => println!("{c}"),
+        None => println!(),
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use papers_core::label::Label;
+
+    use super::*;
+
+    fn check<T: std::fmt::Debug>(
+        lines: &[&str],
+        parse: impl Fn(&str) -> Result<T, String>,
+        expected: Expect,
+    ) {
+        let mut lines = lines.iter();
+        let mut rejected = Vec::new();
+        let result = prompt_until(
+            || lines.next().map(|l| l.to_string()).ok_or(Cancelled),
+            |input| parse(input).inspect_err(|err| rejected.push(err.clone())),
+        );
+        expected.assert_debug_eq(&(result.map_err(|c| c.to_string()), rejected));
+    }
+
+    #[test]
+    fn test_reprompt() {
+        check(
+            &["a", "key=value"],
+            parse::<Label>,
+            expect![[r#"
+                (
+                    Ok(
+                        Label {
+                            key: "key",
+                            value: String(
+                                "value",
+                            ),
+                        },
+                    ),
+                    [
+                        "Invalid value \"a\": Missing value, should be of the form `key=value`",
+                    ],
+                )
+            "#]],
+        );
+        check(
+            &["a=1 b", "a=1 b=2", "unused"],
+            |i| parse_vec::<Label>(i, " ").map(|l| l.len()),
+            expect![[r#"
+                (
+                    Ok(
+                        2,
+                    ),
+                    [
+                        "Invalid value \"b\": Missing value, should be of the form `key=value`",
+                    ],
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_selection() {
+        check(
+            &["0", "2-5", "4,1-2 2", "unused"],
+            |i| parse_selection(i, 4),
+            expect![[r#"
+                (
+                    Ok(
+                        [
+                            0,
+                            1,
+                            3,
+                        ],
+                    ),
+                    [
+                        "Expected a number from 1 to 4, not \"0\"",
+                        "Expected a number from 1 to 4, not \"5\"",
+                    ],
+                )
+            "#]],
+        );
+        check(
+            &["ALL"],
+            |i| parse_selection(i, 3),
+            expect![[r#"
+            (
+                Ok(
+                    [
+                        0,
+                        1,
+                        2,
+                    ],
+                ),
+                [],
+            )
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_skip() {
+        check(
+            &["nope", ""],
+            |i| parse_bool(i, true),
+            expect![[r#"
+            (
+                Ok(
+                    true,
+                ),
+                [
+                    "Expected y or n, not \"nope\"",
+                ],
+            )
+        "#]],
+        );
+        check(
+            &[""],
+            parse_opt::<Label>,
+            expect![[r#"
+            (
+                Ok(
+                    None,
+                ),
+                [],
+            )
+        "#]],
+        );
+        check(
+            &[""],
+            |i| parse_selection(i, 3),
+            expect![[r#"
+            (
+                Ok(
+                    [],
+                ),
+                [],
+            )
+        "#]],
+        );
+        check(
+            &[""],
+            |i| parse_default::<u32>(i, "3"),
+            expect![[r#"
+            (
+                Ok(
+                    3,
+                ),
+                [],
+            )
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_end_of_input() {
+        check(
+            &["x"],
+            parse::<u32>,
+            expect![[r#"
+            (
+                Err(
+                    "Cancelled",
+                ),
+                [
+                    "Invalid value \"x\": invalid digit found in string",
+                ],
+            )
+        "#]],
+        );
     }
 }
@@ -29,3 +29,165 @@ pub mod rename_files;
 
 /// Fuzzy searching.
 pub mod fuzzy;
+
+/// Title extraction from documents.
+pub mod title;
+
+/// Shared client for external http services.
+pub mod http;
+
+/// Snapshots of web pages.
+pub mod snapshot;
+
+/// Talks and videos.
+pub mod talk;
+
+/// Resolvers for RFCs and technical reports.
+pub mod reports;
+
+/// Publisher specific handling of landing pages.
+pub mod publishers;
+
+/// Shell completion generation and installation.
+pub mod completions;
+
+/// Man page generation.
+pub mod man;
+
+/// Long-form help about concepts.
+pub mod help_topics;
+
+/// Output styles shared by all commands.
+pub mod output;
+
+/// Templates for printing fields of papers.
+pub mod paper_format;
+
+/// Logging to files.
+pub mod logging;
+
+/// Times to filter from.
+pub mod since;
+
+/// Find and replace over metadata.
+pub mod sed;
+
+/// Rewriting label values from a mapping.
+pub mod remap;
+
+/// Validating and normalising urls.
+pub mod urls;
+
+/// Checking documents are complete.
+pub mod integrity;
+
+/// Papers on arXiv.
+pub mod arxiv;
+
+/// Citation counts.
+pub mod citations;
+
+/// Dates to read papers by.
+pub mod deadlines;
+
+/// Exporting papers for sharing.
+pub mod export;
+
+/// Going through papers one at a time.
+pub mod triage;
+
+/// Disk usage of documents.
+pub mod du;
+
+/// Running the commands configured to convert documents.
+pub mod external;
+
+/// Shrinking documents.
+pub mod optimize;
+
+/// Thumbnails of the first pages of documents.
+pub mod thumbnails;
+
+/// Deciding which problems the doctor fixes.
+pub mod doctor;
+
+/// Summary of the papers that need attention.
+pub mod status;
+
+/// JSON Schemas for the metadata of papers.
+pub mod schema;
+
+/// Seeding new repos from templates.
+pub mod init;
+
+/// Editing lines of input at a prompt.
+pub mod line_editor;
+
+/// Papers waiting for their documents to be fetched.
+pub mod fetch_missing;
+
+/// Statistics on how reviewing papers is going.
+pub mod review_stats;
+
+/// Lengths of time given on the command line.
+pub mod duration;
+
+/// Papers opened or edited recently, to put them first when picking.
+pub mod recent;
+
+/// The vocabulary of tags, labels and authors used across the repo.
+pub mod vocab;
+
+/// Telling the notes written about papers apart from their template.
+pub mod notes;
+
+/// Machine readable progress of long operations.
+pub mod progress;
+
+/// Checkpoints letting interrupted bulk operations be resumed.
+pub mod checkpoint;
+
+/// Editing notes in the user's editor without clobbering changes made meanwhile.
+pub mod edit;
+
+/// Board of papers in columns by how far through reading them is.
+pub mod board;
+
+/// Tree of papers grouped by tag, author or year.
+pub mod tree;
+
+/// Write-ups of what was added and read each month.
+pub mod monthly;
+
+/// Pages of public reading notes for static site generators.
+pub mod publish;
+
+/// Uploading notes to a paste service or gist to share them.
+pub mod share;
+
+/// Merging papers whose documents are the same.
+pub mod dedupe;
+
+/// Highlights kept in the notes, synced with other services.
+pub mod highlights;
+
+/// Syncing annotations from Hypothes.is into the notes.
+pub mod hypothesis;
+
+/// Pushing highlights to Readwise and pulling them back into the notes.
+pub mod readwise;
+
+/// Watching a directory for new PDFs to add.
+pub mod watch;
+
+/// Importing papers from a Calibre library and exporting them for one.
+pub mod calibre;
+
+/// Finding the publications of authors on DBLP.
+pub mod dblp;
+
+/// Adding books by their ISBN, using metadata from Open Library.
+pub mod book;
+
+/// Importing papers from Google Scholar BibTeX exports.
+pub mod scholar;
@@ -0,0 +1,278 @@
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    io::{stdout, Write},
+    path::PathBuf,
+};
+
+use crossterm::{
+    cursor::MoveToColumn,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    terminal::{self, Clear, ClearType},
+};
+use directories::ProjectDirs;
+use tracing::debug;
+
+/// Number of lines of history kept between runs.
+const HISTORY_LEN: usize = 500;
+
+/// What to do after a key press at a prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Keep editing.
+    Continue,
+    /// Accept the line.
+    Submit,
+    /// Abandon the line.
+    Cancel,
+}
+
+/// A line being edited at a prompt, along with the history to move through.
+#[derive(Debug, Default)]
+pub struct LineEditor {
+    line: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    /// Position in the history, equal to its length when editing a new line.
+    position: usize,
+    /// The new line, kept while looking through the history.
+    draft: Option<String>,
+}
+
+impl LineEditor {
+    /// An empty line with the given history, oldest first.
+    pub fn new(history: Vec<String>) -> Self {
+        Self {
+            position: history.len(),
+            history,
+            ..Default::default()
+        }
+    }
+
+    /// The line as it is now.
+    pub fn line(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    /// Number of characters before the cursor.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn set_line(&mut self, line: &str) {
+        self.line = line.chars().collect();
+        self.cursor = self.line.len();
+    }
+
+    fn recall(&mut self, position: usize) {
+        if self.draft.is_none() {
+            self.draft = Some(self.line());
+        }
+        self.position = position;
+        let line = match self.history.get(position) {
+            Some(entry) => entry.clone(),
+            None => self.draft.take().unwrap_or_default(),
+        };
+        self.set_line(&line);
+    }
+
+    /// Apply a key press to the line.
+    pub fn apply(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Step {
+        let control = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Char('c') if control => return Step::Cancel,
+            KeyCode::Char('d') if control && self.line.is_empty() => return Step::Cancel,
+            KeyCode::Char('d') if control && self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            KeyCode::Char('a') if control => self.cursor = 0,
+            KeyCode::Char('e') if control => self.cursor = self.line.len(),
+            KeyCode::Char('u') if control => {
+                self.line.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char('k') if control => self.line.truncate(self.cursor),
+            KeyCode::Char('w') if control => {
+                let mut start = self.cursor;
+                while start > 0 && self.line[start - 1] == ' ' {
+                    start -= 1;
+                }
+                while start > 0 && self.line[start - 1] != ' ' {
+                    start -= 1;
+                }
+                self.line.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            KeyCode::Char(_) if control => {}
+            KeyCode::Char(c) => {
+                self.line.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.line.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.line.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.line.len(),
+            KeyCode::Up if self.position > 0 => self.recall(self.position - 1),
+            KeyCode::Down if self.position < self.history.len() => self.recall(self.position + 1),
+            KeyCode::Enter => return Step::Submit,
+            KeyCode::Esc => return Step::Cancel,
+            _ => {}
+        }
+        Step::Continue
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    ProjectDirs::from("io", "jeffas", "papers").map(|d| d.data_dir().join("history"))
+}
+
+fn load_history() -> Vec<String> {
+    history_path()
+        .and_then(|path| read_to_string(path).ok())
+        .map(|h| h.lines().map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+fn save_history(mut history: Vec<String>, line: &str) {
+    if line.is_empty() || history.last().is_some_and(|l| l == line) {
+        return;
+    }
+    history.push(line.to_owned());
+    let skip = history.len().saturating_sub(HISTORY_LEN);
+    let Some(path) = history_path() else {
+        return;
+    };
+    let saved = path
+        .parent()
+        .map_or(Ok(()), create_dir_all)
+        .and_then(|()| write(&path, history[skip..].join("\n") + "\n"));
+    if let Err(err) = saved {
+        debug!(?path, %err, "Failed to save input history");
+    }
+}
+
+fn redraw(prompt: &str, editor: &LineEditor) -> std::io::Result<()> {
+    let mut out = stdout();
+    queue!(out, MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+    write!(out, "{prompt}{}", editor.line())?;
+    let column = prompt.chars().count() + editor.cursor();
+    queue!(out, MoveToColumn(column.try_into().unwrap_or(u16::MAX)))?;
+    out.flush()
+}
+
+/// Read a line from the terminal with editing and history, `None` if it was cancelled.
+pub fn read_line(prompt: &str) -> std::io::Result<Option<String>> {
+    let history = load_history();
+    let mut editor = LineEditor::new(history.clone());
+    terminal::enable_raw_mode()?;
+    let step = loop {
+        if let Err(err) = redraw(prompt, &editor) {
+            break Err(err);
+        }
+        match event::read() {
+            Ok(Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            })) => match editor.apply(code, modifiers) {
+                Step::Continue => {}
+                step => break Ok(step),
+            },
+            Ok(_) => {}
+            Err(err) => break Err(err),
+        }
+    };
+    terminal::disable_raw_mode()?;
+    println!();
+    match step? {
+        Step::Submit => {
+            let line = editor.line();
+            save_history(history, line.trim());
+            Ok(Some(line))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    type Key = (KeyCode, KeyModifiers);
+
+    fn check(history: &[&str], keys: &[Key], expected: Expect) {
+        let mut editor = LineEditor::new(history.iter().map(|h| h.to_string()).collect());
+        let steps = keys
+            .iter()
+            .map(|(code, modifiers)| editor.apply(*code, *modifiers))
+            .filter(|s| *s != Step::Continue)
+            .collect::<Vec<_>>();
+        expected.assert_eq(&format!(
+            "{:?} {} {:?}",
+            editor.line(),
+            editor.cursor(),
+            steps
+        ));
+    }
+
+    fn typed(s: &str) -> Vec<Key> {
+        s.chars().map(|c| key(KeyCode::Char(c))).collect()
+    }
+
+    fn key(code: KeyCode) -> Key {
+        (code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> Key {
+        (KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut keys = typed("helo");
+        keys.extend([
+            key(KeyCode::Left),
+            key(KeyCode::Char('l')),
+            key(KeyCode::End),
+            key(KeyCode::Enter),
+        ]);
+        check(&[], &keys, expect![[r#""hello" 5 [Submit]"#]]);
+
+        let mut keys = typed("one two");
+        keys.extend([ctrl('w'), key(KeyCode::Home), key(KeyCode::Delete)]);
+        check(&[], &keys, expect![[r#""ne " 0 []"#]]);
+
+        let mut keys = typed("abc");
+        keys.extend([key(KeyCode::Left), ctrl('k'), key(KeyCode::Backspace)]);
+        check(&[], &keys, expect![[r#""a" 1 []"#]]);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut keys = typed("new");
+        keys.extend([key(KeyCode::Up), key(KeyCode::Up), key(KeyCode::Up)]);
+        check(&["first", "second"], &keys, expect![[r#""first" 5 []"#]]);
+
+        keys.extend([key(KeyCode::Down), key(KeyCode::Down)]);
+        check(&["first", "second"], &keys, expect![[r#""new" 3 []"#]]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        check(&[], &[ctrl('c')], expect![[r#""" 0 [Cancel]"#]]);
+        check(&[], &[ctrl('d')], expect![[r#""" 0 [Cancel]"#]]);
+        let mut keys = typed("ab");
+        keys.extend([key(KeyCode::Home), ctrl('d'), key(KeyCode::Esc)]);
+        check(&[], &keys, expect![[r#""b" 0 [Cancel]"#]]);
+    }
+}
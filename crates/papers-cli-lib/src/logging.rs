@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{filter::EnvFilter, fmt, Layer, Registry};
+
+/// How often to start a new log file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    /// A new file every hour.
+    Hourly,
+    /// A new file every day.
+    #[default]
+    Daily,
+    /// Always write to the same file.
+    Never,
+}
+
+impl From<LogRotation> for Rotation {
+    fn from(rotation: LogRotation) -> Self {
+        match rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        }
+    }
+}
+
+/// Configuration for writing logs to files, alongside the usual output on stderr.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogConfig {
+    /// Directory to write json log files to, file logging is disabled if not set.
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+
+    /// How often to start a new log file.
+    #[serde(default)]
+    pub rotation: LogRotation,
+
+    /// Number of log files to keep, older ones are deleted.
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+
+    /// Filter for what gets logged to the file, in the same form as `RUST_LOG`.
+    #[serde(default = "default_level")]
+    pub level: String,
+}
+
+fn default_max_files() -> usize {
+    7
+}
+
+fn default_level() -> String {
+    "info".to_owned()
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            rotation: LogRotation::default(),
+            max_files: default_max_files(),
+            level: default_level(),
+        }
+    }
+}
+
+/// A layer writing json logs to rotating files in the configured directory, if there is one.
+pub fn file_layer(
+    config: &LogConfig,
+) -> anyhow::Result<Option<Box<dyn Layer<Registry> + Send + Sync>>> {
+    let Some(dir) = &config.dir else {
+        return Ok(None);
+    };
+    let appender = RollingFileAppender::builder()
+        .rotation(config.rotation.into())
+        .filename_prefix("papers")
+        .filename_suffix("log")
+        .max_log_files(config.max_files)
+        .build(dir)?;
+    let filter = EnvFilter::builder().parse(&config.level)?;
+    let layer = fmt::layer()
+        .json()
+        .with_writer(appender)
+        .with_filter(filter)
+        .boxed();
+    Ok(Some(layer))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use papers_core::audit;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_audit_entries_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogConfig {
+            dir: Some(dir.path().to_owned()),
+            rotation: LogRotation::Never,
+            ..Default::default()
+        };
+        let subscriber = tracing_subscriber::registry().with(file_layer(&config).unwrap());
+        tracing::subscriber::with_default(subscriber, || {
+            audit::record("rename", Path::new("a.pdf"), Some(Path::new("A.pdf")));
+        });
+
+        let log = std::fs::read_to_string(dir.path().join("papers.log")).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(log.lines().next().unwrap()).unwrap();
+        assert_eq!(entry["target"], audit::AUDIT_TARGET);
+        assert_eq!(entry["fields"]["action"], "rename");
+        assert_eq!(entry["fields"]["path"], "a.pdf");
+        assert_eq!(entry["fields"]["other"], "A.pdf");
+        assert!(entry["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,57 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use roff::{bold, roman, Roff};
+
+use crate::{cli::Cli, config::CONFIG_KEYS};
+
+/// Generate man pages for the cli and each of its subcommands into the given directory, returning
+/// the paths written.
+pub fn gen_man_pages(outdir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut cmd = Cli::command().name("papers").disable_help_subcommand(true);
+    cmd.build();
+    let mut paths = Vec::new();
+    write_pages(&cmd, outdir, true, &mut paths)?;
+    Ok(paths)
+}
+
+fn write_pages(
+    cmd: &Command,
+    outdir: &Path,
+    root: bool,
+    paths: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let man = Man::new(cmd.clone());
+    let path = outdir.join(man.get_filename());
+    let mut file = File::create(&path)?;
+    man.render(&mut file)?;
+    if root {
+        file.write_all(config_section().render().as_bytes())?;
+    }
+    paths.push(path);
+
+    for subcommand in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        write_pages(subcommand, outdir, false, paths)?;
+    }
+    Ok(())
+}
+
+/// Section documenting the keys of the config file.
+fn config_section() -> Roff {
+    let mut roff = Roff::new();
+    roff.control("SH", ["CONFIGURATION"]);
+    roff.text([roman(
+        "The config file is yaml, loaded from the path given by --config-file.",
+    )]);
+    for (key, description) in CONFIG_KEYS {
+        roff.control("TP", []);
+        roff.text([bold(*key)]);
+        roff.text([roman(*description)]);
+    }
+    roff
+}
@@ -0,0 +1,322 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate};
+use papers_core::{
+    journal::{history, Action, Entry},
+    paper::LoadedPaper,
+};
+use serde::Serialize;
+
+use crate::{export::heading, notes::NotesTemplate, output::Render};
+
+/// Heading of the section of the notes that summarises a paper.
+pub const SUMMARY_SECTION: &str = "Summary";
+
+/// A calendar month, written as `2024-05`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(into = "String")]
+pub struct Month(NaiveDate);
+
+impl Month {
+    /// The month that `date` is in.
+    pub fn of(date: NaiveDate) -> Self {
+        Self(date.with_day(1).unwrap_or(date))
+    }
+
+    /// Whether `date` is in the month.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date.year() == self.0.year() && date.month() == self.0.month()
+    }
+
+    /// The month written out, e.g. `May 2024`.
+    pub fn long(&self) -> String {
+        self.0.format("%B %Y").to_string()
+    }
+}
+
+impl FromStr for Month {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let date = NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d")
+            .with_context(|| format!("Invalid month {s:?}, expected e.g. 2024-05"))?;
+        Ok(Self(date))
+    }
+}
+
+impl Display for Month {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.format("%Y-%m"))
+    }
+}
+
+impl From<Month> for String {
+    fn from(month: Month) -> Self {
+        month.to_string()
+    }
+}
+
+/// The first line written in the summary section of the notes, leaving out lines from the
+/// `template`.
+pub fn summary(notes: &str, template: &NotesTemplate) -> Option<String> {
+    let mut level = None;
+    for line in notes.lines() {
+        match (heading(line), level) {
+            (Some((l, text)), None) if text.eq_ignore_ascii_case(SUMMARY_SECTION) => {
+                level = Some(l);
+            }
+            // the section ends at the next heading at the same or a higher level
+            (Some((l, _)), Some(section)) if l <= section => return None,
+            (None, Some(_)) if template.words(line) > 0 => return Some(line.trim().to_owned()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A paper in a monthly report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportPaper {
+    /// Path of the paper, relative to the root.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+    /// Url the paper was fetched from.
+    pub url: Option<String>,
+    /// First line of the summary in the notes.
+    pub summary: Option<String>,
+}
+
+impl ReportPaper {
+    fn of(paper: &LoadedPaper, template: &NotesTemplate) -> Self {
+        Self {
+            path: paper.path.clone(),
+            title: paper.meta.title.clone(),
+            url: paper.meta.url.clone(),
+            summary: summary(&paper.notes, template),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        let mut line = match &self.url {
+            Some(url) => format!("- [{}]({url})", self.title),
+            None => format!("- {}", self.title),
+        };
+        if let Some(summary) = &self.summary {
+            line.push_str(&format!(": {summary}"));
+        }
+        line
+    }
+}
+
+/// What was added and read in a month.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonthlyReport {
+    /// The month reported on.
+    pub month: Month,
+    /// Papers added during the month.
+    pub added: Vec<ReportPaper>,
+    /// Papers reviewed during the month.
+    pub read: Vec<ReportPaper>,
+}
+
+impl MonthlyReport {
+    /// Report on the papers for `month`, using the journal `entries` for when they were reviewed.
+    pub fn of(
+        month: Month,
+        papers: &[LoadedPaper],
+        entries: &[Entry],
+        template: &NotesTemplate,
+    ) -> Self {
+        let added = papers
+            .iter()
+            .filter(|p| month.contains(p.meta.created_at.date()))
+            .map(|p| ReportPaper::of(p, template))
+            .collect();
+        let read = papers
+            .iter()
+            .filter(|p| {
+                // all entries, so that reviews from before a rename are still found
+                p.meta.last_review.is_some_and(|r| month.contains(r.date()))
+                    || history(entries, &p.path)
+                        .iter()
+                        .any(|e| e.action == Action::Reviewed && month.contains(e.at.date()))
+            })
+            .map(|p| ReportPaper::of(p, template))
+            .collect();
+        Self { month, added, read }
+    }
+
+    fn sections(&self) -> [(&str, &[ReportPaper]); 2] {
+        [("Added", &self.added), ("Read", &self.read)]
+    }
+}
+
+impl Display for MonthlyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        for (name, papers) in self.sections() {
+            lines.push(format!("{name} in {}: {}", self.month, papers.len()));
+            for paper in papers {
+                match &paper.summary {
+                    Some(summary) => lines.push(format!("  {}: {summary}", paper.title)),
+                    None => lines.push(format!("  {}", paper.title)),
+                }
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for MonthlyReport {
+    fn header(&self) -> Vec<String> {
+        ["section", "path", "title", "url", "summary"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.sections()
+            .into_iter()
+            .flat_map(|(name, papers)| {
+                papers.iter().map(move |p| {
+                    vec![
+                        name.to_lowercase(),
+                        p.path.display().to_string(),
+                        p.title.clone(),
+                        p.url.clone().unwrap_or_default(),
+                        p.summary.clone().unwrap_or_default(),
+                    ]
+                })
+            })
+            .collect()
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = format!("# Reading in {}\n", self.month.long());
+        for (name, papers) in self.sections() {
+            out.push_str(&format!("\n## {name}\n\n"));
+            if papers.is_empty() {
+                out.push_str("Nothing this month.\n");
+            }
+            for paper in papers {
+                out.push_str(&paper.markdown());
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+    use expect_test::expect;
+    use papers_core::paper::PaperMeta;
+
+    use super::*;
+
+    fn at(month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn paper(title: &str, added: NaiveDateTime, url: Option<&str>, notes: &str) -> LoadedPaper {
+        LoadedPaper {
+            meta: PaperMeta {
+                title: title.to_owned(),
+                url: url.map(str::to_owned),
+                created_at: added,
+                ..Default::default()
+            },
+            path: PathBuf::from(format!("{title}.md")),
+            notes: notes.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_month() {
+        let month = "2024-05".parse::<Month>().unwrap();
+        assert_eq!(month.to_string(), "2024-05");
+        assert_eq!(month.long(), "May 2024");
+        assert!(month.contains(NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()));
+        assert!(!month.contains(NaiveDate::from_ymd_opt(2023, 5, 1).unwrap()));
+        assert_eq!(Month::of(at(12, 25).date()).to_string(), "2024-12");
+        assert!("2024-13".parse::<Month>().is_err());
+        assert!("May".parse::<Month>().is_err());
+    }
+
+    #[test]
+    fn test_summary() {
+        let template = NotesTemplate::new("## Summary\n\n## Thoughts\n");
+        assert_eq!(summary("## Summary\n\n## Thoughts\n", &template), None);
+        assert_eq!(
+            summary("## Summary\n\nLeader based consensus.\nMore.\n", &template),
+            Some("Leader based consensus.".to_owned())
+        );
+        // only the summary section counts
+        assert_eq!(
+            summary("## Summary\n\n## Thoughts\nToo long.\n", &template),
+            None
+        );
+        assert_eq!(
+            summary("# Notes\n## summary\n### Detail\nIn detail.\n", &template),
+            Some("In detail.".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let template = NotesTemplate::default();
+        let mut reviewed = paper("paxos", at(1, 1), None, "");
+        reviewed.meta.last_review = Some(at(5, 20));
+        let papers = [
+            paper(
+                "raft",
+                at(5, 2),
+                Some("https://raft.github.io/raft.pdf"),
+                "## Summary\nUnderstandable consensus.\n",
+            ),
+            reviewed,
+            paper("zab", at(4, 30), None, ""),
+            paper("spanner", at(3, 1), None, ""),
+        ];
+        // reviewed before being renamed
+        let mut review = Entry::new(Action::Reviewed, PathBuf::from("zab-draft.md"), None);
+        review.at = at(5, 1);
+        let rename = Entry::new(
+            Action::Renamed,
+            PathBuf::from("zab-draft.md"),
+            Some(PathBuf::from("zab.md")),
+        );
+        let report = MonthlyReport::of(
+            "2024-05".parse().unwrap(),
+            &papers,
+            &[review, rename],
+            &template,
+        );
+        expect![[r#"
+            # Reading in May 2024
+
+            ## Added
+
+            - [raft](https://raft.github.io/raft.pdf): Understandable consensus.
+
+            ## Read
+
+            - paxos
+            - zab
+        "#]]
+        .assert_eq(&report.markdown());
+        expect![[r#"
+            Added in 2024-05: 1
+              raft: Understandable consensus.
+            Read in 2024-05: 2
+              paxos
+              zab"#]]
+        .assert_eq(&report.to_string());
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeSet;
+
+/// Lines of the notes template, which don't count as notes written about a paper.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NotesTemplate {
+    lines: BTreeSet<String>,
+}
+
+impl NotesTemplate {
+    /// The lines of `template`, ignoring surrounding whitespace.
+    pub fn new(template: &str) -> Self {
+        let lines = template
+            .lines()
+            .map(|l| l.trim().to_owned())
+            .filter(|l| !l.is_empty())
+            .collect();
+        Self { lines }
+    }
+
+    /// Number of words written in `notes`, leaving out the lines that came from the template.
+    pub fn words(&self, notes: &str) -> usize {
+        notes
+            .lines()
+            .filter(|l| !self.lines.contains(l.trim()))
+            .map(|l| l.split_whitespace().count())
+            .sum()
+    }
+
+    /// Whether anything has been written in `notes` beyond the template.
+    pub fn has_notes(&self, notes: &str) -> bool {
+        self.words(notes) > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        let template = NotesTemplate::new("\n# Summary\n\n# Thoughts\n");
+        assert_eq!(template.words(""), 0);
+        assert_eq!(template.words("\n# Summary\n\n# Thoughts\n"), 0);
+        assert_eq!(template.words("\n  # Summary  \n\n# Thoughts\n\n"), 0);
+        assert!(!template.has_notes("\n# Summary\n"));
+        assert_eq!(
+            template.words("\n# Summary\n\nConsensus made simple.\n\n# Thoughts\nleader based\n"),
+            5
+        );
+        assert_eq!(NotesTemplate::default().words("# Summary\n"), 2);
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    fs::{remove_file, rename},
+    path::Path,
+};
+
+use tracing::{debug, info};
+
+use crate::external::run_template;
+
+/// Command used to optimise pdfs when none is configured, linearising them for fast opening and
+/// compressing their streams.
+pub const DEFAULT_OPTIMIZE_COMMAND: &str =
+    "qpdf --linearize --object-streams=generate --compress-streams=y {input} {output}";
+
+/// Run the optimiser `command` over the pdf at `path`, with `{input}` and `{output}` substituted,
+/// returning the size before and after.
+///
+/// The result only replaces the original if it is smaller.
+pub fn optimize_pdf(command: &str, path: &Path) -> anyhow::Result<(u64, u64)> {
+    let before = path.metadata()?.len();
+    let mut output = path.as_os_str().to_owned();
+    output.push(".optimized");
+    let output = Path::new(&output);
+
+    info!(?path, "Optimizing pdf");
+    let status = run_template("Optimize", command, path, output);
+    let after = match status {
+        Ok(status) if status.success() => output.metadata()?.len(),
+        Ok(status) => {
+            let _ = remove_file(output);
+            anyhow::bail!("Optimize command failed with {}", status);
+        }
+        Err(err) => {
+            let _ = remove_file(output);
+            return Err(err);
+        }
+    };
+
+    if after > 0 && after < before {
+        rename(output, path)?;
+        Ok((before, after))
+    } else {
+        debug!(
+            ?path,
+            before, after, "Optimized pdf is no smaller, keeping the original"
+        );
+        remove_file(output)?;
+        Ok((before, before))
+    }
+}
@@ -0,0 +1,1226 @@
+use std::{
+    fmt::Display,
+    io::{stdout, Write},
+    path::PathBuf,
+};
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use papers_core::{
+    journal::Entry,
+    paper::PaperMeta,
+    snapshot::{Restored, Snapshot},
+    trash::Trashed,
+};
+use serde::Serialize;
+
+use crate::{deadlines::describe, du::display_size, table::TablePaper};
+
+/// Output style for the results of commands.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputStyle {
+    /// Pretty table format.
+    #[default]
+    Table,
+    /// Json format.
+    Json,
+    /// Yaml format.
+    Yaml,
+    /// Comma separated values, with a header row.
+    Csv,
+    /// Tab separated values without a header, for scripts.
+    Plain,
+    /// Markdown, a table unless the command has a write-up of its own.
+    Markdown,
+}
+
+/// The result of a command, which can be printed in any [`OutputStyle`].
+///
+/// The [`Display`] implementation is used for the table style, serde for json and yaml, and the
+/// header and rows for csv, plain and markdown.
+pub trait Render: Serialize + Display {
+    /// Names of the columns.
+    fn header(&self) -> Vec<String>;
+
+    /// Cells of each row.
+    fn rows(&self) -> Vec<Vec<String>>;
+
+    /// Markdown to share, a table of the header and rows by default.
+    fn markdown(&self) -> String {
+        let row = |cells: &[String]| {
+            let cells = cells
+                .iter()
+                .map(|c| c.replace('|', "\\|").replace('\n', " "))
+                .collect::<Vec<_>>();
+            format!("| {} |\n", cells.join(" | "))
+        };
+        let header = self.header();
+        let mut table = row(&header);
+        table.push_str(&row(&vec!["---".to_owned(); header.len()]));
+        for cells in self.rows() {
+            table.push_str(&row(&cells));
+        }
+        table
+    }
+}
+
+impl OutputStyle {
+    /// Print a value to stdout in this style.
+    pub fn print<R: Render>(&self, value: &R) -> anyhow::Result<()> {
+        self.write(value, &mut stdout().lock())
+    }
+
+    /// Write a value in this style.
+    pub fn write<R: Render>(&self, value: &R, w: &mut impl Write) -> anyhow::Result<()> {
+        match self {
+            Self::Table => {
+                let rendered = value.to_string();
+                if !rendered.is_empty() {
+                    writeln!(w, "{rendered}")?;
+                }
+            }
+            Self::Json => serde_json::to_writer(w, value)?,
+            Self::Yaml => serde_yaml::to_writer(w, value)?,
+            Self::Csv => {
+                write_csv_row(w, &value.header())?;
+                for row in value.rows() {
+                    write_csv_row(w, &row)?;
+                }
+            }
+            Self::Plain => {
+                for row in value.rows() {
+                    writeln!(w, "{}", row.join("\t"))?;
+                }
+            }
+            Self::Markdown => write!(w, "{}", value.markdown())?,
+        }
+        Ok(())
+    }
+}
+
+fn write_csv_row(w: &mut impl Write, cells: &[String]) -> std::io::Result<()> {
+    let cells = cells
+        .iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    writeln!(w, "{}", cells.join(","))
+}
+
+/// Papers that were added to the repo.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Added(pub Vec<PaperMeta>);
+
+impl Display for Added {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|p| format!("Added paper {}", p.title))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Added {
+    fn header(&self) -> Vec<String> {
+        TablePaper::header(&[])
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        TablePaper::rows(self.0.iter().cloned(), &[])
+    }
+}
+
+/// A paper written by an import.
+#[derive(Debug, Serialize)]
+pub struct ImportedPaper {
+    /// The paper as written.
+    #[serde(flatten)]
+    pub meta: PaperMeta,
+    /// Whether the paper was already in the repo and had what it was missing filled in, rather
+    /// than being added.
+    #[serde(skip)]
+    pub merged: bool,
+}
+
+/// Papers written by an import.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Imported(pub Vec<ImportedPaper>);
+
+impl Display for Imported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|p| {
+                if p.merged {
+                    format!("Merged into paper {}", p.meta.title)
+                } else {
+                    format!("Added paper {}", p.meta.title)
+                }
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Imported {
+    fn header(&self) -> Vec<String> {
+        TablePaper::header(&[])
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        TablePaper::rows(self.0.iter().map(|p| p.meta.clone()), &[])
+    }
+}
+
+/// A file that was renamed.
+#[derive(Debug, Serialize)]
+pub struct Rename {
+    /// Where the file was.
+    pub from: PathBuf,
+    /// Where the file is now.
+    pub to: PathBuf,
+}
+
+/// Files that were renamed.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Renames(pub Vec<Rename>);
+
+impl Display for Renames {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|r| format!("Renaming {:?} to {:?}", r.from, r.to))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Renames {
+    fn header(&self) -> Vec<String> {
+        vec!["from".to_owned(), "to".to_owned()]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|r| {
+                vec![
+                    r.from.to_string_lossy().into_owned(),
+                    r.to.to_string_lossy().into_owned(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Kind of problem found by the doctor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProblemKind {
+    /// Notes file isn't named after its title.
+    NotesWrongPath,
+    /// Notes file would share a path with another on case-insensitive filesystems.
+    NotesCollision,
+    /// Document named by the notes doesn't exist, fixed by relinking an unmatched file that looks
+    /// like it.
+    MissingFile,
+    /// Document isn't named after its title.
+    FileWrongPath,
+    /// File isn't referenced by any notes.
+    UnmatchedFile,
+    /// Url can't be parsed.
+    InvalidUrl,
+    /// Url isn't in its canonical form.
+    UrlNotNormalised,
+    /// Document is empty, truncated or not of the type its extension says.
+    CorruptFile,
+    /// Paper has a tag that isn't in the tag registry, only checked in strict mode.
+    UnregisteredTag,
+    /// Notes of a paper added a while ago are still just the notes template.
+    UntouchedNotes,
+    /// Document has the same contents as that of a paper added earlier, fixed by merging the
+    /// paper into that one.
+    DuplicateFile,
+}
+
+/// A problem found in the repo.
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    /// What is wrong.
+    pub kind: ProblemKind,
+    /// The file with the problem.
+    pub path: PathBuf,
+    /// The related path, e.g. where the file should be.
+    pub other: Option<PathBuf>,
+    /// More about the problem, e.g. what a url should be.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Whether the problem was fixed.
+    pub fixed: bool,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = &self.path;
+        let other = self.other.as_ref().unwrap_or(path);
+        match self.kind {
+            ProblemKind::NotesWrongPath => {
+                write!(
+                    f,
+                    "Paper notes at wrong path. current={path:?}, expected={other:?}"
+                )?;
+                if self.fixed {
+                    write!(
+                        f,
+                        "\nMoving paper notes. current={path:?}, expected={other:?}"
+                    )?;
+                }
+            }
+            ProblemKind::NotesCollision => write!(
+                f,
+                "Paper notes paths collide ignoring case. current={path:?}, other={other:?}"
+            )?,
+            ProblemKind::MissingFile => {
+                write!(
+                    f,
+                    "File is not at the named location. current={path:?}, filename={other:?}"
+                )?;
+                if let Some(found) = &self.detail {
+                    write!(f, ", found={found:?}")?;
+                    if self.fixed {
+                        write!(f, "\nRelinking file. current={path:?}, filename={found:?}")?;
+                    }
+                }
+            }
+            ProblemKind::FileWrongPath => {
+                write!(
+                    f,
+                    "File at wrong path. current={path:?}, expected={other:?}"
+                )?;
+                if self.fixed {
+                    write!(f, "\nMoving file. current={path:?}, expected={other:?}")?;
+                }
+            }
+            ProblemKind::UnmatchedFile => write!(f, "Found unmatched file {path:?}")?,
+            ProblemKind::InvalidUrl => write!(
+                f,
+                "Paper url is invalid. paper={path:?}, {}",
+                self.detail.as_deref().unwrap_or_default()
+            )?,
+            ProblemKind::CorruptFile => {
+                let reason = self.detail.as_deref().unwrap_or_default();
+                write!(f, "File is corrupt, {reason}. current={path:?}")?;
+                if self.fixed {
+                    write!(f, "\nFetched file again. current={path:?}")?;
+                }
+            }
+            ProblemKind::UnregisteredTag => write!(
+                f,
+                "Paper tag is not registered. paper={path:?}, tag={:?}",
+                self.detail.as_deref().unwrap_or_default()
+            )?,
+            ProblemKind::UntouchedNotes => write!(
+                f,
+                "Paper notes are still the template. paper={path:?}, added={} days ago",
+                self.detail.as_deref().unwrap_or_default()
+            )?,
+            ProblemKind::DuplicateFile => {
+                write!(
+                    f,
+                    "Paper document is a duplicate of another's. paper={path:?}, other={other:?}"
+                )?;
+                if self.fixed {
+                    write!(f, "\nMerging paper. paper={path:?}, into={other:?}")?;
+                }
+            }
+            ProblemKind::UrlNotNormalised => {
+                let expected = self.detail.as_deref().unwrap_or_default();
+                write!(
+                    f,
+                    "Paper url is not normalised. paper={path:?}, expected={expected:?}"
+                )?;
+                if self.fixed {
+                    write!(f, "\nUpdating paper url. paper={path:?}, url={expected:?}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Problems found in the repo.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Problems(pub Vec<Problem>);
+
+impl Display for Problems {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self.0.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Problems {
+    fn header(&self) -> Vec<String> {
+        ["kind", "path", "other", "detail", "fixed"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|p| {
+                vec![
+                    serde_yaml::to_string(&p.kind).unwrap().trim().to_owned(),
+                    p.path.to_string_lossy().into_owned(),
+                    p.other
+                        .as_ref()
+                        .map(|o| o.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    p.detail.clone().unwrap_or_default(),
+                    p.fixed.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A tag along with what the tag registry says it is for.
+#[derive(Debug, Serialize)]
+pub struct TagDescription {
+    /// The tag.
+    pub tag: String,
+    /// Number of papers with the tag.
+    pub count: usize,
+    /// Whether the tag is in the registry.
+    pub registered: bool,
+    /// What the tag means.
+    pub description: Option<String>,
+    /// When to put the tag on a paper.
+    pub usage: Option<String>,
+}
+
+/// Tags used in the repo or in the tag registry.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct TagDescriptions(pub Vec<TagDescription>);
+
+impl Display for TagDescriptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|t| {
+                let mut line = format!("{} ({})", t.tag, t.count);
+                if !t.registered {
+                    line.push_str(" unregistered");
+                }
+                if let Some(description) = &t.description {
+                    line.push_str(&format!(": {description}"));
+                }
+                if let Some(usage) = &t.usage {
+                    line.push_str(&format!("\n  Usage: {usage}"));
+                }
+                line
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for TagDescriptions {
+    fn header(&self) -> Vec<String> {
+        ["tag", "count", "registered", "description", "usage"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|t| {
+                vec![
+                    t.tag.clone(),
+                    t.count.to_string(),
+                    t.registered.to_string(),
+                    t.description.clone().unwrap_or_default(),
+                    t.usage.clone().unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Changes recorded in the journal of the repo.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct History(pub Vec<Entry>);
+
+impl Display for History {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|e| {
+                let mut line = format!("{} {} {} {:?}", e.at, e.user, e.action, e.path);
+                if let Some(other) = &e.other {
+                    line.push_str(&format!(" to {other:?}"));
+                }
+                if let Some(details) = &e.details {
+                    line.push_str(&format!(" ({details})"));
+                }
+                line
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for History {
+    fn header(&self) -> Vec<String> {
+        ["at", "user", "action", "path", "other", "details"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|e| {
+                vec![
+                    e.at.to_string(),
+                    e.user.clone(),
+                    e.action.to_string(),
+                    e.path.to_string_lossy().into_owned(),
+                    e.other
+                        .as_ref()
+                        .map(|o| o.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    e.details.clone().unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A change to a metadata field of a paper.
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Name of the field that changed.
+    pub field: String,
+    /// Value before the change.
+    pub from: String,
+    /// Value after the change.
+    pub to: String,
+}
+
+/// Changes to metadata fields.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct FieldChanges(pub Vec<FieldChange>);
+
+impl Display for FieldChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|c| format!("{:?} {}\n- {}\n+ {}", c.path, c.field, c.from, c.to))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for FieldChanges {
+    fn header(&self) -> Vec<String> {
+        ["path", "field", "from", "to"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|c| {
+                vec![
+                    c.path.to_string_lossy().into_owned(),
+                    c.field.clone(),
+                    c.from.clone(),
+                    c.to.clone(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A paper with a newer version available.
+#[derive(Debug, Serialize)]
+pub struct Update {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Id of the paper, without a version.
+    pub id: String,
+    /// Version of the stored document, if known.
+    pub current: Option<u32>,
+    /// Latest version available.
+    pub latest: u32,
+    /// Whether the latest version was fetched.
+    pub fetched: bool,
+}
+
+/// Papers with newer versions available.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Updates(pub Vec<Update>);
+
+impl Display for Updates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|u| {
+                let current = u.current.map_or("unknown".to_owned(), |c| format!("v{c}"));
+                let mut line = format!(
+                    "New version of {:?} ({}): {current} -> v{}",
+                    u.path, u.id, u.latest
+                );
+                if u.fetched {
+                    line.push_str(", fetched");
+                }
+                line
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Updates {
+    fn header(&self) -> Vec<String> {
+        ["path", "id", "current", "latest", "fetched"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|u| {
+                vec![
+                    u.path.to_string_lossy().into_owned(),
+                    u.id.clone(),
+                    u.current.map(|c| c.to_string()).unwrap_or_default(),
+                    u.latest.to_string(),
+                    u.fetched.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A document that was optimized.
+#[derive(Debug, Serialize)]
+pub struct OptimizedFile {
+    /// Path of the document.
+    pub filename: PathBuf,
+    /// Size before optimizing, in bytes.
+    pub before: u64,
+    /// Size after optimizing, in bytes, the same as before if the document was kept.
+    pub after: u64,
+}
+
+/// Documents that were optimized.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Optimized(pub Vec<OptimizedFile>);
+
+impl Display for Optimized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = self
+            .0
+            .iter()
+            .map(|o| {
+                format!(
+                    "{}: {} -> {}",
+                    o.filename.display(),
+                    display_size(o.before),
+                    display_size(o.after)
+                )
+            })
+            .collect::<Vec<_>>();
+        let saved = self.0.iter().map(|o| o.before - o.after).sum();
+        lines.push(format!("Saved {}", display_size(saved)));
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Optimized {
+    fn header(&self) -> Vec<String> {
+        ["filename", "before", "after"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|o| {
+                vec![
+                    o.filename.to_string_lossy().into_owned(),
+                    o.before.to_string(),
+                    o.after.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A missing document that was fetched, or failed to be.
+#[derive(Debug, Serialize)]
+pub struct FetchedDocument {
+    /// Path of the paper.
+    pub path: PathBuf,
+    /// Url the document was fetched from.
+    pub url: String,
+    /// Where the document was saved, if it was fetched.
+    pub filename: Option<PathBuf>,
+    /// Why the document couldn't be fetched.
+    pub error: Option<String>,
+}
+
+/// Missing documents that were fetched.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct FetchedDocuments(pub Vec<FetchedDocument>);
+
+impl Display for FetchedDocuments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = self
+            .0
+            .iter()
+            .map(|d| match (&d.filename, &d.error) {
+                (Some(filename), _) => format!("Fetched {filename:?} for {:?}", d.path),
+                (None, error) => format!(
+                    "Failed to fetch {} for {:?}: {}",
+                    d.url,
+                    d.path,
+                    error.as_deref().unwrap_or_default()
+                ),
+            })
+            .collect::<Vec<_>>();
+        let fetched = self.0.iter().filter(|d| d.filename.is_some()).count();
+        lines.push(format!(
+            "Fetched {fetched}, failed {}",
+            self.0.len() - fetched
+        ));
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for FetchedDocuments {
+    fn header(&self) -> Vec<String> {
+        ["path", "url", "filename", "error"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|d| {
+                vec![
+                    d.path.to_string_lossy().into_owned(),
+                    d.url.clone(),
+                    d.filename
+                        .as_ref()
+                        .map(|f| f.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    d.error.clone().unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The thumbnail of a document.
+#[derive(Debug, Serialize)]
+pub struct DocumentThumbnail {
+    /// Path of the document.
+    pub filename: PathBuf,
+    /// Path of the thumbnail.
+    pub thumbnail: PathBuf,
+    /// Whether the thumbnail was rendered now, rather than already being up to date.
+    pub rendered: bool,
+}
+
+/// Thumbnails of documents.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct DocumentThumbnails(pub Vec<DocumentThumbnail>);
+
+impl Display for DocumentThumbnails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|t| {
+                let status = if t.rendered { "rendered" } else { "up to date" };
+                format!(
+                    "{}: {} ({status})",
+                    t.filename.display(),
+                    t.thumbnail.display()
+                )
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for DocumentThumbnails {
+    fn header(&self) -> Vec<String> {
+        ["filename", "thumbnail", "rendered"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|t| {
+                vec![
+                    t.filename.to_string_lossy().into_owned(),
+                    t.thumbnail.to_string_lossy().into_owned(),
+                    t.rendered.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Disk usage of the documents in a group of papers.
+#[derive(Debug, Serialize)]
+pub struct UsageGroup {
+    /// Name of the group.
+    pub group: String,
+    /// Number of papers with documents in the group.
+    pub papers: usize,
+    /// Total size of the documents, in bytes.
+    pub bytes: u64,
+}
+
+/// Disk usage of groups of papers.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct UsageGroups(pub Vec<UsageGroup>);
+
+impl Display for UsageGroups {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|g| {
+                format!(
+                    "{}: {} in {} papers",
+                    g.group,
+                    display_size(g.bytes),
+                    g.papers
+                )
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for UsageGroups {
+    fn header(&self) -> Vec<String> {
+        ["group", "papers", "bytes"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|g| vec![g.group.clone(), g.papers.to_string(), g.bytes.to_string()])
+            .collect()
+    }
+}
+
+/// Disk usage of the document of a paper.
+#[derive(Debug, Serialize)]
+pub struct FileUsage {
+    /// Path of the document.
+    pub filename: PathBuf,
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Size of the document, in bytes.
+    pub bytes: u64,
+}
+
+/// Disk usage of documents.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct FileUsages(pub Vec<FileUsage>);
+
+impl Display for FileUsages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|u| format!("{}: {}", u.filename.display(), display_size(u.bytes)))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for FileUsages {
+    fn header(&self) -> Vec<String> {
+        ["filename", "path", "bytes"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|u| {
+                vec![
+                    u.filename.to_string_lossy().into_owned(),
+                    u.path.to_string_lossy().into_owned(),
+                    u.bytes.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A paper with a date to read it by.
+#[derive(Debug, Serialize)]
+pub struct Deadline {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+    /// Date to read the paper by.
+    pub read_by: NaiveDate,
+    /// Days until the date, negative once it has passed.
+    pub days_left: i64,
+}
+
+/// Papers with dates to read them by.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Deadlines(pub Vec<Deadline>);
+
+impl Display for Deadlines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|d| format!("{} ({}) {}", d.read_by, describe(d.days_left), d.title))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Deadlines {
+    fn header(&self) -> Vec<String> {
+        ["path", "title", "read_by", "days_left"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|d| {
+                vec![
+                    d.path.to_string_lossy().into_owned(),
+                    d.title.clone(),
+                    d.read_by.to_string(),
+                    d.days_left.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A paper whose citation count grew.
+#[derive(Debug, Serialize)]
+pub struct Trending {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+    /// Citation count from the last update.
+    pub citations: u64,
+    /// Growth in the citation count in the last update.
+    pub increase: i64,
+}
+
+/// Papers whose citation counts grew the most.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct TrendingPapers(pub Vec<Trending>);
+
+impl Display for TrendingPapers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|t| format!("+{} {} ({} citations)", t.increase, t.title, t.citations))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for TrendingPapers {
+    fn header(&self) -> Vec<String> {
+        ["path", "title", "citations", "increase"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|t| {
+                vec![
+                    t.path.to_string_lossy().into_owned(),
+                    t.title.clone(),
+                    t.citations.to_string(),
+                    t.increase.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Summary of a snapshot of the repo.
+#[derive(Debug, Serialize)]
+pub struct SnapshotSummary {
+    /// Name of the snapshot.
+    pub name: String,
+    /// When it was taken.
+    pub created_at: chrono::NaiveDateTime,
+    /// Number of papers in it.
+    pub papers: usize,
+}
+
+impl From<&Snapshot> for SnapshotSummary {
+    fn from(snapshot: &Snapshot) -> Self {
+        Self {
+            name: snapshot.name.clone(),
+            created_at: snapshot.created_at,
+            papers: snapshot.papers.len(),
+        }
+    }
+}
+
+/// Snapshots of the repo.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Snapshots(pub Vec<SnapshotSummary>);
+
+impl Display for Snapshots {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|s| format!("{} {} ({} papers)", s.name, s.created_at, s.papers))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Snapshots {
+    fn header(&self) -> Vec<String> {
+        ["name", "created_at", "papers"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|s| {
+                vec![
+                    s.name.clone(),
+                    s.created_at.to_string(),
+                    s.papers.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Papers in the trash.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct TrashedPapers(pub Vec<Trashed>);
+
+impl Display for TrashedPapers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|t| match &t.file {
+                Some(file) => format!("{} {:?} and {:?}", t.name, t.path, file),
+                None => format!("{} {:?}", t.name, t.path),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for TrashedPapers {
+    fn header(&self) -> Vec<String> {
+        ["name", "trashed_at", "path", "file"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|t| {
+                vec![
+                    t.name.clone(),
+                    t.trashed_at.to_string(),
+                    t.path.display().to_string(),
+                    t.file
+                        .as_ref()
+                        .map(|f| f.display().to_string())
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A paper whose metadata was restored from a snapshot.
+#[derive(Debug, Serialize)]
+pub struct RestoredPaper {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// What was done to it.
+    pub action: Restored,
+}
+
+/// Papers whose metadata was restored from a snapshot.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct RestoredPapers(pub Vec<RestoredPaper>);
+
+impl Display for RestoredPapers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|r| match r.action {
+                Restored::Reverted => format!("Restored {:?}", r.path),
+                Restored::Recreated => format!("Recreated {:?} without notes", r.path),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for RestoredPapers {
+    fn header(&self) -> Vec<String> {
+        ["path", "action"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|r| {
+                vec![
+                    r.path.to_string_lossy().into_owned(),
+                    serde_yaml::to_string(&r.action).unwrap().trim().to_owned(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(style: OutputStyle, value: &impl Render, expected: Expect) {
+        let mut out = Vec::new();
+        style.write(value, &mut out).unwrap();
+        expected.assert_eq(&String::from_utf8(out).unwrap());
+    }
+
+    fn renames() -> Renames {
+        Renames(vec![
+            Rename {
+                from: "a.pdf".into(),
+                to: "A, \"quoted\".pdf".into(),
+            },
+            Rename {
+                from: "b.pdf".into(),
+                to: "B.pdf".into(),
+            },
+        ])
+    }
+
+    #[test]
+    fn test_csv() {
+        check(
+            OutputStyle::Csv,
+            &renames(),
+            expect![[r#"
+                from,to
+                a.pdf,"A, ""quoted"".pdf"
+                b.pdf,B.pdf
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        check(
+            OutputStyle::Markdown,
+            &Renames(vec![Rename {
+                from: "a|b.pdf".into(),
+                to: "A.pdf".into(),
+            }]),
+            expect![[r#"
+                | from | to |
+                | --- | --- |
+                | a\|b.pdf | A.pdf |
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_plain() {
+        check(
+            OutputStyle::Plain,
+            &renames(),
+            expect![[r#"
+                a.pdf	A, "quoted".pdf
+                b.pdf	B.pdf
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        check(
+            OutputStyle::Json,
+            &renames(),
+            expect![[
+                r#"[{"from":"a.pdf","to":"A, \"quoted\".pdf"},{"from":"b.pdf","to":"B.pdf"}]"#
+            ]],
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::str::FromStr;
+
+use papers_core::paper::LoadedPaper;
+
+/// Fields of a paper that can be used in a format template.
+const FIELDS: &[&str] = &[
+    "title",
+    "path",
+    "url",
+    "filename",
+    "doc_type",
+    "authors",
+    "tags",
+    "labels",
+    "created_at",
+    "modified_at",
+    "last_review",
+    "next_review",
+    "read_by",
+    "source",
+    "source_detail",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(String),
+    Label(String),
+    Extra(String),
+}
+
+/// A template for printing papers one per line, e.g. `{title}\t{url}`.
+///
+/// Fields are written in braces, `{label.<key>}` gives the value of a single label,
+/// `{extra.<key>}` that of an extra field and `{{`/`}}` give literal braces. The escapes `\t`,
+/// `\n` and `\\` are understood so templates can be written in single quotes in the shell.
+/// Fields with multiple values are joined with `,`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaperFormat {
+    parts: Vec<Part>,
+}
+
+impl FromStr for PaperFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => {
+                        literal.push('\\');
+                        literal.push(other);
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| format!("Unclosed field in format {s:?}"))?;
+                    let name = rest[..end].trim();
+                    let part = if let Some(key) = name.strip_prefix("label.") {
+                        Part::Label(key.to_owned())
+                    } else if let Some(key) = name.strip_prefix("extra.") {
+                        Part::Extra(key.to_owned())
+                    } else if FIELDS.contains(&name) {
+                        Part::Field(name.to_owned())
+                    } else {
+                        return Err(format!(
+                            "Unknown field {name:?}, expected one of {}, label.<key> or extra.<key>",
+                            FIELDS.join(", ")
+                        ));
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err(format!("Unmatched '}}' in format {s:?}")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+}
+
+impl PaperFormat {
+    /// Render a paper with this template.
+    pub fn render(&self, paper: &LoadedPaper) -> String {
+        let meta = &paper.meta;
+        let join = |items: Vec<String>| items.join(",");
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => out.push_str(literal),
+                Part::Label(key) => {
+                    if let Some(value) = meta.labels.get(key) {
+                        out.push_str(&value.to_string());
+                    }
+                }
+                Part::Extra(key) => match meta.extra.get(key) {
+                    Some(serde_yaml::Value::String(value)) => out.push_str(value),
+                    Some(value) => out.push_str(&serde_json::to_string(value).unwrap_or_default()),
+                    None => {}
+                },
+                Part::Field(field) => {
+                    let value = match field.as_str() {
+                        "title" => meta.title.clone(),
+                        "path" => paper.path.to_string_lossy().into_owned(),
+                        "url" => meta.url.clone().unwrap_or_default(),
+                        "filename" => meta
+                            .filename
+                            .as_ref()
+                            .map(|f| f.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        "doc_type" => meta.doc_type.to_string(),
+                        "authors" => join(meta.authors.iter().map(|a| a.to_string()).collect()),
+                        "tags" => join(meta.tags.iter().map(|t| t.to_string()).collect()),
+                        "labels" => join(
+                            meta.labels
+                                .iter()
+                                .map(|(k, v)| format!("{k}={v}"))
+                                .collect(),
+                        ),
+                        "created_at" => meta.created_at.to_string(),
+                        "modified_at" => meta.modified_at.to_string(),
+                        "last_review" => {
+                            meta.last_review.map(|d| d.to_string()).unwrap_or_default()
+                        }
+                        "next_review" => {
+                            meta.next_review.map(|d| d.to_string()).unwrap_or_default()
+                        }
+                        "read_by" => meta.read_by.map(|d| d.to_string()).unwrap_or_default(),
+                        "source" => meta
+                            .provenance
+                            .as_ref()
+                            .map(|p| p.source.to_string())
+                            .unwrap_or_default(),
+                        "source_detail" => meta
+                            .provenance
+                            .as_ref()
+                            .and_then(|p| p.detail.clone())
+                            .unwrap_or_default(),
+                        _ => unreachable!("fields are checked when parsing"),
+                    };
+                    out.push_str(&value);
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use expect_test::{expect, Expect};
+    use papers_core::{author::Author, paper::PaperMeta, primitive::Primitive, tag::Tag};
+
+    use super::*;
+
+    fn check(format: &str, expected: Expect) {
+        let paper = LoadedPaper {
+            path: "A Paper.md".into(),
+            meta: PaperMeta {
+                title: "A Paper".to_owned(),
+                url: Some("https://example.com/a.pdf".to_owned()),
+                authors: vec![Author::new("Jane"), Author::new("John")],
+                tags: BTreeSet::from([Tag::new("db"), Tag::new("to-read")]),
+                labels: BTreeMap::from([(
+                    "venue".to_owned(),
+                    Primitive::String("osdi".to_owned()),
+                )]),
+                extra: BTreeMap::from([
+                    ("pages".to_owned(), serde_yaml::from_str("[1, 12]").unwrap()),
+                    ("venue".to_owned(), "SOSP".into()),
+                ]),
+                ..Default::default()
+            },
+            notes: String::new(),
+        };
+        let rendered = format.parse::<PaperFormat>().map(|f| f.render(&paper));
+        expected.assert_debug_eq(&rendered);
+    }
+
+    #[test]
+    fn test_fields() {
+        check(
+            r"{title}\t{url}\t{authors}",
+            expect![[r#"
+                Ok(
+                    "A Paper\thttps://example.com/a.pdf\tJane,John",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_labels_and_braces() {
+        check(
+            "{{{label.venue}}} {label.missing}{tags}",
+            expect![[r#"
+                Ok(
+                    "{osdi} db,to-read",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_extra() {
+        check(
+            "{extra.venue} {extra.pages}{extra.missing}",
+            expect![[r#"
+                Ok(
+                    "SOSP [1,12]",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        check(
+            "{nope}",
+            expect![[r#"
+                Err(
+                    "Unknown field \"nope\", expected one of title, path, url, filename, doc_type, authors, tags, labels, created_at, modified_at, last_review, next_review, read_by, source, source_detail, label.<key> or extra.<key>",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_unclosed() {
+        check(
+            "{title",
+            expect![[r#"
+                Err(
+                    "Unclosed field in format \"{title\"",
+                )
+            "#]],
+        );
+    }
+}
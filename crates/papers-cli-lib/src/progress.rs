@@ -0,0 +1,146 @@
+use std::{
+    fmt::Display,
+    io::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// How to report the progress of long operations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProgressStyle {
+    /// Don't report progress.
+    #[default]
+    None,
+    /// A json object per line on stderr, for wrappers to show progress with.
+    Json,
+}
+
+/// An update on the progress of an operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent<'a> {
+    /// The operation started, with the number of items it will go through.
+    Start {
+        /// Name of the operation, the command running it.
+        operation: &'a str,
+        /// Number of items in the operation.
+        total: usize,
+    },
+    /// Work on an item started.
+    Item {
+        /// Name of the operation, the command running it.
+        operation: &'a str,
+        /// Position of the item, counting from one.
+        current: usize,
+        /// Number of items in the operation.
+        total: usize,
+        /// The item being worked on, usually a path.
+        item: String,
+    },
+    /// The operation went through all of its items.
+    Finish {
+        /// Name of the operation, the command running it.
+        operation: &'a str,
+        /// Number of items in the operation.
+        total: usize,
+    },
+}
+
+/// Reports the progress of an operation going through a known number of items.
+///
+/// Items can be reported from several threads at once.
+#[derive(Debug)]
+pub struct Progress {
+    style: ProgressStyle,
+    operation: &'static str,
+    total: usize,
+    current: AtomicUsize,
+}
+
+impl Progress {
+    /// Start reporting on `operation` which has `total` items.
+    pub fn start(style: ProgressStyle, operation: &'static str, total: usize) -> Self {
+        let progress = Self {
+            style,
+            operation,
+            total,
+            current: AtomicUsize::new(0),
+        };
+        progress.emit(&ProgressEvent::Start { operation, total });
+        progress
+    }
+
+    /// Report that work on `item` started.
+    pub fn item(&self, item: impl Display) {
+        let current = self.current.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.style == ProgressStyle::None {
+            return;
+        }
+        self.emit(&ProgressEvent::Item {
+            operation: self.operation,
+            current,
+            total: self.total,
+            item: item.to_string(),
+        });
+    }
+
+    /// Report that the operation finished.
+    pub fn finish(self) {
+        self.emit(&ProgressEvent::Finish {
+            operation: self.operation,
+            total: self.total,
+        });
+    }
+
+    fn emit(&self, event: &ProgressEvent) {
+        match self.style {
+            ProgressStyle::None => {}
+            ProgressStyle::Json => {
+                let Ok(line) = serde_json::to_string(event) else {
+                    return;
+                };
+                // locked so that lines from different threads don't interleave, and progress
+                // failing to print shouldn't fail the operation
+                let _ = writeln!(std::io::stderr().lock(), "{line}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_event_lines() {
+        let events = [
+            ProgressEvent::Start {
+                operation: "doctor",
+                total: 2,
+            },
+            ProgressEvent::Item {
+                operation: "doctor",
+                current: 1,
+                total: 2,
+                item: "paper.md".to_owned(),
+            },
+            ProgressEvent::Finish {
+                operation: "doctor",
+                total: 2,
+            },
+        ];
+        let lines = events
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap())
+            .collect::<Vec<_>>();
+        expect![[r#"
+            {"event":"start","operation":"doctor","total":2}
+            {"event":"item","operation":"doctor","current":1,"total":2,"item":"paper.md"}
+            {"event":"finish","operation":"doctor","total":2}"#]]
+        .assert_eq(&lines.join("\n"));
+    }
+}
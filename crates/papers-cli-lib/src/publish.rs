@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use papers_core::{paper::PaperMeta, primitive::Primitive, tag::Tag};
+use serde::Serialize;
+
+/// Tag of the papers that `papers publish` writes out.
+pub const PUBLIC_TAG: &str = "public";
+
+/// Static site generator to write pages for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SiteGenerator {
+    /// Zola, with toml frontmatter and tags as a taxonomy.
+    #[default]
+    Zola,
+    /// Hugo, with yaml frontmatter.
+    Hugo,
+}
+
+/// Fields of a paper that aren't part of the generator's own schema.
+#[derive(Debug, Serialize)]
+struct Extra {
+    authors: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paper_url: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    labels: BTreeMap<String, Primitive>,
+}
+
+#[derive(Debug, Serialize)]
+struct Taxonomies {
+    tags: Vec<String>,
+}
+
+/// Frontmatter of a Zola page, tables last as toml needs.
+#[derive(Debug, Serialize)]
+struct ZolaFrontmatter {
+    title: String,
+    date: NaiveDate,
+    updated: NaiveDate,
+    taxonomies: Taxonomies,
+    extra: Extra,
+}
+
+/// Frontmatter of a Hugo page.
+#[derive(Debug, Serialize)]
+struct HugoFrontmatter {
+    title: String,
+    date: NaiveDate,
+    lastmod: NaiveDate,
+    tags: Vec<String>,
+    params: Extra,
+}
+
+impl SiteGenerator {
+    /// The page for a paper, its metadata mapped to the generator's frontmatter above the notes.
+    ///
+    /// The [`PUBLIC_TAG`] is left out as every published paper has it.
+    pub fn page(&self, meta: &PaperMeta, notes: &str) -> anyhow::Result<String> {
+        let public = Tag::new(PUBLIC_TAG);
+        let tags = meta
+            .tags
+            .iter()
+            .filter(|t| **t != public)
+            .map(|t| t.to_string())
+            .collect();
+        let extra = Extra {
+            authors: meta.authors.iter().map(|a| a.to_string()).collect(),
+            paper_url: meta.url.clone(),
+            labels: meta.labels.clone(),
+        };
+        let date = meta.created_at.date();
+        let updated = meta.modified_at.date();
+        let notes = notes.trim_start_matches('\n');
+        let page = match self {
+            Self::Zola => {
+                let frontmatter = toml::to_string(&ZolaFrontmatter {
+                    title: meta.title.clone(),
+                    date,
+                    updated,
+                    taxonomies: Taxonomies { tags },
+                    extra,
+                })?;
+                format!("+++\n{frontmatter}+++\n\n{notes}")
+            }
+            Self::Hugo => {
+                let frontmatter = serde_yaml::to_string(&HugoFrontmatter {
+                    title: meta.title.clone(),
+                    date,
+                    lastmod: updated,
+                    tags,
+                    params: extra,
+                })?;
+                format!("---\n{frontmatter}---\n\n{notes}")
+            }
+        };
+        Ok(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use papers_core::author::Author;
+
+    use super::*;
+
+    fn check(generator: SiteGenerator, expected: Expect) {
+        let meta = PaperMeta {
+            title: "In Search of an \"Understandable\" Consensus Algorithm".to_owned(),
+            url: Some("https://raft.github.io/raft.pdf".to_owned()),
+            tags: [PUBLIC_TAG, "consensus"].map(Tag::new).into(),
+            labels: BTreeMap::from([("venue".to_owned(), "ATC".parse().unwrap())]),
+            authors: vec![Author::new("Diego Ongaro"), Author::new("John Ousterhout")],
+            created_at: NaiveDate::from_ymd_opt(2024, 5, 2)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+            modified_at: NaiveDate::from_ymd_opt(2024, 5, 20)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            ..Default::default()
+        };
+        expected.assert_eq(
+            &generator
+                .page(&meta, "\n## Summary\n\nLeader based.\n")
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_zola() {
+        check(
+            SiteGenerator::Zola,
+            expect![[r#"
+            +++
+            title = "In Search of an \"Understandable\" Consensus Algorithm"
+            date = "2024-05-02"
+            updated = "2024-05-20"
+
+            [taxonomies]
+            tags = ["consensus"]
+
+            [extra]
+            authors = ["Diego Ongaro", "John Ousterhout"]
+            paper_url = "https://raft.github.io/raft.pdf"
+
+            [extra.labels]
+            venue = "ATC"
+            +++
+
+            ## Summary
+
+            Leader based.
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_hugo() {
+        check(
+            SiteGenerator::Hugo,
+            expect![[r#"
+            ---
+            title: In Search of an "Understandable" Consensus Algorithm
+            date: 2024-05-02
+            lastmod: 2024-05-20
+            tags:
+            - consensus
+            params:
+              authors:
+              - Diego Ongaro
+              - John Ousterhout
+              paper_url: https://raft.github.io/raft.pdf
+              labels:
+                venue: ATC
+            ---
+
+            ## Summary
+
+            Leader based.
+        "#]],
+        );
+    }
+}
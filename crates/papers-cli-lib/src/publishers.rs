@@ -0,0 +1,170 @@
+use reqwest::Url;
+use tracing::{debug, info};
+
+use crate::http::HttpClient;
+
+/// DOI prefixes of publishers whose pdf urls can be derived from the DOI directly.
+const ACM_DOI_PREFIX: &str = "10.1145/";
+const SPRINGER_DOI_PREFIX: &str = "10.1007/";
+
+/// Map a publisher landing page or DOI url to the url of the pdf, if the publisher is known.
+pub fn pdf_url(url: &Url) -> Option<Url> {
+    let host = url.host_str()?;
+    let path = url.path().trim_start_matches('/');
+    let mapped = match host {
+        "doi.org" | "dx.doi.org" => {
+            if path.starts_with(ACM_DOI_PREFIX) {
+                format!("https://dl.acm.org/doi/pdf/{path}")
+            } else if path.starts_with(SPRINGER_DOI_PREFIX) {
+                format!("https://link.springer.com/content/pdf/{path}.pdf")
+            } else {
+                return None;
+            }
+        }
+        "dl.acm.org" => {
+            let doi = path
+                .strip_prefix("doi/abs/")
+                .or_else(|| path.strip_prefix("doi/fullHtml/"))
+                .or_else(|| path.strip_prefix("doi/"))?;
+            if doi.starts_with("pdf/") {
+                return None;
+            }
+            format!("https://dl.acm.org/doi/pdf/{doi}")
+        }
+        "link.springer.com" => {
+            let doi = path
+                .strip_prefix("article/")
+                .or_else(|| path.strip_prefix("chapter/"))?;
+            format!("https://link.springer.com/content/pdf/{doi}.pdf")
+        }
+        "ieeexplore.ieee.org" => {
+            let number = path
+                .strip_prefix("document/")
+                .or_else(|| path.strip_prefix("abstract/document/"))?
+                .trim_end_matches('/');
+            if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            format!("https://ieeexplore.ieee.org/stampPDF/getPDF.jsp?tp=&arnumber={number}")
+        }
+        _ => return None,
+    };
+    Url::parse(&mapped).ok()
+}
+
+/// Apply an institutional proxy template, substituting `{url}` with the target url.
+pub fn apply_proxy(url: &Url, proxy: &str) -> anyhow::Result<Url> {
+    let proxied = if proxy.contains("{url}") {
+        proxy.replace("{url}", url.as_str())
+    } else {
+        format!("{proxy}{url}")
+    };
+    Ok(Url::parse(&proxied)?)
+}
+
+/// Resolve the url a document should be downloaded from.
+///
+/// DOIs that can't be mapped directly are followed to their landing page first.
+pub fn resolve_download_url(
+    client: &HttpClient,
+    url: &Url,
+    proxy: Option<&str>,
+) -> anyhow::Result<Url> {
+    let pdf = match pdf_url(url) {
+        Some(pdf) => Some(pdf),
+        None if matches!(url.host_str(), Some("doi.org" | "dx.doi.org")) => {
+            debug!(%url, "Resolving DOI to landing page");
+            let landing = client.head(url)?.url().clone();
+            info!(%url, %landing, "Resolved DOI");
+            pdf_url(&landing).or(Some(landing))
+        }
+        None => None,
+    };
+
+    match (pdf, proxy) {
+        (Some(pdf), Some(proxy)) => apply_proxy(&pdf, proxy),
+        (Some(pdf), None) => Ok(pdf),
+        (None, _) => Ok(url.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(url: &str, expected: Expect) {
+        let url = Url::parse(url).unwrap();
+        let actual = pdf_url(&url).map(|u| u.to_string());
+        expected.assert_debug_eq(&actual);
+    }
+
+    #[test]
+    fn test_acm_doi() {
+        check(
+            "https://doi.org/10.1145/3341301.3359630",
+            expect![[r#"
+                Some(
+                    "https://dl.acm.org/doi/pdf/10.1145/3341301.3359630",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_acm_abs() {
+        check(
+            "https://dl.acm.org/doi/abs/10.1145/3341301.3359630",
+            expect![[r#"
+                Some(
+                    "https://dl.acm.org/doi/pdf/10.1145/3341301.3359630",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_springer_article() {
+        check(
+            "https://link.springer.com/article/10.1007/s00446-012-0174-9",
+            expect![[r#"
+                Some(
+                    "https://link.springer.com/content/pdf/10.1007/s00446-012-0174-9.pdf",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_ieee() {
+        check(
+            "https://ieeexplore.ieee.org/document/1234567/",
+            expect![[r#"
+                Some(
+                    "https://ieeexplore.ieee.org/stampPDF/getPDF.jsp?tp=&arnumber=1234567",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        check(
+            "https://example.com/paper",
+            expect![[r#"
+            None
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_proxy() {
+        let url = Url::parse("https://dl.acm.org/doi/pdf/10.1145/1").unwrap();
+        let proxied = apply_proxy(&url, "https://proxy.example.edu/login?url={url}")
+            .unwrap()
+            .to_string();
+        expect!["https://proxy.example.edu/login?url=https://dl.acm.org/doi/pdf/10.1145/1"]
+            .assert_eq(&proxied);
+    }
+}
@@ -0,0 +1,291 @@
+use anyhow::Context;
+use papers_core::paper::PaperMeta;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    highlights::{paper_uris, Highlight},
+    http::HttpClient,
+};
+
+/// Where highlights are created.
+const HIGHLIGHTS_URL: &str = "https://readwise.io/api/v2/highlights/";
+
+/// Where all highlights are exported from, a page at a time.
+const EXPORT_URL: &str = "https://readwise.io/api/v2/export/";
+
+/// Environment variable holding the api token, unless another is configured.
+const DEFAULT_TOKEN_ENV: &str = "READWISE_TOKEN";
+
+/// Source of the markers of pulled highlights.
+const MARKER_SOURCE: &str = "readwise";
+
+/// Source type of the highlights pushed, so they aren't pulled back.
+const SOURCE_TYPE: &str = "papers";
+
+/// Settings for `papers readwise`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadwiseConfig {
+    /// Environment variable holding the api token, `READWISE_TOKEN` if not set.
+    #[serde(default)]
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct NewHighlight<'a> {
+    text: &'a str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_url: Option<&'a str>,
+    source_type: &'a str,
+    category: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    note: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct NewHighlights<'a> {
+    highlights: Vec<NewHighlight<'a>>,
+}
+
+/// A page of the export of all highlights.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportPage {
+    next_page_cursor: Option<String>,
+    results: Vec<Book>,
+}
+
+/// A document in Readwise with the highlights made on it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Book {
+    /// Title of the document.
+    pub title: String,
+    /// Where the highlights came from, e.g. `kindle`.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Url of the document.
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// Url of the document in the app it was read in.
+    #[serde(default)]
+    pub unique_url: Option<String>,
+    highlights: Vec<BookHighlight>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BookHighlight {
+    id: u64,
+    text: String,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+/// Body of a request creating `highlights` on the paper.
+fn new_highlights(meta: &PaperMeta, highlights: &[Highlight]) -> anyhow::Result<String> {
+    let author = (!meta.authors.is_empty()).then(|| {
+        meta.authors
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let highlights = highlights
+        .iter()
+        .map(|h| {
+            let (text, note) = match &h.quote {
+                Some(quote) => (quote.as_str(), h.text.as_str()),
+                None => (h.text.as_str(), ""),
+            };
+            NewHighlight {
+                text,
+                title: &meta.title,
+                author: author.clone(),
+                source_url: meta.url.as_deref(),
+                source_type: SOURCE_TYPE,
+                category: "articles",
+                note,
+            }
+        })
+        .collect();
+    Ok(serde_json::to_string(&NewHighlights { highlights })?)
+}
+
+/// Whether the highlight was pulled from Readwise, so isn't pushed back.
+pub fn is_pulled(highlight: &Highlight) -> bool {
+    highlight
+        .marker
+        .as_deref()
+        .is_some_and(|m| m.starts_with(&format!("{MARKER_SOURCE}:")))
+}
+
+impl Book {
+    /// Whether the book is the paper, by its url or DOI, or failing that its title.
+    pub fn is_paper(&self, meta: &PaperMeta) -> bool {
+        let uris = paper_uris(meta);
+        let by_url = [&self.source_url, &self.unique_url]
+            .into_iter()
+            .flatten()
+            .any(|u| uris.contains(u));
+        by_url || self.title.trim().eq_ignore_ascii_case(meta.title.trim())
+    }
+
+    /// Highlights made in Readwise or apps it syncs with, leaving out deleted ones and those pushed
+    /// from papers.
+    pub fn highlights(&self) -> Vec<Highlight> {
+        if self.source.as_deref() == Some(SOURCE_TYPE) {
+            return Vec::new();
+        }
+        self.highlights
+            .iter()
+            .filter(|h| !h.is_deleted)
+            .map(|h| Highlight {
+                marker: Some(format!("{MARKER_SOURCE}:{}", h.id)),
+                quote: Some(h.text.clone()),
+                text: h.note.clone(),
+            })
+            .collect()
+    }
+}
+
+impl ReadwiseConfig {
+    fn token(&self) -> anyhow::Result<String> {
+        let var = self.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+        std::env::var(var).with_context(|| format!("No token for Readwise, set {var}"))
+    }
+
+    /// Create the highlights of a paper in Readwise, which leaves out any it already has.
+    pub fn push(
+        &self,
+        http: &HttpClient,
+        meta: &PaperMeta,
+        highlights: &[Highlight],
+    ) -> anyhow::Result<()> {
+        let token = self.token()?;
+        let body = new_highlights(meta, highlights)?;
+        http.post(&Url::parse(HIGHLIGHTS_URL)?, |r| {
+            r.header("Authorization", format!("Token {token}"))
+                .header("Content-Type", "application/json")
+                .body(body)
+        })?;
+        Ok(())
+    }
+
+    /// All the documents in Readwise with their highlights.
+    pub fn export(&self, http: &HttpClient) -> anyhow::Result<Vec<Book>> {
+        let token = self.token()?;
+        let mut books = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut url = Url::parse(EXPORT_URL)?;
+            if let Some(cursor) = &cursor {
+                url.query_pairs_mut().append_pair("pageCursor", cursor);
+            }
+            let body = http
+                .get_with(&url, |r| {
+                    r.header("Authorization", format!("Token {token}"))
+                })?
+                .text()?;
+            let page: ExportPage =
+                serde_json::from_str(&body).context("Reading the Readwise export")?;
+            books.extend(page.results);
+            cursor = page.next_page_cursor;
+            if cursor.is_none() {
+                return Ok(books);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use papers_core::author::Author;
+
+    use super::*;
+
+    fn raft() -> PaperMeta {
+        PaperMeta {
+            title: "In Search of an Understandable Consensus Algorithm".to_owned(),
+            url: Some("https://raft.github.io/raft.pdf".to_owned()),
+            authors: vec![Author::new("Diego Ongaro"), Author::new("John Ousterhout")],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new_highlights() {
+        let highlights = [
+            Highlight {
+                marker: None,
+                quote: Some("understandable".to_owned()),
+                text: "The main goal".to_owned(),
+            },
+            Highlight {
+                marker: None,
+                quote: None,
+                text: "Written by hand".to_owned(),
+            },
+        ];
+        let body: serde_json::Value =
+            serde_json::from_str(&new_highlights(&raft(), &highlights).unwrap()).unwrap();
+        expect![[r#"
+            {
+              "highlights": [
+                {
+                  "author": "Diego Ongaro, John Ousterhout",
+                  "category": "articles",
+                  "note": "The main goal",
+                  "source_type": "papers",
+                  "source_url": "https://raft.github.io/raft.pdf",
+                  "text": "understandable",
+                  "title": "In Search of an Understandable Consensus Algorithm"
+                },
+                {
+                  "author": "Diego Ongaro, John Ousterhout",
+                  "category": "articles",
+                  "source_type": "papers",
+                  "source_url": "https://raft.github.io/raft.pdf",
+                  "text": "Written by hand",
+                  "title": "In Search of an Understandable Consensus Algorithm"
+                }
+              ]
+            }"#]]
+        .assert_eq(&serde_json::to_string_pretty(&body).unwrap());
+    }
+
+    #[test]
+    fn test_export() {
+        let page: ExportPage = serde_json::from_str(
+            r#"{"count": 2, "nextPageCursor": null, "results": [
+                {"user_book_id": 1, "title": "in search of an understandable consensus algorithm ", "author": "Diego Ongaro", "source": "reader", "source_url": null, "unique_url": null, "highlights": [
+                    {"id": 10, "text": "leader election", "note": "", "is_deleted": false},
+                    {"id": 11, "text": "gone", "note": "", "is_deleted": true}
+                ]},
+                {"user_book_id": 2, "title": "Other", "source": "papers", "source_url": "https://raft.github.io/raft.pdf", "highlights": [
+                    {"id": 12, "text": "pushed", "note": ""}
+                ]}
+            ]}"#,
+        )
+        .unwrap();
+        let [reader, pushed] = &page.results[..] else {
+            panic!("expected two books");
+        };
+        assert!(reader.is_paper(&raft()));
+        assert!(pushed.is_paper(&raft()));
+        assert!(!reader.is_paper(&PaperMeta::default()));
+        assert_eq!(
+            reader.highlights(),
+            vec![Highlight {
+                marker: Some("readwise:10".to_owned()),
+                quote: Some("leader election".to_owned()),
+                text: String::new(),
+            }]
+        );
+        assert_eq!(pushed.highlights(), Vec::new());
+    }
+}
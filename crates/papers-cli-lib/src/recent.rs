@@ -0,0 +1,160 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fs::{create_dir_all, File},
+    io::{BufReader, BufWriter, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Where the index of papers opened or edited recently is kept, relative to the root of the repo.
+pub const RECENT_PATH: &str = ".papers/recent.json";
+
+/// Number of papers remembered as recently used.
+const RECENT_LEN: usize = 200;
+
+/// Days after which a use of a paper counts half as much towards its frecency.
+const HALF_LIFE_DAYS: f64 = 7.0;
+
+/// How often and how recently a paper has been used.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Usage {
+    /// When the paper was last opened or edited.
+    last: NaiveDateTime,
+    /// Frecency as of `last`, each use adds one and decays with [`HALF_LIFE_DAYS`].
+    score: f64,
+}
+
+impl Usage {
+    /// The score decayed to `now`.
+    fn frecency(&self, now: NaiveDateTime) -> f64 {
+        let days = (now - self.last).num_seconds().max(0) as f64 / (24.0 * 60.0 * 60.0);
+        self.score * 0.5f64.powf(days / HALF_LIFE_DAYS)
+    }
+}
+
+/// Papers that have been opened or edited, with when and how often.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Recent {
+    usages: BTreeMap<PathBuf, Usage>,
+}
+
+impl Recent {
+    /// The recently used papers of the repo at `root`, empty if none have been recorded.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(RECENT_PATH);
+        let usages = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file)).unwrap_or_else(|err| {
+                debug!(?path, %err, "Ignoring unreadable index of recent papers");
+                BTreeMap::new()
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                debug!(?path, %err, "Failed to open index of recent papers");
+                BTreeMap::new()
+            }
+        };
+        Self { usages }
+    }
+
+    fn save(&self, root: &Path) -> anyhow::Result<()> {
+        let path = root.join(RECENT_PATH);
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, &self.usages)?;
+        Ok(())
+    }
+
+    /// Count a use of the paper at `path`, relative to the root, at `now`.
+    pub fn touch(&mut self, path: &Path, now: NaiveDateTime) {
+        let score = self.frecency(path, now) + 1.0;
+        self.usages
+            .insert(path.to_owned(), Usage { last: now, score });
+        if self.usages.len() > RECENT_LEN {
+            let mut lasts = self.usages.values().map(|u| u.last).collect::<Vec<_>>();
+            lasts.sort_by_key(|last| Reverse(*last));
+            let cutoff = lasts[RECENT_LEN - 1];
+            self.usages.retain(|_, u| u.last >= cutoff);
+        }
+    }
+
+    /// Record that the paper at `path` in the repo at `root` was just used.
+    ///
+    /// Failing to save the index only loses the ordering, so it isn't an error.
+    pub fn record(root: &Path, path: &Path) {
+        let mut recent = Self::load(root);
+        recent.touch(path, Utc::now().naive_utc());
+        if let Err(err) = recent.save(root) {
+            debug!(%err, "Failed to save index of recent papers");
+        }
+    }
+
+    /// How often and how recently the paper at `path` has been used as of `now`, zero if it
+    /// hasn't been.
+    pub fn frecency(&self, path: &Path, now: NaiveDateTime) -> f64 {
+        self.usages.get(path).map_or(0.0, |u| u.frecency(now))
+    }
+
+    /// Put the recently used papers first, most recent first, keeping the order of the rest.
+    ///
+    /// `path` gives the path of each item relative to the root.
+    pub fn order<T>(&self, items: &mut [T], path: impl Fn(&T) -> PathBuf) {
+        items.sort_by_cached_key(|item| Reverse(self.usages.get(&path(item)).map(|u| u.last)));
+    }
+
+    /// Put the papers with the highest frecency first, keeping the order of those never used.
+    ///
+    /// `path` gives the path of each item relative to the root.
+    pub fn order_by_frecency<T>(&self, items: &mut [T], path: impl Fn(&T) -> PathBuf) {
+        let now = Utc::now().naive_utc();
+        // scores are only compared, so a few decimal places are plenty
+        items.sort_by_cached_key(|item| Reverse((self.frecency(&path(item), now) * 1000.0) as u64));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn day(d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 8, d)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_order() {
+        let dir = tempfile::tempdir().unwrap();
+        Recent::record(dir.path(), Path::new("a.md"));
+        Recent::record(dir.path(), Path::new("c.md"));
+        Recent::record(dir.path(), Path::new("a.md"));
+
+        let mut paths = ["b.md", "c.md", "d.md", "a.md"].map(PathBuf::from);
+        Recent::load(dir.path()).order(&mut paths, PathBuf::clone);
+        assert_eq!(paths, ["a.md", "c.md", "b.md", "d.md"].map(PathBuf::from));
+    }
+
+    #[test]
+    fn test_frecency() {
+        let mut recent = Recent::default();
+        for d in 1..=4 {
+            recent.touch(Path::new("often.md"), day(d));
+        }
+        recent.touch(Path::new("once.md"), day(15));
+        recent.touch(Path::new("old.md"), day(1));
+
+        let frecency = |path: &str| recent.frecency(Path::new(path), day(15));
+        // used often but a while ago still beats used once just now
+        assert!(frecency("often.md") > frecency("once.md"));
+        assert!(frecency("once.md") > frecency("old.md"));
+        assert_eq!(frecency("unused.md"), 0.0);
+    }
+}
@@ -0,0 +1,171 @@
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use anyhow::Context;
+use papers_core::{paper::PaperMeta, primitive::Primitive};
+
+/// Mapping from label values to the values they should be rewritten to, e.g.
+///
+/// ```yaml
+/// "Proc. OSDI": OSDI
+/// osdi: OSDI
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LabelMap {
+    map: BTreeMap<String, Primitive>,
+}
+
+impl LabelMap {
+    /// Load a mapping from a yaml file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path).with_context(|| format!("Opening map file {path:?}"))?;
+        let mapping: serde_yaml::Mapping =
+            serde_yaml::from_reader(file).with_context(|| format!("Parsing map file {path:?}"))?;
+        Self::from_mapping(mapping)
+    }
+
+    fn from_mapping(mapping: serde_yaml::Mapping) -> anyhow::Result<Self> {
+        let mut map = BTreeMap::new();
+        for (from, to) in mapping {
+            // keys like 2019 are numbers in yaml but labels are matched on their text
+            let from = serde_yaml::from_value::<Primitive>(from)?.to_string();
+            let to = serde_yaml::from_value::<Primitive>(to)?;
+            map.insert(from, to);
+        }
+        Ok(Self { map })
+    }
+
+    /// Whether a value is already one that things are mapped to.
+    fn is_target(&self, value: &str) -> bool {
+        self.map.values().any(|v| v.to_string() == value)
+    }
+
+    /// Rewrite the value of the label `key` on a paper, returning the old and new values if it
+    /// changed.
+    ///
+    /// Values that aren't in the map and aren't already a mapped-to value are returned as the
+    /// error.
+    pub fn remap(
+        &self,
+        key: &str,
+        meta: &mut PaperMeta,
+    ) -> Result<Option<(String, String)>, String> {
+        let Some(value) = meta.labels.get_mut(key) else {
+            return Ok(None);
+        };
+        let current = value.to_string();
+        match self.map.get(&current) {
+            Some(new) if *new == *value => Ok(None),
+            Some(new) => {
+                *value = new.clone();
+                Ok(Some((current, new.to_string())))
+            }
+            None if self.is_target(&current) => Ok(None),
+            None => Err(current),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(map: &str, value: &str, expected: Expect) {
+        let map = LabelMap::from_mapping(serde_yaml::from_str(map).unwrap()).unwrap();
+        let mut meta = PaperMeta {
+            labels: BTreeMap::from([("venue".to_owned(), value.parse().unwrap())]),
+            ..Default::default()
+        };
+        let result = map.remap("venue", &mut meta);
+        expected.assert_debug_eq(&(result, &meta.labels["venue"]));
+    }
+
+    const MAP: &str = "
+osdi: OSDI
+Proc. OSDI: OSDI
+2019: '2019'
+";
+
+    #[test]
+    fn test_mapped() {
+        check(
+            MAP,
+            "Proc. OSDI",
+            expect![[r#"
+                (
+                    Ok(
+                        Some(
+                            (
+                                "Proc. OSDI",
+                                "OSDI",
+                            ),
+                        ),
+                    ),
+                    String(
+                        "OSDI",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_number_key() {
+        check(
+            MAP,
+            "2019",
+            expect![[r#"
+                (
+                    Ok(
+                        Some(
+                            (
+                                "2019",
+                                "2019",
+                            ),
+                        ),
+                    ),
+                    String(
+                        "2019",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_already_target() {
+        check(
+            MAP,
+            "OSDI",
+            expect![[r#"
+                (
+                    Ok(
+                        None,
+                    ),
+                    String(
+                        "OSDI",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_unmapped() {
+        check(
+            MAP,
+            "SOSP",
+            expect![[r#"
+                (
+                    Err(
+                        "SOSP",
+                    ),
+                    String(
+                        "SOSP",
+                    ),
+                )
+            "#]],
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use papers_core::{paper::PaperMeta, repo::PROHIBITED_PATH_CHARS};
+use papers_core::{filename::sanitize_stem, paper::PaperMeta};
 
 /// Strategy to rename files.
 #[derive(Debug, Clone, clap::ValueEnum)]
@@ -14,7 +14,7 @@ impl Strategy {
             Self::Title => Ok(paper.title.to_owned()),
         };
 
-        name.map(|n| n.replace(PROHIBITED_PATH_CHARS, ""))
+        name.map(|n| sanitize_stem(&n))
     }
 }
 
@@ -0,0 +1,218 @@
+use std::{fmt::Display, str::FromStr};
+
+use papers_core::{
+    author::Author,
+    label::Label,
+    metadata::{parse_crossref, Doi},
+    primitive::Primitive,
+};
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use crate::{citations::DOI_LABEL, http::HttpClient, snapshot::extract_page_meta};
+
+/// Metadata resolved for a document from an external source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedDoc {
+    /// Title of the document.
+    pub title: Option<String>,
+    /// Authors of the document.
+    pub authors: Vec<Author>,
+    /// Canonical url to fetch the document from.
+    pub url: Url,
+    /// Labels identifying the document.
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RfcResponse {
+    title: String,
+    #[serde(default)]
+    authors: Vec<String>,
+}
+
+/// Resolve an IETF RFC by its number.
+pub fn resolve_rfc(client: &HttpClient, number: u32) -> anyhow::Result<ResolvedDoc> {
+    let meta_url = format!("https://www.rfc-editor.org/rfc/rfc{number}.json");
+    info!(url = meta_url, "Fetching RFC metadata");
+    let res: RfcResponse = serde_json::from_str(&client.get_text(&Url::parse(&meta_url)?)?)?;
+    debug!(?res, "Got RFC metadata");
+    Ok(ResolvedDoc {
+        title: Some(res.title.trim().to_owned()),
+        authors: res
+            .authors
+            .iter()
+            .map(|a| a.trim_end_matches(", Ed.").trim())
+            .filter(|a| !a.is_empty())
+            .map(Author::new)
+            .collect(),
+        url: Url::parse(&format!("https://www.rfc-editor.org/rfc/rfc{number}.pdf"))?,
+        labels: vec![Label::new("rfc", Primitive::String(number.to_string()))],
+    })
+}
+
+/// Identifier of a technical report on a known report server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TechReport {
+    /// University of Cambridge Computer Laboratory, e.g. `UCAM-CL-TR-123`.
+    Cambridge(u32),
+    /// UC Berkeley EECS, e.g. `EECS-2009-28`.
+    Berkeley {
+        /// Year of the report.
+        year: u32,
+        /// Number of the report within the year.
+        number: u32,
+    },
+}
+
+impl FromStr for TechReport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_uppercase();
+        let parse = |n: &str| {
+            n.parse::<u32>()
+                .map_err(|e| format!("Invalid report number {:?}: {}", n, e))
+        };
+        if let Some(number) = upper.strip_prefix("UCAM-CL-TR-") {
+            Ok(Self::Cambridge(parse(number)?))
+        } else if let Some((year, number)) = upper
+            .strip_prefix("EECS-")
+            .and_then(|rest| rest.split_once('-'))
+        {
+            Ok(Self::Berkeley {
+                year: parse(year)?,
+                number: parse(number)?,
+            })
+        } else {
+            Err(format!(
+                "Unknown technical report {:?}, expected UCAM-CL-TR-<n> or EECS-<year>-<n>",
+                s
+            ))
+        }
+    }
+}
+
+impl Display for TechReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cambridge(number) => write!(f, "UCAM-CL-TR-{number}"),
+            Self::Berkeley { year, number } => write!(f, "EECS-{year}-{number}"),
+        }
+    }
+}
+
+impl TechReport {
+    /// Url of the landing page for the report.
+    pub fn page_url(&self) -> String {
+        match self {
+            Self::Cambridge(_) => format!("https://www.cl.cam.ac.uk/techreports/{self}.html"),
+            Self::Berkeley { year, .. } => {
+                format!("https://www2.eecs.berkeley.edu/Pubs/TechRpts/{year}/{self}.html")
+            }
+        }
+    }
+
+    /// Url of the document for the report.
+    pub fn pdf_url(&self) -> String {
+        match self {
+            Self::Cambridge(_) => format!("https://www.cl.cam.ac.uk/techreports/{self}.pdf"),
+            Self::Berkeley { year, .. } => {
+                format!("https://www2.eecs.berkeley.edu/Pubs/TechRpts/{year}/{self}.pdf")
+            }
+        }
+    }
+}
+
+/// Resolve a technical report from its server's landing page.
+pub fn resolve_tech_report(
+    client: &HttpClient,
+    report: &TechReport,
+) -> anyhow::Result<ResolvedDoc> {
+    let page_url = report.page_url();
+    info!(url = page_url, "Fetching technical report page");
+    let html = client.get_text(&Url::parse(&page_url)?)?;
+    let page = extract_page_meta(&html);
+    Ok(ResolvedDoc {
+        title: page.title,
+        authors: page.authors,
+        url: Url::parse(&report.pdf_url())?,
+        labels: vec![Label::new("report", Primitive::String(report.to_string()))],
+    })
+}
+
+/// Resolve a work by its DOI, using its metadata from Crossref.
+pub fn resolve_doi(client: &HttpClient, doi: &Doi) -> anyhow::Result<ResolvedDoc> {
+    let meta_url = doi.crossref_url();
+    info!(url = meta_url, "Fetching DOI metadata");
+    let work = parse_crossref(&client.get_text(&Url::parse(&meta_url)?)?)?;
+    debug!(?work, "Got DOI metadata");
+    let mut labels = vec![Label::new(DOI_LABEL, Primitive::String(doi.to_string()))];
+    if let Some(year) = work.year {
+        labels.push(Label::new("year", Primitive::Number(year.into())));
+    }
+    if let Some(journal) = work.journal {
+        labels.push(Label::new("journal", Primitive::String(journal)));
+    }
+    Ok(ResolvedDoc {
+        title: work.title,
+        authors: work.authors,
+        url: Url::parse(&doi.url())?,
+        labels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(report: &str, expected: Expect) {
+        let actual = TechReport::from_str(report).map(|r| (r.to_string(), r.pdf_url()));
+        expected.assert_debug_eq(&actual);
+    }
+
+    #[test]
+    fn test_cambridge() {
+        check(
+            "ucam-cl-tr-123",
+            expect![[r#"
+                Ok(
+                    (
+                        "UCAM-CL-TR-123",
+                        "https://www.cl.cam.ac.uk/techreports/UCAM-CL-TR-123.pdf",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_berkeley() {
+        check(
+            "EECS-2009-28",
+            expect![[r#"
+                Ok(
+                    (
+                        "EECS-2009-28",
+                        "https://www2.eecs.berkeley.edu/Pubs/TechRpts/2009/EECS-2009-28.pdf",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        check(
+            "MSR-TR-2020-1",
+            expect![[r#"
+                Err(
+                    "Unknown technical report \"MSR-TR-2020-1\", expected UCAM-CL-TR-<n> or EECS-<year>-<n>",
+                )
+            "#]],
+        );
+    }
+}
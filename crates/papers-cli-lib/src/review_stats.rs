@@ -0,0 +1,409 @@
+use std::{fmt::Display, path::PathBuf};
+
+use chrono::{Days, NaiveDate};
+use papers_core::{
+    journal::{history, Action, Entry},
+    paper::{LoadedPaper, PaperMeta},
+};
+use serde::Serialize;
+
+use crate::{deadlines::describe, output::Render};
+
+/// Papers reviewed at least this many times whose interval is still short are stuck.
+pub const STUCK_REVIEWS: usize = 3;
+
+/// Intervals between reviews shorter than this many days count as short.
+pub const STUCK_DAYS: i64 = 7;
+
+/// How reviewing a single paper has gone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaperReviews {
+    /// Path of the paper.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+    /// Number of times the paper has been reviewed, from the journal.
+    pub reviews: usize,
+    /// Days between the last review and the next one scheduled.
+    pub interval_days: Option<i64>,
+    /// Average factor each gap between reviews grew by over the one before.
+    pub growth: Option<f64>,
+    /// Whether the paper has been reviewed many times but still comes back soon.
+    pub stuck: bool,
+}
+
+/// Number of reviews coming up in a week.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WeekReviews {
+    /// First day of the week.
+    pub from: NaiveDate,
+    /// Papers coming up for review that week.
+    pub reviews: usize,
+}
+
+/// When reviews are due, counting the papers that aren't kept out of reviews.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ReviewSchedule {
+    /// Papers due by the end of today, including those never reviewed.
+    pub due: usize,
+    /// Papers that were due before today.
+    pub overdue: usize,
+    /// Reviews coming up in each of the weeks after today.
+    pub upcoming: Vec<WeekReviews>,
+    /// Average days between the last review of a paper and its next one.
+    pub average_interval_days: Option<f64>,
+}
+
+impl ReviewSchedule {
+    /// Schedule of the papers as of `today`, with the reviews of the next `weeks` weeks.
+    pub fn of<'a>(
+        papers: impl IntoIterator<Item = &'a PaperMeta>,
+        today: NaiveDate,
+        weeks: usize,
+    ) -> Self {
+        let mut schedule = Self {
+            upcoming: (0..weeks as u64)
+                .map(|week| WeekReviews {
+                    from: today + Days::new(1 + week * 7),
+                    reviews: 0,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut intervals = Vec::new();
+        for meta in papers.into_iter().filter(|m| !m.no_review) {
+            if let Some((last, next)) = meta.last_review.zip(meta.next_review) {
+                intervals.push((next - last).num_days() as f64);
+            }
+            let days_left = meta
+                .next_review
+                .map_or(0, |n| (n.date() - today).num_days());
+            if days_left <= 0 {
+                schedule.due += 1;
+                schedule.overdue += usize::from(days_left < 0);
+            } else if let Some(week) = schedule.upcoming.get_mut((days_left as usize - 1) / 7) {
+                week.reviews += 1;
+            }
+        }
+        schedule.average_interval_days =
+            (!intervals.is_empty()).then(|| intervals.iter().sum::<f64>() / intervals.len() as f64);
+        schedule
+    }
+}
+
+impl Display for ReviewSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let interval = self
+            .average_interval_days
+            .map_or("-".to_owned(), |d| format!("{d:.1} days"));
+        write!(
+            f,
+            "Due today {}, {} overdue, average interval {interval}",
+            self.due, self.overdue
+        )?;
+        for week in &self.upcoming {
+            write!(f, "\nWeek from {}: {} due", week.from, week.reviews)?;
+        }
+        Ok(())
+    }
+}
+
+/// Review statistics for the papers that have been reviewed, and when reviews are due.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ReviewStats {
+    /// Statistics of each paper that has been reviewed.
+    pub papers: Vec<PaperReviews>,
+    /// When reviews are due.
+    pub schedule: ReviewSchedule,
+}
+
+impl ReviewStats {
+    /// Statistics for the papers, using the journal `entries` for the times they were reviewed,
+    /// and their schedule over the next `weeks` weeks from `today`.
+    pub fn of(papers: &[LoadedPaper], entries: &[Entry], today: NaiveDate, weeks: usize) -> Self {
+        let mut stats = papers
+            .iter()
+            .filter_map(|paper| {
+                // all entries, so that reviews from before a rename are still found
+                let times = history(entries, &paper.path)
+                    .into_iter()
+                    .filter(|e| e.action == Action::Reviewed)
+                    .map(|e| e.at)
+                    .collect::<Vec<_>>();
+                let interval_days = paper
+                    .meta
+                    .last_review
+                    .zip(paper.meta.next_review)
+                    .map(|(last, next)| (next - last).num_days());
+                if times.is_empty() && interval_days.is_none() {
+                    return None;
+                }
+                let gaps = times
+                    .windows(2)
+                    .map(|w| (w[1] - w[0]).num_seconds() as f64)
+                    .collect::<Vec<_>>();
+                let ratios = gaps
+                    .windows(2)
+                    .filter(|g| g[0] > 0.0)
+                    .map(|g| g[1] / g[0])
+                    .collect::<Vec<_>>();
+                let growth =
+                    (!ratios.is_empty()).then(|| ratios.iter().sum::<f64>() / ratios.len() as f64);
+                Some(PaperReviews {
+                    path: paper.path.clone(),
+                    title: paper.meta.title.clone(),
+                    reviews: times.len(),
+                    interval_days,
+                    growth,
+                    stuck: times.len() >= STUCK_REVIEWS
+                        && interval_days.is_some_and(|d| d < STUCK_DAYS),
+                })
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.path.cmp(&b.path));
+        Self {
+            papers: stats,
+            schedule: ReviewSchedule::of(papers.iter().map(|p| &p.meta), today, weeks),
+        }
+    }
+}
+
+impl Display for ReviewStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for p in &self.papers {
+            let interval = p
+                .interval_days
+                .map_or("-".to_owned(), |d| format!("{d} days"));
+            let growth = p.growth.map_or("-".to_owned(), |g| format!("x{g:.1}"));
+            let stuck = if p.stuck { ", stuck" } else { "" };
+            writeln!(
+                f,
+                "{:?}: {} reviews, interval {interval}, growth {growth}{stuck}",
+                p.path, p.reviews
+            )?;
+        }
+        let growths = self
+            .papers
+            .iter()
+            .filter_map(|p| p.growth)
+            .collect::<Vec<_>>();
+        let average = if growths.is_empty() {
+            "-".to_owned()
+        } else {
+            format!("x{:.1}", growths.iter().sum::<f64>() / growths.len() as f64)
+        };
+        writeln!(
+            f,
+            "Reviewed {} papers, average interval growth {average}, {} stuck at short intervals",
+            self.papers.len(),
+            self.papers.iter().filter(|p| p.stuck).count()
+        )?;
+        write!(f, "{}", self.schedule)
+    }
+}
+
+impl Render for ReviewStats {
+    fn header(&self) -> Vec<String> {
+        [
+            "path",
+            "title",
+            "reviews",
+            "interval_days",
+            "growth",
+            "stuck",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.papers
+            .iter()
+            .map(|p| {
+                vec![
+                    p.path.to_string_lossy().into_owned(),
+                    p.title.clone(),
+                    p.reviews.to_string(),
+                    p.interval_days.map(|d| d.to_string()).unwrap_or_default(),
+                    p.growth.map(|g| format!("{g:.2}")).unwrap_or_default(),
+                    p.stuck.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A paper coming up for review.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DueReview {
+    /// Path of the notes.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+    /// Date of the next review, none if the paper has never been reviewed.
+    pub next_review: Option<NaiveDate>,
+    /// Days until the review, negative once it is overdue.
+    pub days_left: i64,
+}
+
+/// Papers coming up for review, most overdue first.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ReviewQueue(pub Vec<DueReview>);
+
+impl ReviewQueue {
+    /// Papers due for review within `within` days of `today`, along with any overdue or never
+    /// reviewed.
+    pub fn of(papers: Vec<LoadedPaper>, today: NaiveDate, within: i64) -> Self {
+        let mut queue = papers
+            .into_iter()
+            .filter(|p| !p.meta.no_review)
+            .map(|p| {
+                let next_review = p.meta.next_review.map(|n| n.date());
+                DueReview {
+                    days_left: next_review.map_or(0, |n| (n - today).num_days()),
+                    next_review,
+                    title: p.meta.title,
+                    path: p.path,
+                }
+            })
+            .filter(|d| d.days_left <= within)
+            .collect::<Vec<_>>();
+        queue.sort_by(|a, b| {
+            a.next_review
+                .cmp(&b.next_review)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        Self(queue)
+    }
+}
+
+impl Display for ReviewQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .iter()
+            .map(|d| match d.next_review {
+                Some(next) => format!("{next} ({}) {}", describe(d.days_left), d.title),
+                None => format!("never reviewed {}", d.title),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for ReviewQueue {
+    fn header(&self) -> Vec<String> {
+        ["path", "title", "next_review", "days_left"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|d| {
+                vec![
+                    d.path.to_string_lossy().into_owned(),
+                    d.title.clone(),
+                    d.next_review.map(|n| n.to_string()).unwrap_or_default(),
+                    d.days_left.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Days, NaiveDate};
+    use expect_test::expect;
+    use papers_core::paper::PaperMeta;
+
+    use super::*;
+
+    fn paper(path: &str, interval: Option<u64>) -> LoadedPaper {
+        let last = NaiveDate::from_ymd_opt(2023, 8, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        LoadedPaper {
+            path: path.into(),
+            meta: PaperMeta {
+                title: path.trim_end_matches(".md").to_owned(),
+                last_review: interval.map(|_| last),
+                next_review: interval.map(|d| last + Days::new(d)),
+                ..Default::default()
+            },
+            notes: String::new(),
+        }
+    }
+
+    fn reviewed(path: &str, day: u32) -> Entry {
+        let mut entry = Entry::new(Action::Reviewed, path.into(), None);
+        entry.at = NaiveDate::from_ymd_opt(2023, 7, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        entry
+    }
+
+    #[test]
+    fn test_review_stats() {
+        let papers = [
+            paper("growing.md", Some(16)),
+            paper("stuck.md", Some(2)),
+            paper("unreviewed.md", None),
+        ];
+        // reviews from before a rename still count
+        let entries = [
+            reviewed("growing-draft.md", 1),
+            reviewed("growing-draft.md", 2),
+            Entry::new(
+                Action::Renamed,
+                "growing-draft.md".into(),
+                Some("growing.md".into()),
+            ),
+            reviewed("growing.md", 4),
+            reviewed("growing.md", 8),
+            reviewed("stuck.md", 1),
+            reviewed("stuck.md", 3),
+            reviewed("stuck.md", 5),
+            Entry::new(Action::Updated, "unreviewed.md".into(), None),
+        ];
+        let today = NaiveDate::from_ymd_opt(2023, 8, 5).unwrap();
+        let stats = ReviewStats::of(&papers, &entries, today, 3);
+        expect![[r#"
+            "growing.md": 4 reviews, interval 16 days, growth x2.0
+            "stuck.md": 3 reviews, interval 2 days, growth x1.0, stuck
+            Reviewed 2 papers, average interval growth x1.5, 1 stuck at short intervals
+            Due today 2, 1 overdue, average interval 9.0 days
+            Week from 2023-08-06: 0 due
+            Week from 2023-08-13: 1 due
+            Week from 2023-08-20: 0 due"#]]
+        .assert_eq(&stats.to_string());
+    }
+
+    #[test]
+    fn test_review_queue() {
+        let mut manual = paper("manual.md", None);
+        manual.meta.no_review = true;
+        let papers = vec![
+            paper("later.md", Some(16)),
+            paper("soon.md", Some(6)),
+            paper("overdue.md", Some(2)),
+            paper("new.md", None),
+            manual,
+        ];
+        let today = NaiveDate::from_ymd_opt(2023, 8, 5).unwrap();
+        expect![[r#"
+            never reviewed new
+            2023-08-03 (overdue by 2d) overdue"#]]
+        .assert_eq(&ReviewQueue::of(papers.clone(), today, 0).to_string());
+        expect![[r#"
+            never reviewed new
+            2023-08-03 (overdue by 2d) overdue
+            2023-08-07 (due in 2d) soon"#]]
+        .assert_eq(&ReviewQueue::of(papers, today, 7).to_string());
+    }
+}
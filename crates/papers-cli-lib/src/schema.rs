@@ -0,0 +1,54 @@
+use clap::ValueEnum;
+use papers_core::paper::PaperMeta;
+use schemars::{schema::RootSchema, schema_for};
+
+/// What to describe with a JSON Schema.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaKind {
+    /// The frontmatter of a notes file.
+    #[default]
+    Frontmatter,
+    /// The json read by `papers import`, a list of papers.
+    Import,
+}
+
+impl SchemaKind {
+    /// The JSON Schema for this kind of document.
+    pub fn schema(&self) -> RootSchema {
+        let mut schema = match self {
+            Self::Frontmatter => schema_for!(PaperMeta),
+            Self::Import => schema_for!(Vec<PaperMeta>),
+        };
+        let metadata = schema.schema.metadata();
+        metadata.title = Some(
+            match self {
+                Self::Frontmatter => "papers frontmatter",
+                Self::Import => "papers import",
+            }
+            .to_owned(),
+        );
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontmatter_schema_has_all_fields() {
+        let schema = serde_json::to_value(SchemaKind::Frontmatter.schema()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        let meta = PaperMeta {
+            read_by: Some(Default::default()),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_value(meta).unwrap();
+        for key in serialized.as_object().unwrap().keys() {
+            assert!(
+                properties.contains_key(key),
+                "{key} missing from the schema"
+            );
+        }
+    }
+}
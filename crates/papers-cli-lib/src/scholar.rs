@@ -0,0 +1,535 @@
+use std::collections::btree_map::Entry;
+
+use chrono::NaiveDateTime;
+use papers_core::{author::Author, paper::PaperMeta, primitive::Primitive};
+
+use crate::{citations::DOI_LABEL, dblp::VENUE_LABEL, doctor::similarity};
+
+/// Titles at least this alike, without being the same, are reported as maybe the same paper.
+pub const SIMILAR_TITLE_SIMILARITY: f64 = 0.8;
+
+/// An entry of a BibTeX export from Google Scholar.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScholarEntry {
+    /// Citation key, which Scholar doesn't keep unique.
+    pub key: String,
+    /// Title of the work.
+    pub title: String,
+    /// Authors of the work, in order.
+    pub authors: Vec<String>,
+    /// Whether Scholar cut the author list short with `and others`.
+    pub truncated: bool,
+    /// Year the work was published.
+    pub year: Option<u32>,
+    /// Journal or conference the work appeared in.
+    pub venue: Option<String>,
+    /// Publisher of the work.
+    pub publisher: Option<String>,
+    /// DOI of the work.
+    pub doi: Option<String>,
+    /// Link to the work.
+    pub url: Option<String>,
+}
+
+/// Letter with an accent written as a LaTeX command, e.g. `\"u`, or the bare letter if unknown.
+fn accent(mark: char, letter: char) -> char {
+    let accented = match mark {
+        '"' => "aäeëiïoöuüyÿAÄEËIÏOÖUÜ",
+        '\'' => "aáeéiíoóuúyýcćnńsśzźAÁEÉIÍOÓUÚYÝCĆNŃSŚZŹ",
+        '`' => "aàeèiìoòuùAÀEÈIÌOÒUÙ",
+        '^' => "aâeêiîoôuûAÂEÊIÎOÔUÛ",
+        '~' => "aãnñoõAÃNÑOÕ",
+        'c' => "cçCÇsşSŞ",
+        'v' => "cčsšzžrřeěnňCČSŠZŽRŘEĚNŇ",
+        _ => "",
+    };
+    let chars = accented.chars().collect::<Vec<_>>();
+    chars
+        .chunks(2)
+        .find(|pair| pair[0] == letter)
+        .map_or(letter, |pair| pair[1])
+}
+
+/// Plain text of a BibTeX value, resolving accents and escapes and dropping braces and other
+/// commands.
+fn latex_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(mark @ ('"' | '\'' | '`' | '^' | '~')) => {
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    if let Some(letter) = chars.next() {
+                        text.push(accent(mark, letter));
+                    }
+                    if braced {
+                        chars.next_if_eq(&'}');
+                    }
+                }
+                Some(mark @ ('c' | 'v'))
+                    if chars.peek().is_some_and(|c| *c == '{' || *c == ' ') =>
+                {
+                    chars.next();
+                    if let Some(letter) = chars.next() {
+                        text.push(accent(mark, letter));
+                    }
+                    chars.next_if_eq(&'}');
+                }
+                Some(c) if c.is_ascii_alphabetic() => {
+                    // a command like \emph, whose argument is kept
+                    while chars.next_if(|c| c.is_ascii_alphabetic()).is_some() {}
+                }
+                Some(c) => text.push(c),
+                None => {}
+            },
+            '{' | '}' => {}
+            '~' => text.push(' '),
+            c => text.push(c),
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Split `s` on `sep` where it isn't inside braces, ignoring case.
+fn split_top_level<'a>(s: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let (bytes, sep) = (s.as_bytes(), sep.as_bytes());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            _ if depth == 0
+                && bytes[i..].len() >= sep.len()
+                && bytes[i..i + sep.len()].eq_ignore_ascii_case(sep) =>
+            {
+                parts.push(&s[start..i]);
+                i += sep.len();
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Authors of a BibTeX author field, and whether the list was cut short with `and others`.
+fn parse_authors(field: &str) -> (Vec<String>, bool) {
+    let mut truncated = false;
+    let authors = split_top_level(
+        &field.split_whitespace().collect::<Vec<_>>().join(" "),
+        " and ",
+    )
+    .into_iter()
+    .map(|name| match split_top_level(name, ",").as_slice() {
+        [last, first] => latex_text(&format!("{first} {last}")),
+        _ => latex_text(name),
+    })
+    .filter(|name| {
+        let others = name.eq_ignore_ascii_case("others");
+        truncated |= others;
+        !others && !name.is_empty()
+    })
+    .collect();
+    (authors, truncated)
+}
+
+/// Reads the entries of a BibTeX file.
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Characters up to, but not including, one matching `end`.
+    fn until(&mut self, end: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !end(c)) {
+            self.bump();
+        }
+        &self.s[start..self.pos]
+    }
+
+    /// Contents of a braced value, with the opening brace already read.
+    fn braced(&mut self) -> &str {
+        let start = self.pos;
+        let mut depth = 1;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return &self.s[start..self.pos - 1];
+                    }
+                }
+                _ => {}
+            }
+        }
+        &self.s[start..]
+    }
+
+    /// Contents of a quoted value, with the opening quote already read.
+    fn quoted(&mut self) -> &str {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '"' if depth == 0 => return &self.s[start..self.pos - 1],
+                _ => {}
+            }
+        }
+        &self.s[start..]
+    }
+
+    /// Fields of an entry, with its opening brace already read, up to its closing brace.
+    fn fields(&mut self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let name = self
+                .until(|c| c == '=' || c == ',' || c == '}')
+                .trim()
+                .to_lowercase();
+            match self.bump() {
+                Some('=') => {}
+                Some(',') => continue,
+                _ => return fields,
+            }
+            self.skip_whitespace();
+            let value = match self.peek() {
+                Some('{') => {
+                    self.bump();
+                    self.braced().to_owned()
+                }
+                Some('"') => {
+                    self.bump();
+                    self.quoted().to_owned()
+                }
+                _ => self.until(|c| c == ',' || c == '}').trim().to_owned(),
+            };
+            fields.push((name, value));
+        }
+    }
+
+    fn entries(&mut self) -> Vec<ScholarEntry> {
+        let mut entries = Vec::new();
+        loop {
+            self.until(|c| c == '@');
+            if self.bump().is_none() {
+                return entries;
+            }
+            let kind = self.until(|c| c == '{' || c.is_whitespace()).to_lowercase();
+            self.skip_whitespace();
+            if self.bump() != Some('{') {
+                continue;
+            }
+            if matches!(kind.as_str(), "comment" | "string" | "preamble") {
+                self.braced();
+                continue;
+            }
+            let key = self.until(|c| c == ',' || c == '}').trim().to_owned();
+            self.bump();
+            let mut entry = ScholarEntry {
+                key,
+                ..Default::default()
+            };
+            for (name, value) in self.fields() {
+                let text = || Some(latex_text(&value)).filter(|v| !v.is_empty());
+                match name.as_str() {
+                    "title" => entry.title = latex_text(&value),
+                    "author" => (entry.authors, entry.truncated) = parse_authors(&value),
+                    "year" => entry.year = latex_text(&value).parse().ok(),
+                    "journal" | "booktitle" => entry.venue = entry.venue.or_else(text),
+                    "publisher" => entry.publisher = text(),
+                    "doi" => entry.doi = text(),
+                    "url" => entry.url = Some(value.trim().to_owned()).filter(|v| !v.is_empty()),
+                    _ => {}
+                }
+            }
+            entries.push(entry);
+        }
+    }
+}
+
+impl ScholarEntry {
+    /// Fill in what this entry is missing from another entry for the same work.
+    fn merge(&mut self, other: ScholarEntry) {
+        if self.authors.is_empty() || (self.truncated && other.authors.len() > self.authors.len()) {
+            self.authors = other.authors;
+            self.truncated = other.truncated;
+        }
+        self.year = self.year.or(other.year);
+        self.venue = self.venue.take().or(other.venue);
+        self.publisher = self.publisher.take().or(other.publisher);
+        self.doi = self.doi.take().or(other.doi);
+        self.url = self.url.take().or(other.url);
+    }
+
+    /// Paper for the entry, added at `now`.
+    pub fn meta(&self, now: NaiveDateTime) -> PaperMeta {
+        let mut meta = PaperMeta {
+            title: self.title.clone(),
+            url: self.url.clone(),
+            authors: self.authors.iter().map(|a| Author::new(a)).collect(),
+            created_at: now,
+            modified_at: now,
+            ..Default::default()
+        };
+        if let Some(year) = self.year {
+            meta.labels
+                .insert("year".to_owned(), Primitive::Number(year.into()));
+        }
+        for (label, value) in [
+            (VENUE_LABEL, &self.venue),
+            ("publisher", &self.publisher),
+            (DOI_LABEL, &self.doi),
+        ] {
+            if let Some(value) = value {
+                meta.labels
+                    .insert(label.to_owned(), Primitive::String(value.clone()));
+            }
+        }
+        meta
+    }
+
+    /// Whether this entry is for the same work as an existing paper.
+    pub fn is_paper(&self, meta: &PaperMeta) -> bool {
+        let doi = meta.labels.get(DOI_LABEL).map(|d| d.to_string());
+        same_work(
+            &self.title,
+            self.doi.as_deref(),
+            &meta.title,
+            doi.as_deref(),
+        )
+    }
+
+    /// Whether this entry's title is alike an existing paper's, so may be for the same work
+    /// without it being certain enough to merge them.
+    pub fn may_be_paper(&self, meta: &PaperMeta) -> bool {
+        similarity(&self.title, &meta.title) >= SIMILAR_TITLE_SIMILARITY
+    }
+
+    /// Fill in what an existing paper for the same work is missing, returning whether it
+    /// changed. A truncated author list only replaces a shorter one.
+    pub fn merge_into(&self, existing: &mut PaperMeta, now: NaiveDateTime) -> bool {
+        let imported = self.meta(now);
+        let mut changed = false;
+        if existing.authors.is_empty()
+            || (!self.truncated && imported.authors.len() > existing.authors.len())
+        {
+            changed |= existing.authors != imported.authors;
+            existing.authors = imported.authors;
+        }
+        if existing.url.is_none() && imported.url.is_some() {
+            existing.url = imported.url;
+            changed = true;
+        }
+        for (key, value) in imported.labels {
+            if let Entry::Vacant(entry) = existing.labels.entry(key) {
+                entry.insert(value);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Title with case, punctuation and spacing ignored.
+fn normalise_title(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether two works are the same, by their DOIs if both have one, otherwise by their titles
+/// with case and punctuation ignored.
+fn same_work(title: &str, doi: Option<&str>, other_title: &str, other_doi: Option<&str>) -> bool {
+    match (doi, other_doi) {
+        (Some(doi), Some(other)) => doi.eq_ignore_ascii_case(other),
+        _ => normalise_title(title) == normalise_title(other_title),
+    }
+}
+
+/// Entries of a Google Scholar BibTeX export, merging the entries for the same work, which
+/// Scholar gives when a work is listed more than once, and dropping those without a title.
+pub fn parse_bibtex(bibtex: &str) -> Vec<ScholarEntry> {
+    let mut entries: Vec<ScholarEntry> = Vec::new();
+    for entry in (Parser { s: bibtex, pos: 0 }).entries() {
+        if entry.title.is_empty() {
+            continue;
+        }
+        match entries.iter_mut().find(|e| {
+            same_work(
+                &e.title,
+                e.doi.as_deref(),
+                &entry.title,
+                entry.doi.as_deref(),
+            )
+        }) {
+            Some(existing) => existing.merge(entry),
+            None => entries.push(entry),
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_latex_text() {
+        assert_eq!(
+            latex_text(r#"{G}{\"o}del, {\'E}cole \& {\c{c}}a \emph{Paxos}~made"#),
+            "Gödel, École & ça Paxos made"
+        );
+    }
+
+    #[test]
+    fn test_parse_bibtex() {
+        let bibtex = r#"
+@inproceedings{ongaro2014search,
+  title={In search of an understandable consensus algorithm},
+  author={Ongaro, Diego and Ousterhout, John},
+  booktitle={2014 USENIX annual technical conference (USENIX ATC 14)},
+  pages={305--319},
+  year={2014}
+}
+
+@article{lamport2001paxos,
+  title={Paxos made simple},
+  author={Lamport, Leslie and others},
+  journal={ACM SIGACT News},
+  volume={32},
+  number={4},
+  year={2001}
+}
+
+@article{lamport2001paxos,
+  title = "{P}axos Made Simple",
+  author = {Lamport, Leslie},
+  publisher = {ACM},
+  year = 2001,
+}
+
+@misc{untitled,
+  author={Nobody}
+}
+"#;
+        expect![[r#"
+            [
+                ScholarEntry {
+                    key: "ongaro2014search",
+                    title: "In search of an understandable consensus algorithm",
+                    authors: [
+                        "Diego Ongaro",
+                        "John Ousterhout",
+                    ],
+                    truncated: false,
+                    year: Some(
+                        2014,
+                    ),
+                    venue: Some(
+                        "2014 USENIX annual technical conference (USENIX ATC 14)",
+                    ),
+                    publisher: None,
+                    doi: None,
+                    url: None,
+                },
+                ScholarEntry {
+                    key: "lamport2001paxos",
+                    title: "Paxos made simple",
+                    authors: [
+                        "Leslie Lamport",
+                    ],
+                    truncated: true,
+                    year: Some(
+                        2001,
+                    ),
+                    venue: Some(
+                        "ACM SIGACT News",
+                    ),
+                    publisher: Some(
+                        "ACM",
+                    ),
+                    doi: None,
+                    url: None,
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&parse_bibtex(bibtex));
+    }
+
+    #[test]
+    fn test_is_paper() {
+        let entry = &parse_bibtex(
+            "@inproceedings{a, title={In search of an understandable consensus algorithm}, \
+             doi={10.5555/2643634.2643666}}",
+        )[0];
+        let mut meta = PaperMeta {
+            title: "in-search-of-an-understandable-consensus-algorithm".to_owned(),
+            ..Default::default()
+        };
+        assert!(entry.is_paper(&meta));
+        meta.title = "Raft: In search of an understandable consensus algorithm".to_owned();
+        assert!(!entry.is_paper(&meta));
+        assert!(entry.may_be_paper(&meta));
+        // the same DOI is the same work whatever the title
+        meta.labels.insert(
+            DOI_LABEL.to_owned(),
+            Primitive::String("10.5555/2643634.2643666".to_owned()),
+        );
+        assert!(entry.is_paper(&meta));
+    }
+
+    #[test]
+    fn test_merge_into() {
+        let now = NaiveDateTime::default();
+        let entry = &parse_bibtex(
+            "@article{a, title={Paxos made simple}, author={Lamport, Leslie and others}, year={2001}}",
+        )[0];
+        let mut existing = PaperMeta {
+            title: "Paxos Made Simple".to_owned(),
+            authors: vec![Author::new("Leslie Lamport"), Author::new("Someone Else")],
+            ..Default::default()
+        };
+        assert!(entry.merge_into(&mut existing, now));
+        // the truncated list doesn't replace the full one
+        assert_eq!(existing.authors.len(), 2);
+        assert_eq!(
+            existing.labels.get("year").map(|y| y.to_string()),
+            Some("2001".to_owned())
+        );
+        assert!(!entry.merge_into(&mut existing, now));
+    }
+}
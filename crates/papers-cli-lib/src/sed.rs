@@ -0,0 +1,304 @@
+use std::{path::PathBuf, str::FromStr};
+
+use clap::ValueEnum;
+use papers_core::{author::Author, paper::PaperMeta, tag::Tag};
+use regex::{Regex, RegexBuilder};
+
+/// A sed style substitution, e.g. `s/Tehcnique/Technique/g`.
+///
+/// Any character can be used as the delimiter. The flags `g` (replace every match rather than just
+/// the first) and `i` (ignore case) are supported. In the replacement `&` is the whole match and
+/// `\1` to `\9` are capture groups.
+#[derive(Debug, Clone)]
+pub struct Substitution {
+    regex: Regex,
+    replacement: String,
+    global: bool,
+}
+
+impl FromStr for Substitution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if chars.next() != Some('s') {
+            return Err(format!(
+                "Expression {s:?} should start with 's', e.g. s/old/new/"
+            ));
+        }
+        let delimiter = chars
+            .next()
+            .ok_or_else(|| format!("Expression {s:?} is missing a delimiter"))?;
+
+        // split on unescaped delimiters
+        let mut parts = vec![String::new()];
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(c) if c == delimiter => parts.last_mut().unwrap().push(c),
+                    Some(c) => {
+                        let part = parts.last_mut().unwrap();
+                        part.push('\\');
+                        part.push(c);
+                    }
+                    None => parts.last_mut().unwrap().push('\\'),
+                },
+                c if c == delimiter => parts.push(String::new()),
+                c => parts.last_mut().unwrap().push(c),
+            }
+        }
+        let [pattern, replacement, flags] = <[String; 3]>::try_from(parts).map_err(|_| {
+            let d = delimiter;
+            format!("Expression {s:?} should have the form s{d}pattern{d}replacement{d}flags")
+        })?;
+
+        let mut global = false;
+        let mut builder = RegexBuilder::new(&pattern);
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'i' => {
+                    builder.case_insensitive(true);
+                }
+                other => return Err(format!("Unknown flag {other:?}, expected g or i")),
+            }
+        }
+        let regex = builder
+            .build()
+            .map_err(|err| format!("Invalid pattern {pattern:?}: {err}"))?;
+
+        Ok(Self {
+            regex,
+            replacement: sed_replacement(&replacement),
+            global,
+        })
+    }
+}
+
+/// Convert a sed replacement into the syntax used by the regex crate.
+fn sed_replacement(replacement: &str) -> String {
+    let mut out = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => out.push_str("${0}"),
+            '$' => out.push_str("$$"),
+            '\\' => match chars.next() {
+                Some(d @ '0'..='9') => {
+                    out.push_str("${");
+                    out.push(d);
+                    out.push('}');
+                }
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('$') => out.push_str("$$"),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl Substitution {
+    /// Apply the substitution to a value.
+    pub fn apply(&self, value: &str) -> String {
+        let limit = if self.global { 0 } else { 1 };
+        self.regex
+            .replacen(value, limit, self.replacement.as_str())
+            .into_owned()
+    }
+}
+
+/// Metadata field that a substitution can be applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SedField {
+    /// The title.
+    Title,
+    /// The url.
+    Url,
+    /// The document filename, the file itself is not moved.
+    Filename,
+    /// Each of the authors.
+    Authors,
+    /// Each of the tags.
+    Tags,
+}
+
+impl SedField {
+    /// Render the field as a single string, for showing changes.
+    fn render(&self, meta: &PaperMeta) -> String {
+        match self {
+            Self::Title => meta.title.clone(),
+            Self::Url => meta.url.clone().unwrap_or_default(),
+            Self::Filename => meta
+                .filename
+                .as_ref()
+                .map(|f| f.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Self::Authors => meta
+                .authors
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::Tags => meta
+                .tags
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// Apply a substitution to this field of the paper, returning the change if there was one.
+    ///
+    /// Optional fields that become empty are removed, as are authors and tags.
+    pub fn apply(&self, sub: &Substitution, meta: &mut PaperMeta) -> Option<(String, String)> {
+        let before = self.render(meta);
+        let optional = |value: String| (!value.is_empty()).then_some(value);
+        match self {
+            Self::Title => meta.title = sub.apply(&meta.title),
+            Self::Url => meta.url = meta.url.as_deref().and_then(|u| optional(sub.apply(u))),
+            Self::Filename => {
+                meta.filename = meta
+                    .filename
+                    .as_ref()
+                    .and_then(|f| optional(sub.apply(&f.to_string_lossy())))
+                    .map(PathBuf::from)
+            }
+            Self::Authors => {
+                meta.authors = meta
+                    .authors
+                    .iter()
+                    .filter_map(|a| optional(sub.apply(&a.to_string())))
+                    .map(|a| Author::new(&a))
+                    .collect()
+            }
+            Self::Tags => {
+                meta.tags = meta
+                    .tags
+                    .iter()
+                    .filter_map(|t| optional(sub.apply(t.key())))
+                    .map(|t| Tag::new(&t))
+                    .collect()
+            }
+        }
+        let after = self.render(meta);
+        (before != after).then_some((before, after))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(expr: &str, value: &str, expected: Expect) {
+        let result = expr.parse::<Substitution>().map(|s| s.apply(value));
+        expected.assert_debug_eq(&result);
+    }
+
+    #[test]
+    fn test_first_only() {
+        check(
+            "s/Tehcnique/Technique/",
+            "Tehcnique and Tehcnique",
+            expect![[r#"
+                Ok(
+                    "Technique and Tehcnique",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_flags() {
+        check(
+            "s/tehcnique/Technique/gi",
+            "Tehcnique and tehcnique",
+            expect![[r#"
+                Ok(
+                    "Technique and Technique",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_groups_and_delimiter() {
+        check(
+            r"s|(\w+)/(\w+)|\2 of \1 & $5|",
+            "a/b",
+            expect![[r#"
+                Ok(
+                    "b of a a/b $5",
+                )
+            "#]],
+        );
+        check(
+            r"s/a\/b/c/",
+            "a/b",
+            expect![[r#"
+                Ok(
+                    "c",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        check(
+            "s/a/b",
+            "",
+            expect![[r#"
+                Err(
+                    "Expression \"s/a/b\" should have the form s/pattern/replacement/flags",
+                )
+            "#]],
+        );
+        check(
+            "s/(/b/",
+            "",
+            expect![[r#"
+                Err(
+                    "Invalid pattern \"(\": regex parse error:\n    (\n    ^\nerror: unclosed group",
+                )
+            "#]],
+        );
+        check(
+            "s/a/b/x",
+            "",
+            expect![[r#"
+                Err(
+                    "Unknown flag 'x', expected g or i",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_tags_field() {
+        let mut meta = PaperMeta {
+            tags: BTreeSet::from([Tag::new("to-read"), Tag::new("db")]),
+            ..Default::default()
+        };
+        let sub = "s/^to-read$//".parse().unwrap();
+        let change = SedField::Tags.apply(&sub, &mut meta);
+        expect![[r#"
+            Some(
+                (
+                    "db, to-read",
+                    "db",
+                ),
+            )
+        "#]]
+        .assert_debug_eq(&change);
+        assert_eq!(SedField::Tags.apply(&sub, &mut meta), None);
+    }
+}
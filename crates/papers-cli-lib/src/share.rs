@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use papers_core::paper::PaperMeta;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::http::HttpClient;
+
+/// Where gists are created.
+const GISTS_URL: &str = "https://api.github.com/gists";
+
+/// Environment variable holding the token for creating gists, unless another is configured.
+const DEFAULT_GIST_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+/// Service to upload shared notes to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareService {
+    /// A GitHub gist, needing a token.
+    #[default]
+    Gist,
+    /// A paste service that takes the notes as the body of a POST and replies with their url,
+    /// such as `https://paste.rs/`.
+    Paste,
+}
+
+/// Settings for `papers share`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareConfig {
+    /// Service to upload notes to.
+    #[serde(default)]
+    pub service: ShareService,
+
+    /// Url to post notes to for the paste service.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Environment variable holding the token sent with uploads, `GITHUB_TOKEN` for gists if not
+    /// set.
+    #[serde(default)]
+    pub token_env: Option<String>,
+
+    /// Whether gists are listed publicly, rather than only visible to those with the link.
+    #[serde(default)]
+    pub public: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct GistFile<'a> {
+    content: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct NewGist<'a> {
+    description: &'a str,
+    public: bool,
+    files: BTreeMap<String, GistFile<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedGist {
+    html_url: String,
+}
+
+/// The notes to share, below a short header of the metadata if `with_meta`.
+pub fn render_shared(meta: &PaperMeta, notes: &str, with_meta: bool) -> String {
+    let notes = notes.trim_matches('\n');
+    if !with_meta {
+        return format!("{notes}\n");
+    }
+    let mut header = vec![format!("# {}", meta.title), String::new()];
+    if !meta.authors.is_empty() {
+        let authors = meta
+            .authors
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>();
+        header.push(format!("- Authors: {}", authors.join(", ")));
+    }
+    if let Some(url) = &meta.url {
+        header.push(format!("- Link: <{url}>"));
+    }
+    if !meta.tags.is_empty() {
+        let tags = meta.tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        header.push(format!("- Tags: {}", tags.join(", ")));
+    }
+    format!("{}\n\n{notes}\n", header.join("\n").trim_end())
+}
+
+impl ShareConfig {
+    fn token(&self) -> anyhow::Result<Option<String>> {
+        let var = match (&self.token_env, self.service) {
+            (Some(var), _) => var.as_str(),
+            (None, ShareService::Gist) => DEFAULT_GIST_TOKEN_ENV,
+            (None, ShareService::Paste) => return Ok(None),
+        };
+        match std::env::var(var) {
+            Ok(token) => Ok(Some(token)),
+            Err(_) if self.service == ShareService::Paste => Ok(None),
+            Err(_) => anyhow::bail!("No token for creating gists, set {var}"),
+        }
+    }
+
+    /// Upload `content` as `name` to the configured service, returning the url it can be seen at.
+    pub fn upload(&self, http: &HttpClient, name: &str, content: &str) -> anyhow::Result<String> {
+        let token = self.token()?;
+        let authorise = |request: reqwest::blocking::RequestBuilder| match &token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        match self.service {
+            ShareService::Gist => {
+                let gist = NewGist {
+                    description: name,
+                    public: self.public,
+                    files: BTreeMap::from([(format!("{name}.md"), GistFile { content })]),
+                };
+                let body = serde_json::to_string(&gist)?;
+                let reply = http
+                    .post(&Url::parse(GISTS_URL)?, |r| {
+                        authorise(r)
+                            .header("Accept", "application/vnd.github+json")
+                            .header("Content-Type", "application/json")
+                            .body(body)
+                    })?
+                    .text()?;
+                let created: CreatedGist =
+                    serde_json::from_str(&reply).context("Reading the created gist")?;
+                Ok(created.html_url)
+            }
+            ShareService::Paste => {
+                let url = self
+                    .url
+                    .as_deref()
+                    .context("No url for the paste service, set share.url in the config")?;
+                let body = content.to_owned();
+                let reply = http
+                    .post(&Url::parse(url)?, |r| authorise(r).body(body))?
+                    .text()?;
+                Ok(reply.trim().to_owned())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use papers_core::{author::Author, tag::Tag};
+
+    use super::*;
+
+    #[test]
+    fn test_render_shared() {
+        let meta = PaperMeta {
+            title: "raft".to_owned(),
+            url: Some("https://raft.github.io/raft.pdf".to_owned()),
+            tags: [Tag::new("consensus")].into(),
+            authors: vec![Author::new("Diego Ongaro"), Author::new("John Ousterhout")],
+            ..Default::default()
+        };
+        let notes = "\n## Summary\nLeader based.\n";
+        expect![[r#"
+            ## Summary
+            Leader based.
+        "#]]
+        .assert_eq(&render_shared(&meta, notes, false));
+        expect![[r#"
+            # raft
+
+            - Authors: Diego Ongaro, John Ousterhout
+            - Link: <https://raft.github.io/raft.pdf>
+            - Tags: consensus
+
+            ## Summary
+            Leader based.
+        "#]]
+        .assert_eq(&render_shared(&meta, notes, true));
+        let bare = PaperMeta {
+            title: "raft".to_owned(),
+            ..Default::default()
+        };
+        expect![[r#"
+            # raft
+
+            ## Summary
+            Leader based.
+        "#]]
+        .assert_eq(&render_shared(&bare, notes, true));
+    }
+}
@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use papers_core::paper::PaperMeta;
+
+use crate::duration::{parse_length, unit_names};
+
+/// A point in time to filter things by, supporting nice parsing from cli.
+///
+/// - 2023-08-01
+/// - 2023-08-01T12:30:00
+/// - 12h, 3d, 2w or 1mo ago
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Since(pub NaiveDateTime);
+
+/// Parse a date like `2023-08-01`, optionally with a time like `2023-08-01T12:30:00`.
+pub fn parse_date(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+        })
+}
+
+impl FromStr for Since {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(at) = parse_date(s) {
+            return Ok(Self(at));
+        }
+        parse_length(s)
+            .and_then(|ago| Utc::now().naive_utc().checked_sub_signed(ago))
+            .map(Self)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid time {s:?}, expected a date like 2023-08-01 or an age like 3d in {}",
+                    unit_names()
+                )
+            })
+    }
+}
+
+/// Filters on when papers were added, changed and last reviewed, shared by commands that pick
+/// out papers.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct TimeFilters {
+    /// Filter down to papers added since this time, a date like 2023-08-01 or an age like 2w.
+    #[clap(long)]
+    pub added_since: Option<Since>,
+
+    /// Filter down to papers added before this time, a date like 2023-08-01 or an age like 2w.
+    #[clap(long)]
+    pub added_before: Option<Since>,
+
+    /// Filter down to papers changed since this time, a date like 2023-08-01 or an age like 2w.
+    #[clap(long)]
+    pub modified_since: Option<Since>,
+
+    /// Filter down to papers changed before this time, a date like 2023-08-01 or an age like 2w.
+    #[clap(long)]
+    pub modified_before: Option<Since>,
+
+    /// Filter down to papers last reviewed since this time, a date like 2023-08-01 or an age
+    /// like 1mo.
+    #[clap(long)]
+    pub read_since: Option<Since>,
+
+    /// Filter down to papers last reviewed before this time, a date like 2023-08-01 or an age
+    /// like 1mo. Papers never reviewed count as reviewed before any time.
+    #[clap(long)]
+    pub read_before: Option<Since>,
+}
+
+impl TimeFilters {
+    /// Whether a paper passes all of the filters.
+    pub fn matches(&self, meta: &PaperMeta) -> bool {
+        let after = |since: Option<Since>, at: Option<NaiveDateTime>| {
+            since.is_none_or(|Since(since)| at.is_some_and(|at| at >= since))
+        };
+        let before = |until: Option<Since>, at: Option<NaiveDateTime>| {
+            until.is_none_or(|Since(until)| at.is_none_or(|at| at < until))
+        };
+        after(self.added_since, Some(meta.created_at))
+            && before(self.added_before, Some(meta.created_at))
+            && after(self.modified_since, Some(meta.modified_at))
+            && before(self.modified_before, Some(meta.modified_at))
+            && after(self.read_since, meta.last_review)
+            && before(self.read_before, meta.last_review)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(s: &str, expected: Expect) {
+        expected.assert_debug_eq(&s.parse::<Since>());
+    }
+
+    #[test]
+    fn test_date() {
+        check(
+            "2023-08-01",
+            expect![[r#"
+                Ok(
+                    Since(
+                        2023-08-01T00:00:00,
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_datetime() {
+        check(
+            "2023-08-01T12:30:00",
+            expect![[r#"
+                Ok(
+                    Since(
+                        2023-08-01T12:30:00,
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_age() {
+        let Since(at) = "2d".parse().unwrap();
+        let ago = Utc::now().naive_utc() - at;
+        assert!(ago >= Duration::days(2) && ago < Duration::days(2) + Duration::minutes(1));
+    }
+
+    #[test]
+    fn test_invalid() {
+        check(
+            "3x",
+            expect![[r#"
+                Err(
+                    "Invalid time \"3x\", expected a date like 2023-08-01 or an age like 3d in s, m, h, d, w, mo, y",
+                )
+            "#]],
+        );
+        check(
+            "d",
+            expect![[r#"
+                Err(
+                    "Invalid time \"d\", expected a date like 2023-08-01 or an age like 3d in s, m, h, d, w, mo, y",
+                )
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use papers_core::{
+    author::Author,
+    filename::{sanitize_stem, unique_path},
+};
+use reqwest::Url;
+use tracing::{debug, info};
+
+use crate::{arxiv::ArxivId, http::HttpClient};
+
+/// Metadata found in the head of a web page.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PageMeta {
+    /// Title of the page.
+    pub title: Option<String>,
+    /// Authors of the page.
+    pub authors: Vec<Author>,
+}
+
+/// Snapshot a web page into the given directory, returning the path to the saved file and the
+/// metadata extracted from the page.
+///
+/// If a `command` template is given it is used to convert the page (e.g. to a pdf), with `{url}`
+/// and `{output}` substituted, otherwise the html is saved directly.
+pub fn snapshot_url(
+    client: &HttpClient,
+    url: &Url,
+    dir: &Path,
+    command: Option<&str>,
+) -> anyhow::Result<(PathBuf, PageMeta)> {
+    info!(%url, "Fetching page for snapshot");
+    let html = client.get_text(url)?;
+    let meta = extract_page_meta(&html);
+    debug!(?meta, "Extracted page metadata");
+
+    let stem = meta
+        .title
+        .as_deref()
+        .map(sanitize_stem)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| "snapshot".to_owned());
+
+    let path = if let Some(command) = command {
+        // never overwriting an earlier snapshot of a page with the same title
+        let path = unique_path(&dir.join(&stem).with_extension("pdf"));
+        let mut parts = command.split_whitespace().map(|part| {
+            part.replace("{url}", url.as_str())
+                .replace("{output}", &path.to_string_lossy())
+        });
+        let program = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Snapshot command is empty"))?;
+        info!(program, ?path, "Converting page with snapshot command");
+        let status = Command::new(program).args(parts).status()?;
+        if !status.success() {
+            anyhow::bail!("Snapshot command failed with {}", status);
+        }
+        path
+    } else {
+        let path = unique_path(&dir.join(&stem).with_extension("html"));
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        file.write_all(html.as_bytes())?;
+        path
+    };
+    info!(?path, "Saved snapshot");
+
+    Ok((path, meta))
+}
+
+/// Fetch the metadata of the page at a url without saving it, looking at the abstract page for
+/// arXiv pdfs.
+pub fn fetch_page_meta(client: &HttpClient, url: &Url) -> anyhow::Result<PageMeta> {
+    let url = ArxivId::from_url(url).map_or_else(|| url.clone(), |id| id.abs_url());
+    info!(%url, "Fetching page metadata");
+    let html = client.get_text(&url)?;
+    let meta = extract_page_meta(&html);
+    debug!(?meta, "Extracted page metadata");
+    Ok(meta)
+}
+
+/// Extract the title and authors from the metadata of a html page.
+pub fn extract_page_meta(html: &str) -> PageMeta {
+    let mut og_title = None;
+    let mut citation_title = None;
+    let mut citation_authors = Vec::new();
+    let mut meta_authors = Vec::new();
+
+    for tag in tags(html, "meta") {
+        let key = attribute(tag, "property")
+            .or_else(|| attribute(tag, "name"))
+            .map(|k| k.to_lowercase());
+        let content = match attribute(tag, "content") {
+            Some(content) if !content.trim().is_empty() => content,
+            _ => continue,
+        };
+        match key.as_deref() {
+            Some("og:title") => og_title = Some(content),
+            Some("citation_title") => citation_title = Some(content),
+            Some("citation_author") => citation_authors.push(Author::new(&content)),
+            Some("author") | Some("article:author") if !content.starts_with("http") => {
+                meta_authors.push(Author::new(&content))
+            }
+            _ => {}
+        }
+    }
+
+    let title_tag = html
+        .find("<title")
+        .and_then(|start| {
+            let rest = &html[start..];
+            let open_end = rest.find('>')? + 1;
+            let close = rest.find("</title>")?;
+            rest.get(open_end..close)
+        })
+        .map(decode_entities);
+
+    let title = citation_title
+        .or(og_title)
+        .or(title_tag)
+        .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|t| !t.is_empty());
+    let authors = if citation_authors.is_empty() {
+        meta_authors
+    } else {
+        citation_authors
+    };
+
+    PageMeta { title, authors }
+}
+
+/// Find the contents of all opening tags with the given name.
+fn tags<'a>(html: &'a str, name: &str) -> Vec<&'a str> {
+    let open = format!("<{name}");
+    html.match_indices(&open)
+        .filter_map(|(i, _)| {
+            let rest = &html[i + open.len()..];
+            let end = rest.find('>')?;
+            Some(&rest[..end])
+        })
+        .collect()
+}
+
+/// Get the value of an attribute from the inside of a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    // ascii only so that offsets into it are the same as into the tag
+    let lower = tag.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(pos) = lower[search_from..].find(name) {
+        let start = search_from + pos;
+        search_from = start + name.len();
+        // make sure we matched a whole attribute name
+        if start > 0 && !lower[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let rest = tag[search_from..].trim_start();
+        let Some(rest) = rest.strip_prefix('=') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next(),
+            Some(_) => rest.split(|c: char| c.is_whitespace() || c == '/').next(),
+            None => None,
+        };
+        return value.map(decode_entities);
+    }
+    None
+}
+
+/// Decode the html entities that commonly appear in text.
+pub fn decode_entities(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(html: &str, expected: Expect) {
+        expected.assert_debug_eq(&extract_page_meta(html));
+    }
+
+    #[test]
+    fn test_title_tag() {
+        check(
+            "<html><head><title>\n  A blog post &amp; more\n</title></head></html>",
+            expect![[r#"
+                PageMeta {
+                    title: Some(
+                        "A blog post & more",
+                    ),
+                    authors: [],
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_meta_preferred() {
+        check(
+            r#"<head>
+<title>Site | Post</title>
+<meta property="og:title" content="The Post">
+<meta name="author" content='Jane Doe'/>
+<meta property="article:author" content="https://example.com/jane">
+</head>"#,
+            expect![[r#"
+                PageMeta {
+                    title: Some(
+                        "The Post",
+                    ),
+                    authors: [
+                        Author {
+                            author: "Jane Doe",
+                        },
+                    ],
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_citation_meta() {
+        check(
+            r#"<meta name="citation_title" content="Paper Title">
+<meta name="citation_author" content="Doe, Jane">
+<meta name="citation_author" content="Smith, John">
+<meta name="author" content="Publisher">"#,
+            expect![[r#"
+                PageMeta {
+                    title: Some(
+                        "Paper Title",
+                    ),
+                    authors: [
+                        Author {
+                            author: "Doe, Jane",
+                        },
+                        Author {
+                            author: "Smith, John",
+                        },
+                    ],
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_non_ascii_attributes() {
+        assert_eq!(
+            attribute(r#"meta name="İstanbul" content="Ünïcode Tïtle""#, "content"),
+            Some("Ünïcode Tïtle".to_owned())
+        );
+    }
+}
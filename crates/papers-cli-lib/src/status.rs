@@ -0,0 +1,115 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use papers_core::{paper::PaperMeta, tag::Tag};
+use serde::Serialize;
+
+use crate::{
+    deadlines::{days_left, DUE_SOON_DAYS},
+    output::Render,
+    triage::{INBOX_TAG, QUEUE_TAG},
+};
+
+/// Counts of the papers that need attention.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Status {
+    /// Papers due for review.
+    pub reviews: usize,
+    /// Papers in the inbox.
+    pub inbox: usize,
+    /// Papers queued to read.
+    pub queue: usize,
+    /// Papers to be read within the next week, or overdue.
+    pub due: usize,
+}
+
+impl Status {
+    /// Count the papers that need attention on `today`.
+    pub fn of<'a>(papers: impl IntoIterator<Item = &'a PaperMeta>, today: NaiveDate) -> Self {
+        let (inbox, queue) = (Tag::new(INBOX_TAG), Tag::new(QUEUE_TAG));
+        let mut status = Self::default();
+        for meta in papers {
+            status.reviews += usize::from(meta.is_reviewable());
+            status.inbox += usize::from(meta.tags.contains(&inbox));
+            status.queue += usize::from(meta.tags.contains(&queue));
+            status.due += usize::from(days_left(meta, today).is_some_and(|d| d <= DUE_SOON_DAYS));
+        }
+        status
+    }
+
+    /// Compact form for a shell prompt or status bar, e.g. `r3 i2 q5 d1`, leaving out counts of
+    /// zero.
+    pub fn short(&self) -> String {
+        [
+            ('r', self.reviews),
+            ('i', self.inbox),
+            ('q', self.queue),
+            ('d', self.due),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(key, count)| format!("{key}{count}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Due for review: {}", self.reviews)?;
+        writeln!(f, "In the inbox: {}", self.inbox)?;
+        writeln!(f, "Queued to read: {}", self.queue)?;
+        write!(f, "Due to be read soon: {}", self.due)
+    }
+}
+
+impl Render for Status {
+    fn header(&self) -> Vec<String> {
+        ["reviews", "inbox", "queue", "due"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![[self.reviews, self.inbox, self.queue, self.due]
+            .map(|c| c.to_string())
+            .to_vec()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let today = NaiveDate::from_ymd_opt(2023, 8, 10).unwrap();
+        let reviewed = chrono::NaiveDate::from_ymd_opt(9999, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0);
+        let papers = [
+            PaperMeta {
+                tags: BTreeSet::from([Tag::new("inbox")]),
+                ..Default::default()
+            },
+            PaperMeta {
+                tags: BTreeSet::from([Tag::new("to-read")]),
+                read_by: NaiveDate::from_ymd_opt(2023, 8, 12),
+                next_review: reviewed,
+                ..Default::default()
+            },
+            PaperMeta {
+                read_by: NaiveDate::from_ymd_opt(2023, 9, 1),
+                next_review: reviewed,
+                ..Default::default()
+            },
+        ];
+        let status = Status::of(&papers, today);
+        expect!["r1 i1 q1 d1"].assert_eq(&status.short());
+        expect![""].assert_eq(&Status::default().short());
+    }
+}
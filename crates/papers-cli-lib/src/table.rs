@@ -1,7 +1,19 @@
 use std::{collections::BTreeMap, collections::BTreeSet, fmt::Display, time::Duration};
 
-use papers_core::{author::Author, label::Label, paper::PaperMeta, tag::Tag};
+use papers_core::{
+    author::Author,
+    label::Label,
+    paper::{LoadedPaper, PaperMeta},
+    tag::Tag,
+};
 use serde::Serialize;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    deadlines::{days_left, describe, DUE_SOON_DAYS},
+    notes::NotesTemplate,
+    output::Render,
+};
 
 /// Paper format for display in a table.
 #[derive(Debug, Serialize)]
@@ -14,12 +26,59 @@ pub struct TablePaper {
     pub title: String,
     /// Tags for this document.
     pub tags: BTreeSet<Tag>,
-    /// Labels for this document.
+    /// Labels for this document, other than those with their own columns.
     pub labels: BTreeSet<Label>,
+    /// Values of the labels with their own columns, in order.
+    pub label_columns: Vec<String>,
     /// Authors for this document.
     pub authors: Vec<Author>,
     /// Age since creation.
     pub age: Duration,
+    /// How soon the paper should be read by, if that is soon.
+    pub due: Option<String>,
+    /// State of the paper at a glance, if the status column is shown.
+    pub status: Option<PaperStatus>,
+    /// Number of words written in the notes, if the notes column is shown.
+    pub notes_words: Option<usize>,
+}
+
+/// State of a paper at a glance, shown as a glyph for each part in the status column.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PaperStatus {
+    /// Whether the paper has been reviewed at least once.
+    pub read: bool,
+    /// Whether the paper has a document.
+    pub has_file: bool,
+    /// Whether the paper has notes written below its frontmatter.
+    pub has_notes: bool,
+    /// Whether the next review of the paper is in the past.
+    pub overdue: bool,
+}
+
+impl PaperStatus {
+    /// The state of a paper as of `now`, only counting notes written beyond the `template`.
+    pub fn of(paper: &LoadedPaper, template: &NotesTemplate, now: chrono::NaiveDateTime) -> Self {
+        let meta = &paper.meta;
+        Self {
+            read: meta.last_review.is_some(),
+            has_file: meta.filename.is_some(),
+            has_notes: template.has_notes(&paper.notes),
+            overdue: !meta.no_review && meta.next_review.is_some_and(|r| r < now),
+        }
+    }
+
+    /// One glyph for each part, in a fixed order so they line up down the column.
+    pub fn glyphs(&self) -> String {
+        [
+            (self.read, '✓'),
+            (self.has_file, '▤'),
+            (self.has_notes, '✎'),
+            (self.overdue, '!'),
+        ]
+        .into_iter()
+        .map(|(on, glyph)| if on { glyph } else { '·' })
+        .collect()
+    }
 }
 
 fn display_duration(dur: &Duration) -> String {
@@ -39,17 +98,26 @@ fn display_duration(dur: &Duration) -> String {
 }
 
 impl TablePaper {
-    /// Convert a paper to its table view counterpart.
-    pub fn from_paper(p: PaperMeta, now: chrono::NaiveDateTime) -> Self {
+    /// Convert a paper to its table view counterpart, giving each of `label_columns` its own
+    /// column.
+    pub fn from_paper(p: PaperMeta, now: chrono::NaiveDateTime, label_columns: &[String]) -> Self {
         let age = now - p.created_at;
         let age = match age.to_std() {
             Ok(duration) => duration,
             Err(_) => (-age).to_std().unwrap(),
         };
+        let due = days_left(&p, now.date())
+            .filter(|&d| d <= DUE_SOON_DAYS)
+            .map(describe);
+        let columns = label_columns
+            .iter()
+            .map(|key| p.field(key).map(|v| v.to_string()).unwrap_or_default())
+            .collect();
         let filename = p.filename.map(|f| f.to_string_lossy().into_owned());
         let labels = p
             .labels
             .into_iter()
+            .filter(|(k, _)| !label_columns.contains(k))
             .map(|(k, v)| Label::new(&k, v))
             .collect();
         Self {
@@ -58,13 +126,39 @@ impl TablePaper {
             title: p.title,
             tags: p.tags,
             labels,
+            label_columns: columns,
             authors: p.authors,
             age,
+            due,
+            status: None,
+            notes_words: None,
         }
     }
 
-    fn to_row(&self) -> comfy_table::Row {
-        let title = self.title.clone();
+    /// Names of the columns of a paper.
+    pub fn header(label_columns: &[String]) -> Vec<String> {
+        let mut header = vec!["title".to_owned(), "authors".to_owned(), "tags".to_owned()];
+        header.extend(label_columns.iter().cloned());
+        header.extend(["labels".to_owned(), "age".to_owned()]);
+        header
+    }
+
+    /// Cells for each of the given papers, aged from now.
+    pub fn rows(
+        papers: impl Iterator<Item = PaperMeta>,
+        label_columns: &[String],
+    ) -> Vec<Vec<String>> {
+        let now = now_naive();
+        papers
+            .map(|p| Self::from_paper(p, now, label_columns).cells())
+            .collect()
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let title = match &self.due {
+            Some(due) => format!("{} ({due})", self.title),
+            None => self.title.clone(),
+        };
         let tags = self
             .tags
             .iter()
@@ -85,17 +179,136 @@ impl TablePaper {
             .join(", ");
         let age = display_duration(&self.age);
 
-        let columns = vec![title, authors, tags, labels, age];
+        let mut cells = self.status.iter().map(|s| s.glyphs()).collect::<Vec<_>>();
+        cells.extend([title, authors, tags]);
+        cells.extend(self.label_columns.iter().cloned());
+        cells.push(labels);
+        cells.extend(self.notes_words.map(|w| w.to_string()));
+        cells.push(age);
+        cells
+    }
+
+    /// A row of the table, each cell cut down to one line of at most the width of its column.
+    fn to_row(&self, widths: Option<&[usize]>) -> comfy_table::Row {
+        let overdue = self.status.is_some_and(|s| s.overdue);
+        let cells = self.cells().into_iter().enumerate().map(|(i, cell)| {
+            let width = widths.and_then(|w| w.get(i)).copied().unwrap_or(usize::MAX);
+            let cell = comfy_table::Cell::new(truncate(&cell, width));
+            // the status column comes first, and is only coloured when something needs doing
+            if i == 0 && overdue {
+                cell.fg(comfy_table::Color::Red)
+            } else {
+                cell
+            }
+        });
+        comfy_table::Row::from(cells.collect::<Vec<_>>())
+    }
+}
+
+/// Zero width joiner, which joins the characters either side of it into one.
+const ZWJ: char = '\u{200d}';
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// Split text into the clusters of characters that show as one, along with their widths.
+///
+/// This covers combining marks, emoji joined with zero width joiners and flags, rather than all
+/// of the grapheme cluster rules.
+fn graphemes(s: &str) -> Vec<(&str, usize)> {
+    // start, end and width of each cluster
+    let mut clusters: Vec<(usize, usize, usize)> = Vec::new();
+    let mut previous = None;
+    // whether the last cluster is a single regional indicator waiting for its pair
+    let mut open_flag = false;
+    for (i, c) in s.char_indices() {
+        let end = i + c.len_utf8();
+        let width = c.width().unwrap_or(0);
+        let joined = match clusters.last_mut() {
+            Some(last) if previous == Some(ZWJ) || width == 0 => {
+                last.1 = end;
+                true
+            }
+            Some(last) if open_flag && is_regional_indicator(c) => {
+                last.1 = end;
+                last.2 += width;
+                open_flag = false;
+                true
+            }
+            _ => false,
+        };
+        if !joined {
+            clusters.push((i, end, width));
+            open_flag = is_regional_indicator(c);
+        }
+        previous = Some(c);
+    }
+    clusters
+        .into_iter()
+        .map(|(start, end, width)| (&s[start..end], width))
+        .collect()
+}
+
+/// Number of columns text takes up in a terminal.
+fn display_width(s: &str) -> usize {
+    graphemes(s).iter().map(|(_, w)| w).sum()
+}
+
+/// Cut text down to its first line and at most `width` columns, ending in an ellipsis if anything
+/// was cut, without splitting characters that show as one.
+pub fn truncate(s: &str, width: usize) -> String {
+    let line = s.lines().next().unwrap_or_default();
+    if display_width(line) <= width && line.len() == s.trim_end_matches('\n').len() {
+        return line.to_owned();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for (cluster, w) in graphemes(line) {
+        // leave room for the ellipsis
+        if used + w + 1 > width {
+            break;
+        }
+        truncated.push_str(cluster);
+        used += w;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
 
-        let mut row = comfy_table::Row::from(columns);
-        row.max_height(1);
-        row
+/// Share out `available` columns between columns wanting `wanted` widths, giving narrow columns
+/// all they want and splitting the rest evenly between the wide ones.
+fn fit_widths(wanted: &[usize], available: usize) -> Vec<usize> {
+    if wanted.iter().sum::<usize>() <= available {
+        return wanted.to_vec();
     }
+    let mut order = (0..wanted.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| wanted[i]);
+    let mut widths = vec![0; wanted.len()];
+    let mut remaining = available;
+    for (placed, i) in order.into_iter().enumerate() {
+        let share = remaining / (wanted.len() - placed);
+        widths[i] = wanted[i].min(share);
+        remaining -= widths[i];
+    }
+    widths
 }
 
 /// A way to print tables to the terminal.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
 pub struct Table {
-    papers: Vec<TablePaper>,
+    papers: Vec<PaperMeta>,
+    #[serde(skip)]
+    label_columns: Vec<String>,
+    /// State of each paper for the status column, which is only shown if given.
+    #[serde(skip)]
+    statuses: Vec<PaperStatus>,
+    /// Words in the notes of each paper for the notes column, which is only shown if given.
+    #[serde(skip)]
+    notes_words: Vec<usize>,
 }
 
 fn now_naive() -> chrono::NaiveDateTime {
@@ -105,39 +318,119 @@ fn now_naive() -> chrono::NaiveDateTime {
 }
 
 impl From<Vec<PaperMeta>> for Table {
-    fn from(v: Vec<PaperMeta>) -> Self {
-        let now = now_naive();
-        let papers = v
-            .into_iter()
-            .map(|p| TablePaper::from_paper(p, now))
-            .collect();
-        Self { papers }
+    fn from(papers: Vec<PaperMeta>) -> Self {
+        Self {
+            papers,
+            label_columns: Vec::new(),
+            statuses: Vec::new(),
+            notes_words: Vec::new(),
+        }
     }
 }
 
 impl Table {
-    fn header() -> comfy_table::Row {
-        comfy_table::Row::from(vec!["title", "authors", "tags", "labels", "age"])
+    /// Give each of the labels, or extra fields, their own column.
+    #[must_use]
+    pub fn with_label_columns(mut self, label_columns: Vec<String>) -> Self {
+        self.label_columns = label_columns;
+        self
+    }
+
+    /// Show a status column first, with the state of each of the papers in order.
+    #[must_use]
+    pub fn with_statuses(mut self, statuses: Vec<PaperStatus>) -> Self {
+        self.statuses = statuses;
+        self
+    }
+
+    /// Show a notes column before the age, with the number of words written in the notes of each
+    /// of the papers in order.
+    #[must_use]
+    pub fn with_notes_words(mut self, notes_words: Vec<usize>) -> Self {
+        self.notes_words = notes_words;
+        self
+    }
+
+    fn columns(&self) -> Vec<String> {
+        let mut header = Vec::new();
+        if !self.statuses.is_empty() {
+            header.push("status".to_owned());
+        }
+        header.extend(TablePaper::header(&self.label_columns));
+        if !self.notes_words.is_empty() {
+            header.insert(header.len() - 1, "notes".to_owned());
+        }
+        header
+    }
+
+    fn table_papers(&self, now: chrono::NaiveDateTime) -> Vec<TablePaper> {
+        self.papers
+            .iter()
+            .enumerate()
+            .map(|(i, paper)| TablePaper {
+                status: self.statuses.get(i).copied(),
+                notes_words: self.notes_words.get(i).copied(),
+                ..TablePaper::from_paper(paper.clone(), now, &self.label_columns)
+            })
+            .collect()
+    }
+}
+
+impl Render for Table {
+    fn header(&self) -> Vec<String> {
+        self.columns()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.table_papers(now_naive())
+            .iter()
+            .map(TablePaper::cells)
+            .collect()
     }
 }
 
 impl Display for Table {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = comfy_table::Table::new().width();
+        write!(f, "{}", self.render(width, now_naive()))
+    }
+}
+
+impl Table {
+    /// The table as it shows in a terminal `width` columns wide, if known, with rows cut down to
+    /// fit.
+    fn render(&self, width: Option<u16>, now: chrono::NaiveDateTime) -> comfy_table::Table {
         let mut tab = comfy_table::Table::new();
         tab.load_preset(comfy_table::presets::UTF8_FULL_CONDENSED)
             .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS)
-            .set_content_arrangement(comfy_table::ContentArrangement::Dynamic);
+            // rows are cut down to fit here rather than wrapped
+            .set_content_arrangement(comfy_table::ContentArrangement::Disabled);
 
-        tab.set_header(Self::header());
+        let header = self.columns();
+        let papers = self.table_papers(now);
 
-        let authors_column = tab.column_mut(2).unwrap();
-        authors_column.set_delimiter(',');
+        let widths = width.map(|width| {
+            let mut wanted = header.iter().map(|h| display_width(h)).collect::<Vec<_>>();
+            for paper in &papers {
+                for (w, cell) in wanted.iter_mut().zip(paper.cells()) {
+                    *w = (*w).max(display_width(cell.lines().next().unwrap_or_default()));
+                }
+            }
+            // a border and a space either side for each column, and the border at the end
+            let borders = 3 * wanted.len() + 1;
+            fit_widths(&wanted, usize::from(width).saturating_sub(borders))
+        });
 
-        for paper in &self.papers {
-            tab.add_row(paper.to_row());
-        }
+        let header = header.iter().enumerate().map(|(i, h)| {
+            let width = widths.as_ref().map_or(usize::MAX, |w| w[i]);
+            truncate(h, width)
+        });
+        tab.set_header(header.collect::<Vec<_>>());
 
-        write!(f, "{}", tab)
+        for paper in &papers {
+            tab.add_row(paper.to_row(widths.as_deref()));
+        }
+        tab
     }
 }
 
@@ -152,6 +445,7 @@ pub struct TableCount {
 
 impl TableCount {
     /// Add a new entry.
+    #[allow(clippy::should_implement_trait)]
     pub fn add(mut self, value: String) -> Self {
         *self.counts.entry(value).or_default() += 1;
         self
@@ -162,18 +456,24 @@ impl TableCount {
         self.sort_by_count = true;
     }
 
-    fn header() -> comfy_table::Row {
-        comfy_table::Row::from(vec!["key", "count"])
-    }
-
-    fn rows(&self) -> Vec<comfy_table::Row> {
+    fn items(&self) -> Vec<(&String, &usize)> {
         let mut items: Vec<_> = self.counts.iter().collect();
         if self.sort_by_count {
             items.sort_by_key(|(_, count)| *count);
         }
         items
+    }
+}
+
+impl Render for TableCount {
+    fn header(&self) -> Vec<String> {
+        vec!["key".to_owned(), "count".to_owned()]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.items()
             .into_iter()
-            .map(|(k, c)| comfy_table::Row::from(vec![k, &c.to_string()]))
+            .map(|(k, c)| vec![k.clone(), c.to_string()])
             .collect()
     }
 }
@@ -186,7 +486,7 @@ impl Display for TableCount {
             .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS)
             .set_content_arrangement(comfy_table::ContentArrangement::Dynamic);
 
-        tab.set_header(Self::header());
+        tab.set_header(self.header());
 
         for row in self.rows() {
             tab.add_row(row);
@@ -195,3 +495,134 @@ impl Display for TableCount {
         write!(f, "{}", tab)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use papers_core::primitive::Primitive;
+
+    use super::*;
+
+    #[test]
+    fn test_label_columns() {
+        let meta = PaperMeta {
+            title: "A Paper".to_owned(),
+            labels: BTreeMap::from([
+                ("doi".to_owned(), Primitive::String("10.1/a".to_owned())),
+                ("year".to_owned(), Primitive::Number(2019.into())),
+            ]),
+            extra: BTreeMap::from([("venue".to_owned(), "SOSP".into())]),
+            ..Default::default()
+        };
+        let columns = ["year", "venue", "missing"].map(String::from);
+        let paper = TablePaper::from_paper(meta.clone(), meta.created_at, &columns);
+        expect![[r#"
+            [
+                [
+                    "title",
+                    "authors",
+                    "tags",
+                    "year",
+                    "venue",
+                    "missing",
+                    "labels",
+                    "age",
+                ],
+                [
+                    "A Paper",
+                    "",
+                    "",
+                    "2019",
+                    "SOSP",
+                    "",
+                    "doi=10.1/a",
+                    "0s",
+                ],
+            ]
+        "#]]
+        .assert_debug_eq(&[TablePaper::header(&columns), paper.cells()]);
+    }
+
+    fn check_truncate(s: &str, width: usize, expected: Expect) {
+        let truncated = truncate(s, width);
+        assert!(display_width(&truncated) <= width);
+        expected.assert_eq(&truncated);
+    }
+
+    #[test]
+    fn test_truncate() {
+        check_truncate("Raft", 10, expect!["Raft"]);
+        check_truncate("In Search of Raft", 10, expect!["In Search…"]);
+        check_truncate("分布式系统的共识", 7, expect!["分布式…"]);
+        check_truncate("Cafe\u{301} au lait", 5, expect!["Café…"]);
+        check_truncate("👩‍💻 at work", 3, expect!["👩‍💻…"]);
+        check_truncate("🇬🇧🇫🇷 flags", 4, expect!["🇬🇧…"]);
+        check_truncate("first\nsecond", 20, expect!["first…"]);
+        check_truncate("Raft", 0, expect![""]);
+    }
+
+    #[test]
+    fn test_render_narrow() {
+        let now = chrono::NaiveDate::from_ymd_opt(2023, 8, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let paper = |title: &str| PaperMeta {
+            title: title.to_owned(),
+            created_at: now,
+            tags: BTreeSet::from([Tag::new("to-read")]),
+            ..Default::default()
+        };
+        let table = Table::from(vec![
+            paper("分布式系统中的共识算法研究"),
+            paper("🚀 Rockets 🚀 and more rockets"),
+        ]);
+        expect![[r#"
+            ╭─────────────┬─────────┬─────────┬────────┬─────╮
+            │ title       ┆ authors ┆ tags    ┆ labels ┆ age │
+            ╞═════════════╪═════════╪═════════╪════════╪═════╡
+            │ 分布式系统… ┆         ┆ to-read ┆        ┆ 0s  │
+            │ 🚀 Rockets… ┆         ┆ to-read ┆        ┆ 0s  │
+            ╰─────────────┴─────────┴─────────┴────────┴─────╯"#]]
+        .assert_eq(&table.render(Some(50), now).to_string());
+    }
+
+    #[test]
+    fn test_status_column() {
+        let now = chrono::NaiveDate::from_ymd_opt(2023, 8, 10)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let paper = |title: &str, notes: &str| LoadedPaper {
+            path: format!("{title}.md").into(),
+            meta: PaperMeta {
+                title: title.to_owned(),
+                created_at: now,
+                ..Default::default()
+            },
+            notes: notes.to_owned(),
+        };
+        let mut read = paper("read", "\n# Notes\nEasy to follow.\n");
+        read.meta.filename = Some("read.pdf".into());
+        read.meta.last_review = Some(now - chrono::Days::new(7));
+        read.meta.next_review = Some(now - chrono::Days::new(1));
+        let papers = [read, paper("unread", "\n# Notes\n")];
+        let template = NotesTemplate::new("# Notes");
+        let statuses = papers
+            .iter()
+            .map(|p| PaperStatus::of(p, &template, now))
+            .collect();
+        let words = papers.iter().map(|p| template.words(&p.notes)).collect();
+        let table = Table::from(papers.into_iter().map(|p| p.meta).collect::<Vec<_>>())
+            .with_statuses(statuses)
+            .with_notes_words(words);
+        expect![[r#"
+            ╭────────┬────────┬─────────┬──────┬────────┬───────┬─────╮
+            │ status ┆ title  ┆ authors ┆ tags ┆ labels ┆ notes ┆ age │
+            ╞════════╪════════╪═════════╪══════╪════════╪═══════╪═════╡
+            │ ✓▤✎!   ┆ read   ┆         ┆      ┆        ┆ 3     ┆ 0s  │
+            │ ····   ┆ unread ┆         ┆      ┆        ┆ 0     ┆ 0s  │
+            ╰────────┴────────┴─────────┴──────┴────────┴───────┴─────╯"#]]
+        .assert_eq(&table.render(None, now).to_string());
+    }
+}
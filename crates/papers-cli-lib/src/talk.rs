@@ -0,0 +1,103 @@
+use papers_core::author::Author;
+use reqwest::Url;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use crate::http::HttpClient;
+
+/// Hosts serving talks, along with their oEmbed endpoints.
+const OEMBED_PROVIDERS: &[(&str, &str)] = &[
+    ("youtube.com", "https://www.youtube.com/oembed"),
+    ("youtu.be", "https://www.youtube.com/oembed"),
+    ("vimeo.com", "https://vimeo.com/api/oembed.json"),
+];
+
+/// Section appended to the notes of talks for logging timestamps.
+pub const TALK_NOTES_SECTION: &str = "\n## Timestamps\n\n- 00:00 \n";
+
+/// Metadata about a talk.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TalkMeta {
+    /// Title of the talk.
+    pub title: Option<String>,
+    /// Channel or speaker that published the talk.
+    pub channel: Option<Author>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OEmbedResponse {
+    title: Option<String>,
+    author_name: Option<String>,
+}
+
+/// Get the oEmbed endpoint for a url if it points to a known video host.
+pub fn oembed_endpoint(url: &Url) -> Option<&'static str> {
+    let host = url.host_str()?;
+    OEMBED_PROVIDERS
+        .iter()
+        .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{domain}")))
+        .map(|(_, endpoint)| *endpoint)
+}
+
+/// Fetch the metadata for a talk from its host's oEmbed endpoint.
+pub fn fetch_talk_meta(client: &HttpClient, url: &Url) -> anyhow::Result<TalkMeta> {
+    let endpoint = oembed_endpoint(url)
+        .ok_or_else(|| anyhow::anyhow!("No oEmbed provider known for {}", url))?;
+    let endpoint = Url::parse_with_params(endpoint, [("url", url.as_str()), ("format", "json")])?;
+    info!(%endpoint, "Fetching talk metadata");
+    let res: OEmbedResponse = serde_json::from_str(&client.get_text(&endpoint)?)?;
+    debug!(?res, "Got oEmbed response");
+    Ok(TalkMeta {
+        title: res.title.filter(|t| !t.trim().is_empty()),
+        channel: res
+            .author_name
+            .filter(|a| !a.trim().is_empty())
+            .map(|a| Author::new(&a)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(url: &str, expected: Expect) {
+        let url = Url::parse(url).unwrap();
+        expected.assert_debug_eq(&oembed_endpoint(&url));
+    }
+
+    #[test]
+    fn test_youtube() {
+        check(
+            "https://www.youtube.com/watch?v=abc",
+            expect![[r#"
+                Some(
+                    "https://www.youtube.com/oembed",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_short_youtube() {
+        check(
+            "https://youtu.be/abc",
+            expect![[r#"
+                Some(
+                    "https://www.youtube.com/oembed",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_not_video() {
+        check(
+            "https://notyoutube.com/watch?v=abc",
+            expect![[r#"
+            None
+        "#]],
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, read_dir, remove_file},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::external::run_template;
+
+/// Directory in the repo to keep thumbnails in when none is configured, as they are pruned to
+/// those of the repo's documents.
+pub const THUMBNAILS_DIR: &str = ".papers/thumbnails";
+
+/// Command used to render thumbnails when none is configured, drawing the first page with
+/// ghostscript.
+pub const DEFAULT_THUMBNAIL_COMMAND: &str = "gs -q -dSAFER -dBATCH -dNOPAUSE -sDEVICE=png16m \
+     -dFirstPage=1 -dLastPage=1 -r30 -sOutputFile={output} {input}";
+
+/// Configuration for rendering thumbnails of the first pages of documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThumbnailConfig {
+    /// Command to render a png of the first page, with `{input}` and `{output}` substituted.
+    /// Uses ghostscript if not set.
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Directory to keep thumbnails in, defaults to [`THUMBNAILS_DIR`] in the repo.
+    /// Thumbnails not of the repo's documents are pruned from it, so it shouldn't be shared.
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+}
+
+/// Thumbnails of documents, named by the hash of the document so that they are rendered again
+/// when the document changes.
+#[derive(Debug)]
+pub struct Thumbnails {
+    dir: PathBuf,
+    command: String,
+}
+
+impl Thumbnails {
+    /// Thumbnails of the repo at `root`, in the configured directory.
+    pub fn new(config: &ThumbnailConfig, root: &Path) -> Self {
+        let dir = config
+            .dir
+            .clone()
+            .unwrap_or_else(|| root.join(THUMBNAILS_DIR));
+        Self {
+            dir,
+            command: config
+                .command
+                .clone()
+                .unwrap_or_else(|| DEFAULT_THUMBNAIL_COMMAND.to_owned()),
+        }
+    }
+
+    /// Where the thumbnail of a document with the given hash lives.
+    pub fn path(&self, hash: &str) -> PathBuf {
+        self.dir.join(hash).with_extension("png")
+    }
+
+    /// The thumbnail for a document with the given hash, rendering it if there isn't one for
+    /// those contents.
+    ///
+    /// Returns the path of the thumbnail and whether it was rendered.
+    pub fn get(&self, document: &Path, hash: &str) -> anyhow::Result<(PathBuf, bool)> {
+        let path = self.path(hash);
+        if path.is_file() {
+            debug!(?document, ?path, "Thumbnail is up to date");
+            return Ok((path, false));
+        }
+        create_dir_all(&self.dir)?;
+
+        info!(?document, "Rendering thumbnail");
+        let status = run_template("Thumbnail", &self.command, document, &path)?;
+        if !status.success() || !path.is_file() {
+            // don't leave a partial thumbnail to be mistaken for a good one
+            let _ = remove_file(&path);
+            anyhow::bail!("Thumbnail command failed with {}", status);
+        }
+        Ok((path, true))
+    }
+
+    /// Remove thumbnails of documents that aren't in `keep`, returning how many were removed.
+    pub fn prune(&self, keep: &BTreeSet<String>) -> std::io::Result<usize> {
+        let entries = match read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            let stale = path.extension().is_some_and(|e| e == "png")
+                && path
+                    .file_stem()
+                    .is_some_and(|s| !keep.contains(s.to_string_lossy().as_ref()));
+            if stale {
+                debug!(?path, "Removing stale thumbnail");
+                remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use papers_core::hashes::hash_file;
+
+    use super::*;
+
+    #[test]
+    fn test_thumbnails() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ThumbnailConfig {
+            command: Some("cp {input} {output}".to_owned()),
+            dir: None,
+        };
+        let thumbnails = Thumbnails::new(&config, &dir.path().join("repo"));
+        let document = dir.path().join("a.pdf");
+        std::fs::write(&document, "first").unwrap();
+
+        let first_hash = hash_file(&document).unwrap();
+        let (path, rendered) = thumbnails.get(&document, &first_hash).unwrap();
+        assert!(rendered);
+        assert!(path.is_file());
+        let (_, rendered) = thumbnails.get(&document, &first_hash).unwrap();
+        assert!(!rendered);
+
+        // thumbnails of another repo are kept apart
+        let other = Thumbnails::new(&config, &dir.path().join("other"));
+        let (other_path, rendered) = other.get(&document, &first_hash).unwrap();
+        assert!(rendered);
+
+        // changing the document gives it a new thumbnail
+        std::fs::write(&document, "second").unwrap();
+        let hash = hash_file(&document).unwrap();
+        let (_, rendered) = thumbnails.get(&document, &hash).unwrap();
+        assert!(rendered);
+        assert_ne!(hash, first_hash);
+
+        assert_eq!(thumbnails.prune(&BTreeSet::from([hash])).unwrap(), 1);
+        assert!(!thumbnails.path(&first_hash).exists());
+        assert!(other_path.is_file());
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use pdf::{
+    content::{Op, TextDrawAdjusted},
+    file::FileOptions,
+};
+use tracing::{debug, warn};
+
+/// Prefixes that tools put into the pdf title field instead of the real title.
+const GARBAGE_TITLE_PREFIXES: &[&str] = &["microsoft word - ", "microsoft powerpoint - "];
+
+/// Suffixes that indicate the title field is actually a source filename.
+const GARBAGE_TITLE_SUFFIXES: &[&str] = &[".doc", ".docx", ".dvi", ".pdf", ".ps", ".tex"];
+
+/// Titles that carry no information.
+const GARBAGE_TITLES: &[&str] = &["untitled", "title", "paper", "article"];
+
+/// Spacing in a `TJ` array (thousandths of an em) that is treated as a word break.
+const WORD_GAP: f32 = -200.;
+
+/// Maximum length of a title guessed from the page layout.
+const MAX_LAYOUT_TITLE_LEN: usize = 300;
+
+/// A title found for a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedTitle {
+    /// The title text.
+    pub title: String,
+    /// Whether the title came from a trustworthy source, rather than being guessed.
+    pub confident: bool,
+}
+
+/// Try and extract a title from a document.
+///
+/// The pdf metadata is used if it looks sensible, otherwise the largest text on the first page is
+/// used as a low-confidence guess.
+pub fn extract_title(file: &Path) -> Option<ExtractedTitle> {
+    let pdf_file = match FileOptions::cached().open(file) {
+        Ok(pdf_file) => pdf_file,
+        Err(err) => {
+            debug!(%err, ?file, "Failed to open pdf file");
+            return None;
+        }
+    };
+    debug!(?file, "Loaded pdf file");
+
+    if let Some(info) = pdf_file.trailer.info_dict.as_ref() {
+        debug!(?file, ?info, "Found the info dict");
+        if let Some(Ok(found_title)) = info.title.as_ref().map(|t| t.to_string()) {
+            let found_title = found_title.trim();
+            if is_garbage_title(found_title) {
+                debug!(
+                    ?file,
+                    title = found_title,
+                    "Ignoring unhelpful metadata title"
+                );
+            } else {
+                debug!(?file, title = found_title, "Setting auto title");
+                return Some(ExtractedTitle {
+                    title: found_title.to_owned(),
+                    confident: true,
+                });
+            }
+        }
+    }
+    warn!("Couldn't find a title in pdf metadata");
+
+    let runs = match pdf_file.get_page(0) {
+        Ok(page) => match page.contents.as_ref() {
+            Some(contents) => match contents.operations(&pdf_file.resolver()) {
+                Ok(ops) => text_runs(&ops),
+                Err(err) => {
+                    debug!(%err, ?file, "Failed to parse first page contents");
+                    return None;
+                }
+            },
+            None => return None,
+        },
+        Err(err) => {
+            debug!(%err, ?file, "Failed to load first page");
+            return None;
+        }
+    };
+
+    let title = largest_text_block(&runs)?;
+    debug!(?file, title, "Guessed title from first page layout");
+    Some(ExtractedTitle {
+        title,
+        confident: false,
+    })
+}
+
+/// Whether a metadata title looks like it was filled in by a tool rather than the author.
+pub fn is_garbage_title(title: &str) -> bool {
+    let lower = title.trim().to_lowercase();
+    lower.chars().filter(|c| c.is_alphabetic()).count() < 3
+        || GARBAGE_TITLES.contains(&lower.as_str())
+        || GARBAGE_TITLE_PREFIXES.iter().any(|p| lower.starts_with(p))
+        || GARBAGE_TITLE_SUFFIXES.iter().any(|s| lower.ends_with(s))
+}
+
+/// Collect the text drawn by some content operations, along with the effective font size of each
+/// run.
+fn text_runs(ops: &[Op]) -> Vec<(f32, String)> {
+    let mut runs = Vec::new();
+    let mut font_size = 0.;
+    let mut scale = 1.;
+    for op in ops {
+        match op {
+            Op::TextFont { size, .. } => font_size = *size,
+            Op::SetTextMatrix { matrix } if matrix.d != 0. => scale = matrix.d.abs(),
+            Op::BeginText => scale = 1.,
+            Op::TextDraw { text } => runs.push((font_size * scale, text.to_string_lossy())),
+            Op::TextDrawAdjusted { array } => {
+                let mut text = String::new();
+                for item in array {
+                    match item {
+                        TextDrawAdjusted::Text(t) => text.push_str(&t.to_string_lossy()),
+                        TextDrawAdjusted::Spacing(s) if *s <= WORD_GAP => text.push(' '),
+                        TextDrawAdjusted::Spacing(_) => {}
+                    }
+                }
+                runs.push((font_size * scale, text));
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// Find the first contiguous block of text set in the largest font size.
+fn largest_text_block(runs: &[(f32, String)]) -> Option<String> {
+    let is_text = |t: &str| t.chars().any(char::is_alphabetic);
+    let largest = runs
+        .iter()
+        .filter(|(_, t)| is_text(t))
+        .map(|(s, _)| *s)
+        .fold(0., f32::max);
+    if largest <= 0. {
+        return None;
+    }
+
+    let is_largest = |s: f32| (s - largest).abs() < 0.5;
+    let block = runs
+        .iter()
+        .skip_while(|(s, t)| !is_largest(*s) || !is_text(t))
+        .take_while(|(s, t)| is_largest(*s) || t.trim().is_empty())
+        .map(|(_, t)| t.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let title = block.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if title.len() > MAX_LAYOUT_TITLE_LEN || is_garbage_title(&title) {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check_block(runs: &[(f32, &str)], expected: Expect) {
+        let runs = runs
+            .iter()
+            .map(|(s, t)| (*s, t.to_string()))
+            .collect::<Vec<_>>();
+        expected.assert_debug_eq(&largest_text_block(&runs));
+    }
+
+    #[test]
+    fn test_garbage_titles() {
+        let titles = [
+            "Microsoft Word - paper.docx",
+            "main.tex",
+            "Untitled",
+            "12",
+            "My Actual Paper",
+        ];
+        let garbage = titles.map(is_garbage_title);
+        expect![[r#"
+            [
+                true,
+                true,
+                true,
+                true,
+                false,
+            ]
+        "#]]
+        .assert_debug_eq(&garbage);
+    }
+
+    #[test]
+    fn test_largest_block() {
+        check_block(
+            &[
+                (8., "arXiv:1234.5678"),
+                (17., "Attention Is"),
+                (17., "All You Need"),
+                (10., "Some Authors"),
+                (17., "Abstract"),
+            ],
+            expect![[r#"
+                Some(
+                    "Attention Is All You Need",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_largest_block_ignores_symbols() {
+        check_block(
+            &[(30., "*"), (14., "A Title"), (10., "body")],
+            expect![[r#"
+                Some(
+                    "A Title",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_largest_block_empty() {
+        check_block(
+            &[(10., "  "), (12., "1")],
+            expect![[r#"
+            None
+        "#]],
+        );
+    }
+}
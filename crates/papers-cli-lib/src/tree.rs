@@ -0,0 +1,230 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    path::PathBuf,
+};
+
+use clap::ValueEnum;
+use papers_core::paper::{LoadedPaper, PaperMeta};
+use serde::Serialize;
+
+use crate::{
+    du::{UsageBy, NO_GROUP},
+    output::Render,
+};
+
+/// Separator between the levels of a tag, e.g. `systems/consensus` is within `systems`.
+pub const TAG_SEPARATOR: char = '/';
+
+/// What to group papers by in the tree.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TreeBy {
+    /// Each tag of the paper, nested by the levels of the tag.
+    #[default]
+    Tag,
+    /// Each author of the paper.
+    Author,
+    /// The `year` label of the paper, or the year it was added.
+    Year,
+}
+
+impl TreeBy {
+    /// Groups that the paper is in, each as the path from the top of the tree.
+    pub fn groups(&self, meta: &PaperMeta) -> Vec<Vec<String>> {
+        match self {
+            Self::Tag if meta.tags.is_empty() => vec![vec![NO_GROUP.to_owned()]],
+            Self::Tag => meta
+                .tags
+                .iter()
+                .map(|t| {
+                    t.key()
+                        .split(TAG_SEPARATOR)
+                        .filter(|l| !l.is_empty())
+                        .map(str::to_owned)
+                        .collect()
+                })
+                .collect(),
+            Self::Author if meta.authors.is_empty() => vec![vec![NO_GROUP.to_owned()]],
+            Self::Author => meta.authors.iter().map(|a| vec![a.to_string()]).collect(),
+            Self::Year => UsageBy::Year
+                .groups(meta)
+                .into_iter()
+                .map(|y| vec![y])
+                .collect(),
+        }
+    }
+}
+
+/// A paper as shown in the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TreePaper {
+    /// Path of the paper, relative to the root.
+    pub path: PathBuf,
+    /// Title of the paper.
+    pub title: String,
+}
+
+/// A group of papers, with the groups nested within it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Group {
+    /// Papers directly in this group.
+    pub papers: Vec<TreePaper>,
+    /// Groups within this one, by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Group>,
+}
+
+impl Group {
+    /// Paths of the papers in this group and those within it.
+    fn paths(&self) -> BTreeSet<&PathBuf> {
+        let mut paths = self.papers.iter().map(|p| &p.path).collect::<BTreeSet<_>>();
+        for group in self.groups.values() {
+            paths.extend(group.paths());
+        }
+        paths
+    }
+
+    /// Lines of the tree below this group, each prefixed by `indent`.
+    fn lines(&self, indent: &str, lines: &mut Vec<String>) {
+        let mut entries = self
+            .groups
+            .iter()
+            .map(|(name, group)| (format!("{name} ({})", group.paths().len()), Some(group)))
+            .chain(self.papers.iter().map(|p| (p.title.clone(), None)))
+            .peekable();
+        while let Some((line, group)) = entries.next() {
+            let (branch, nested) = if entries.peek().is_none() {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            lines.push(format!("{indent}{branch}{line}"));
+            if let Some(group) = group {
+                group.lines(&format!("{indent}{nested}"), lines);
+            }
+        }
+    }
+
+    fn rows(&self, path: &str, rows: &mut Vec<Vec<String>>) {
+        for paper in &self.papers {
+            rows.push(vec![
+                path.to_owned(),
+                paper.path.display().to_string(),
+                paper.title.clone(),
+            ]);
+        }
+        for (name, group) in &self.groups {
+            group.rows(&format!("{path}{TAG_SEPARATOR}{name}"), rows);
+        }
+    }
+}
+
+/// Papers grouped into a tree, by the name of each group at the top.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Tree(pub BTreeMap<String, Group>);
+
+impl Tree {
+    /// Group the papers by `by`, keeping their order within a group.
+    pub fn of<'a>(papers: impl IntoIterator<Item = &'a LoadedPaper>, by: TreeBy) -> Self {
+        let mut tree = Self::default();
+        for paper in papers {
+            for path in by.groups(&paper.meta) {
+                let Some((top, rest)) = path.split_first() else {
+                    continue;
+                };
+                let group = rest
+                    .iter()
+                    .fold(tree.0.entry(top.clone()).or_default(), |g, name| {
+                        g.groups.entry(name.clone()).or_default()
+                    });
+                group.papers.push(TreePaper {
+                    path: paper.path.clone(),
+                    title: paper.meta.title.clone(),
+                });
+            }
+        }
+        tree
+    }
+}
+
+impl Display for Tree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        for (name, group) in &self.0 {
+            lines.push(format!("{name} ({})", group.paths().len()));
+            group.lines("", &mut lines);
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Tree {
+    fn header(&self) -> Vec<String> {
+        ["group", "path", "title"].map(String::from).to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for (name, group) in &self.0 {
+            group.rows(name, &mut rows);
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use papers_core::{author::Author, tag::Tag};
+
+    use super::*;
+
+    fn paper(title: &str, tags: &[&str], authors: &[&str]) -> LoadedPaper {
+        LoadedPaper {
+            meta: PaperMeta {
+                title: title.to_owned(),
+                tags: tags.iter().map(|t| Tag::new(t)).collect(),
+                authors: authors.iter().map(|a| Author::new(a)).collect(),
+                ..Default::default()
+            },
+            path: PathBuf::from(format!("{title}.md")),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_tree() {
+        let papers = [
+            paper("paxos", &["systems/consensus"], &["Lamport"]),
+            paper("raft", &["systems/consensus", "systems"], &["Ongaro"]),
+            paper("spanner", &["systems/db"], &[]),
+            paper("attention", &["ml"], &[]),
+            paper("untagged", &[], &["Lamport"]),
+        ];
+        expect![[r#"
+            (none) (1)
+            └── untagged
+            ml (1)
+            └── attention
+            systems (3)
+            ├── consensus (2)
+            │   ├── paxos
+            │   └── raft
+            ├── db (1)
+            │   └── spanner
+            └── raft"#]]
+        .assert_eq(&Tree::of(&papers, TreeBy::Tag).to_string());
+        expect![[r#"
+            (none) (2)
+            ├── spanner
+            └── attention
+            Lamport (2)
+            ├── paxos
+            └── untagged
+            Ongaro (1)
+            └── raft"#]]
+        .assert_eq(&Tree::of(&papers, TreeBy::Author).to_string());
+        expect![[r#"[["(none)", "untagged.md", "untagged"], ["ml", "attention.md", "attention"], ["systems", "raft.md", "raft"], ["systems/consensus", "paxos.md", "paxos"], ["systems/consensus", "raft.md", "raft"], ["systems/db", "spanner.md", "spanner"]]"#]].assert_eq(&format!("{:?}", Tree::of(&papers, TreeBy::Tag).rows()));
+    }
+}
@@ -0,0 +1,164 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use papers_core::{paper::LoadedPaper, tag::Tag};
+
+/// Tag for papers queued to be read.
+pub const QUEUE_TAG: &str = "to-read";
+
+/// Tag for papers put away without reading.
+pub const ARCHIVE_TAG: &str = "archived";
+
+/// Tag for papers that were added in bulk and haven't been triaged or read yet.
+pub const INBOX_TAG: &str = "inbox";
+
+/// Number of lines of notes shown for each paper.
+const SNIPPET_LINES: usize = 5;
+
+/// What to do with a paper being triaged, chosen with a single key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriageAction {
+    /// Add some tags, staying on the paper.
+    Tag,
+    /// Queue the paper for reading.
+    Queue,
+    /// Archive the paper.
+    Archive,
+    /// Leave the paper as it is.
+    Skip,
+    /// Open the document, staying on the paper.
+    Open,
+    /// Stop triaging.
+    Quit,
+}
+
+impl TriageAction {
+    /// Prompt listing the keys for each action.
+    pub const PROMPT: &'static str = "[t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it";
+
+    /// The action for a key, `None` for a key with no action.
+    pub fn from_key(key: Option<char>) -> Option<Self> {
+        match key.map(|k| k.to_ascii_lowercase()) {
+            Some('t') => Some(Self::Tag),
+            Some('q') => Some(Self::Queue),
+            Some('a') => Some(Self::Archive),
+            Some('s' | ' ') => Some(Self::Skip),
+            Some('o') => Some(Self::Open),
+            Some('x') | None => Some(Self::Quit),
+            Some(_) => None,
+        }
+    }
+
+    /// Apply the action to the tags of a paper, returning whether they changed.
+    pub fn retag(&self, tags: &mut BTreeSet<Tag>) -> bool {
+        let (add, remove) = match self {
+            Self::Queue => (QUEUE_TAG, ARCHIVE_TAG),
+            Self::Archive => (ARCHIVE_TAG, QUEUE_TAG),
+            _ => return false,
+        };
+        let removed = tags.remove(&Tag::new(remove)) | clear_inbox(tags);
+        tags.insert(Tag::new(add)) || removed
+    }
+}
+
+/// Take a paper out of the inbox, returning whether it was in it.
+pub fn clear_inbox(tags: &mut BTreeSet<Tag>) -> bool {
+    tags.remove(&Tag::new(INBOX_TAG))
+}
+
+/// Summary of a paper for deciding what to do with it.
+pub struct TriageCard<'a>(pub &'a LoadedPaper);
+
+impl Display for TriageCard<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let LoadedPaper { path, meta, notes } = self.0;
+        let join = |items: Vec<String>| items.join(", ");
+        writeln!(f, "{}", meta.title)?;
+        writeln!(f, "  path:    {}", path.display())?;
+        if let Some(url) = &meta.url {
+            writeln!(f, "  url:     {url}")?;
+        }
+        if !meta.authors.is_empty() {
+            let authors = meta.authors.iter().map(|a| a.to_string()).collect();
+            writeln!(f, "  authors: {}", join(authors))?;
+        }
+        if !meta.tags.is_empty() {
+            let tags = meta.tags.iter().map(|t| t.to_string()).collect();
+            writeln!(f, "  tags:    {}", join(tags))?;
+        }
+        if !meta.labels.is_empty() {
+            let labels = meta
+                .labels
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect();
+            writeln!(f, "  labels:  {}", join(labels))?;
+        }
+        for line in notes
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .take(SNIPPET_LINES)
+        {
+            writeln!(f, "  | {line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use papers_core::{author::Author, paper::PaperMeta};
+
+    use super::*;
+
+    fn check_retag(action: TriageAction, tags: &[&str], expected: Expect) {
+        let mut tags = tags.iter().map(|t| Tag::new(t)).collect::<BTreeSet<_>>();
+        let changed = action.retag(&mut tags);
+        let tags = tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        expected.assert_eq(&format!("{changed} {}", tags.join(",")));
+    }
+
+    #[test]
+    fn test_retag() {
+        check_retag(TriageAction::Queue, &["db"], expect!["true db,to-read"]);
+        check_retag(
+            TriageAction::Archive,
+            &["to-read"],
+            expect!["true archived"],
+        );
+        check_retag(TriageAction::Queue, &["to-read"], expect!["false to-read"]);
+        check_retag(TriageAction::Skip, &["db"], expect!["false db"]);
+        check_retag(
+            TriageAction::Queue,
+            &["inbox", "db"],
+            expect!["true db,to-read"],
+        );
+        check_retag(TriageAction::Skip, &["inbox"], expect!["false inbox"]);
+    }
+
+    #[test]
+    fn test_card() {
+        let paper = LoadedPaper {
+            path: "a-paper.md".into(),
+            meta: PaperMeta {
+                title: "A Paper".to_owned(),
+                authors: vec![Author::new("Jane")],
+                tags: BTreeSet::from([Tag::new("db")]),
+                ..Default::default()
+            },
+            notes: "# Summary\n\nIt is good.\n\n1\n2\n3\n4\n".to_owned(),
+        };
+        expect![[r#"
+            A Paper
+              path:    a-paper.md
+              authors: Jane
+              tags:    db
+              | # Summary
+              | It is good.
+              | 1
+              | 2
+              | 3
+        "#]]
+        .assert_eq(&TriageCard(&paper).to_string());
+    }
+}
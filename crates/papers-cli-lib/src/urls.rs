@@ -0,0 +1,133 @@
+use reqwest::Url;
+use tracing::debug;
+
+use crate::http::HttpClient;
+
+/// Query parameters that only track where a link was clicked.
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "_hsenc", "_hsmi", "igshid",
+    "ref_src",
+];
+
+/// Hosts serving arXiv abstract pages.
+const ARXIV_HOSTS: &[&str] = &["arxiv.org", "www.arxiv.org", "export.arxiv.org"];
+
+fn is_tracking_param(key: &str) -> bool {
+    key.starts_with("utm_") || TRACKING_PARAMS.contains(&key)
+}
+
+/// Parse a url and put it in a canonical form, without touching the network.
+///
+/// Tracking query parameters are removed and arXiv abstract links are rewritten to
+/// `https://arxiv.org/abs/<id>`.
+pub fn normalise_url(url: &str) -> Result<Url, String> {
+    let mut url = Url::parse(url.trim()).map_err(|err| err.to_string())?;
+
+    if let Some(host) = url.host_str() {
+        if ARXIV_HOSTS.contains(&host) {
+            if let Some(id) = url.path().strip_prefix("/abs/") {
+                let id = id.trim_end_matches('/');
+                return Url::parse(&format!("https://arxiv.org/abs/{id}"))
+                    .map_err(|e| e.to_string());
+            }
+        }
+    }
+
+    if url.query_pairs().any(|(k, _)| is_tracking_param(&k)) {
+        let kept = url
+            .query_pairs()
+            .filter(|(k, _)| !is_tracking_param(k))
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect::<Vec<_>>();
+        if kept.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    Ok(url)
+}
+
+/// The https version of an http url, if the host serves it.
+pub fn upgrade_https(client: &HttpClient, url: &Url) -> Option<Url> {
+    if url.scheme() != "http" || client.is_offline() {
+        return None;
+    }
+    let mut https = url.clone();
+    https.set_scheme("https").ok()?;
+    match client.head(&https) {
+        Ok(res) if res.status().is_success() => Some(https),
+        Ok(res) => {
+            debug!(%https, status = %res.status(), "Host doesn't serve https");
+            None
+        }
+        Err(err) => {
+            debug!(%https, %err, "Host doesn't serve https");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(url: &str, expected: Expect) {
+        let normal = normalise_url(url).map(|u| u.to_string());
+        expected.assert_debug_eq(&normal);
+    }
+
+    #[test]
+    fn test_tracking_params() {
+        check(
+            "https://example.com/paper?id=3&utm_source=twitter&fbclid=abc",
+            expect![[r#"
+                Ok(
+                    "https://example.com/paper?id=3",
+                )
+            "#]],
+        );
+        check(
+            "https://example.com/paper?utm_source=twitter#section",
+            expect![[r#"
+                Ok(
+                    "https://example.com/paper#section",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_arxiv() {
+        check(
+            "http://export.arxiv.org/abs/2101.00001v2/?context=cs",
+            expect![[r#"
+                Ok(
+                    "https://arxiv.org/abs/2101.00001v2",
+                )
+            "#]],
+        );
+        check(
+            "https://arxiv.org/pdf/2101.00001.pdf",
+            expect![[r#"
+                Ok(
+                    "https://arxiv.org/pdf/2101.00001.pdf",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        check(
+            "example.com/paper",
+            expect![[r#"
+                Err(
+                    "relative URL without a base",
+                )
+            "#]],
+        );
+    }
+}
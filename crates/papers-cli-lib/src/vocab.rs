@@ -0,0 +1,188 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::NaiveDate;
+use papers_core::{label::Label, paper::PaperMeta};
+use serde::Serialize;
+
+use crate::output::Render;
+
+/// How much a tag, label or author is used across the repo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Term {
+    /// The tag, label key, label as `key=value`, or author.
+    pub name: String,
+    /// Number of papers using it.
+    pub count: usize,
+    /// When the first paper using it was added.
+    pub first_used: NaiveDate,
+    /// When the last paper using it was added.
+    pub last_used: NaiveDate,
+}
+
+/// The complete sets of tags, labels and authors in the repo.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Vocabulary {
+    /// Tags of papers.
+    pub tags: Vec<Term>,
+    /// Keys of labels.
+    pub label_keys: Vec<Term>,
+    /// Labels with their values.
+    pub label_values: Vec<Term>,
+    /// Authors of papers.
+    pub authors: Vec<Term>,
+}
+
+#[derive(Default)]
+struct Terms(BTreeMap<String, Term>);
+
+impl Terms {
+    fn add(&mut self, name: String, added: NaiveDate) {
+        let term = self.0.entry(name.clone()).or_insert(Term {
+            name,
+            count: 0,
+            first_used: added,
+            last_used: added,
+        });
+        term.count += 1;
+        term.first_used = term.first_used.min(added);
+        term.last_used = term.last_used.max(added);
+    }
+
+    fn into_vec(self) -> Vec<Term> {
+        self.0.into_values().collect()
+    }
+}
+
+impl Vocabulary {
+    /// The vocabulary used by the papers, each term counted once per paper.
+    pub fn of<'a>(papers: impl IntoIterator<Item = &'a PaperMeta>) -> Self {
+        let mut tags = Terms::default();
+        let mut label_keys = Terms::default();
+        let mut label_values = Terms::default();
+        let mut authors = Terms::default();
+        for meta in papers {
+            let added = meta.created_at.date();
+            for tag in &meta.tags {
+                tags.add(tag.key().to_owned(), added);
+            }
+            for (key, value) in &meta.labels {
+                label_keys.add(key.clone(), added);
+                label_values.add(Label::new(key, value.clone()).to_string(), added);
+            }
+            let mut names = meta
+                .authors
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+            for name in names {
+                authors.add(name, added);
+            }
+        }
+        Self {
+            tags: tags.into_vec(),
+            label_keys: label_keys.into_vec(),
+            label_values: label_values.into_vec(),
+            authors: authors.into_vec(),
+        }
+    }
+
+    fn kinds(&self) -> [(&str, &[Term]); 4] {
+        [
+            ("tag", &self.tags),
+            ("label_key", &self.label_keys),
+            ("label_value", &self.label_values),
+            ("author", &self.authors),
+        ]
+    }
+}
+
+impl Display for Vocabulary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .kinds()
+            .into_iter()
+            .flat_map(|(kind, terms)| {
+                terms.iter().map(move |t| {
+                    format!(
+                        "{kind} {:?}: {} papers, first used {}, last used {}",
+                        t.name, t.count, t.first_used, t.last_used
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Render for Vocabulary {
+    fn header(&self) -> Vec<String> {
+        ["kind", "name", "count", "first_used", "last_used"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.kinds()
+            .into_iter()
+            .flat_map(|(kind, terms)| {
+                terms.iter().map(move |t| {
+                    vec![
+                        kind.to_owned(),
+                        t.name.clone(),
+                        t.count.to_string(),
+                        t.first_used.to_string(),
+                        t.last_used.to_string(),
+                    ]
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use papers_core::{author::Author, tag::Tag};
+
+    use super::*;
+
+    fn paper(day: u32, tags: &[&str], labels: &[(&str, &str)], authors: &[&str]) -> PaperMeta {
+        PaperMeta {
+            tags: tags.iter().map(|t| Tag::new(t)).collect(),
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.parse().unwrap()))
+                .collect(),
+            authors: authors.iter().map(|a| Author::new(a)).collect(),
+            created_at: NaiveDate::from_ymd_opt(2023, 8, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let papers = [
+            paper(3, &["db"], &[("venue", "osdi")], &["Ongaro", "Ousterhout"]),
+            paper(1, &["db", "consensus"], &[("venue", "podc")], &["Lamport"]),
+            paper(2, &[], &[("year", "2014")], &["Ongaro", "Ongaro"]),
+        ];
+        let vocab = Vocabulary::of(&papers);
+        expect![[r#"
+            tag "consensus": 1 papers, first used 2023-08-01, last used 2023-08-01
+            tag "db": 2 papers, first used 2023-08-01, last used 2023-08-03
+            label_key "venue": 2 papers, first used 2023-08-01, last used 2023-08-03
+            label_key "year": 1 papers, first used 2023-08-02, last used 2023-08-02
+            label_value "venue=osdi": 1 papers, first used 2023-08-03, last used 2023-08-03
+            label_value "venue=podc": 1 papers, first used 2023-08-01, last used 2023-08-01
+            label_value "year=2014": 1 papers, first used 2023-08-02, last used 2023-08-02
+            author "Lamport": 1 papers, first used 2023-08-01, last used 2023-08-01
+            author "Ongaro": 2 papers, first used 2023-08-02, last used 2023-08-03
+            author "Ousterhout": 1 papers, first used 2023-08-03, last used 2023-08-03"#]]
+        .assert_eq(&vocab.to_string());
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use papers_core::cancel::CancellationToken;
+
+/// How often to check for cancellation while waiting for the next look at the directory.
+const CANCEL_CHECK: Duration = Duration::from_millis(100);
+
+/// Whether the path names a PDF.
+fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+/// PDFs directly in `dir` with their sizes, in order of path.
+pub fn pdfs(dir: &Path) -> anyhow::Result<BTreeMap<PathBuf, u64>> {
+    let mut pdfs = BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // browsers download to a temporary name first, so only finished files are pdfs
+        if is_pdf(&path) && entry.file_type()?.is_file() {
+            pdfs.insert(path, entry.metadata()?.len());
+        }
+    }
+    Ok(pdfs)
+}
+
+/// A directory, such as downloads, that is looked at every so often for new PDFs.
+#[derive(Debug)]
+pub struct Inbox {
+    dir: PathBuf,
+    /// PDFs already handled, or there when the watch started.
+    seen: BTreeSet<PathBuf>,
+    /// New PDFs with the size they had when last looked at, waiting for them to stop changing.
+    pending: BTreeMap<PathBuf, u64>,
+}
+
+impl Inbox {
+    /// Start watching `dir`, leaving alone the PDFs already in it.
+    pub fn new(dir: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            dir: dir.to_owned(),
+            seen: pdfs(dir)?.into_keys().collect(),
+            pending: BTreeMap::new(),
+        })
+    }
+
+    /// New PDFs that have finished being written, being the same size as the last time they were
+    /// looked at.
+    pub fn poll(&mut self) -> anyhow::Result<Vec<PathBuf>> {
+        let current = pdfs(&self.dir)?;
+        // a file moved away and downloaded again is new once more
+        self.seen.retain(|p| current.contains_key(p));
+        let mut ready = Vec::new();
+        let mut pending = BTreeMap::new();
+        for (path, size) in current {
+            if self.seen.contains(&path) {
+                continue;
+            }
+            if size > 0 && self.pending.get(&path) == Some(&size) {
+                self.seen.insert(path.clone());
+                ready.push(path);
+            } else {
+                pending.insert(path, size);
+            }
+        }
+        self.pending = pending;
+        Ok(ready)
+    }
+
+    /// Wait for new PDFs, looking at the directory every `interval`, until cancelled.
+    pub fn wait(
+        &mut self,
+        interval: Duration,
+        cancel: &CancellationToken,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        loop {
+            let started = Instant::now();
+            while started.elapsed() < interval {
+                cancel.check()?;
+                std::thread::sleep(CANCEL_CHECK.min(interval));
+            }
+            let ready = self.poll()?;
+            if !ready.is_empty() {
+                return Ok(ready);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        std::fs::write(path("existing.pdf"), "old").unwrap();
+        let mut inbox = Inbox::new(dir.path()).unwrap();
+        assert_eq!(inbox.poll().unwrap(), Vec::<PathBuf>::new());
+
+        // new files are only ready once they stop growing
+        std::fs::write(path("raft.PDF"), "partial").unwrap();
+        std::fs::write(path("raft.pdf.part"), "partial").unwrap();
+        std::fs::write(path("notes.txt"), "text").unwrap();
+        assert_eq!(inbox.poll().unwrap(), Vec::<PathBuf>::new());
+        std::fs::write(path("raft.PDF"), "complete").unwrap();
+        assert_eq!(inbox.poll().unwrap(), Vec::<PathBuf>::new());
+        assert_eq!(inbox.poll().unwrap(), vec![path("raft.PDF")]);
+        assert_eq!(inbox.poll().unwrap(), Vec::<PathBuf>::new());
+
+        // moved away and downloaded again
+        std::fs::remove_file(path("raft.PDF")).unwrap();
+        assert_eq!(inbox.poll().unwrap(), Vec::<PathBuf>::new());
+        std::fs::write(path("raft.PDF"), "again").unwrap();
+        inbox.poll().unwrap();
+        assert_eq!(inbox.poll().unwrap(), vec![path("raft.PDF")]);
+    }
+}
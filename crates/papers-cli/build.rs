@@ -17,6 +17,11 @@ fn main() -> Result<(), Error> {
     gen_completions(shells::Zsh, &share_dir);
     gen_completions(shells::Fish, &share_dir);
 
+    let man_dir = share_dir.join("man");
+    create_dir_all(&man_dir).unwrap();
+    papers_cli_lib::man::gen_man_pages(&man_dir).unwrap();
+    println!("cargo:warning=man pages are generated: {:?}", man_dir);
+
     Ok(())
 }
 
@@ -1,21 +1,17 @@
 use clap::Parser;
 use directories::ProjectDirs;
+use papers_core::cancel::CancellationToken;
 use std::io;
 use tracing::debug;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
-use papers_cli_lib::cli::Cli;
+use papers_cli_lib::cli::{Cli, NoResults};
 use papers_cli_lib::config::Config;
+use papers_cli_lib::interactive::Cancelled;
+use papers_cli_lib::logging::file_layer;
 
 fn main() -> anyhow::Result<()> {
     let options = Cli::parse();
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::builder().from_env().unwrap())
-        .with_writer(io::stderr)
-        .init();
-
-    debug!(?options, "Parsed options");
-
     let config_file = if let Some(config_file) = options.config_file.as_ref() {
         config_file.clone()
     } else if let Some(dirs) = ProjectDirs::from("io", "jeffas", "papers") {
@@ -24,15 +20,53 @@ fn main() -> anyhow::Result<()> {
         anyhow::bail!("Failed to make project dirs")
     };
     let mut config = Config::load(&config_file)?;
+
+    tracing_subscriber::registry()
+        .with(file_layer(&config.logging)?)
+        .with(
+            fmt::layer()
+                .with_writer(io::stderr)
+                .with_filter(EnvFilter::builder().from_env()?),
+        )
+        .init();
+
+    debug!(?options, "Parsed options");
     debug!(?config, ?config_file, "Loaded config file");
 
     if let Some(default_repo) = options.default_repo {
         config.default_repo = default_repo;
     }
 
+    if options.offline {
+        config.http.offline = true;
+    }
+
     debug!(?config, "Merged config and options");
 
-    options.cmd.execute(&config)?;
+    let cancel = CancellationToken::new();
+    let handler_cancel = cancel.clone();
+    ctrlc::set_handler(move || {
+        if handler_cancel.is_cancelled() {
+            // a second interrupt forces us to quit
+            std::process::exit(130);
+        }
+        eprintln!("Cancelling, press Ctrl-C again to force quit");
+        handler_cancel.cancel();
+    })?;
+
+    match options.cmd.execute(
+        &config,
+        &config_file,
+        options.output,
+        options.progress,
+        &cancel,
+    ) {
+        // nothing matched, which is reported quietly through the exit code
+        Err(err) if err.is::<NoResults>() => std::process::exit(1),
+        // the user backed out of a prompt
+        Err(err) if err.is::<Cancelled>() => std::process::exit(130),
+        res => res?,
+    }
 
     Ok(())
 }
@@ -1,4 +1,9 @@
 mod common;
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+};
+
 use common::Fixture;
 use expect_test::expect;
 
@@ -13,16 +18,92 @@ fn test_help() {
             Usage: papers add [OPTIONS]
 
             Options:
-              -c, --config-file <CONFIG_FILE>    Config file path to load
-              -u, --url <URL>                    Url to fetch from
-                  --default-repo <DEFAULT_REPO>  Default repo to use if not found in parents of current directory
-                  --fetch <FETCH>                Whether to fetch the document from URL or not [possible values: true, false]
-              -f, --file <FILE>                  File to add
-                  --title <TITLE>                Title of the file
-              -a, --author <author>              Authors to associate with these files
-              -t, --tag <tag>                    Tags to associate with these files
-              -l, --label <label>                Labels to associate with these files. Labels take the form `key=value`
-              -h, --help                         Print help"#]],
+              -c, --config-file <CONFIG_FILE>
+                      Config file path to load
+
+              -u, --url <URL>
+                      Url to fetch from. A `file://` url or local path is copied into the repo instead
+
+                  --default-repo <DEFAULT_REPO>
+                      Default repo to use if not found in parents of current directory
+
+                  --fetch <FETCH>
+                      Whether to fetch the document from URL or not
+
+                      [possible values: true, false]
+
+              -f, --file <FILE>
+                      File to add
+
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
+                  --title <TITLE>
+                      Title of the file
+
+              -a, --author <author>
+                      Authors to associate with these files
+
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
+
+                      [default: none]
+
+                      Possible values:
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
+
+              -t, --tag <tag>
+                      Tags to associate with these files
+
+              -l, --label <label>
+                      Labels to associate with these files. Labels take the form `key=value`
+
+                  --snapshot
+                      Save a snapshot of the web page at the url as the document, rather than fetching it directly
+
+                  --doc-type <DOC_TYPE>
+                      Type of document, detected from the url if not given
+
+                  --rfc <RFC>
+                      Number of an IETF RFC to add, filling in its metadata and url
+
+                  --tech-report <TECH_REPORT>
+                      Id of a technical report to add, e.g. UCAM-CL-TR-123 or EECS-2009-28, filling in its metadata and url
+
+                  --doi <DOI>
+                      DOI of a paper to add, e.g. 10.1145/3132747.3132784, filling in its title, authors, year and journal from Crossref
+
+                  --isbn <ISBN>
+                      ISBN of a book to add, e.g. 978-1-4493-7332-0, filling in its title, authors, year and publisher from Open Library. The document type defaults to book
+
+                  --read-by <READ_BY>
+                      Date to read the paper by, e.g. 2023-08-01 or 2w from today
+
+                  --copy
+                      Copy a file from outside the repo into it, rather than asking
+
+                  --move
+                      Move a file from outside the repo into it, rather than asking
+
+                  --force
+                      Replace an existing file when fetching the document, rather than asking or saving it under a new name
+
+              -h, --help
+                      Print help (see a summary with '-h')"#]],
         expect![""],
     );
 }
@@ -54,7 +135,7 @@ fn test_add_present_file() {
     f.check_ok(
         "add --file file1.pdf",
         expect![""],
-        expect!["error: Failed to add paper: Is a directory (os error 21)"],
+        expect!["error: Failed to add paper: Paper has no title to name its notes file"],
     );
 }
 
@@ -74,7 +155,7 @@ fn test_add_file_from_nested_dir() {
     f.check_ok(
         "add --file nested/file1.pdf",
         expect![""],
-        expect!["error: Failed to add paper: Is a directory (os error 21)"],
+        expect!["error: Failed to add paper: Paper has no title to name its notes file"],
     );
 }
 
@@ -84,7 +165,7 @@ fn test_add_file_from_neighbour() {
     f.check_ok(
         "add --file ../neighbour/file1.pdf",
         expect![""],
-        expect!["error: Failed to add paper: Is a directory (os error 21)"],
+        expect!["error: Failed to add paper: Paper has no title to name its notes file"],
     );
 }
 
@@ -95,6 +176,210 @@ fn test_add_interactive() {
         "add",
         "",
         expect![""],
-        expect!["error: Failed to add paper: Is a directory (os error 21)"],
+        expect!["error: Failed to add paper: Paper has no title to name its notes file"],
+    );
+}
+
+#[test]
+fn test_add_offline_skips_fetch() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title offline-title --url https://example.com/paper.pdf --fetch true --offline",
+        expect![[r#"
+            Offline, skipping fetching documents
+            Added paper offline-title"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_add_duplicate_file() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first --file file1.pdf",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second --file nested/file1.pdf",
+        expect!["Added paper second"],
+        expect![[
+            r#"warning: "nested/file1.pdf" is a duplicate of "root/file1.pdf" from "first.md""#
+        ]],
+    );
+}
+
+#[test]
+fn test_add_local_url() {
+    let mut f = Fixture::new();
+    let outside = tempfile::tempdir().unwrap();
+    let path = outside.path().join("outside.pdf");
+    std::fs::write(&path, "outside pdf\n").unwrap();
+    f.check_ok(
+        &format!("add --title outside --url file://{}", path.display()),
+        expect!["Added paper outside"],
+        expect![""],
+    );
+    // relative paths are taken from the current directory, and files in the repo stay in place
+    f.check_ok(
+        "add --title nested --url nested/file1.pdf",
+        expect!["Added paper nested"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --format {filename}",
+        expect![[r#"
+        root/nested/file1.pdf
+        outside.pdf"#]],
+        expect![""],
+    );
+    assert!(path.is_file());
+}
+
+#[test]
+fn test_add_file_outside_repo() {
+    let mut f = Fixture::new();
+    let outside = tempfile::tempdir().unwrap();
+    let path = outside.path().join("outside.pdf");
+    std::fs::write(&path, "outside pdf\n").unwrap();
+    let path = path.display();
+    f.check_ok(
+        &format!("add --title left --file {path}"),
+        expect![""],
+        expect!["error: Failed to add paper: File does not live in the root"],
+    );
+    f.check_ok(
+        &format!("add --title copied --file {path} --copy"),
+        expect!["Added paper copied"],
+        expect![""],
     );
+    let path = outside.path().join("other.pdf");
+    std::fs::write(&path, "other pdf\n").unwrap();
+    f.check_ok(
+        &format!("add --title moved --file {} --move", path.display()),
+        expect!["Added paper moved"],
+        expect![""],
+    );
+    assert!(!path.exists());
+    f.check_ok(
+        "list --format {filename}",
+        expect![[r#"
+        outside.pdf
+        other.pdf"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_add_arxiv_offline() {
+    let mut f = Fixture::new();
+    // the document is fetched from the pdf rather than the abstract page
+    f.check_ok(
+        "add --url https://arxiv.org/abs/2101.00001v2 --title survey --offline",
+        expect![[r#"
+            Offline, skipping arXiv metadata for 2101.00001v2
+            Offline, skipping fetching documents
+            Added paper survey"#]],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{url}",
+        expect!["survey	https://arxiv.org/pdf/2101.00001v2"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_add_dir_defaults() {
+    let mut f = Fixture::new();
+    let nested = f.root_dir().join("nested");
+    std::fs::write(
+        nested.join(".papersdefaults.yaml"),
+        "tags: [teaching]\nlabels:\n  course: 101\nnotes_template: template.md\n",
+    )
+    .unwrap();
+    std::fs::write(nested.join("template.md"), "# Lecture notes\n").unwrap();
+
+    f.check_ok(
+        "add --file nested/file1.pdf --title lecture -l course=102",
+        expect!["Added paper lecture"],
+        expect![""],
+    );
+    // papers outside the directory don't get its defaults
+    f.check_ok(
+        "add --title other",
+        expect!["Added paper other"],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{tags}\t{labels}",
+        expect![[r#"
+            lecture	teaching	course=102
+            other"#]],
+        expect![""],
+    );
+    let notes = std::fs::read_to_string(f.root_dir().parent().unwrap().join("lecture.md")).unwrap();
+    assert!(notes.ends_with("# Lecture notes\n"), "{notes}");
+}
+
+#[test]
+fn test_add_dir_defaults_fetched() {
+    let mut f = Fixture::new();
+    let nested = f.root_dir().join("nested");
+    std::fs::write(nested.join(".papersdefaults.yaml"), "tags: [teaching]\n").unwrap();
+    // serves a document for the paper to fetch
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request);
+            let body = "test pdf";
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+
+    // the document is fetched outside the directory, but the paper is added from inside it
+    f.in_dir(nested);
+    f.check_ok(
+        &format!(
+            "add --url http://127.0.0.1:{port}/lecture.pdf --file ../lecture.pdf --fetch true \
+             --title lecture"
+        ),
+        expect!["Added paper lecture"],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{tags}\t{filename}",
+        expect!["lecture	teaching	root/lecture.pdf"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_add_book() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title ddia --doc-type book",
+        expect!["Added paper ddia"],
+        expect![""],
+    );
+    let notes = std::fs::read_to_string(f.root_dir().parent().unwrap().join("ddia.md")).unwrap();
+    assert!(notes.contains("doc_type: book"), "{notes}");
+    assert!(notes.contains("## Chapters\n\n### Chapter 1\n"), "{notes}");
+
+    let output = f.run("add --isbn 978-1-4493-7332-1");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid ISBN"), "{stderr}");
+
+    let output = f.run("add --isbn 978-1-4493-7332-0 --offline");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Offline, not fetching"), "{stderr}");
 }
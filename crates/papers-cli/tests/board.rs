@@ -0,0 +1,36 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+const PAPERS: &str = r#"[
+    {"title": "raft", "url": null, "filename": null, "tags": [], "labels": {}, "authors": ["Ongaro"], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": "2023-08-02T00:00:00", "next_review": null, "notes": "Leader based.\n"},
+    {"title": "paxos", "url": null, "filename": null, "tags": [], "labels": {}, "authors": ["Lamport"], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null, "notes": "Made simple.\n"},
+    {"title": "zab", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null}
+]"#;
+
+#[test]
+fn test_board_by_status() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    f.check_ok(
+        "board --by status",
+        expect![[r#"
+        ╭────────────┬─────────────┬──────────╮
+        │ unread (1) ┆ reading (1) ┆ read (1) │
+        ╞════════════╪═════════════╪══════════╡
+        │ zab        ┆ paxos       ┆ raft     │
+        │            ┆ Lamport     ┆ Ongaro   │
+        ╰────────────┴─────────────┴──────────╯"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "board -o plain",
+        expect![[r#"
+        unread	zab.md	zab
+        reading	paxos.md	paxos
+        read	raft.md	raft"#]],
+        expect![""],
+    );
+}
@@ -0,0 +1,91 @@
+mod common;
+use std::{path::Path, process::Command};
+
+use common::Fixture;
+use expect_test::expect;
+
+/// Make a Calibre library with just the tables that are read, returning false if sqlite3 isn't
+/// installed.
+fn make_library(library: &Path) -> bool {
+    let book = library.join("Diego Ongaro/Raft (1)");
+    std::fs::create_dir_all(&book).unwrap();
+    std::fs::write(book.join("Raft - Diego Ongaro.pdf"), "raft pdf\n").unwrap();
+    let sql = "
+        CREATE TABLE books (id INTEGER PRIMARY KEY, title TEXT, path TEXT, timestamp TIMESTAMP);
+        CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT);
+        CREATE TABLE books_authors_link (id INTEGER PRIMARY KEY, book INTEGER, author INTEGER);
+        CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT);
+        CREATE TABLE books_tags_link (id INTEGER PRIMARY KEY, book INTEGER, tag INTEGER);
+        CREATE TABLE identifiers (id INTEGER PRIMARY KEY, book INTEGER, type TEXT, val TEXT);
+        CREATE TABLE data (id INTEGER PRIMARY KEY, book INTEGER, format TEXT, name TEXT);
+        CREATE TABLE comments (id INTEGER PRIMARY KEY, book INTEGER, text TEXT);
+        INSERT INTO books VALUES
+            (1, 'Raft', 'Diego Ongaro/Raft (1)', '2024-05-01 10:00:00+00:00'),
+            (2, 'A Novel', 'Someone/A Novel (2)', '2024-05-02 10:00:00+00:00');
+        INSERT INTO authors VALUES (1, 'Diego Ongaro'), (2, 'John Ousterhout');
+        INSERT INTO books_authors_link VALUES (1, 1, 1), (2, 1, 2);
+        INSERT INTO tags VALUES (1, 'papers'), (2, 'consensus');
+        INSERT INTO books_tags_link VALUES (1, 1, 1), (2, 1, 2);
+        INSERT INTO identifiers VALUES (1, 1, 'doi', '10.5555/2643634.2643666');
+        INSERT INTO data VALUES (1, 1, 'PDF', 'Raft - Diego Ongaro');
+    ";
+    match Command::new("sqlite3")
+        .arg(library.join("metadata.db"))
+        .arg(sql)
+        .status()
+    {
+        Ok(status) => status.success(),
+        Err(_) => false,
+    }
+}
+
+#[test]
+fn test_import_export_calibre() {
+    let mut f = Fixture::new();
+    let library = tempfile::tempdir().unwrap();
+    if !make_library(library.path()) {
+        println!("Skipping, sqlite3 isn't installed");
+        return;
+    }
+    f.check_ok(
+        &format!("import --format calibre {}", library.path().display()),
+        expect!["Added paper Raft"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --format {title}|{filename}|{tags}|{labels}|{authors}",
+        expect!["Raft|Raft - Diego Ongaro.pdf|consensus,inbox|calibre_id=1,doi=10.5555/2643634.2643666|Diego Ongaro,John Ousterhout"],
+        expect![""],
+    );
+
+    let out = tempfile::tempdir().unwrap();
+    let output = f.run(&format!("export --format calibre {}", out.path().display()));
+    assert!(output.status.success());
+    let book = out.path().join("Raft");
+    assert!(book.join("Raft - Diego Ongaro.pdf").is_file());
+    let opf = std::fs::read_to_string(book.join("metadata.opf")).unwrap();
+    assert!(opf.contains("<dc:title>Raft</dc:title>"), "{opf}");
+}
+
+#[test]
+fn test_import_calibre_missing_library() {
+    let f = Fixture::new();
+    let library = tempfile::tempdir().unwrap();
+    let output = f.run(&format!(
+        "import --format calibre {}",
+        library.path().display()
+    ));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No Calibre library at"), "{stderr}");
+}
+
+#[test]
+fn test_import_directory_needs_calibre() {
+    let f = Fixture::new();
+    let library = tempfile::tempdir().unwrap();
+    let output = f.run(&format!("import {}", library.path().display()));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Path was not a file"), "{stderr}");
+}
@@ -0,0 +1,30 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_no_arxiv_papers() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first --url https://example.com/first.pdf",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    // nothing to ask arXiv about so this works offline
+    f.check_ok("check-updates --offline", expect![""], expect![""]);
+}
+
+#[test]
+fn test_arxiv_offline() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first --url https://arxiv.org/abs/2101.00001v1 --offline",
+        expect![[r#"
+            Offline, skipping arXiv metadata for 2101.00001v1
+            Offline, skipping fetching documents
+            Added paper first"#]],
+        expect![""],
+    );
+    let output = f.run("check-updates --offline");
+    assert!(!output.status.success());
+}
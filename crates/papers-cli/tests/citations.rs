@@ -0,0 +1,57 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+const PAPERS: &str = r#"[
+    {"title": "steady", "url": null, "filename": null, "tags": [], "labels": {"citations": 50, "citations_previous": 48}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "rising", "url": null, "filename": null, "tags": [], "labels": {"citations": 30, "citations_previous": 10}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "new", "url": null, "filename": null, "tags": [], "labels": {"citations": 5}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "unknown", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null}
+]"#;
+
+#[test]
+fn test_trending() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    f.check_ok(
+        "citations trending",
+        expect![[r#"
+            +20 rising (30 citations)
+            +2 steady (50 citations)"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "citations trending -n 1 -o plain",
+        expect!["rising.md	rising	30	20"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_sort_by_citations() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    f.check_ok(
+        "list --sort citations --format {title}",
+        expect![[r#"
+            steady
+            rising
+            new
+            unknown"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_update_offline() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "citations update --offline",
+        expect!["Offline, skipping updating citations"],
+        expect![""],
+    );
+}
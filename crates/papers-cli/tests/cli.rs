@@ -13,24 +13,87 @@ fn test_help() {
             Usage: papers [OPTIONS] <COMMAND>
 
             Commands:
-              add           Add a paper to the repo
-              list          List the papers stored with this repo
-              rename-files  Automatically rename files to match their entry in the database
-              edit          Edit the notes file for a paper
-              open          Open the pdf file for the given paper
-              review        Review papers that have been unseen too long
-              completions   Generate cli completion files
-              import        Import a list of tasks in json format
-              doctor        Check consistency of things in the repo
-              tags          List stats about tags
-              labels        List stats about labels
-              authors       List stats about authors
-              help          Print this message or the help of the given subcommand(s)
+              init           Create a repo in the default repo directory
+              add            Add a paper to the repo
+              list           List the papers stored with this repo
+              rename-files   Automatically rename files to match their entry in the database
+              edit           Edit the notes file for a paper
+              open           Open the pdf file for the given paper
+              review         Review papers that have been unseen too long
+              inbox          List the papers in the inbox, those imported but not yet triaged or reviewed
+              status         Count the papers due for review, in the inbox, queued to read and due to be read soon
+              triage         Go through papers one at a time, tagging, queueing or archiving each with a single key
+              completions    Generate cli completion files
+              man            Generate man pages for papers and each of its subcommands
+              help           Print help for a subcommand or a topic, see `papers help topics`
+              import         Import a list of tasks in json format, or yaml for files ending in `.yaml` or `.yml`
+              watch          Watch a directory, such as downloads, adding each new PDF that appears in it
+              schema         Print a JSON Schema for the frontmatter of notes files, or the json read by `import`, for editors and other tools to check them against
+              export         Export the notes files of papers, with their metadata, to a directory for sharing
+              publish        Write the papers tagged `public` as pages of a static site, e.g. the content directory of a blog
+              share          Upload the notes of a paper to the gist or paste service in the `share` config, printing the url they can be seen at
+              export-json    Print the papers as a json array, for external processing and backups
+              verify-export  Check that an export has everything in the repo, by reading it as `import` would and comparing each paper with the repo
+              doctor         Check consistency of things in the repo
+              dedupe         Merge papers whose documents have the same contents, moving all but the one kept in each group to the trash
+              check-updates  Check arXiv for newer versions of papers than the ones stored
+              fetch-missing  Fetch the documents of papers that have a url but no document, such as those added with `--fetch false`
+              deadlines      List papers with a read by date, most urgent first
+              citations      Track citation counts of papers from Semantic Scholar
+              log            Show the history of changes made to the repo
+              sed            Find and replace in a metadata field of papers with a sed style expression
+              snapshot       Save and restore the metadata of all papers
+              optimize-pdfs  Shrink pdf documents and linearise them for fast opening, using `optimize_command` from the config
+              thumbnails     Render thumbnails of the first pages of documents, using `thumbnails.command` from the config
+              du             Show how much disk space documents use, listing the largest
+              tags           List stats about tags
+              labels         List stats about labels
+              config         Share settings between machines
+              authors        List stats about authors
+              vocab          Work with the tags, labels and authors used across the repo
+              board          Show papers as cards in columns, such as how far through reading them is
+              tree           Show papers in a tree of groups, such as nested tags
+              report         Report what was added and read in a month, e.g. as markdown to share with `-o markdown`
+              remove         Move a paper to the trash, from where `papers trash restore` can put it back
+              trash          Work with papers that have been removed
+              hypothesis     Work with annotations made on Hypothes.is
+              readwise       Send highlights to Readwise and get back those made elsewhere
+              dblp           Work with publications listed on DBLP
 
             Options:
-              -c, --config-file <CONFIG_FILE>    Config file path to load
-                  --default-repo <DEFAULT_REPO>  Default repo to use if not found in parents of current directory
-              -h, --help                         Print help"#]],
+              -c, --config-file <CONFIG_FILE>
+                      Config file path to load
+
+                  --default-repo <DEFAULT_REPO>
+                      Default repo to use if not found in parents of current directory
+
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
+
+                      [default: none]
+
+                      Possible values:
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
+
+              -h, --help
+                      Print help (see a summary with '-h')"#]],
         expect![""],
     );
 }
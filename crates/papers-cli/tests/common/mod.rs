@@ -1,4 +1,12 @@
-use papers_cli_lib::config::{Config, PaperDefaults, PathOrString};
+use papers_cli_lib::config::{Config, ExistingFile, PaperDefaults, PathOrString};
+use papers_cli_lib::export::AnonymiseConfig;
+use papers_cli_lib::http::HttpConfig;
+use papers_cli_lib::hypothesis::HypothesisConfig;
+use papers_cli_lib::logging::LogConfig;
+use papers_cli_lib::readwise::ReadwiseConfig;
+use papers_cli_lib::share::ShareConfig;
+use papers_cli_lib::thumbnails::ThumbnailConfig;
+use papers_core::review::ReviewConfig;
 use std::fs::create_dir_all;
 use std::io::Write;
 use std::process::{Output, Stdio};
@@ -13,6 +21,7 @@ pub struct Fixture {
     do_init: bool,
     initialised: bool,
     debug: bool,
+    dir: Option<PathBuf>,
 }
 
 impl Fixture {
@@ -22,6 +31,7 @@ impl Fixture {
             do_init: true,
             initialised: false,
             debug: false,
+            dir: None,
         };
 
         create_dir_all(s.root_dir()).unwrap();
@@ -60,6 +70,12 @@ impl Fixture {
         self.debug = true;
     }
 
+    /// Run later commands from `dir` rather than the root.
+    #[allow(dead_code)]
+    pub fn in_dir(&mut self, dir: PathBuf) {
+        self.dir = Some(dir);
+    }
+
     pub fn root_dir(&self) -> PathBuf {
         self.root.path().join("root")
     }
@@ -69,6 +85,23 @@ impl Fixture {
             default_repo: self.root.path().to_owned(),
             notes_template: PathOrString::default(),
             paper_defaults: PaperDefaults::default(),
+            snapshot_command: None,
+            institutional_proxy: None,
+            optimize_command: None,
+            require_marker: false,
+            exact_filters: false,
+            existing_file: ExistingFile::default(),
+            label_columns: Vec::new(),
+            status_column: false,
+            notes_column: false,
+            http: HttpConfig::default(),
+            logging: LogConfig::default(),
+            thumbnails: ThumbnailConfig::default(),
+            anonymise: AnonymiseConfig::default(),
+            share: ShareConfig::default(),
+            hypothesis: HypothesisConfig::default(),
+            readwise: ReadwiseConfig::default(),
+            review: ReviewConfig::default(),
         }
     }
 
@@ -85,10 +118,10 @@ impl Fixture {
 
         let exe = PathBuf::from(env!("CARGO_BIN_EXE_papers"));
         println!("Found exe: {:?}", exe);
-        println!("Using dir: {:?}", self.root_dir());
+        let dir = self.dir.clone().unwrap_or_else(|| self.root_dir());
+        println!("Using dir: {:?}", dir);
         let mut cmd = Command::new(exe);
-        cmd.args(args.split_whitespace())
-            .current_dir(self.root_dir());
+        cmd.args(args.split_whitespace()).current_dir(dir);
         if self.debug {
             cmd.env("RUST_LOG", "debug");
         }
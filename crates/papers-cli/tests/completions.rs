@@ -0,0 +1,92 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_help() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "completions --help",
+        expect![[r#"
+            Generate cli completion files
+
+            Usage: papers completions [OPTIONS] [SHELL] [DIR]
+
+            Arguments:
+              [SHELL]
+                      Shell to generate for, detected from the environment if not given
+
+                      [possible values: bash, elvish, fish, powershell, zsh]
+
+              [DIR]
+                      Directory to save completion files to
+
+                      [default: .]
+
+            Options:
+              -c, --config-file <CONFIG_FILE>
+                      Config file path to load
+
+                  --install
+                      Install the completions into the standard location for the shell
+
+                  --default-repo <DEFAULT_REPO>
+                      Default repo to use if not found in parents of current directory
+
+                  --stdout
+                      Write the completions to stdout, e.g. for packaging
+
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
+
+                      [default: none]
+
+                      Possible values:
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
+
+              -h, --help
+                      Print help (see a summary with '-h')"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_stdout() {
+    let f = Fixture::new();
+    let output = f.run("completions fish --stdout");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("complete -c papers"), "{stdout}");
+}
+
+#[test]
+fn test_install_conflicts_with_stdout() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "completions fish --install --stdout",
+        expect![[""]],
+        expect![[r#"
+            error: the argument '--install' cannot be used with '--stdout'
+
+            Usage: papers completions --install --config-file <CONFIG_FILE> <SHELL> [DIR]
+
+            For more information, try '--help'."#]],
+    );
+}
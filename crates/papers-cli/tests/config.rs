@@ -0,0 +1,35 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_config_export_import() {
+    let mut f = Fixture::new();
+    f.check_ok("config export --diff", expect!["{}"], expect![""]);
+
+    let profile = f.root_dir().join("profile.yaml");
+    std::fs::write(
+        &profile,
+        "default_repo: /elsewhere\nrequire_marker: true\nhttp:\n  timeout_secs: 60\n",
+    )
+    .unwrap();
+    f.check_ok(
+        &format!("config import {}", profile.display()),
+        expect!["Imported the config profile"],
+        expect![""],
+    );
+    f.check_ok(
+        "config export --diff",
+        expect![[r#"
+        require_marker: true
+        http:
+          timeout_secs: 60"#]],
+        expect![""],
+    );
+    assert_eq!(
+        f.config().default_repo,
+        papers_cli_lib::config::Config::load(&f.config_path())
+            .unwrap()
+            .default_repo
+    );
+}
@@ -0,0 +1,22 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_import_offline() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "dblp import --author Diego_Ongaro --offline",
+        expect!["Offline, skipping searching DBLP"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_import_needs_author() {
+    let f = Fixture::new();
+    let output = f.run("dblp import");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--author <AUTHOR>"), "{stderr}");
+}
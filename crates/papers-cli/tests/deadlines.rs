@@ -0,0 +1,78 @@
+mod common;
+use common::Fixture;
+use expect_test::{expect, Expect};
+
+/// Check only some columns of plain output, leaving out those that depend on the time.
+fn check_columns(f: &Fixture, args: &str, columns: &[usize], expected: Expect) {
+    let output = f.run(args);
+    assert!(output.status.success());
+    let rows = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| {
+            let cells = l.split('\t').collect::<Vec<_>>();
+            columns
+                .iter()
+                .map(|&c| cells[c])
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect::<Vec<_>>();
+    expected.assert_eq(&rows.join("\n"));
+}
+
+#[test]
+fn test_deadlines() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title soon --read-by 3d",
+        expect!["Added paper soon"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title later --read-by 4w",
+        expect!["Added paper later"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title today --read-by 0d",
+        expect!["Added paper today"],
+        expect![""],
+    );
+    f.check_ok("add --title none", expect!["Added paper none"], expect![""]);
+
+    check_columns(
+        &f,
+        "deadlines -o plain",
+        &[0, 3],
+        expect![[r#"
+            today.md	0
+            soon.md	3"#]],
+    );
+    check_columns(
+        &f,
+        "deadlines --within 30 -o plain",
+        &[0, 3],
+        expect![[r#"
+            today.md	0
+            soon.md	3
+            later.md	28"#]],
+    );
+    check_columns(
+        &f,
+        "list --sort title -o plain",
+        &[0],
+        expect![[r#"
+            later
+            none
+            soon (due in 3d)
+            today (due today)"#]],
+    );
+}
+
+#[test]
+fn test_invalid_read_by() {
+    let f = Fixture::new();
+    let output = f.run("add --title soon --read-by tomorrow");
+    assert!(!output.status.success());
+}
@@ -0,0 +1,94 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+fn add_twice(f: &mut Fixture) {
+    f.check_ok(
+        "add --title first --file file1.pdf -t consensus",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second --file nested/file1.pdf -t distributed",
+        expect!["Added paper second"],
+        expect![[
+            r#"warning: "nested/file1.pdf" is a duplicate of "root/file1.pdf" from "first.md""#
+        ]],
+    );
+}
+
+#[test]
+fn test_dedupe() {
+    let mut f = Fixture::new();
+    add_twice(&mut f);
+    let output = f.run("dedupe --yes");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with(r#"Merged "second.md" into "first.md", moved to the trash as "#),
+        "{stdout}"
+    );
+    f.check_ok(
+        r"list --format {title}\t{tags}",
+        expect!["first	consensus,distributed"],
+        expect![""],
+    );
+    assert!(f.root_dir().join("file1.pdf").is_file());
+    assert!(!f.root_dir().join("nested/file1.pdf").exists());
+    f.check_ok(
+        "dedupe",
+        expect!["No duplicate documents found"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_duplicates() {
+    let mut f = Fixture::new();
+    add_twice(&mut f);
+    f.check_ok(
+        "doctor --offline",
+        expect![[r#"
+        File is corrupt, file is not a pdf. current="root/file1.pdf"
+        File at wrong path. current="root/file1.pdf", expected="first.pdf"
+        File is corrupt, file is not a pdf. current="root/nested/file1.pdf"
+        File at wrong path. current="root/nested/file1.pdf", expected="second.pdf"
+        Paper document is a duplicate of another's. paper="second.md", other="first.md""#]],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline --fix duplicate-file",
+        expect![[r#"
+            File is corrupt, file is not a pdf. current="root/file1.pdf"
+            File at wrong path. current="root/file1.pdf", expected="first.pdf"
+            File is corrupt, file is not a pdf. current="root/nested/file1.pdf"
+            File at wrong path. current="root/nested/file1.pdf", expected="second.pdf"
+            Paper document is a duplicate of another's. paper="second.md", other="first.md"
+            Merging paper. paper="second.md", into="first.md""#]],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{tags}",
+        expect!["first	consensus,distributed"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_fix_all_keeps_duplicates() {
+    let mut f = Fixture::new();
+    add_twice(&mut f);
+    let notes = std::fs::read_to_string(f.root_dir().parent().unwrap().join("first.md")).unwrap();
+    assert!(notes.contains("\nsha256: "), "{notes}");
+
+    // merging trashes papers, so fixing everything leaves duplicates unless asked for
+    let output = f.run("doctor --offline --fix");
+    assert!(output.status.success());
+    f.check_ok(
+        r"list --format {title}\t{tags}",
+        expect![[r#"
+            first	consensus
+            second	distributed"#]],
+        expect![""],
+    );
+}
@@ -0,0 +1,328 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+const PAPERS: &str = r#"[
+    {"title": "tracked", "url": "http://example.com/paper?id=1&utm_source=feed", "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "arxiv", "url": "http://export.arxiv.org/abs/2101.00001v2", "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "fine", "url": "https://example.com/", "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "invalid", "url": "example.com/paper", "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null}
+]"#;
+
+#[test]
+fn test_doctor_urls() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    f.check_ok(
+        "doctor --offline -o plain",
+        expect![[r#"
+            url-not-normalised	arxiv.md		https://arxiv.org/abs/2101.00001v2	false
+            invalid-url	invalid.md		url="example.com/paper", error="relative URL without a base"	false
+            url-not-normalised	tracked.md		http://example.com/paper?id=1	false"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline --fix",
+        expect![[r#"
+            Paper url is not normalised. paper="arxiv.md", expected="https://arxiv.org/abs/2101.00001v2"
+            Updating paper url. paper="arxiv.md", url="https://arxiv.org/abs/2101.00001v2"
+            Paper url is invalid. paper="invalid.md", url="example.com/paper", error="relative URL without a base"
+            Paper url is not normalised. paper="tracked.md", expected="http://example.com/paper?id=1"
+            Updating paper url. paper="tracked.md", url="http://example.com/paper?id=1""#]],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline",
+        expect![[r#"Paper url is invalid. paper="invalid.md", url="example.com/paper", error="relative URL without a base""#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --sort title --format {title}:{url}",
+        expect![[r#"
+            arxiv:https://arxiv.org/abs/2101.00001v2
+            fine:https://example.com/
+            invalid:example.com/paper
+            tracked:http://example.com/paper?id=1"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_corrupt_file() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title paper --file file1.pdf --url https://example.com/paper.pdf",
+        expect!["Added paper paper"],
+        expect![""],
+    );
+    std::fs::write(f.root_dir().join("file1.pdf"), "").unwrap();
+    f.check_ok(
+        "doctor --offline --refetch -o plain",
+        expect![[r#"
+        corrupt-file	root/file1.pdf		file is empty	false
+        file-wrong-path	root/file1.pdf	paper.pdf		false"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_ignored_files() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title paper",
+        expect!["Added paper paper"],
+        expect![""],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    std::fs::write(repo_root.join(".papersignore"), "template.md\n*.txt\n").unwrap();
+    // not a paper, so would fail to load
+    std::fs::write(repo_root.join("template.md"), "# {title}\n").unwrap();
+    std::fs::write(repo_root.join("draft.txt"), "draft").unwrap();
+    std::fs::write(repo_root.join("stray.pdf"), "stray").unwrap();
+
+    f.check_ok(
+        "doctor --offline -o plain",
+        expect!["unmatched-file	stray.pdf			false"],
+        expect![""],
+    );
+    f.check_ok("list --format {path}", expect!["paper.md"], expect![""]);
+}
+
+#[test]
+fn test_doctor_strict_tags() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title paper -t db -t dbs",
+        expect!["Added paper paper"],
+        expect![""],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    std::fs::write(
+        repo_root.join(".paperstags.yaml"),
+        "db:\n  description: Databases\n",
+    )
+    .unwrap();
+
+    f.check_ok("doctor --offline", expect![""], expect![""]);
+    f.check_ok(
+        "doctor --offline --strict",
+        expect![[r#"Paper tag is not registered. paper="paper.md", tag="dbs""#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_untouched_notes() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title annotated",
+        expect!["Added paper annotated"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title untouched",
+        expect!["Added paper untouched"],
+        expect![""],
+    );
+    let notes = f.root_dir().parent().unwrap().join("annotated.md");
+    let mut contents = std::fs::read_to_string(&notes).unwrap();
+    contents.push_str("\nWorth rereading.\n");
+    std::fs::write(&notes, contents).unwrap();
+
+    f.check_ok(
+        "doctor --offline --untouched-days 1",
+        expect![""],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline --untouched-days 0",
+        expect![[r#"Paper notes are still the template. paper="untouched.md", added=0 days ago"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_require_marker() {
+    let mut f = Fixture::new();
+    let mut config = f.config();
+    config.require_marker = true;
+    serde_yaml::to_writer(std::fs::File::create(f.config_path()).unwrap(), &config).unwrap();
+    f.check_ok(
+        "add --title paper",
+        expect!["Added paper paper"],
+        expect![""],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    assert!(std::fs::read_to_string(repo_root.join("paper.md"))
+        .unwrap()
+        .starts_with("---\npapers: true\n"));
+    // other notes in the same directory, with and without frontmatter
+    std::fs::write(
+        repo_root.join("daily.md"),
+        "---\ntitle: daily\n---\ntoday\n",
+    )
+    .unwrap();
+    std::fs::write(repo_root.join("scratch.md"), "just notes\n").unwrap();
+
+    f.check_ok("doctor --offline -o plain", expect![""], expect![""]);
+    f.check_ok("list --format {path}", expect!["paper.md"], expect![""]);
+}
+
+#[test]
+fn test_doctor_fix_interactive() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title a --file file1.pdf",
+        expect!["Added paper a"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title b --file nested/file1.pdf",
+        expect!["Added paper b"],
+        expect![[r#"warning: "nested/file1.pdf" is a duplicate of "root/file1.pdf" from "a.md""#]],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    // added in the same second, so the duplicate kept is the first by path
+    for name in ["a.md", "b.md"] {
+        let notes = std::fs::read_to_string(repo_root.join(name)).unwrap();
+        let notes = notes
+            .lines()
+            .map(|l| {
+                if l.starts_with("created_at:") {
+                    "created_at: 2024-05-01T00:00:00"
+                } else {
+                    l
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(repo_root.join(name), notes).unwrap();
+    }
+    std::fs::rename(repo_root.join("a.md"), repo_root.join("moved.md")).unwrap();
+
+    // fix all documents at the wrong path, but leave the notes
+    f.check_ok_with_stdin(
+        "doctor --offline --interactive -o plain",
+        "a\nn\n",
+        expect![[r#"
+            File at wrong path. current="root/nested/file1.pdf", expected="b.pdf"
+            Fix? [y]es, [n]o, [a]lways, ne[v]er: Paper notes at wrong path. current="moved.md", expected="a.md"
+            Fix? [y]es, [n]o, [a]lways, ne[v]er: Paper document is a duplicate of another's. paper="moved.md", other="b.md"
+            Fix? [y]es, [n]o, [a]lways, ne[v]er: corrupt-file	root/nested/file1.pdf		file is not a pdf	false
+            file-wrong-path	root/nested/file1.pdf	b.pdf		true
+            notes-wrong-path	moved.md	a.md		false
+            corrupt-file	root/file1.pdf		file is not a pdf	false
+            file-wrong-path	root/file1.pdf	a.pdf		true
+            duplicate-file	moved.md	b.md		false"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline -o plain",
+        expect![[r#"
+            corrupt-file	b.pdf		file is not a pdf	false
+            notes-wrong-path	moved.md	a.md		false
+            corrupt-file	a.pdf		file is not a pdf	false
+            duplicate-file	moved.md	b.md		false"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline --fix notes-wrong-path,url-not-normalised -o plain",
+        expect![[r#"
+            corrupt-file	b.pdf		file is not a pdf	false
+            notes-wrong-path	moved.md	a.md		true
+            corrupt-file	a.pdf		file is not a pdf	false
+            duplicate-file	b.md	a.md		false"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline -o plain",
+        expect![[r#"
+            corrupt-file	a.pdf		file is not a pdf	false
+            corrupt-file	b.pdf		file is not a pdf	false
+            duplicate-file	b.md	a.md		false"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_relink_missing_file() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title raft --file file1.pdf",
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    std::fs::rename(
+        f.root_dir().join("file1.pdf"),
+        repo_root.join("file1 (1).pdf"),
+    )
+    .unwrap();
+    std::fs::write(repo_root.join("unrelated.pdf"), "unrelated").unwrap();
+
+    f.check_ok(
+        "doctor --offline -o plain",
+        expect![[r#"
+        missing-file	raft.md	root/file1.pdf	file1 (1).pdf	false
+        unmatched-file	file1 (1).pdf			false
+        unmatched-file	unrelated.pdf			false"#]],
+        expect![""],
+    );
+    f.check_ok("doctor --offline --fix missing-file", expect![[r#"
+        File is not at the named location. current="raft.md", filename="root/file1.pdf", found="file1 (1).pdf"
+        Relinking file. current="raft.md", filename="file1 (1).pdf"
+        Found unmatched file "unrelated.pdf""#]], expect![""]);
+    f.check_ok(
+        "list --format {filename}",
+        expect!["file1 (1).pdf"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_doctor_progress_json() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    f.check_ok(
+        "doctor --offline --progress json",
+        expect![""],
+        expect![[r#"
+            {"event":"start","operation":"doctor","total":2}
+            {"event":"item","operation":"doctor","current":1,"total":2,"item":"first.md"}
+            {"event":"item","operation":"doctor","current":2,"total":2,"item":"second.md"}
+            {"event":"finish","operation":"doctor","total":2}"#]],
+    );
+}
+
+#[test]
+fn test_doctor_notes_collision() {
+    let mut f = Fixture::new();
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    std::fs::write(repo_root.join("a.pdf"), "%PDF-1.4\na\n%%EOF\n").unwrap();
+    std::fs::write(repo_root.join("b.pdf"), "%PDF-1.4\nb\n%%EOF\n").unwrap();
+    f.check_ok(
+        "add --title Raft --file ../a.pdf",
+        expect!["Added paper Raft"],
+        expect![""],
+    );
+    f.run("add --title RAFT --file ../b.pdf");
+    // the colliding paper's document is still its own, not an unmatched file
+    f.check_ok(
+        "doctor --offline -o plain",
+        expect![[r#"
+            file-wrong-path	b.pdf	RAFT.pdf		false
+            notes-collision	Raft.md	RAFT.md		false"#]],
+        expect![""],
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_du() {
+    let mut f = Fixture::new();
+    std::fs::write(f.root_dir().join("big.pdf"), vec![b'a'; 3000]).unwrap();
+    f.check_ok(
+        "add --title small --file file1.pdf --tag db --tag to-read",
+        expect!["Added paper small"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title big --file big.pdf --tag db",
+        expect!["Added paper big"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title none --tag ml",
+        expect!["Added paper none"],
+        expect![""],
+    );
+
+    f.check_ok(
+        "du",
+        expect![[r#"
+            root/big.pdf: 2.9 KiB
+            root/file1.pdf: 9 B"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "du -n 1 -o plain",
+        expect!["root/big.pdf	big.md	3000"],
+        expect![""],
+    );
+    f.check_ok(
+        "du --by tag",
+        expect![[r#"
+            db: 2.9 KiB in 2 papers
+            to-read: 9 B in 1 papers"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "du --by status -o plain",
+        expect![[r#"
+        (none)	1	3000
+        to-read	1	9"#]],
+        expect![""],
+    );
+}
@@ -0,0 +1,324 @@
+mod common;
+use std::{
+    fs::{read_to_string, write, File},
+    path::Path,
+    str::from_utf8,
+};
+
+use common::Fixture;
+use expect_test::{expect, Expect};
+use papers_core::tag::Tag;
+
+const PAPERS: &str = r#"[
+    {"title": "first", "url": null, "filename": null, "tags": ["db", "reviewer"], "labels": {"rating": 2, "venue": "osdi"}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": "2023-08-02T00:00:00", "next_review": "2023-08-04T00:00:00"}
+]"#;
+
+const NOTES: &str =
+    "# Summary\nA database.\n\n## Private\nThe reviews were harsh.\n\n## Questions\nWhy?\n";
+
+fn check_exported(path: &Path, expected: Expect) {
+    let contents = read_to_string(path).unwrap();
+    // modification and import times vary so are left out
+    let contents = contents
+        .lines()
+        .filter(|l| !l.starts_with("modified_at") && !l.starts_with("  at:"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    expected.assert_eq(&contents);
+}
+
+#[test]
+fn test_export() {
+    let f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+    let notes_path = f.root_dir().parent().unwrap().join("first.md");
+    let contents = read_to_string(&notes_path).unwrap();
+    write(&notes_path, format!("{contents}{NOTES}")).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let output = f.run(&format!("export {}", out.path().display()));
+    assert!(output.status.success());
+    check_exported(
+        &out.path().join("first.md"),
+        expect![[r#"
+            ---
+            title: first
+            url: null
+            filename: null
+            tags:
+            - db
+            - inbox
+            - reviewer
+            labels:
+              rating: 2
+              venue: osdi
+            authors: []
+            created_at: 2023-08-01T00:00:00
+            last_review: 2023-08-02T00:00:00
+            next_review: 2023-08-04T00:00:00
+            provenance:
+              source: import
+              detail: stdin
+            ---
+            # Summary
+            A database.
+
+            ## Private
+            The reviews were harsh.
+
+            ## Questions
+            Why?"#]],
+    );
+}
+
+#[test]
+fn test_export_anonymised() {
+    let f = Fixture::new();
+    let mut config = f.config();
+    config.anonymise.labels.insert("rating".to_owned());
+    config.anonymise.tags.insert(Tag::new("reviewer"));
+    config.anonymise.sections.push("Private".to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+    let notes_path = f.root_dir().parent().unwrap().join("first.md");
+    let contents = read_to_string(&notes_path).unwrap();
+    write(&notes_path, format!("{contents}{NOTES}")).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let output = f.run(&format!(
+        "export {} --anonymise --tag db",
+        out.path().display()
+    ));
+    assert!(output.status.success());
+    check_exported(
+        &out.path().join("first.md"),
+        expect![[r#"
+            ---
+            title: first
+            url: null
+            filename: null
+            tags:
+            - db
+            - inbox
+            labels:
+              venue: osdi
+            authors: []
+            created_at: 2023-08-01T00:00:00
+            last_review: null
+            next_review: null
+            ---
+            # Summary
+            A database.
+
+            ## Questions
+            Why?"#]],
+    );
+}
+
+#[test]
+fn test_export_inside_repo() {
+    let f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    // exporting into the repo would overwrite its notes with the exported copies
+    for dir in [".", "..", "out", "../nested/out"] {
+        let output = f.run(&format!("export {dir}"));
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("is inside the repo"), "{stderr}");
+    }
+    assert!(!f.root_dir().join("out").exists());
+}
+
+#[test]
+fn test_export_json() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+    let notes_path = f.root_dir().parent().unwrap().join("first.md");
+    let contents = read_to_string(&notes_path).unwrap();
+    write(&notes_path, format!("{contents}{NOTES}")).unwrap();
+    f.check_ok(
+        "add --file file1.pdf --title second",
+        expect!["Added paper second"],
+        expect![""],
+    );
+
+    let output = f.run("export-json");
+    assert!(output.status.success());
+    let papers: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(papers[0]["path"], "first.md");
+    assert_eq!(papers[0]["title"], "first");
+    assert!(papers[0].get("notes").is_none());
+    assert!(papers[1].get("attachments").is_none());
+
+    let output = f.run("export-json --full");
+    assert!(output.status.success());
+    let papers: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(papers[0]["notes"].as_str().unwrap().trim(), NOTES.trim());
+    assert_eq!(papers[0]["attachments"], serde_json::json!([]));
+    assert_eq!(papers[1]["path"], "second.md");
+    assert_eq!(
+        papers[1]["attachments"],
+        serde_json::json!(["root/file1.pdf"])
+    );
+}
+
+/// The fields of the export bundle, which importing and other tools rely on.
+#[test]
+fn test_export_json_format() {
+    let f = Fixture::new();
+    let output = f.run_with_stdin("import - --no-inbox", PAPERS);
+    assert!(output.status.success());
+    let notes_path = f.root_dir().parent().unwrap().join("first.md");
+    let contents = read_to_string(&notes_path).unwrap();
+    write(&notes_path, format!("{contents}{NOTES}")).unwrap();
+
+    let output = f.run("export-json --full");
+    assert!(output.status.success());
+    // modification and import times vary so are left out
+    let exported = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|l| !l.contains("\"modified_at\"") && !l.contains("\"at\""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r##"
+        [
+          {
+            "path": "first.md",
+            "title": "first",
+            "url": null,
+            "filename": null,
+            "tags": [
+              "db",
+              "reviewer"
+            ],
+            "labels": {
+              "rating": 2,
+              "venue": "osdi"
+            },
+            "authors": [],
+            "created_at": "2023-08-01T00:00:00",
+            "last_review": "2023-08-02T00:00:00",
+            "next_review": "2023-08-04T00:00:00",
+            "provenance": {
+              "source": "import",
+              "detail": "stdin",
+            },
+            "notes": "# Summary\nA database.\n\n## Private\nThe reviews were harsh.\n\n## Questions\nWhy?\n",
+            "attachments": []
+          }
+        ]"##]].assert_eq(&exported);
+}
+
+#[test]
+fn test_verify_export() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+    let notes_path = f.root_dir().parent().unwrap().join("first.md");
+    let contents = read_to_string(&notes_path).unwrap();
+    write(&notes_path, format!("{contents}{NOTES}")).unwrap();
+    f.check_ok(
+        "add --title second -t ml",
+        expect!["Added paper second"],
+        expect![""],
+    );
+
+    let full = f.run("export-json --full");
+    write(f.root_dir().join("full.json"), &full.stdout).unwrap();
+    f.check_ok(
+        "verify-export full.json",
+        expect!["Export matches the repo, 2 papers"],
+        expect![""],
+    );
+    let yaml = f.run("list -o yaml");
+    write(f.root_dir().join("list.yaml"), &yaml.stdout).unwrap();
+    f.check_ok(
+        "verify-export list.yaml",
+        expect!["Export matches the repo, 2 papers"],
+        expect![""],
+    );
+    let ml = f.run("export-json --tag ml");
+    write(f.root_dir().join("ml.json"), &ml.stdout).unwrap();
+    f.check_ok(
+        "verify-export ml.json --tag ml",
+        expect!["Export matches the repo, 1 papers"],
+        expect![""],
+    );
+    f.check_ok(
+        "verify-export ml.json -o plain",
+        expect!["first.md	paper	first"],
+        expect!["error: Export differs from the repo in 1 places"],
+    );
+
+    write(&notes_path, format!("{contents}# Summary\nChanged.\n")).unwrap();
+    let output = f.run("verify-export full.json");
+    assert_eq!(output.status.code(), Some(1));
+    f.check_ok(
+        "verify-export full.json",
+        expect![[r##"Field differs. path="first.md", field=notes, repo="# Summary\nChanged.\n", export="# Summary\nA database.\n\n## Private\nThe reviews were harsh.\n\n## Questions\nWhy?\n""##]],
+        expect!["error: Export differs from the repo in 1 places"],
+    );
+}
+
+#[test]
+fn test_import_full_export() {
+    let f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+    let notes_path = f.root_dir().parent().unwrap().join("first.md");
+    let contents = read_to_string(&notes_path).unwrap();
+    write(&notes_path, format!("{contents}{NOTES}")).unwrap();
+    let full = f.run("export-json --full");
+
+    let mut restored = Fixture::new();
+    let output = restored.run_with_stdin("import - --no-inbox", from_utf8(&full.stdout).unwrap());
+    assert!(output.status.success());
+    let notes = read_to_string(restored.root_dir().parent().unwrap().join("first.md")).unwrap();
+    assert!(notes.ends_with(NOTES), "{notes}");
+    restored.check_ok("list --format {title}", expect!["first"], expect![""]);
+}
+
+#[test]
+fn test_import_resume() {
+    let mut f = Fixture::new();
+    let papers = r#"[
+        {"title": "first", "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00"},
+        {"title": "second", "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00"}
+    ]"#;
+    f.check_ok_with_stdin(
+        "import - --resume",
+        "[]",
+        expect![""],
+        expect!["warning: No interrupted import to resume, starting over"],
+    );
+    // the second paper can't be written, interrupting the import
+    let repo = f.root_dir().parent().unwrap().to_owned();
+    std::fs::create_dir(repo.join("second.md")).unwrap();
+    let output = f.run_with_stdin("import -", papers);
+    assert!(!output.status.success());
+    std::fs::remove_dir(repo.join("second.md")).unwrap();
+    write(
+        repo.join("first.md"),
+        "---\ntitle: first\n---\nedited since",
+    )
+    .unwrap();
+
+    // the first paper isn't imported again, keeping its edits
+    f.check_ok_with_stdin(
+        "import - --resume",
+        papers,
+        expect!["Added paper second"],
+        expect![""],
+    );
+    assert_eq!(
+        read_to_string(repo.join("first.md")).unwrap(),
+        "---\ntitle: first\n---\nedited since"
+    );
+}
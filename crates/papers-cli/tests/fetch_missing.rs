@@ -0,0 +1,91 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+use papers_core::hashes::hash_bytes;
+
+#[test]
+fn test_add_without_fetching() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title later --url https://example.com/later.pdf --fetch false",
+        expect!["Added paper later"],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{tags}",
+        expect!["later	to-fetch"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_fetch_missing_offline() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title later --url https://example.com/later.pdf --fetch false",
+        expect!["Added paper later"],
+        expect![""],
+    );
+    f.check_ok(
+        "fetch-missing --offline",
+        expect!["Offline, skipping fetching documents"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_fetch_missing_skips_talks() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title talk --doc-type talk --url https://example.com/talk -t watch",
+        expect!["Added paper talk"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title later --url https://example.com/later.pdf --fetch false",
+        expect!["Added paper later"],
+        expect![""],
+    );
+    // talks are opened by their url, and the other paper doesn't match the filter
+    f.check_ok(
+        "fetch-missing -t watch",
+        expect!["Fetched 0, failed 0"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_fetch_missing_resume() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title later --url https://example.com/later.pdf --fetch false",
+        expect!["Added paper later"],
+        expect![""],
+    );
+    // an earlier run fetched the document but was killed before attaching it
+    let repo = f.root_dir().parent().unwrap().to_owned();
+    std::fs::write(repo.join("later.pdf"), "%PDF-1.4").unwrap();
+    std::fs::create_dir_all(repo.join(".papers/checkpoints")).unwrap();
+    let input = hash_bytes(br#"[["later.md","https://example.com/later.pdf"]]"#);
+    std::fs::write(
+        repo.join(".papers/checkpoints/fetch-missing.jsonl"),
+        format!("{{\"input\":\"{input}\"}}\n{{\"item\":\"later.md\",\"value\":{{\"filename\":\"later.pdf\",\"mirror\":null}}}}\n"),
+    )
+    .unwrap();
+
+    f.check_ok(
+        "fetch-missing --resume",
+        expect![[r#"
+            Fetched "later.pdf" for "later.md"
+            Fetched 1, failed 0"#]],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{filename}\t{tags}",
+        expect!["later	later.pdf"],
+        expect![""],
+    );
+    assert!(!repo
+        .join(".papers/checkpoints/fetch-missing.jsonl")
+        .exists());
+}
@@ -0,0 +1,108 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_topics() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "help topics",
+        expect![[r#"
+        Help topics:
+          reviews       Spaced repetition of papers with `papers review`
+          query-syntax  Filtering papers in `papers list`
+          templates     Templates for the notes of new papers
+    "#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_topic() {
+    let mut f = Fixture::new();
+    f.check_ok("help reviews", expect![[r#"
+        Papers can be reviewed periodically so that their contents stay fresh.
+
+        Running `papers review` lets you pick from the papers that are due for review and opens their
+        notes in $EDITOR (add `--open` to open the document too). Once the editor closes the review is
+        recorded in the paper's `last_review` and `next_review` fields.
+
+        After editing you are asked how well you remembered the paper: again, hard, good or easy. Again
+        starts the waits over, hard makes the next wait only a little longer, good lengthens it as usual
+        and easy a further third. Press enter to skip rating, which counts as good. The ratings are kept,
+        with when they were given, in the paper's `ratings` field.
+
+        A paper that has never been reviewed is always due. After each review the wait until the next one
+        grows: the first waits are one and two days, after that the number of days between the last two
+        reviews is raised to the configured `review.power`. With the default power of 2 reviews happen
+        after 1, 2, 4, 16, 256, ... days.
+
+        The review dates are plain frontmatter fields, so they can be edited by hand to bring a review
+        forward or push it back."#]], expect![""]);
+}
+
+#[test]
+fn test_subcommand() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "help open",
+        expect![[r#"
+            Open the pdf file for the given paper
+
+            Usage: papers open [OPTIONS] [PATH]
+
+            Arguments:
+              [PATH]
+                      Path of the paper to open, part of its title, a glob, or `@last-added` or `@last-edited`. Fuzzy selected if not given
+
+            Options:
+              -c, --config-file <CONFIG_FILE>
+                      Config file path to load
+
+                  --default-repo <DEFAULT_REPO>
+                      Default repo to use if not found in parents of current directory
+
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
+
+                      [default: none]
+
+                      Possible values:
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
+
+              -h, --help
+                      Print help (see a summary with '-h')
+        "#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_unknown() {
+    let f = Fixture::new();
+    let output = f.run("help nothing");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr
+            .contains(r#"No subcommand or help topic called "nothing", see `papers help topics`"#),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,34 @@
+mod common;
+use std::fs::File;
+
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_sync_without_user() {
+    let f = Fixture::new();
+    let output = f.run("hypothesis sync");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No Hypothes.is user"), "{stderr}");
+}
+
+#[test]
+fn test_sync_offline() {
+    let mut f = Fixture::new();
+    let mut config = f.config();
+    config.hypothesis.user = Some("reader".to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    f.check_ok(
+        "add --title raft --url https://raft.github.io/raft.pdf --offline",
+        expect![[r#"
+            Offline, skipping fetching documents
+            Added paper raft"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "hypothesis sync --offline",
+        expect!["Offline, skipping syncing annotations"],
+        expect![""],
+    );
+}
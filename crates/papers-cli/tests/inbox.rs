@@ -0,0 +1,59 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+const PAPERS: &str = r#"[
+    {"title": "first", "url": null, "filename": null, "tags": ["db"], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null},
+    {"title": "second", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-02T00:00:00", "modified_at": "2023-08-02T00:00:00", "last_review": null, "next_review": null}
+]"#;
+
+#[test]
+fn test_inbox_triage() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+    f.check_ok(
+        "add --title added",
+        expect!["Added paper added"],
+        expect![""],
+    );
+
+    // queue the first, skip the second
+    f.check_ok_with_stdin(
+        "inbox --triage",
+        "q\ns\n",
+        expect![[r#"
+
+            (1/2) first
+              path:    first.md
+              tags:    db, inbox
+            [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:
+            (2/2) second
+              path:    second.md
+              tags:    inbox
+            [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --sort title --format {title}:{tags}",
+        expect![[r#"
+            added:
+            first:db,to-read
+            second:inbox"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_import_no_inbox() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import - --no-inbox", PAPERS);
+    assert!(output.status.success());
+    f.check_ok(
+        "list --sort title --format {title}:{tags}",
+        expect![[r#"
+            first:db
+            second:"#]],
+        expect![""],
+    );
+}
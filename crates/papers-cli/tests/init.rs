@@ -0,0 +1,67 @@
+mod common;
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
+
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_init_template() {
+    let mut f = Fixture::new();
+    f.no_init();
+    let template = tempfile::tempdir().unwrap();
+    create_dir_all(template.path().join("templates")).unwrap();
+    write(
+        template.path().join("config.yaml"),
+        "notes_template:\n  file: templates/notes.md\n",
+    )
+    .unwrap();
+    write(
+        template.path().join("templates/notes.md"),
+        "# Group notes\n",
+    )
+    .unwrap();
+    write(template.path().join(".papersignore"), "templates/\n").unwrap();
+    write(
+        template.path().join("example.md"),
+        "---\ntitle: Example\nurl: null\nfilename: null\ntags: []\nlabels: {}\nauthors: []\ncreated_at: 2023-01-01T00:00:00\nmodified_at: 2023-01-01T00:00:00\nlast_review: null\nnext_review: null\n---\n",
+    )
+    .unwrap();
+    let root = f.root_dir().parent().unwrap().to_owned();
+    remove_file(f.config_path()).unwrap();
+
+    f.check_ok(
+        &format!(
+            "init --template {} --default-repo {}",
+            template.path().display(),
+            root.display()
+        ),
+        expect![[r#"
+            Copied .papersignore
+            Copied example.md
+            Copied templates/notes.md
+            Wrote the template's config"#]],
+        expect![""],
+    );
+    f.check_ok("add --title new", expect!["Added paper new"], expect![""]);
+    f.check_ok(
+        "list --sort title --format {title}",
+        expect![[r#"
+            Example
+            new"#]],
+        expect![""],
+    );
+    assert!(read_to_string(root.join("new.md"))
+        .unwrap()
+        .ends_with("# Group notes\n"));
+
+    // an existing config and files are left alone
+    f.check_ok(
+        &format!("init --template {}", template.path().display()),
+        expect![""],
+        expect![[r#"
+            warning: Not overwriting .papersignore
+            warning: Not overwriting example.md
+            warning: Not overwriting templates/notes.md
+            warning: Not overwriting the existing config file with the template's config"#]],
+    );
+}
@@ -0,0 +1,45 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_labels_remap() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -l venue=osdi",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second -l venue=OSDI",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title third -l venue=sosp",
+        expect!["Added paper third"],
+        expect![""],
+    );
+    std::fs::write(f.root_dir().join("map.yaml"), "osdi: OSDI\n").unwrap();
+
+    f.check_ok(
+        "labels remap --key venue --map map.yaml --dry-run",
+        expect![[r#"
+            "first.md" label.venue
+            - osdi
+            + OSDI"#]],
+        expect![[r#"warning: Unmapped values for venue: "sosp" (1)"#]],
+    );
+    f.check_ok(
+        "labels remap --key venue --map map.yaml -o plain",
+        expect!["first.md	label.venue	osdi	OSDI"],
+        expect![[r#"warning: Unmapped values for venue: "sosp" (1)"#]],
+    );
+    f.check_ok(
+        "labels --sort -o plain",
+        expect![[r#"
+            venue=sosp	1
+            venue=OSDI	2"#]],
+        expect![""],
+    );
+}
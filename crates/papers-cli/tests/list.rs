@@ -28,34 +28,323 @@ fn test_help() {
               -a, --author <author>
                       Filter down to papers that have all of the given authors
 
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
               -t, --tag <tag>
                       Filter down to papers that have all of the given tags
 
               -l, --label <label>
                       Filter down to papers that have all of the given labels. Labels take the form `key=value`
 
-              -o, --output <OUTPUT>
-                      Output the filtered selection of papers in different formats
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
 
-                      [default: table]
+                      [default: none]
 
                       Possible values:
-                      - table: Pretty table format
-                      - json:  Json format
-                      - yaml:  Yaml format
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
 
-                  --sort <SORT>
-                      Sort entries by a criterion
+                  --source <SOURCE>
+                      Filter down to papers that entered the repo this way: add or import
+
+                  --added-since <ADDED_SINCE>
+                      Filter down to papers added since this time, a date like 2023-08-01 or an age like 2w
+
+                  --added-before <ADDED_BEFORE>
+                      Filter down to papers added before this time, a date like 2023-08-01 or an age like 2w
+
+                  --modified-since <MODIFIED_SINCE>
+                      Filter down to papers changed since this time, a date like 2023-08-01 or an age like 2w
+
+                  --modified-before <MODIFIED_BEFORE>
+                      Filter down to papers changed before this time, a date like 2023-08-01 or an age like 2w
+
+                  --read-since <READ_SINCE>
+                      Filter down to papers last reviewed since this time, a date like 2023-08-01 or an age like 1mo
+
+                  --read-before <READ_BEFORE>
+                      Filter down to papers last reviewed before this time, a date like 2023-08-01 or an age like 1mo. Papers never reviewed count as reviewed before any time
+
+                  --title-re <TITLE_RE>
+                      Filter down to papers whose titles match this regular expression
+
+                  --file-re <FILE_RE>
+                      Filter down to papers whose document filenames match this regular expression
+
+                  --author-re <AUTHOR_RE>
+                      Filter down to papers with an author whose name matches this regular expression
+
+                  --has-notes
+                      Filter down to papers with notes written beyond the notes template
 
-                      [default: title]
+                  --no-notes
+                      Filter down to papers with nothing written in their notes beyond the notes template
+
+              -q, --query <QUERY>
+                      Filter down to papers that fuzzy match this across their titles, authors, tags and labels, as in the fuzzy picker, best matches first
+
+                  --sort <SORT>
+                      Sort entries by a criterion, by title if not given unless ranking matches of `--query`
 
                       Possible values:
                       - title:       Sort by title
                       - created-at:  Sort by creation
                       - modified-at: Sort by modification
+                      - citations:   Sort by citation count, most cited first
+                      - frecency:    Sort by how often and how recently papers were opened or edited, most first
+
+                  --format <FORMAT>
+                      Print each paper on a line using a template of fields, e.g. '{title}\t{url}'. See `papers help query-syntax` for the available fields
+
+                  --fail-if-empty
+                      Exit with status 1 if no papers match
 
               -h, --help
                       Print help (see a summary with '-h')"#]],
         expect![""],
     );
 }
+
+#[test]
+fn test_list_format() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -t to-read --url https://example.com/first.pdf --fetch false",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {title}\t{url}\t{tags}",
+        expect!["first	https://example.com/first.pdf	to-fetch,to-read"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list_fail_if_empty() {
+    let mut f = Fixture::new();
+    f.check_ok("list --fail-if-empty", expect![""], expect![""]);
+    assert_eq!(f.run("list --fail-if-empty").status.code(), Some(1));
+
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    assert_eq!(f.run("list --fail-if-empty").status.code(), Some(0));
+    assert_eq!(
+        f.run("list --fail-if-empty --title other").status.code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_list_extra_fields() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    let path = f.root_dir().parent().unwrap().join("first.md");
+    let content = std::fs::read_to_string(&path).unwrap();
+    let content = content.replacen("---\n", "---\nextra:\n  venue: SOSP\n  pages: 12\n", 1);
+    std::fs::write(&path, content).unwrap();
+
+    f.check_ok(
+        r"list --label venue=SOSP --format {title}\t{extra.venue}\t{extra.pages}",
+        expect!["first	SOSP	12"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list_source() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title added",
+        expect!["Added paper added"],
+        expect![""],
+    );
+    let output = f.run_with_stdin(
+        "import -",
+        r#"[{"title": "imported", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": null, "next_review": null}]"#,
+    );
+    assert!(output.status.success());
+    f.check_ok(
+        r"list --source import --format {title}\t{source_detail}",
+        expect!["imported	stdin"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --source add --format {title}",
+        expect!["added"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list_time_filters() {
+    let mut f = Fixture::new();
+    f.check_ok("add --title new", expect!["Added paper new"], expect![""]);
+    let output = f.run_with_stdin(
+        "import -",
+        r#"[{"title": "old", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2023-08-01T00:00:00", "modified_at": "2023-08-01T00:00:00", "last_review": "2023-08-02T00:00:00", "next_review": null}]"#,
+    );
+    assert!(output.status.success());
+    f.check_ok(
+        "list --added-since 2w --format {title}",
+        expect!["new"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --added-before 2023-09-01 --format {title}",
+        expect!["old"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --read-since 2023-08-01 --format {title}",
+        expect!["old"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --read-before 2023-08-01 --format {title}",
+        expect!["new"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list_query() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title handcrafted-types",
+        expect!["Added paper handcrafted-types"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title raft -a Ongaro",
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title paxos -t consensus",
+        expect!["Added paper paxos"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --query rft --format {title}",
+        expect![[r#"
+            raft
+            handcrafted-types"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --query consen --format {title}",
+        expect!["paxos"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --query rft --sort title --format {title}",
+        expect![[r#"
+            handcrafted-types
+            raft"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list_folded_filters() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -a Müller -t ml",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --author muller --format {title}",
+        expect!["first"],
+        expect![""],
+    );
+    f.check_ok("list -t ML --format {title}", expect!["first"], expect![""]);
+}
+
+#[test]
+fn test_list_regex_filters() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title raft -a Ongaro",
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title paxos-made-simple -a Lamport",
+        expect!["Added paper paxos-made-simple"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --title-re ^(raft|paxos)$ --format {title}",
+        expect!["raft"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --author-re ^Lam --format {title}",
+        expect!["paxos-made-simple"],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --file-re \.pdf$ --format {title}",
+        expect![""],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list_has_notes() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title annotated",
+        expect!["Added paper annotated"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title skimmed",
+        expect!["Added paper skimmed"],
+        expect![""],
+    );
+    let notes = f.root_dir().parent().unwrap().join("annotated.md");
+    let mut contents = std::fs::read_to_string(&notes).unwrap();
+    contents.push_str("\nLeader election with randomised timeouts.\n");
+    std::fs::write(&notes, contents).unwrap();
+
+    f.check_ok(
+        "list --has-notes --format {title}",
+        expect!["annotated"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --no-notes --format {title}",
+        expect!["skimmed"],
+        expect![""],
+    );
+}
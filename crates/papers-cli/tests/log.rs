@@ -0,0 +1,61 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+fn actions(f: &Fixture, args: &str) -> Vec<String> {
+    let output = f.run(&format!("log -o json {args}"));
+    assert!(output.status.success());
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    entries
+        .iter()
+        .map(|e| {
+            format!(
+                "{} {}",
+                e["action"].as_str().unwrap(),
+                e["path"].as_str().unwrap()
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_log() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first --file file1.pdf",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    assert!(f.run("rename-files title").status.success());
+
+    expect![[r#"
+        [
+            "added first.md",
+            "added second.md",
+            "renamed root/file1.pdf",
+            "updated first.md",
+        ]
+    "#]]
+    .assert_debug_eq(&actions(&f, ""));
+    expect![[r#"
+        [
+            "renamed root/file1.pdf",
+        ]
+    "#]]
+    .assert_debug_eq(&actions(&f, "--paper root/first.pdf"));
+    expect![[r#"
+        [
+            "added second.md",
+        ]
+    "#]]
+    .assert_debug_eq(&actions(&f, "--paper second.md"));
+    expect![[r#"
+        []
+    "#]]
+    .assert_debug_eq(&actions(&f, "--since 2999-01-01"));
+}
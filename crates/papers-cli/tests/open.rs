@@ -13,12 +13,43 @@ fn test_help() {
             Usage: papers open [OPTIONS] [PATH]
 
             Arguments:
-              [PATH]  Path of the paper to open, fuzzy selected if not given
+              [PATH]
+                      Path of the paper to open, part of its title, a glob, or `@last-added` or `@last-edited`. Fuzzy selected if not given
 
             Options:
-              -c, --config-file <CONFIG_FILE>    Config file path to load
-                  --default-repo <DEFAULT_REPO>  Default repo to use if not found in parents of current directory
-              -h, --help                         Print help"#]],
+              -c, --config-file <CONFIG_FILE>
+                      Config file path to load
+
+                  --default-repo <DEFAULT_REPO>
+                      Default repo to use if not found in parents of current directory
+
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
+
+                      [default: none]
+
+                      Possible values:
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
+
+              -h, --help
+                      Print help (see a summary with '-h')"#]],
         expect![""],
     );
 }
@@ -0,0 +1,61 @@
+mod common;
+use std::fs::File;
+
+use common::Fixture;
+use expect_test::expect;
+
+fn set_command(f: &Fixture, command: &str) {
+    let mut config = f.config();
+    config.optimize_command = Some(command.to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+}
+
+#[test]
+fn test_optimize_pdfs() {
+    let mut f = Fixture::new();
+    // stands in for an optimiser, writing a tiny output
+    set_command(&f, "truncate -s 4 {output}");
+    std::fs::write(f.root_dir().join("big.pdf"), vec![b'a'; 3000]).unwrap();
+    std::fs::write(f.root_dir().join("tiny.pdf"), "abc").unwrap();
+    f.check_ok(
+        "add --title big --file big.pdf",
+        expect!["Added paper big"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title tiny --file tiny.pdf",
+        expect!["Added paper tiny"],
+        expect![""],
+    );
+
+    f.check_ok(
+        "optimize-pdfs",
+        expect![[r#"
+        root/big.pdf: 2.9 KiB -> 4 B
+        root/tiny.pdf: 3 B -> 3 B
+        Saved 2.9 KiB"#]],
+        expect![""],
+    );
+    assert_eq!(
+        std::fs::read(f.root_dir().join("big.pdf")).unwrap().len(),
+        4
+    );
+    f.check_ok(
+        "optimize-pdfs big.md -o plain",
+        expect!["root/big.pdf	4	4"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_optimize_pdfs_failing_command() {
+    let mut f = Fixture::new();
+    set_command(&f, "false {input}");
+    f.check_ok(
+        "add --title first --file file1.pdf",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok("optimize-pdfs", expect!["Saved 0 B"], expect![[r#"error: Failed to optimize "root/file1.pdf": Optimize command failed with exit status: 1"#]]);
+    assert!(!f.root_dir().join("file1.pdf.optimized").exists());
+}
@@ -0,0 +1,102 @@
+mod common;
+use std::fs::{read_to_string, File};
+
+use common::Fixture;
+use expect_test::expect;
+
+const PAPERS: &str = r#"[
+    {"title": "raft", "url": "https://raft.github.io/raft.pdf", "filename": null, "tags": ["public", "consensus"], "labels": {"rating": 2}, "authors": ["Diego Ongaro"], "created_at": "2024-05-02T00:00:00", "modified_at": "2024-05-02T00:00:00", "last_review": null, "next_review": null, "notes": "\n# Summary\nLeader based.\n\n## Private\nSlow going.\n"},
+    {"title": "draft", "url": null, "filename": null, "tags": ["consensus"], "labels": {}, "authors": [], "created_at": "2024-05-02T00:00:00", "modified_at": "2024-05-02T00:00:00", "last_review": null, "next_review": null}
+]"#;
+
+#[test]
+fn test_publish_hugo() {
+    let f = Fixture::new();
+    let mut config = f.config();
+    config.anonymise.labels.insert("rating".to_owned());
+    config.anonymise.sections.push("Private".to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    let site = tempfile::tempdir().unwrap();
+    let output = f.run(&format!(
+        "publish {} --generator hugo --anonymise",
+        site.path().display()
+    ));
+    assert!(output.status.success());
+    assert!(!site.path().join("draft.md").exists());
+    let page = read_to_string(site.path().join("raft.md")).unwrap();
+    // modification times vary so are left out
+    let page = page
+        .lines()
+        .filter(|l| !l.starts_with("lastmod"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        ---
+        title: raft
+        date: 2024-05-02
+        tags:
+        - consensus
+        - inbox
+        params:
+          authors:
+          - Diego Ongaro
+          paper_url: https://raft.github.io/raft.pdf
+        ---
+
+        # Summary
+        Leader based.
+    "#]]
+    .assert_eq(&page);
+}
+
+#[test]
+fn test_publish_inside_repo() {
+    let mut f = Fixture::new();
+    f.check_ok_with_stdin(
+        "import -",
+        PAPERS,
+        expect![[r#"
+        Added paper raft
+        Added paper draft"#]],
+        expect![""],
+    );
+
+    // publishing into the repo would overwrite its notes with the pages
+    for dir in [".", "..", "site"] {
+        let output = f.run(&format!("publish {dir}"));
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("is inside the repo"), "{stderr}");
+    }
+    assert!(!f.root_dir().join("site").exists());
+    f.check_ok(
+        "list --format {title}",
+        expect![[r#"
+        draft
+        raft"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_publish_same_names() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title Raft -t public",
+        expect!["Added paper Raft"],
+        expect![""],
+    );
+    f.run("add --title RAFT -t public");
+
+    // the pages would overwrite each other on a case-insensitive filesystem
+    let site = tempfile::tempdir().unwrap();
+    let output = f.run(&format!("publish {}", site.path().display()));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("would be the same page"), "{stderr}");
+    assert!(!site.path().join("Raft.md").exists());
+    assert!(!site.path().join("RAFT.md").exists());
+}
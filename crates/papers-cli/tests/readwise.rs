@@ -0,0 +1,73 @@
+mod common;
+use std::fs::File;
+
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_offline() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "readwise push --offline",
+        expect!["Offline, skipping pushing highlights"],
+        expect![""],
+    );
+    f.check_ok(
+        "readwise pull --offline",
+        expect!["Offline, skipping pulling highlights"],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_pull_without_token() {
+    let f = Fixture::new();
+    let mut config = f.config();
+    config.readwise.token_env = Some("PAPERS_TEST_MISSING_READWISE_TOKEN".to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    let output = f.run("readwise pull");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("No token for Readwise, set PAPERS_TEST_MISSING_READWISE_TOKEN"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_push_failures() {
+    let mut f = Fixture::new();
+    let mut config = f.config();
+    config.readwise.token_env = Some("PAPERS_TEST_MISSING_READWISE_TOKEN".to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    f.check_ok("add --title a", expect!["Added paper a"], expect![""]);
+    f.run("add --title b");
+    f.run("add --title c");
+    let repo = f.root_dir().parent().unwrap().to_owned();
+    for title in ["a", "b", "c"] {
+        let path = repo.join(title).with_extension("md");
+        let mut notes = std::fs::read_to_string(&path).unwrap();
+        notes.push_str("\n## Highlights\n\n- > a passage\n");
+        std::fs::write(&path, notes).unwrap();
+    }
+
+    // every paper is tried and the summary printed, before failing
+    let output = f.run("readwise push");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Pushed 0 highlights from 0 papers to Readwise"),
+        "{stdout}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for title in ["a", "b", "c"] {
+        assert!(
+            stderr.contains(&format!("Failed to push highlights from \"{title}.md\"")),
+            "{stderr}"
+        );
+    }
+    assert!(
+        stderr.contains("Failed to push highlights from 3 papers"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,39 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+const PAPERS: &str = r#"[
+    {"title": "raft", "url": "https://raft.github.io/raft.pdf", "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2024-05-02T00:00:00", "modified_at": "2024-05-02T00:00:00", "last_review": null, "next_review": null, "notes": "\n## Summary\n\nUnderstandable consensus.\n"},
+    {"title": "paxos", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2024-01-01T00:00:00", "modified_at": "2024-05-20T00:00:00", "last_review": "2024-05-20T00:00:00", "next_review": null},
+    {"title": "zab", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2024-04-30T00:00:00", "modified_at": "2024-04-30T00:00:00", "last_review": null, "next_review": null}
+]"#;
+
+#[test]
+fn test_report_markdown() {
+    let mut f = Fixture::new();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    f.check_ok(
+        "report --month 2024-05 -o markdown",
+        expect![[r#"
+            # Reading in May 2024
+
+            ## Added
+
+            - [raft](https://raft.github.io/raft.pdf): Understandable consensus.
+
+            ## Read
+
+            - paxos"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "report --month 2024-04",
+        expect![[r#"
+        Added in 2024-04: 1
+          zab
+        Read in 2024-04: 0"#]],
+        expect![""],
+    );
+}
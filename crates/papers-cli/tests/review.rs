@@ -11,15 +11,172 @@ fn test_help() {
             Review papers that have been unseen too long
 
             Usage: papers review [OPTIONS] [PATH]
+                   papers review <COMMAND>
+
+            Commands:
+              list      List the papers due for review, most overdue first
+              stats     Show how many times each paper has been reviewed, how the gaps between reviews grow, and which papers are stuck at short intervals, along with how many reviews are due today and in the weeks after
+              schedule  Set when papers next come up for review, or stop them coming up at all
 
             Arguments:
-              [PATH]  Path of the paper to review, fuzzy selected if not given
+              [PATH]
+                      Path of the paper to review, part of its title, a glob, or `@last-added` or `@last-edited`. Fuzzy selected if not given
 
             Options:
-              -c, --config-file <CONFIG_FILE>    Config file path to load
-                  --open                         Open the pdf file too
-                  --default-repo <DEFAULT_REPO>  Default repo to use if not found in parents of current directory
-              -h, --help                         Print help"#]],
+              -c, --config-file <CONFIG_FILE>
+                      Config file path to load
+
+                  --default-repo <DEFAULT_REPO>
+                      Default repo to use if not found in parents of current directory
+
+                  --offline
+                      Don't access the network, skipping anything that needs it
+
+              -o, --output <OUTPUT>
+                      Style to print the results of commands in
+
+                      [default: table]
+
+                      Possible values:
+                      - table:    Pretty table format
+                      - json:     Json format
+                      - yaml:     Yaml format
+                      - csv:      Comma separated values, with a header row
+                      - plain:    Tab separated values without a header, for scripts
+                      - markdown: Markdown, a table unless the command has a write-up of its own
+
+                  --open
+                      Open the pdf file too
+
+                  --fail-if-empty
+                      Exit with status 1 if no papers are due for review
+
+                  --progress <PROGRESS>
+                      Style to report the progress of long operations in
+
+                      [default: none]
+
+                      Possible values:
+                      - none: Don't report progress
+                      - json: A json object per line on stderr, for wrappers to show progress with
+
+                  --limit <LIMIT>
+                      Stop the session after reviewing this many papers
+
+                  --time-box <TIME_BOX>
+                      Stop the session once this long has passed, e.g. 30m or 1h, finishing the paper being reviewed
+
+                  --rating <RATING>
+                      How well the paper was remembered: again, hard, good or easy. Asked for after editing the notes if not given
+
+                  --added-since <ADDED_SINCE>
+                      Filter down to papers added since this time, a date like 2023-08-01 or an age like 2w
+
+                  --added-before <ADDED_BEFORE>
+                      Filter down to papers added before this time, a date like 2023-08-01 or an age like 2w
+
+                  --modified-since <MODIFIED_SINCE>
+                      Filter down to papers changed since this time, a date like 2023-08-01 or an age like 2w
+
+                  --modified-before <MODIFIED_BEFORE>
+                      Filter down to papers changed before this time, a date like 2023-08-01 or an age like 2w
+
+                  --read-since <READ_SINCE>
+                      Filter down to papers last reviewed since this time, a date like 2023-08-01 or an age like 1mo
+
+                  --read-before <READ_BEFORE>
+                      Filter down to papers last reviewed before this time, a date like 2023-08-01 or an age like 1mo. Papers never reviewed count as reviewed before any time
+
+              -h, --help
+                      Print help (see a summary with '-h')"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_stats_without_reviews() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "review stats --weeks 0",
+        expect![[r#"
+            Reviewed 0 papers, average interval growth -, 0 stuck at short intervals
+            Due today 1, 0 overdue, average interval -"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_list() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.run("add --title second");
+    f.run("add --title third");
+    f.run("review schedule second --on 2w");
+    f.run("review schedule third --never");
+    f.check_ok("review list", expect!["never reviewed first"], expect![""]);
+    let output = f.run("review list --within 30 --output json");
+    let queue: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(queue[0]["title"], "first");
+    assert_eq!(queue[1]["title"], "second");
+    assert_eq!(queue[1]["days_left"], 14);
+    assert_eq!(queue.as_array().unwrap().len(), 2);
+    assert!(f.run("review list --fail-if-empty").status.success());
+
+    // nothing is due once the first has been scheduled too
+    f.run("review schedule first --on 1w");
+    f.check_ok("review list", expect![""], expect![""]);
+    let output = f.run("review list --fail-if-empty");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_schedule() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok("status --short", expect!["r1"], expect![""]);
+    f.check_ok(
+        "review schedule first --never",
+        expect![[r#""first.md" won't come up for review"#]],
+        expect![""],
+    );
+    f.check_ok("status --short", expect![""], expect![""]);
+    f.check_ok(
+        "review schedule first.md --on 2023-08-01",
+        expect![[r#"Next review of "first.md" on 2023-08-01"#]],
+        expect![""],
+    );
+    f.check_ok(
+        r"list --format {next_review}",
+        expect!["2023-08-01 00:00:00"],
+        expect![""],
+    );
+    f.check_ok("status --short", expect!["r1"], expect![""]);
+}
+
+#[test]
+fn test_session_limit() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "review --limit 0",
+        expect!["Reviewed 0 papers in 0s, 1 still due"],
         expect![""],
     );
 }
@@ -0,0 +1,298 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_schema() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "schema",
+        expect![[r##"
+        {
+          "$schema": "http://json-schema.org/draft-07/schema#",
+          "title": "papers frontmatter",
+          "description": "Metadata of a paper, kept as the frontmatter of its notes file.",
+          "type": "object",
+          "required": [
+            "authors",
+            "created_at",
+            "labels",
+            "modified_at",
+            "tags",
+            "title"
+          ],
+          "properties": {
+            "authors": {
+              "type": "array",
+              "items": {
+                "type": "string"
+              }
+            },
+            "created_at": {
+              "type": "string",
+              "format": "partial-date-time"
+            },
+            "doc_type": {
+              "description": "Kind of document.",
+              "allOf": [
+                {
+                  "$ref": "#/definitions/DocType"
+                }
+              ]
+            },
+            "extra": {
+              "description": "Any other fields to keep with the paper, which papers passes through untouched.",
+              "type": "object",
+              "additionalProperties": true
+            },
+            "filename": {
+              "description": "Path of the document, relative to the root of the repo.",
+              "type": [
+                "string",
+                "null"
+              ]
+            },
+            "labels": {
+              "description": "Extra values, such as a doi or the year published.",
+              "type": "object",
+              "additionalProperties": {
+                "$ref": "#/definitions/Primitive"
+              }
+            },
+            "last_review": {
+              "type": [
+                "string",
+                "null"
+              ],
+              "format": "partial-date-time"
+            },
+            "modified_at": {
+              "type": "string",
+              "format": "partial-date-time"
+            },
+            "next_review": {
+              "type": [
+                "string",
+                "null"
+              ],
+              "format": "partial-date-time"
+            },
+            "no_review": {
+              "description": "Never bring the paper up for review, e.g. for reference manuals.",
+              "type": "boolean"
+            },
+            "provenance": {
+              "description": "How the paper entered the repo.",
+              "anyOf": [
+                {
+                  "$ref": "#/definitions/Provenance"
+                },
+                {
+                  "type": "null"
+                }
+              ]
+            },
+            "ratings": {
+              "description": "Ratings given at past reviews, oldest first.",
+              "type": "array",
+              "items": {
+                "$ref": "#/definitions/ReviewRating"
+              }
+            },
+            "read_by": {
+              "description": "Date to read the paper by.",
+              "type": [
+                "string",
+                "null"
+              ],
+              "format": "date"
+            },
+            "sha256": {
+              "description": "Hex encoded SHA-256 of the contents of the document, to tell when the same document is added twice.",
+              "type": [
+                "string",
+                "null"
+              ]
+            },
+            "tags": {
+              "type": "array",
+              "items": {
+                "type": "string"
+              },
+              "uniqueItems": true
+            },
+            "title": {
+              "description": "Title of the paper, which names its notes file.",
+              "type": "string"
+            },
+            "url": {
+              "description": "Where the paper came from.",
+              "type": [
+                "string",
+                "null"
+              ]
+            }
+          },
+          "definitions": {
+            "DocType": {
+              "description": "The kind of document an entry refers to.",
+              "oneOf": [
+                {
+                  "description": "A paper, usually with a pdf attached.",
+                  "type": "string",
+                  "enum": [
+                    "paper"
+                  ]
+                },
+                {
+                  "description": "A recorded talk or video, opened by its url.",
+                  "type": "string",
+                  "enum": [
+                    "talk"
+                  ]
+                },
+                {
+                  "description": "A book, with notes kept by chapter.",
+                  "type": "string",
+                  "enum": [
+                    "book"
+                  ]
+                }
+              ]
+            },
+            "Primitive": {
+              "anyOf": [
+                {
+                  "type": "null"
+                },
+                {
+                  "type": "boolean"
+                },
+                {
+                  "type": "number",
+                  "format": "double"
+                },
+                {
+                  "type": "string"
+                }
+              ]
+            },
+            "Provenance": {
+              "description": "How a paper entered the repo.",
+              "type": "object",
+              "required": [
+                "at",
+                "source"
+              ],
+              "properties": {
+                "at": {
+                  "description": "When the paper was added.",
+                  "type": "string",
+                  "format": "partial-date-time"
+                },
+                "detail": {
+                  "description": "More about the source, such as the file the paper was imported from.",
+                  "type": [
+                    "string",
+                    "null"
+                  ]
+                },
+                "source": {
+                  "description": "What added the paper.",
+                  "allOf": [
+                    {
+                      "$ref": "#/definitions/Source"
+                    }
+                  ]
+                }
+              }
+            },
+            "Rating": {
+              "description": "How well a paper was remembered when reviewing it.",
+              "oneOf": [
+                {
+                  "description": "Forgotten, so start the intervals over.",
+                  "type": "string",
+                  "enum": [
+                    "again"
+                  ]
+                },
+                {
+                  "description": "Remembered with difficulty, so grow the interval slowly.",
+                  "type": "string",
+                  "enum": [
+                    "hard"
+                  ]
+                },
+                {
+                  "description": "Remembered, so grow the interval as usual.",
+                  "type": "string",
+                  "enum": [
+                    "good"
+                  ]
+                },
+                {
+                  "description": "Remembered easily, so grow the interval more than usual.",
+                  "type": "string",
+                  "enum": [
+                    "easy"
+                  ]
+                }
+              ]
+            },
+            "ReviewRating": {
+              "description": "A rating given at a review, kept so the history can be looked back over.",
+              "type": "object",
+              "required": [
+                "at",
+                "rating"
+              ],
+              "properties": {
+                "at": {
+                  "description": "When the review happened.",
+                  "type": "string",
+                  "format": "partial-date-time"
+                },
+                "rating": {
+                  "description": "How well the paper was remembered.",
+                  "allOf": [
+                    {
+                      "$ref": "#/definitions/Rating"
+                    }
+                  ]
+                }
+              }
+            },
+            "Source": {
+              "description": "What added a paper to the repo.",
+              "oneOf": [
+                {
+                  "description": "Added with `papers add`.",
+                  "type": "string",
+                  "enum": [
+                    "add"
+                  ]
+                },
+                {
+                  "description": "Imported from a list of papers with `papers import`.",
+                  "type": "string",
+                  "enum": [
+                    "import"
+                  ]
+                }
+              ]
+            }
+          }
+        }"##]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_schema_import() {
+    let f = Fixture::new();
+    let output = f.run("schema --kind import");
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["title"], "papers import");
+    assert_eq!(schema["type"], "array");
+}
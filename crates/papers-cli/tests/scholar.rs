@@ -0,0 +1,88 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+const BIBTEX: &str = r#"
+@inproceedings{ongaro2014search,
+  title={In search of an understandable consensus algorithm},
+  author={Ongaro, Diego and Ousterhout, John},
+  booktitle={USENIX ATC},
+  year={2014}
+}
+
+@article{lamport2001paxos,
+  title={Paxos made simple},
+  author={Lamport, Leslie and others},
+  journal={ACM SIGACT News},
+  year={2001}
+}
+
+@article{lamport2001paxos,
+  title={{P}axos Made Simple},
+  author={Lamport, Leslie},
+  year={2001}
+}
+"#;
+
+#[test]
+fn test_import_scholar_bibtex() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title paxos-made-simple",
+        expect!["Added paper paxos-made-simple"],
+        expect![""],
+    );
+    let file = f.root_dir().join("scholar.bib");
+    std::fs::write(&file, BIBTEX).unwrap();
+    f.check_ok(
+        &format!("import --format scholar-bibtex {}", file.display()),
+        expect![[r#"
+            Added paper In search of an understandable consensus algorithm
+            Merged into paper paxos-made-simple"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --format {title}|{authors}|{labels}",
+        expect![[r#"
+            In search of an understandable consensus algorithm|Diego Ongaro,John Ousterhout|venue=USENIX ATC,year=2014
+            paxos-made-simple|Leslie Lamport|venue=ACM SIGACT News,year=2001"#]],
+        expect![""],
+    );
+    // importing again changes nothing
+    f.check_ok(
+        &format!("import --format scholar-bibtex {}", file.display()),
+        expect![""],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_import_scholar_bibtex_similar_title() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title raft-in-search-of-an-understandable-consensus-algorithm",
+        expect!["Added paper raft-in-search-of-an-understandable-consensus-algorithm"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title paxos-made-simple",
+        expect!["Added paper paxos-made-simple"],
+        expect![""],
+    );
+    let file = f.root_dir().join("scholar.bib");
+    std::fs::write(&file, BIBTEX).unwrap();
+    // alike titles are only reported, and the json output isn't mixed with messages
+    let output = f.run(&format!(
+        "import --format scholar-bibtex {} -o json",
+        file.display()
+    ));
+    assert!(output.status.success());
+    let imported: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        imported[0]["title"],
+        "In search of an understandable consensus algorithm"
+    );
+    assert_eq!(imported[1]["title"], "paxos-made-simple");
+    expect![[r#"warning: "In search of an understandable consensus algorithm" may be the same work as "raft-in-search-of-an-understandable-consensus-algorithm.md", adding it separately"#]]
+        .assert_eq(String::from_utf8_lossy(&output.stderr).trim());
+}
@@ -0,0 +1,47 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_sed_title() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title tehcnique-one -t import",
+        expect!["Added paper tehcnique-one"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title tehcnique-two",
+        expect!["Added paper tehcnique-two"],
+        expect![""],
+    );
+    f.check_ok(
+        "sed --field title s/tehcnique/Technique/ -t import --dry-run",
+        expect![[r#"
+            "tehcnique-one.md" title
+            - tehcnique-one
+            + Technique-one"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --sort title --format {title}",
+        expect![[r#"
+            tehcnique-one
+            tehcnique-two"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "sed --field title s/TEHCNIQUE/Technique/i -o plain",
+        expect![[r#"
+            tehcnique-one.md	title	tehcnique-one	Technique-one
+            tehcnique-two.md	title	tehcnique-two	Technique-two"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --sort title --format {title}",
+        expect![[r#"
+            Technique-one
+            Technique-two"#]],
+        expect![""],
+    );
+}
@@ -0,0 +1,95 @@
+mod common;
+use std::fs::File;
+
+use common::Fixture;
+use expect_test::expect;
+use papers_cli_lib::share::ShareService;
+
+const PAPERS: &str = r#"[
+    {"title": "raft", "url": null, "filename": null, "tags": [], "labels": {}, "authors": [], "created_at": "2024-05-02T00:00:00", "modified_at": "2024-05-02T00:00:00", "last_review": null, "next_review": null, "notes": "\n# Summary\nLeader based.\n"}
+]"#;
+
+#[test]
+fn test_help() {
+    let mut f = Fixture::new();
+    f.check_ok("share --help", expect![[r#"
+        Upload the notes of a paper to the gist or paste service in the `share` config, printing the url they can be seen at
+
+        Usage: papers share [OPTIONS] [PATH]
+
+        Arguments:
+          [PATH]
+                  Path of the paper to share, selected interactively if not given
+
+        Options:
+          -c, --config-file <CONFIG_FILE>
+                  Config file path to load
+
+              --with-meta
+                  Put the title, authors, url and tags of the paper above the notes
+
+              --anonymise
+                  Strip personal labels, tags and notes sections set in the `anonymise` config
+
+              --default-repo <DEFAULT_REPO>
+                  Default repo to use if not found in parents of current directory
+
+              --offline
+                  Don't access the network, skipping anything that needs it
+
+          -o, --output <OUTPUT>
+                  Style to print the results of commands in
+
+                  [default: table]
+
+                  Possible values:
+                  - table:    Pretty table format
+                  - json:     Json format
+                  - yaml:     Yaml format
+                  - csv:      Comma separated values, with a header row
+                  - plain:    Tab separated values without a header, for scripts
+                  - markdown: Markdown, a table unless the command has a write-up of its own
+
+              --progress <PROGRESS>
+                  Style to report the progress of long operations in
+
+                  [default: none]
+
+                  Possible values:
+                  - none: Don't report progress
+                  - json: A json object per line on stderr, for wrappers to show progress with
+
+          -h, --help
+                  Print help (see a summary with '-h')"#]], expect![""]);
+}
+
+#[test]
+fn test_share_without_url() {
+    let f = Fixture::new();
+    let mut config = f.config();
+    config.share.service = ShareService::Paste;
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    let output = f.run_with_stdin("import -", PAPERS);
+    assert!(output.status.success());
+
+    let output = f.run("share raft.md");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No url for the paste service"), "{stderr}");
+}
+
+#[test]
+fn test_share_offline() {
+    let mut f = Fixture::new();
+    let mut config = f.config();
+    config.share.service = ShareService::Paste;
+    config.share.url = Some("https://paste.rs/".to_owned());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    f.check_ok_with_stdin("import -", PAPERS, expect!["Added paper raft"], expect![""]);
+
+    // nothing is uploaded without the network
+    let output = f.run("share raft.md --with-meta --offline");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Offline"), "{stderr}");
+}
@@ -0,0 +1,48 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_snapshot_restore() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -t to-read",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    let output = f.run("snapshot create before -o json");
+    assert!(output.status.success());
+    let created: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(created[0]["name"], "before");
+    assert_eq!(created[0]["papers"], 1);
+
+    f.check_ok(
+        "add --title second",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    std::fs::remove_file(repo_root.join("first.md")).unwrap();
+
+    f.check_ok(
+        "snapshot restore before",
+        expect![[r#"Recreated "first.md" without notes"#]],
+        expect![""],
+    );
+    f.check_ok("snapshot restore before", expect![""], expect![""]);
+    f.check_ok(
+        "list --sort title --format {title}:{tags}",
+        expect![[r#"
+            first:to-read
+            second:"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_snapshot_missing() {
+    let mut f = Fixture::new();
+    let output = f.run("snapshot restore nope");
+    assert!(!output.status.success());
+    f.check_ok("snapshot list", expect![""], expect![""]);
+}
@@ -0,0 +1,30 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_status() {
+    let mut f = Fixture::new();
+    f.check_ok("status --short", expect![""], expect![""]);
+    f.check_ok(
+        "add --title a --tag inbox",
+        expect!["Added paper a"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title b --tag to-read --read-by 3d",
+        expect!["Added paper b"],
+        expect![""],
+    );
+    f.check_ok("status --short", expect!["r2 i1 q1 d1"], expect![""]);
+    f.check_ok(
+        "status",
+        expect![[r#"
+        Due for review: 2
+        In the inbox: 1
+        Queued to read: 1
+        Due to be read soon: 1"#]],
+        expect![""],
+    );
+    f.check_ok("status -o plain", expect!["2	1	1	1"], expect![""]);
+}
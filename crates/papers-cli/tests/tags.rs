@@ -0,0 +1,76 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_tags_csv() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -t to-read -t db",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second -t to-read",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    f.check_ok(
+        "tags -o csv --sort",
+        expect![[r#"
+        key,count
+        db,1
+        to-read,2"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "tags -o plain",
+        expect![[r#"
+        db	1
+        to-read	2"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_tags_describe() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -t to-read -t misc",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    let repo_root = f.root_dir().parent().unwrap().to_owned();
+    std::fs::write(
+        repo_root.join(".paperstags.yaml"),
+        "to-read:\n  description: Not read yet\n  usage: Added to new papers\nclassic:\n  description: Everyone should read it\n",
+    )
+    .unwrap();
+    f.check_ok(
+        "tags --describe",
+        expect![[r#"
+            classic (0): Everyone should read it
+            misc (1) unregistered
+            to-read (1): Not read yet
+              Usage: Added to new papers"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "tags --describe -o csv",
+        expect![[r#"
+            tag,count,registered,description,usage
+            classic,0,true,Everyone should read it,
+            misc,1,false,,
+            to-read,1,true,Not read yet,Added to new papers"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_add_json() {
+    let f = Fixture::new();
+    let output = f.run("add --title json-title -o json");
+    assert!(output.status.success());
+    let added: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(added[0]["title"], "json-title");
+}
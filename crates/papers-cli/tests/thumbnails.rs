@@ -0,0 +1,34 @@
+mod common;
+use std::fs::File;
+
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_thumbnails() {
+    let mut f = Fixture::new();
+    // stands in for a renderer, using the document as its own thumbnail
+    let mut config = f.config();
+    config.thumbnails.command = Some("cp {input} {output}".to_owned());
+    config.thumbnails.dir = Some("thumbs".into());
+    serde_yaml::to_writer(File::create(f.config_path()).unwrap(), &config).unwrap();
+    f.check_ok(
+        "add --title first --file file1.pdf",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title no-document",
+        expect!["Added paper no-document"],
+        expect![""],
+    );
+
+    f.check_ok("thumbnails", expect!["root/file1.pdf: thumbs/d36d9a41799949b5e7cb0d7551f8926739a0a22b4d6bb5784ad01a3eabe7c993.png (rendered)"], expect![""]);
+    f.check_ok("thumbnails first.md", expect!["root/file1.pdf: thumbs/d36d9a41799949b5e7cb0d7551f8926739a0a22b4d6bb5784ad01a3eabe7c993.png (up to date)"], expect![""]);
+
+    // thumbnails of documents that are gone are removed
+    let stale = f.root_dir().join("thumbs/stale.png");
+    std::fs::write(&stale, "old").unwrap();
+    f.check_ok("thumbnails", expect!["root/file1.pdf: thumbs/d36d9a41799949b5e7cb0d7551f8926739a0a22b4d6bb5784ad01a3eabe7c993.png (up to date)"], expect![""]);
+    assert!(!stale.exists());
+}
@@ -0,0 +1,39 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_remove_and_restore() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title raft --file nested/file1.pdf",
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    let root = f.root_dir().parent().unwrap().to_owned();
+    let output = f.run("remove raft.md --with-file");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with(r#"Moved "raft.md" and "root/nested/file1.pdf" to the trash as "#),
+        "{stdout}"
+    );
+    assert!(!root.join("raft.md").exists());
+    assert!(!root.join("root/nested/file1.pdf").exists());
+    f.check_ok("list -o plain", expect![""], expect![""]);
+
+    let output = f.run("trash list -o json");
+    assert!(output.status.success());
+    let trashed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(trashed[0]["path"], "raft.md");
+    let name = trashed[0]["name"].as_str().unwrap();
+
+    f.check_ok(
+        &format!("trash restore {name}"),
+        expect![[r#"Restored "raft.md""#]],
+        expect![""],
+    );
+    assert!(root.join("raft.md").is_file());
+    assert!(root.join("root/nested/file1.pdf").is_file());
+    f.check_ok("trash list", expect![""], expect![""]);
+}
@@ -0,0 +1,46 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_tree_by_tag() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title raft -t systems/consensus -a Ongaro",
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title spanner -t systems/db -t systems",
+        expect!["Added paper spanner"],
+        expect![""],
+    );
+    f.check_ok(
+        "tree",
+        expect![[r#"
+        systems (2)
+        ├── consensus (1)
+        │   └── raft
+        ├── db (1)
+        │   └── spanner
+        └── spanner"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "tree --by author",
+        expect![[r#"
+        (none) (1)
+        └── spanner
+        Ongaro (1)
+        └── raft"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "tree -o plain",
+        expect![[r#"
+        systems	spanner.md	spanner
+        systems/consensus	raft.md	raft
+        systems/db	spanner.md	spanner"#]],
+        expect![""],
+    );
+}
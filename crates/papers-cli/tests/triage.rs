@@ -0,0 +1,78 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_triage() {
+    let mut f = Fixture::new();
+    for title in ["first", "second", "third", "fourth"] {
+        let output = f.run(&format!("add --title {title} --tag new"));
+        assert!(output.status.success());
+    }
+
+    // unknown key, tag then queue, archive, skip, exit
+    f.check_ok_with_stdin(
+        "triage --tag new",
+        "z\nt\ndb ml\nq\na\ns\nx\n",
+        expect![[r#"
+
+            (1/4) first
+              path:    first.md
+              tags:    new
+            [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it: [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it: Tags (separated by ' '): [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:
+            (2/4) fourth
+              path:    fourth.md
+              tags:    new
+            [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:
+            (3/4) second
+              path:    second.md
+              tags:    new
+            [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:
+            (4/4) third
+              path:    third.md
+              tags:    new
+            [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:"#]],
+        expect![""],
+    );
+    f.check_ok(
+        "list --sort title --format {title}:{tags}",
+        expect![[r#"
+            first:db,ml,new,to-read
+            fourth:archived,new
+            second:new
+            third:new"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_triage_end_of_input() {
+    let mut f = Fixture::new();
+    let output = f.run("add --title first");
+    assert!(output.status.success());
+    f.check_ok(
+        "triage",
+        expect![[r#"
+
+        (1/1) first
+          path:    first.md
+        [t]ag, [q]ueue, [a]rchive, [s]kip, [o]pen, e[x]it:"#]],
+        expect![""],
+    );
+}
+
+#[test]
+fn test_triage_end_of_input_at_prompt() {
+    let mut f = Fixture::new();
+    let output = f.run("add --title first --tag new");
+    assert!(output.status.success());
+
+    // running out of input while asking for tags cancels rather than asking forever
+    let output = f.run_with_stdin("triage", "t\n");
+    assert_eq!(output.status.code(), Some(130));
+    f.check_ok(
+        "list --format {title}:{tags}",
+        expect!["first:new"],
+        expect![""],
+    );
+}
@@ -0,0 +1,33 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_vocab_export_json() {
+    let mut f = Fixture::new();
+    f.check_ok(
+        "add --title first -t db -l venue=osdi -a Ongaro",
+        expect!["Added paper first"],
+        expect![""],
+    );
+    f.check_ok(
+        "add --title second -t db -t consensus -a Ongaro",
+        expect!["Added paper second"],
+        expect![""],
+    );
+    let output = f.run("vocab export -o json");
+    assert!(output.status.success());
+    let vocab: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let counts = |kind: &str| {
+        vocab[kind]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| format!("{}={}", t["name"].as_str().unwrap(), t["count"]))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(counts("tags"), ["consensus=1", "db=2"]);
+    assert_eq!(counts("label_keys"), ["venue=1"]);
+    assert_eq!(counts("label_values"), ["venue=osdi=1"]);
+    assert_eq!(counts("authors"), ["Ongaro=2"]);
+}
@@ -0,0 +1,59 @@
+mod common;
+use common::Fixture;
+use expect_test::expect;
+
+#[test]
+fn test_watch_once_auto() {
+    let mut f = Fixture::new();
+    let inbox = tempfile::tempdir().unwrap();
+    std::fs::write(inbox.path().join("raft.pdf"), "raft pdf\n").unwrap();
+    std::fs::write(inbox.path().join("raft.pdf.crdownload"), "partial\n").unwrap();
+    std::fs::write(inbox.path().join("notes.txt"), "notes\n").unwrap();
+    f.check_ok(
+        &format!("watch {} --once --auto -t inbox", inbox.path().display()),
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    f.check_ok(
+        "list --format {filename}:{tags}",
+        expect!["raft.pdf:inbox"],
+        expect![""],
+    );
+    // moved into the repo, leaving the rest
+    assert!(!inbox.path().join("raft.pdf").exists());
+    assert!(inbox.path().join("raft.pdf.crdownload").exists());
+
+    // the same document again isn't added twice
+    std::fs::write(inbox.path().join("copy.pdf"), "raft pdf\n").unwrap();
+    let output = f.run(&format!(
+        "watch {} --once --auto --copy",
+        inbox.path().display()
+    ));
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("is a duplicate of"), "{stderr}");
+    assert!(inbox.path().join("copy.pdf").exists());
+}
+
+#[test]
+fn test_watch_copy_without_terminal() {
+    let mut f = Fixture::new();
+    let inbox = tempfile::tempdir().unwrap();
+    std::fs::write(inbox.path().join("raft.pdf"), "raft pdf\n").unwrap();
+    f.check_ok(
+        &format!("watch {} --once --copy", inbox.path().display()),
+        expect!["Added paper raft"],
+        expect![""],
+    );
+    f.check_ok("list --format {filename}", expect!["raft.pdf"], expect![""]);
+    assert!(inbox.path().join("raft.pdf").exists());
+}
+
+#[test]
+fn test_watch_missing_dir() {
+    let f = Fixture::new();
+    let output = f.run("watch missing --once");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Path was not a directory"), "{stderr}");
+}
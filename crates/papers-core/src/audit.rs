@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use tracing::info;
+
+/// Target that audit entries are logged under, so they can be filtered separately.
+pub const AUDIT_TARGET: &str = "papers::audit";
+
+/// Name of the user making changes, from the environment.
+pub fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// Record a change to a file in the repo, for working out what happened after the fact.
+pub fn record(action: &str, path: &Path, other: Option<&Path>) {
+    info!(
+        target: AUDIT_TARGET,
+        user = current_user(),
+        action,
+        path = %path.display(),
+        other = other.map(|o| o.display().to_string()),
+        "Changed repo"
+    );
+}
@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::primitive::Primitive;
+use crate::tag::Tag;
+
+/// File in a directory of the repo giving defaults for papers added into it.
+pub const DIR_DEFAULTS_FILE: &str = ".papersdefaults.yaml";
+
+/// Defaults for papers added into a directory, as written in its [`DIR_DEFAULTS_FILE`].
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DirConfig {
+    tags: BTreeSet<Tag>,
+    labels: BTreeMap<String, Primitive>,
+    notes_template: Option<PathBuf>,
+}
+
+/// Defaults for papers added into a directory, inherited from each directory above it in the
+/// repo.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DirDefaults {
+    /// Tags given to the papers, from every directory.
+    pub tags: BTreeSet<Tag>,
+    /// Labels given to the papers, those of nearer directories replacing ones with the same key.
+    pub labels: BTreeMap<String, Primitive>,
+    /// Notes template of the nearest directory that has one.
+    pub notes_template: Option<PathBuf>,
+}
+
+impl DirDefaults {
+    /// Defaults for papers added into `dir`, relative to `root`, merging the defaults of each
+    /// directory from the root down to it.
+    pub fn load(root: &Path, dir: &Path) -> anyhow::Result<Self> {
+        let mut defaults = Self::default();
+        let mut current = root.to_owned();
+        let dirs = std::iter::once(root.to_owned()).chain(dir.components().map(|c| {
+            current.push(c);
+            current.clone()
+        }));
+        for dir in dirs {
+            let path = dir.join(DIR_DEFAULTS_FILE);
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            // an empty file has no defaults rather than being an error
+            let config: Option<DirConfig> = serde_yaml::from_reader(BufReader::new(file))
+                .with_context(|| format!("Failed to read directory defaults {path:?}"))?;
+            let Some(config) = config else {
+                continue;
+            };
+            defaults.tags.extend(config.tags);
+            defaults.labels.extend(config.labels);
+            if let Some(template) = config.notes_template {
+                // relative to the directory it is set for
+                defaults.notes_template = Some(dir.join(template));
+            }
+        }
+        Ok(defaults)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(
+            DirDefaults::load(root, Path::new("teaching")).unwrap(),
+            DirDefaults::default()
+        );
+
+        create_dir_all(root.join("teaching/2023")).unwrap();
+        write(
+            root.join(DIR_DEFAULTS_FILE),
+            "tags: [shared]\nlabels:\n  owner: me\n",
+        )
+        .unwrap();
+        write(
+            root.join("teaching").join(DIR_DEFAULTS_FILE),
+            "tags: [teaching]\nlabels:\n  owner: dept\n  course: 101\nnotes_template: template.md\n",
+        )
+        .unwrap();
+        write(root.join("teaching/2023").join(DIR_DEFAULTS_FILE), "").unwrap();
+
+        let defaults = DirDefaults::load(root, Path::new("teaching/2023")).unwrap();
+        assert_eq!(
+            defaults.tags,
+            BTreeSet::from([Tag::new("shared"), Tag::new("teaching")])
+        );
+        assert_eq!(
+            defaults.labels.get("owner"),
+            Some(&Primitive::String("dept".to_owned()))
+        );
+        assert!(defaults.labels.contains_key("course"));
+        assert_eq!(
+            defaults.notes_template,
+            Some(root.join("teaching/template.md"))
+        );
+
+        // only directories on the way down apply
+        let defaults = DirDefaults::load(root, Path::new("")).unwrap();
+        assert_eq!(defaults.tags, BTreeSet::from([Tag::new("shared")]));
+
+        write(
+            root.join("teaching").join(DIR_DEFAULTS_FILE),
+            "colour: red\n",
+        )
+        .unwrap();
+        assert!(DirDefaults::load(root, Path::new("teaching")).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use std::{fmt::Display, str::FromStr};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The kind of document an entry refers to.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DocType {
+    /// A paper, usually with a pdf attached.
+    #[default]
+    Paper,
+    /// A recorded talk or video, opened by its url.
+    Talk,
+    /// A book, with notes kept by chapter.
+    Book,
+}
+
+impl DocType {
+    /// Whether this is the default document type.
+    pub fn is_paper(&self) -> bool {
+        *self == Self::Paper
+    }
+}
+
+impl FromStr for DocType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "paper" => Ok(Self::Paper),
+            "talk" => Ok(Self::Talk),
+            "book" => Ok(Self::Book),
+            _ => Err(format!("Unknown document type {:?}", s)),
+        }
+    }
+}
+
+impl Display for DocType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Paper => "paper",
+                Self::Talk => "talk",
+                Self::Book => "book",
+            }
+        )
+    }
+}
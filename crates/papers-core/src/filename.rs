@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use crate::repo::PROHIBITED_PATH_CHARS;
+
+/// Names that Windows reserves for devices, regardless of extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Maximum length in bytes of a file stem, leaving room for an extension within the usual 255 byte
+/// limit and keeping full paths short enough for Windows.
+pub const MAX_STEM_LEN: usize = 200;
+
+/// Turn a title into a file stem that is valid on all platforms.
+///
+/// Prohibited characters are removed, trailing dots and spaces (which Windows strips) are trimmed,
+/// reserved device names are suffixed with an underscore and the result is truncated to
+/// [`MAX_STEM_LEN`].
+pub fn sanitize_stem(title: &str) -> String {
+    let mut stem = title.replace(PROHIBITED_PATH_CHARS, "");
+    if stem.len() > MAX_STEM_LEN {
+        let mut end = MAX_STEM_LEN;
+        while !stem.is_char_boundary(end) {
+            end -= 1;
+        }
+        stem.truncate(end);
+    }
+    let mut stem = stem.trim_end_matches([' ', '.']).to_owned();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(&stem)) {
+        stem.push('_');
+    }
+    stem
+}
+
+/// Key to compare paths by on case-insensitive filesystems, such as the defaults on Windows and
+/// macOS.
+pub fn collision_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// The first of `path`, `<stem>-1.<ext>`, `<stem>-2.<ext>` and so on that doesn't exist yet.
+pub fn unique_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut unique = path.to_owned();
+    let mut n = 0;
+    while unique.exists() {
+        n += 1;
+        unique.set_file_name(format!("{stem}-{n}"));
+        if let Some(extension) = path.extension() {
+            unique.set_extension(extension);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::*;
+
+    fn check(title: &str, expected: Expect) {
+        expected.assert_eq(&sanitize_stem(title));
+    }
+
+    #[test]
+    fn test_plain() {
+        check("A normal title", expect!["A normal title"]);
+    }
+
+    #[test]
+    fn test_reserved_names() {
+        check("con", expect!["con_"]);
+        check("NUL", expect!["NUL_"]);
+        check("Console", expect!["Console"]);
+    }
+
+    #[test]
+    fn test_trailing_dots_and_spaces() {
+        check("Why not? ", expect!["Why not"]);
+    }
+
+    #[test]
+    fn test_truncates_on_char_boundary() {
+        let title = "é".repeat(150);
+        let stem = sanitize_stem(&title);
+        assert_eq!(stem.len(), MAX_STEM_LEN);
+        assert_eq!(stem, "é".repeat(100));
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paper.pdf");
+        assert_eq!(unique_path(&path), path);
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(unique_path(&path), dir.path().join("paper-1.pdf"));
+        std::fs::write(dir.path().join("paper-1.pdf"), "").unwrap();
+        assert_eq!(unique_path(&path), dir.path().join("paper-2.pdf"));
+    }
+}
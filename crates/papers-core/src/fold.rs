@@ -0,0 +1,24 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Fold text for loose matching, lowercasing it and stripping accents so that `Müller` matches
+/// `muller`.
+pub fn fold(s: &str) -> String {
+    s.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(fold("Müller"), "muller");
+        assert_eq!(fold("ML"), "ml");
+        assert_eq!(fold("Erdős"), "erdos");
+        assert_eq!(fold("Ångström"), "angstrom");
+        assert_eq!(fold("plain"), "plain");
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::BTreeSet;
+use std::io::BufRead;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::paper::PaperMeta;
+
+/// Key marking a notes file as a paper, for repos that share their root with other notes.
+pub const MARKER_KEY: &str = "papers";
+
+/// Line that opens and closes the frontmatter block.
+const DELIMITER: &str = "---";
+
+fn is_delimiter(line: &str) -> bool {
+    line.trim_end() == DELIMITER
+}
+
+/// Read only the frontmatter from the start of a notes file, stopping at the closing delimiter so
+/// the notes themselves are never read.
+pub fn read_frontmatter<R: BufRead>(mut reader: R) -> anyhow::Result<String> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !is_delimiter(&line) {
+        anyhow::bail!("No content for file! Is there any frontmatter?")
+    }
+
+    let mut frontmatter = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            anyhow::bail!("Frontmatter is missing its closing delimiter")
+        }
+        if is_delimiter(&line) {
+            return Ok(frontmatter);
+        }
+        frontmatter.push_str(&line);
+    }
+}
+
+/// Split a whole notes file into its frontmatter and the notes that follow it.
+pub fn split_frontmatter(content: &str) -> anyhow::Result<(&str, &str)> {
+    let mut lines = content.split_inclusive('\n');
+    let start = match lines.next() {
+        Some(first) if is_delimiter(first) => first.len(),
+        _ => anyhow::bail!("No content for file! Is there any frontmatter?"),
+    };
+
+    let mut end = start;
+    for line in lines {
+        if is_delimiter(line) {
+            return Ok((&content[start..end], &content[end + line.len()..]));
+        }
+        end += line.len();
+    }
+    anyhow::bail!("Frontmatter is missing its closing delimiter")
+}
+
+/// Parse the paper metadata out of some frontmatter.
+pub fn parse_meta(frontmatter: &str) -> anyhow::Result<PaperMeta> {
+    if frontmatter.trim().is_empty() {
+        anyhow::bail!("No content for file! Is there any frontmatter?")
+    }
+    Ok(serde_yaml::from_str(frontmatter)?)
+}
+
+/// Whether the frontmatter has the marker saying it is a paper, `papers: true`.
+pub fn has_marker(frontmatter: &str) -> bool {
+    serde_yaml::from_str::<serde_yaml::Mapping>(frontmatter)
+        .ok()
+        .and_then(|m| m.get(MARKER_KEY).and_then(|v| v.as_bool()))
+        .unwrap_or(false)
+}
+
+/// Write out the contents of a notes file, with the metadata as frontmatter.
+pub fn render(meta: &PaperMeta, notes: &str) -> anyhow::Result<String> {
+    let data_string = serde_yaml::to_string(meta)?;
+    Ok(format!("{DELIMITER}\n{data_string}{DELIMITER}\n{notes}"))
+}
+
+/// Write out the contents of a notes file like [`render`], marking it as a paper.
+pub fn render_marked(meta: &PaperMeta, notes: &str) -> anyhow::Result<String> {
+    let data_string = serde_yaml::to_string(meta)?;
+    Ok(format!(
+        "{DELIMITER}\n{MARKER_KEY}: true\n{data_string}{DELIMITER}\n{notes}"
+    ))
+}
+
+/// A top level entry of some frontmatter, kept as the text it was written with.
+#[derive(Debug)]
+struct Entry<'a> {
+    /// Key and value of the entry, `None` for comments before the first key.
+    parsed: Option<(String, Value)>,
+    /// The key line along with the lines of its value.
+    body: String,
+    /// Comments and blank lines following the value.
+    trailing: &'a str,
+}
+
+/// Whether a line carries on the value of the entry above it, rather than starting a new one.
+fn is_continuation(line: &str) -> bool {
+    line.starts_with([' ', '\t']) || line.starts_with("- ") || line.trim_end() == "-"
+}
+
+fn is_comment_or_blank(line: &str) -> bool {
+    line.trim().is_empty() || line.starts_with('#')
+}
+
+/// Split frontmatter into its top level entries.
+fn entries(frontmatter: &str) -> anyhow::Result<Vec<Entry<'_>>> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut start = 0;
+    for line in frontmatter.split_inclusive('\n') {
+        let end = start + line.len();
+        match entries.last_mut() {
+            Some(entry) if is_comment_or_blank(line) && !line.starts_with([' ', '\t']) => {
+                let from = end - line.len() - entry.trailing.len();
+                entry.trailing = &frontmatter[from..end];
+            }
+            Some(entry) if is_continuation(line) || is_comment_or_blank(line) => {
+                // comments within a value stay with it
+                entry.body.push_str(entry.trailing);
+                entry.body.push_str(line);
+                entry.trailing = "";
+            }
+            None if is_comment_or_blank(line) => entries.push(Entry {
+                parsed: None,
+                body: line.to_owned(),
+                trailing: "",
+            }),
+            _ => entries.push(Entry {
+                parsed: Some((String::new(), Value::Null)),
+                body: line.to_owned(),
+                trailing: "",
+            }),
+        }
+        start = end;
+    }
+    for entry in &mut entries {
+        if let Some(parsed) = &mut entry.parsed {
+            let mapping: Mapping = serde_yaml::from_str(&entry.body)?;
+            let mut pairs = mapping.into_iter();
+            match (pairs.next(), pairs.next()) {
+                (Some((Value::String(key), value)), None) => *parsed = (key, value),
+                _ => anyhow::bail!("Unexpected frontmatter entry {:?}", entry.body),
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Keys of the frontmatter that belong to [`PaperMeta`].
+fn meta_keys() -> BTreeSet<String> {
+    schemars::schema_for!(PaperMeta)
+        .schema
+        .object
+        .map(|o| o.properties.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// Write the metadata over some existing frontmatter, keeping the order of its keys, its comments
+/// and any keys that aren't part of the metadata.
+///
+/// Entries whose values are unchanged keep the text they were written with, new keys go at the
+/// end.
+pub fn merge(frontmatter: &str, meta: &PaperMeta) -> anyhow::Result<String> {
+    let old = entries(frontmatter)?;
+    let rendered = serde_yaml::to_string(meta)?;
+    let mut new = entries(&rendered)?;
+    let known = meta_keys();
+
+    let mut merged = String::new();
+    for entry in old {
+        match entry.parsed {
+            None => merged.push_str(&entry.body),
+            Some((key, value)) => {
+                if let Some(i) = new
+                    .iter()
+                    .position(|e| matches!(&e.parsed, Some((k, _)) if *k == key))
+                {
+                    let replacement = new.remove(i);
+                    match replacement.parsed {
+                        Some((_, v)) if v == value => merged.push_str(&entry.body),
+                        _ => merged.push_str(&replacement.body),
+                    }
+                } else if !known.contains(&key) {
+                    merged.push_str(&entry.body);
+                }
+                // fields of the metadata that are no longer set are dropped
+            }
+        }
+        if !merged.ends_with('\n') {
+            merged.push('\n');
+        }
+        merged.push_str(entry.trailing);
+    }
+    for entry in new {
+        merged.push_str(&entry.body);
+    }
+    if !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+    Ok(merged)
+}
+
+/// Write out the contents of a notes file like [`render`], over the frontmatter it had before
+/// using [`merge`].
+pub fn render_merged(frontmatter: &str, meta: &PaperMeta, notes: &str) -> anyhow::Result<String> {
+    let data_string = merge(frontmatter, meta)?;
+    Ok(format!("{DELIMITER}\n{data_string}{DELIMITER}\n{notes}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn check_split(content: &str, expected: Expect) {
+        let split = split_frontmatter(content).map_err(|e| e.to_string());
+        let streamed = read_frontmatter(content.as_bytes()).map_err(|e| e.to_string());
+        assert_eq!(
+            split
+                .as_ref()
+                .map(|(f, _)| f.to_string())
+                .map_err(Clone::clone),
+            streamed
+        );
+        expected.assert_debug_eq(&split);
+    }
+
+    #[test]
+    fn test_split() {
+        check_split(
+            "---\ntitle: A\n---\nsome notes\n---\nmore\n",
+            expect![[r#"
+                Ok(
+                    (
+                        "title: A\n",
+                        "some notes\n---\nmore\n",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_split_crlf() {
+        check_split(
+            "---\r\ntitle: A\r\n---\r\nnotes",
+            expect![[r#"
+                Ok(
+                    (
+                        "title: A\r\n",
+                        "notes",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_has_marker() {
+        assert!(has_marker("papers: true\ntitle: A\n"));
+        assert!(!has_marker("papers: false\ntitle: A\n"));
+        assert!(!has_marker("title: A\n"));
+        assert!(!has_marker("- not a mapping\n"));
+        let meta = PaperMeta::default();
+        let (frontmatter, _) = split_frontmatter(&render_marked(&meta, "").unwrap())
+            .map(|(f, n)| (f.to_owned(), n.to_owned()))
+            .unwrap();
+        assert!(has_marker(&frontmatter));
+        assert_eq!(parse_meta(&frontmatter).unwrap(), meta);
+    }
+
+    #[test]
+    fn test_split_no_frontmatter() {
+        check_split(
+            "# Just notes\n",
+            expect![[r#"
+                Err(
+                    "No content for file! Is there any frontmatter?",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_split_unterminated() {
+        check_split(
+            "---\ntitle: A\nnotes",
+            expect![[r#"
+                Err(
+                    "Frontmatter is missing its closing delimiter",
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_stream_stops_at_delimiter() {
+        // invalid utf-8 after the frontmatter would fail the read if we got that far
+        let mut content = b"---\ntitle: A\n---\n".to_vec();
+        content.extend_from_slice(&[0xff, 0xfe, b'\n']);
+        assert_eq!(read_frontmatter(content.as_slice()).unwrap(), "title: A\n");
+    }
+
+    fn check_merge(frontmatter: &str, update: impl FnOnce(&mut PaperMeta), expected: Expect) {
+        let mut meta = parse_meta(frontmatter).unwrap();
+        update(&mut meta);
+        let merged = merge(frontmatter, &meta).unwrap();
+        assert_eq!(parse_meta(&merged).unwrap(), meta);
+        expected.assert_eq(&merged);
+    }
+
+    #[test]
+    fn test_merge() {
+        let frontmatter = "\
+# reading group pick
+title: A Paper # the short title
+venue: SOSP
+tags:
+- db
+# more later
+labels: {}
+authors: []
+read_by: 2023-01-01
+created_at: 2023-01-01T00:00:00
+modified_at: 2023-01-01T00:00:00
+last_review: null
+next_review: null
+url: null
+filename: null
+";
+        check_merge(
+            frontmatter,
+            |meta| {
+                meta.tags.insert(crate::tag::Tag::new("os"));
+                meta.read_by = None;
+                meta.doc_type = crate::doc_type::DocType::Talk;
+            },
+            expect![[r#"
+                # reading group pick
+                title: A Paper # the short title
+                venue: SOSP
+                tags:
+                - db
+                - os
+                # more later
+                labels: {}
+                authors: []
+                created_at: 2023-01-01T00:00:00
+                modified_at: 2023-01-01T00:00:00
+                last_review: null
+                next_review: null
+                url: null
+                filename: null
+                doc_type: talk
+            "#]],
+        );
+        check_merge(
+            frontmatter,
+            |_| {},
+            expect![[r#"
+            # reading group pick
+            title: A Paper # the short title
+            venue: SOSP
+            tags:
+            - db
+            # more later
+            labels: {}
+            authors: []
+            read_by: 2023-01-01
+            created_at: 2023-01-01T00:00:00
+            modified_at: 2023-01-01T00:00:00
+            last_review: null
+            next_review: null
+            url: null
+            filename: null
+        "#]],
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_malformed_frontmatter(content in "(---\n)?([a-z: \\-\\[\\]{}'\"\n\r]|---\n){0,64}") {
+            let split = split_frontmatter(&content).map(|(f, _)| f.to_owned()).map_err(|e| e.to_string());
+            let streamed = read_frontmatter(content.as_bytes()).map_err(|e| e.to_string());
+            prop_assert_eq!(&split, &streamed);
+            if let Ok(frontmatter) = split {
+                let _ = parse_meta(&frontmatter);
+            }
+        }
+
+        #[test]
+        fn fuzz_roundtrip(title in "[^\r\n]{0,32}", notes in "(?s).{0,64}") {
+            let meta = PaperMeta { title, ..Default::default() };
+            let content = format!("---\n{}---\n{notes}", serde_yaml::to_string(&meta).unwrap());
+            let (frontmatter, body) = split_frontmatter(&content).unwrap();
+            prop_assert_eq!(body, notes.as_str());
+            prop_assert_eq!(parse_meta(frontmatter).unwrap(), meta);
+        }
+    }
+}
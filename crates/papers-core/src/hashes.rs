@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::paper::LoadedPaper;
+use crate::repo::Repo;
+
+/// Where the index of document hashes lives, relative to the root of the repo.
+pub const HASHES_PATH: &str = ".papers/hashes.json";
+
+/// Hash of a document, along with what is needed to tell if it has changed since.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct HashEntry {
+    hash: String,
+    size: u64,
+    modified: u64,
+}
+
+/// Hex encoded sha256 of the contents of a file.
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hex encoded sha256 of some bytes.
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Size and modification time of a file, in seconds since the epoch.
+fn stat(path: &Path) -> std::io::Result<(u64, u64)> {
+    let metadata = path.metadata()?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    Ok((metadata.len(), modified))
+}
+
+/// Index of the hashes of documents in the repo, so that duplicates can be found without reading
+/// every file each time.
+#[derive(Debug, Default)]
+struct HashIndex {
+    entries: BTreeMap<PathBuf, HashEntry>,
+}
+
+impl HashIndex {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let entries = match File::open(path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { entries })
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, &self.entries)?;
+        Ok(())
+    }
+
+    /// Hash of the document at `filename`, relative to `root`, only reading it if it changed.
+    fn hash(&mut self, root: &Path, filename: &Path) -> std::io::Result<String> {
+        let (size, modified) = stat(&root.join(filename))?;
+        if let Some(entry) = self.entries.get(filename) {
+            if entry.size == size && entry.modified == modified {
+                return Ok(entry.hash.clone());
+            }
+        }
+        debug!(?filename, "Hashing document");
+        let hash = hash_file(&root.join(filename))?;
+        self.entries.insert(
+            filename.to_owned(),
+            HashEntry {
+                hash: hash.clone(),
+                size,
+                modified,
+            },
+        );
+        Ok(hash)
+    }
+}
+
+impl Repo {
+    /// Papers with documents paired with their hashes, in order of path.
+    ///
+    /// The index of hashes is brought up to date with the documents of all papers on the way.
+    fn hashed_papers(&self) -> anyhow::Result<Vec<(String, LoadedPaper)>> {
+        let index_path = self.root().join(HASHES_PATH);
+        let mut index = HashIndex::load(&index_path)?;
+        let mut papers = self.all_papers()?;
+        papers.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut hashed = Vec::new();
+        for paper in papers {
+            let Some(filename) = &paper.meta.filename else {
+                continue;
+            };
+            match index.hash(self.root(), filename) {
+                Ok(hash) => hashed.push((hash, paper)),
+                Err(err) => debug!(?filename, %err, "Failed to hash document"),
+            }
+        }
+        // papers whose documents have gone don't need their hashes any more
+        let root = self.root();
+        index.entries.retain(|f, _| root.join(f).is_file());
+
+        if let Err(err) = index.save(&index_path) {
+            warn!(%err, "Failed to save the index of document hashes");
+        }
+        Ok(hashed)
+    }
+
+    /// Find a paper whose document has the same contents as `file`, other than `file` itself.
+    pub fn find_duplicate(&self, file: &Path) -> anyhow::Result<Option<LoadedPaper>> {
+        let hash = hash_file(file)?;
+        let own = self.in_root(file).ok();
+        Ok(self
+            .hashed_papers()?
+            .into_iter()
+            .filter(|(_, p)| p.meta.filename != own)
+            .find(|(h, _)| *h == hash)
+            .map(|(_, p)| p))
+    }
+
+    /// Groups of papers whose documents have the same contents, each of at least two papers in
+    /// order of path.
+    pub fn find_duplicates(&self) -> anyhow::Result<Vec<Vec<LoadedPaper>>> {
+        let mut by_hash = BTreeMap::<String, Vec<LoadedPaper>>::new();
+        for (hash, paper) in self.hashed_papers()? {
+            by_hash.entry(hash).or_default().push(paper);
+        }
+        let mut groups = by_hash
+            .into_values()
+            .filter(|g| g.len() > 1)
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| a[0].path.cmp(&b[0].path));
+        Ok(groups)
+    }
+
+    /// Record the hash of a document after adding it or changing its contents, returning it.
+    pub fn update_hash(&self, filename: &Path) -> anyhow::Result<String> {
+        let index_path = self.root().join(HASHES_PATH);
+        let mut index = HashIndex::load(&index_path)?;
+        let filename = self.relative(filename);
+        index.entries.remove(&filename);
+        let hash = index.hash(self.root(), &filename)?;
+        index.save(&index_path)?;
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crate::doc_type::DocType;
+    use crate::paper::{Provenance, Source};
+
+    use super::*;
+
+    fn add(repo: &mut Repo, title: &str, file: &Path) {
+        repo.add(
+            Some(file),
+            None,
+            title.to_owned(),
+            DocType::Paper,
+            Vec::new(),
+            BTreeSet::new(),
+            BTreeMap::new(),
+            None,
+            Provenance::now(Source::Add, None),
+            BTreeMap::new(),
+            "",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_find_duplicate() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repo::load(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.pdf"), "same").unwrap();
+        std::fs::write(dir.path().join("b.pdf"), "different").unwrap();
+        std::fs::write(dir.path().join("c.pdf"), "same").unwrap();
+        add(&mut repo, "a", &dir.path().join("a.pdf"));
+        add(&mut repo, "b", &dir.path().join("b.pdf"));
+
+        let duplicate = repo.find_duplicate(&dir.path().join("c.pdf")).unwrap();
+        assert_eq!(duplicate.map(|p| p.path), Some(PathBuf::from("a.md")));
+        // a file isn't a duplicate of itself
+        let duplicate = repo.find_duplicate(&dir.path().join("b.pdf")).unwrap();
+        assert_eq!(duplicate, None);
+        assert!(dir.path().join(HASHES_PATH).is_file());
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repo::load(dir.path()).unwrap();
+        for (name, contents) in [("a", "same"), ("b", "different"), ("c", "same")] {
+            let file = dir.path().join(format!("{name}.pdf"));
+            std::fs::write(&file, contents).unwrap();
+            add(&mut repo, name, &file);
+        }
+
+        let groups = repo.find_duplicates().unwrap();
+        let paths = groups
+            .iter()
+            .map(|g| g.iter().map(|p| p.path.clone()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![vec![PathBuf::from("a.md"), PathBuf::from("c.md")]]
+        );
+    }
+}
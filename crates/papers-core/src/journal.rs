@@ -0,0 +1,248 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::audit;
+use crate::paper::PaperMeta;
+use crate::repo::now_naive;
+
+/// Where the journal lives, relative to the root of the repo.
+pub const JOURNAL_PATH: &str = ".papers/journal.jsonl";
+
+/// Kind of change made to the repo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// A new paper was written.
+    Added,
+    /// The tags of a paper changed.
+    Retagged,
+    /// A paper was reviewed.
+    Reviewed,
+    /// A file was moved.
+    Renamed,
+    /// Any other change to a paper.
+    Updated,
+    /// A paper was moved to the trash.
+    Removed,
+    /// A paper was put back from the trash.
+    Restored,
+}
+
+impl Action {
+    /// Work out what kind of change writing `new` over `old` is.
+    pub fn classify(old: Option<&PaperMeta>, new: &PaperMeta) -> (Self, Option<String>) {
+        let Some(old) = old else {
+            return (Self::Added, None);
+        };
+        if old.tags != new.tags {
+            let added = new.tags.difference(&old.tags).map(|t| format!("+{t}"));
+            let removed = old.tags.difference(&new.tags).map(|t| format!("-{t}"));
+            let details = added.chain(removed).collect::<Vec<_>>().join(" ");
+            (Self::Retagged, Some(details))
+        } else if old.last_review != new.last_review {
+            (Self::Reviewed, None)
+        } else {
+            (Self::Updated, None)
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Added => "added",
+            Self::Retagged => "retagged",
+            Self::Reviewed => "reviewed",
+            Self::Renamed => "renamed",
+            Self::Updated => "updated",
+            Self::Removed => "removed",
+            Self::Restored => "restored",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A single change recorded in the journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// When the change was made.
+    pub at: NaiveDateTime,
+    /// Who made the change.
+    pub user: String,
+    /// What kind of change it was.
+    pub action: Action,
+    /// The file that changed, relative to the root of the repo.
+    pub path: PathBuf,
+    /// The related path, e.g. where a file was renamed to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub other: Option<PathBuf>,
+    /// Extra information about the change, e.g. which tags were added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl Entry {
+    pub fn new(action: Action, path: PathBuf, other: Option<PathBuf>) -> Self {
+        Self {
+            at: now_naive(),
+            user: audit::current_user(),
+            action,
+            path,
+            other,
+            details: None,
+        }
+    }
+}
+
+/// Append-only log of the changes made to a repo.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// The journal of the repo at `root`.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            path: root.join(JOURNAL_PATH),
+        }
+    }
+
+    /// Add an entry to the end of the journal.
+    pub fn append(&self, entry: &Entry) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        // a single write keeps concurrent appends from interleaving
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// All entries in the journal, oldest first.
+    pub fn entries(&self) -> anyhow::Result<Vec<Entry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => warn!(%err, line, "Skipping unreadable journal entry"),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// The entries about a single file, following it back through any renames.
+pub fn history<'a>(entries: &'a [Entry], path: &Path) -> Vec<&'a Entry> {
+    let mut names = BTreeSet::from([path.to_owned()]);
+    let mut history = Vec::new();
+    for entry in entries.iter().rev() {
+        let matches =
+            names.contains(&entry.path) || entry.other.as_ref().is_some_and(|o| names.contains(o));
+        if matches {
+            if entry.action == Action::Renamed {
+                names.insert(entry.path.clone());
+            }
+            history.push(entry);
+        }
+    }
+    history.reverse();
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::tag::Tag;
+
+    use super::*;
+
+    fn check_history(entries: &[(Action, &str, Option<&str>)], path: &str, expected: Expect) {
+        let entries = entries
+            .iter()
+            .map(|(action, path, other)| Entry::new(*action, path.into(), other.map(PathBuf::from)))
+            .collect::<Vec<_>>();
+        let history = history(&entries, Path::new(path))
+            .into_iter()
+            .map(|e| format!("{} {:?} {:?}", e.action, e.path, e.other))
+            .collect::<Vec<_>>();
+        expected.assert_debug_eq(&history);
+    }
+
+    #[test]
+    fn test_history_follows_renames() {
+        check_history(
+            &[
+                (Action::Added, "a.md", None),
+                (Action::Added, "b.md", None),
+                (Action::Renamed, "a.md", Some("A.md")),
+                (Action::Reviewed, "A.md", None),
+                (Action::Updated, "b.md", None),
+            ],
+            "A.md",
+            expect![[r#"
+                [
+                    "added \"a.md\" None",
+                    "renamed \"a.md\" Some(\"A.md\")",
+                    "reviewed \"A.md\" None",
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_classify_retag() {
+        let old = PaperMeta {
+            tags: BTreeSet::from([Tag::new("a"), Tag::new("b")]),
+            ..Default::default()
+        };
+        let new = PaperMeta {
+            tags: BTreeSet::from([Tag::new("b"), Tag::new("c")]),
+            ..Default::default()
+        };
+        expect![[r#"
+            (
+                Retagged,
+                Some(
+                    "+c -a",
+                ),
+            )
+        "#]]
+        .assert_debug_eq(&Action::classify(Some(&old), &new));
+        assert_eq!(Action::classify(None, &new).0, Action::Added);
+        assert_eq!(Action::classify(Some(&new), &new).0, Action::Updated);
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path());
+        assert!(journal.entries().unwrap().is_empty());
+        let entry = Entry::new(Action::Added, "a.md".into(), None);
+        journal.append(&entry).unwrap();
+        journal.append(&entry).unwrap();
+        assert_eq!(journal.entries().unwrap(), vec![entry.clone(), entry]);
+    }
+}
@@ -1,7 +1,22 @@
+pub mod audit;
 pub mod author;
+pub mod cancel;
+pub mod dir_defaults;
+pub mod doc_type;
+pub mod filename;
+pub mod fold;
+pub mod frontmatter;
+pub mod hashes;
+pub mod journal;
 pub mod label;
+pub mod lock;
+pub mod metadata;
 pub mod paper;
+pub mod papersignore;
 pub mod primitive;
 pub mod repo;
 pub mod review;
+pub mod snapshot;
 pub mod tag;
+pub mod tag_registry;
+pub mod trash;
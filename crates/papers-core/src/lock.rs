@@ -0,0 +1,140 @@
+use std::fmt::Display;
+use std::fs::{create_dir_all, read_to_string, remove_file, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, warn};
+
+/// Where locks on papers being edited are kept, relative to the root of the repo.
+pub const LOCKS_DIR: &str = ".papers/locks";
+
+/// The paper is locked by another process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locked {
+    /// The lock file, to remove by hand if its process is gone.
+    pub lock: PathBuf,
+    /// Id of the process holding the lock, if it could be read.
+    pub pid: Option<u32>,
+}
+
+impl Display for Locked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pid {
+            Some(pid) => write!(f, "Paper is being edited by another process (pid {pid})")?,
+            None => write!(f, "Paper is being edited by another process")?,
+        }
+        write!(f, ", remove {:?} if it isn't", self.lock)
+    }
+}
+
+impl std::error::Error for Locked {}
+
+/// A lock on a paper, so that only one process edits its notes at once.
+///
+/// The lock is released when dropped.
+#[derive(Debug)]
+pub struct PaperLock {
+    lock: PathBuf,
+}
+
+impl PaperLock {
+    /// Lock the paper at `path`, relative to `root`.
+    ///
+    /// Fails with [`Locked`] if another process holds the lock, unless that process is known to
+    /// have gone, in which case the lock is taken over.
+    pub fn acquire(root: &Path, path: &Path) -> anyhow::Result<Self> {
+        let lock = lock_path(root, path);
+        if let Some(parent) = lock.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut taken_over = false;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&lock) {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id())?;
+                    debug!(?lock, "Locked paper");
+                    return Ok(Self { lock });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    let pid = read_to_string(&lock)
+                        .ok()
+                        .and_then(|p| p.trim().parse().ok());
+                    if taken_over || !pid.is_some_and(is_gone) {
+                        return Err(Locked { lock, pid }.into());
+                    }
+                    warn!(?lock, ?pid, "Taking over lock of a process that has gone");
+                    remove_file(&lock)?;
+                    taken_over = true;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Lock the paper at `path` regardless of another process holding it.
+    pub fn force(root: &Path, path: &Path) -> anyhow::Result<Self> {
+        let lock = lock_path(root, path);
+        if let Some(parent) = lock.parent() {
+            create_dir_all(parent)?;
+        }
+        std::fs::write(&lock, std::process::id().to_string())?;
+        Ok(Self { lock })
+    }
+}
+
+impl Drop for PaperLock {
+    fn drop(&mut self) {
+        if let Err(err) = remove_file(&self.lock) {
+            debug!(lock = ?self.lock, %err, "Failed to remove lock");
+        }
+    }
+}
+
+fn lock_path(root: &Path, path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    root.join(LOCKS_DIR).join(name)
+}
+
+/// Whether the process is known to have exited, which can only be told where there is a
+/// `/proc`.
+fn is_gone(pid: u32) -> bool {
+    Path::new("/proc/self").exists() && !Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Path::new("nested/paper.md");
+        let lock = PaperLock::acquire(dir.path(), path).unwrap();
+        let err = PaperLock::acquire(dir.path(), path).unwrap_err();
+        let locked = err.downcast_ref::<Locked>().unwrap();
+        assert_eq!(locked.pid, Some(std::process::id()));
+        // other papers aren't affected
+        drop(PaperLock::acquire(dir.path(), Path::new("other.md")).unwrap());
+
+        drop(lock);
+        let lock = PaperLock::acquire(dir.path(), path).unwrap();
+        drop(lock);
+        assert!(!dir
+            .path()
+            .join(LOCKS_DIR)
+            .join("nested/paper.md.lock")
+            .exists());
+    }
+
+    #[test]
+    fn test_stale_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = dir.path().join(LOCKS_DIR).join("paper.md.lock");
+        create_dir_all(lock.parent().unwrap()).unwrap();
+        // larger than any pid linux hands out
+        std::fs::write(&lock, "4294967295").unwrap();
+        let taken = PaperLock::acquire(dir.path(), Path::new("paper.md"));
+        assert_eq!(taken.is_ok(), Path::new("/proc/self").exists());
+    }
+}
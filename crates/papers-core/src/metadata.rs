@@ -0,0 +1,309 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::author::Author;
+
+/// Base of the Crossref api for looking up works by their DOI.
+pub const CROSSREF_WORKS_URL: &str = "https://api.crossref.org/works/";
+
+/// A digital object identifier, e.g. `10.1145/3132747.3132784`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Doi(String);
+
+impl FromStr for Doi {
+    type Err = String;
+
+    /// Parse a bare DOI, or one given as a `doi:` or `https://doi.org/` link.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let lower = trimmed.to_lowercase();
+        let prefix = [
+            "https://doi.org/",
+            "http://doi.org/",
+            "https://dx.doi.org/",
+            "doi:",
+        ]
+        .into_iter()
+        .find(|p| lower.starts_with(p))
+        .map_or(0, str::len);
+        let doi = &trimmed[prefix..];
+        match doi.split_once('/') {
+            Some((registrant, suffix)) if registrant.starts_with("10.") && !suffix.is_empty() => {
+                Ok(Self(doi.to_owned()))
+            }
+            _ => Err(format!(
+                "Invalid DOI {s:?}, expected something like 10.1145/1234"
+            )),
+        }
+    }
+}
+
+impl Display for Doi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Doi {
+    /// Link that resolves to the publisher's page for the work.
+    pub fn url(&self) -> String {
+        format!("https://doi.org/{}", self.0)
+    }
+
+    /// Where Crossref describes the work.
+    pub fn crossref_url(&self) -> String {
+        format!("{CROSSREF_WORKS_URL}{}", self.0)
+    }
+}
+
+/// An international standard book number, either 10 or 13 digits long, e.g. `9781449373320`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Isbn(String);
+
+impl FromStr for Isbn {
+    type Err = String;
+
+    /// Parse an ISBN with or without hyphens and spaces, or an `isbn:` prefix, checking its check
+    /// digit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let isbn = trimmed
+            .get(..5)
+            .filter(|p| p.eq_ignore_ascii_case("isbn:"))
+            .map_or(trimmed, |_| &trimmed[5..])
+            .chars()
+            .filter(|c| !matches!(c, '-' | ' '))
+            .map(|c| c.to_ascii_uppercase())
+            .collect::<String>();
+        let digit = |c: char, i: usize| match c {
+            'X' if i == 9 => Some(10),
+            c => c.to_digit(10),
+        };
+        let digits = isbn
+            .chars()
+            .enumerate()
+            .map(|(i, c)| digit(c, i))
+            .collect::<Option<Vec<_>>>();
+        let valid = match digits.as_deref() {
+            Some(d) if d.len() == 10 => {
+                d.iter()
+                    .enumerate()
+                    .map(|(i, d)| (10 - i as u32) * d)
+                    .sum::<u32>()
+                    % 11
+                    == 0
+            }
+            Some(d) if d.len() == 13 && !d.contains(&10) => {
+                d.iter()
+                    .enumerate()
+                    .map(|(i, d)| if i % 2 == 0 { *d } else { 3 * d })
+                    .sum::<u32>()
+                    % 10
+                    == 0
+            }
+            _ => false,
+        };
+        if valid {
+            Ok(Self(isbn))
+        } else {
+            Err(format!(
+                "Invalid ISBN {s:?}, expected 10 or 13 digits like 978-1-4493-7332-0"
+            ))
+        }
+    }
+}
+
+impl Display for Isbn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Bibliographic metadata of a work.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WorkMetadata {
+    /// Title of the work.
+    pub title: Option<String>,
+    /// Authors of the work, in order.
+    pub authors: Vec<Author>,
+    /// Year the work was published.
+    pub year: Option<i32>,
+    /// Journal or proceedings the work was published in.
+    pub journal: Option<String>,
+}
+
+/// Response of the Crossref works api.
+#[derive(Debug, Deserialize)]
+pub struct CrossrefResponse {
+    /// The work that was looked up.
+    pub message: CrossrefWork,
+}
+
+/// A work as described by Crossref, keeping only the fields used.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CrossrefWork {
+    /// Titles of the work, the first being the main one.
+    pub title: Vec<String>,
+    /// Authors of the work.
+    pub author: Vec<CrossrefAuthor>,
+    /// Names of the journal or proceedings containing the work.
+    pub container_title: Vec<String>,
+    /// When the work was published, in whichever form came first.
+    pub issued: Option<CrossrefDate>,
+    /// When the work was published.
+    pub published: Option<CrossrefDate>,
+}
+
+/// An author of a work, either a person or an organisation.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CrossrefAuthor {
+    /// Given names of a person.
+    pub given: Option<String>,
+    /// Family name of a person.
+    pub family: Option<String>,
+    /// Name of an organisation.
+    pub name: Option<String>,
+}
+
+/// A partial date, as year, month and day.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CrossrefDate {
+    /// Parts of the date, only the first entry being used.
+    pub date_parts: Vec<Vec<Option<i32>>>,
+}
+
+impl CrossrefDate {
+    fn year(&self) -> Option<i32> {
+        *self.date_parts.first()?.first()?
+    }
+}
+
+impl CrossrefAuthor {
+    fn author(&self) -> Option<Author> {
+        let name = match (&self.given, &self.family, &self.name) {
+            (Some(given), Some(family), _) => format!("{} {}", given.trim(), family.trim()),
+            (None, Some(family), _) => family.trim().to_owned(),
+            (_, None, Some(name)) => name.trim().to_owned(),
+            _ => return None,
+        };
+        Some(Author::new(&name))
+    }
+}
+
+impl From<CrossrefWork> for WorkMetadata {
+    fn from(work: CrossrefWork) -> Self {
+        let first = |values: Vec<String>| {
+            values
+                .into_iter()
+                .map(|v| v.split_whitespace().collect::<Vec<_>>().join(" "))
+                .find(|v| !v.is_empty())
+        };
+        Self {
+            year: work
+                .issued
+                .as_ref()
+                .and_then(CrossrefDate::year)
+                .or_else(|| work.published.as_ref().and_then(CrossrefDate::year)),
+            authors: work
+                .author
+                .iter()
+                .filter_map(CrossrefAuthor::author)
+                .collect(),
+            title: first(work.title),
+            journal: first(work.container_title),
+        }
+    }
+}
+
+/// Parse the metadata of a work from a Crossref works api response.
+pub fn parse_crossref(body: &str) -> anyhow::Result<WorkMetadata> {
+    let res: CrossrefResponse = serde_json::from_str(body)?;
+    Ok(res.message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_doi() {
+        let parse = |s: &str| Doi::from_str(s).map(|d| d.to_string());
+        assert_eq!(parse("10.1145/3132747"), Ok("10.1145/3132747".to_owned()));
+        assert_eq!(
+            parse(" https://doi.org/10.1145/3132747 "),
+            Ok("10.1145/3132747".to_owned())
+        );
+        assert_eq!(parse("DOI:10.5555/a/b"), Ok("10.5555/a/b".to_owned()));
+        assert!(parse("3132747").is_err());
+        assert!(parse("10.1145/").is_err());
+    }
+
+    #[test]
+    fn test_parse_isbn() {
+        let parse = |s: &str| Isbn::from_str(s).map(|i| i.to_string());
+        assert_eq!(parse("9781449373320"), Ok("9781449373320".to_owned()));
+        assert_eq!(
+            parse(" ISBN: 978-1-4493-7332-0 "),
+            Ok("9781449373320".to_owned())
+        );
+        assert_eq!(parse("0-262-03384-4"), Ok("0262033844".to_owned()));
+        assert_eq!(parse("080442957x"), Ok("080442957X".to_owned()));
+        assert!(parse("9781449373321").is_err());
+        assert!(parse("0262033845").is_err());
+        assert!(parse("97814493733").is_err());
+        assert!(parse("isbn").is_err());
+    }
+
+    #[test]
+    fn test_parse_crossref() {
+        let body = r#"{
+            "status": "ok",
+            "message": {
+                "DOI": "10.5555/raft",
+                "title": ["In Search of an Understandable\n   Consensus Algorithm"],
+                "author": [
+                    {"given": "Diego", "family": "Ongaro", "sequence": "first"},
+                    {"given": "John", "family": "Ousterhout"},
+                    {"name": "USENIX"}
+                ],
+                "container-title": ["", "USENIX ATC"],
+                "published": {"date-parts": [[2014, 6]]},
+                "issued": {"date-parts": [[2014, 6, 19]]}
+            }
+        }"#;
+        expect![[r#"
+            WorkMetadata {
+                title: Some(
+                    "In Search of an Understandable Consensus Algorithm",
+                ),
+                authors: [
+                    Author {
+                        author: "Diego Ongaro",
+                    },
+                    Author {
+                        author: "John Ousterhout",
+                    },
+                    Author {
+                        author: "USENIX",
+                    },
+                ],
+                year: Some(
+                    2014,
+                ),
+                journal: Some(
+                    "USENIX ATC",
+                ),
+            }
+        "#]]
+        .assert_debug_eq(&parse_crossref(body).unwrap());
+
+        let empty = parse_crossref(r#"{"message": {"issued": {"date-parts": [[null]]}}}"#);
+        assert_eq!(empty.unwrap(), WorkMetadata::default());
+    }
+}
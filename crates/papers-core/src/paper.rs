@@ -1,9 +1,15 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
+    fmt::Display,
     path::PathBuf,
+    str::FromStr,
 };
 
-use crate::{author::Author, primitive::Primitive, tag::Tag};
+use crate::{
+    author::Author, doc_type::DocType, primitive::Primitive, repo::now_naive, review::ReviewRating,
+    tag::Tag,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -13,16 +19,113 @@ pub struct LoadedPaper {
     pub notes: String,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+/// Metadata of a paper, kept as the frontmatter of its notes file.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PaperMeta {
+    /// Title of the paper, which names its notes file.
     pub title: String,
+    /// Kind of document.
+    #[serde(default, skip_serializing_if = "DocType::is_paper")]
+    pub doc_type: DocType,
+    /// Where the paper came from.
     pub url: Option<String>,
+    /// Path of the document, relative to the root of the repo.
     pub filename: Option<PathBuf>,
+    /// Hex encoded SHA-256 of the contents of the document, to tell when the same document is
+    /// added twice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
     pub tags: BTreeSet<Tag>,
+    /// Extra values, such as a doi or the year published.
     pub labels: BTreeMap<String, Primitive>,
     pub authors: Vec<Author>,
     pub created_at: chrono::NaiveDateTime,
     pub modified_at: chrono::NaiveDateTime,
     pub last_review: Option<chrono::NaiveDateTime>,
     pub next_review: Option<chrono::NaiveDateTime>,
+    /// Never bring the paper up for review, e.g. for reference manuals.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_review: bool,
+    /// Ratings given at past reviews, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ratings: Vec<ReviewRating>,
+    /// Date to read the paper by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_by: Option<chrono::NaiveDate>,
+    /// How the paper entered the repo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Any other fields to keep with the paper, which papers passes through untouched.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(with = "BTreeMap<String, serde_json::Value>")]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl PaperMeta {
+    /// Value of the label with the given key, falling back to an extra field with a plain value.
+    pub fn field(&self, key: &str) -> Option<Primitive> {
+        if let Some(value) = self.labels.get(key) {
+            return Some(value.clone());
+        }
+        let value = self.extra.get(key)?;
+        serde_yaml::from_value(value.clone()).ok()
+    }
+}
+
+/// How a paper entered the repo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Provenance {
+    /// What added the paper.
+    pub source: Source,
+    /// More about the source, such as the file the paper was imported from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// When the paper was added.
+    pub at: chrono::NaiveDateTime,
+}
+
+impl Provenance {
+    /// Provenance of a paper being added now.
+    pub fn now(source: Source, detail: Option<String>) -> Self {
+        Self {
+            source,
+            detail,
+            at: now_naive(),
+        }
+    }
+}
+
+/// What added a paper to the repo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Added with `papers add`.
+    Add,
+    /// Imported from a list of papers with `papers import`.
+    Import,
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "add" => Ok(Self::Add),
+            "import" => Ok(Self::Import),
+            _ => Err(format!("Unknown source {:?}", s)),
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Add => "add",
+                Self::Import => "import",
+            }
+        )
+    }
 }
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use tracing::warn;
+
+use crate::dir_defaults::DIR_DEFAULTS_FILE;
+use crate::tag_registry::TAG_REGISTRY_FILE;
+
+/// File in the root of the repo listing files that aren't papers, in gitignore syntax.
+pub const IGNORE_FILE: &str = ".papersignore";
+
+/// Patterns for files in the repo that shouldn't be treated as papers, such as templates, drafts
+/// and markdown written by other tools.
+#[derive(Debug)]
+pub struct IgnorePatterns(Gitignore);
+
+impl IgnorePatterns {
+    /// Load the patterns from the ignore file in `root`, ignoring nothing if there isn't one.
+    pub fn load(root: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        let path = root.join(IGNORE_FILE);
+        if path.is_file() {
+            if let Some(err) = builder.add(&path) {
+                warn!(%err, ?path, "Failed to read some ignore patterns");
+            }
+        }
+        // the ignore file, tag registry and directory defaults aren't papers or documents either
+        for file in [IGNORE_FILE, TAG_REGISTRY_FILE, DIR_DEFAULTS_FILE] {
+            if let Err(err) = builder.add_line(None, file) {
+                warn!(%err, file, "Failed to ignore a file of the repo");
+            }
+        }
+        match builder.build() {
+            Ok(gitignore) => Self(gitignore),
+            Err(err) => {
+                warn!(%err, ?path, "Failed to load ignore patterns");
+                Self(Gitignore::empty())
+            }
+        }
+    }
+
+    /// Whether the file at `path`, in or relative to the root, is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        // matching panics on paths outside the root, which can't be ignored anyway
+        if path.is_absolute() && !path.starts_with(self.0.path()) {
+            return false;
+        }
+        self.0
+            .matched_path_or_any_parents(path, path.is_dir())
+            .is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(IGNORE_FILE),
+            "# comments are skipped\ntemplate*.md\ndrafts/\n!template-keep.md\n",
+        )
+        .unwrap();
+        let patterns = IgnorePatterns::load(dir.path());
+        let ignored = |p: &str| patterns.is_ignored(&dir.path().join(p));
+        assert!(ignored("template.md"));
+        assert!(ignored("template-notes.md"));
+        assert!(!ignored("template-keep.md"));
+        assert!(ignored("drafts/a.md"));
+        assert!(!ignored("paper.md"));
+        assert!(ignored(IGNORE_FILE));
+        assert!(ignored(TAG_REGISTRY_FILE));
+        assert!(ignored(&format!("teaching/{DIR_DEFAULTS_FILE}")));
+
+        let patterns = IgnorePatterns::load(&dir.path().join("missing"));
+        assert!(!patterns.is_ignored(Path::new("template.md")));
+        assert!(!patterns.is_ignored(&dir.path().join("template.md")));
+    }
+}
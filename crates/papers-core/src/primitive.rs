@@ -1,13 +1,14 @@
 use std::{fmt::Display, str::FromStr};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Primitive {
     Null,
     Bool(bool),
-    Number(serde_yaml::value::Number),
+    Number(#[schemars(with = "f64")] serde_yaml::value::Number),
     String(String),
 }
 
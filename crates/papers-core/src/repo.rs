@@ -1,14 +1,26 @@
-use gray_matter::{engine::YAML, Matter};
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs::{canonicalize, read_dir, File};
-use std::io::{Read, Write};
-use std::path::{Path, PathBuf};
+use std::fs::{read_dir, rename, File};
+use std::io::{BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::Context;
+use regex::Regex;
+use tracing::warn;
 
+use crate::audit;
 use crate::author::Author;
+use crate::cancel::CancellationToken;
+use crate::doc_type::DocType;
+use crate::filename::sanitize_stem;
+use crate::fold::fold;
+use crate::frontmatter::{
+    has_marker, parse_meta, read_frontmatter, render, render_marked, render_merged,
+    split_frontmatter,
+};
+use crate::journal::{Action, Entry, Journal};
 use crate::label::Label;
-use crate::paper::{LoadedPaper, PaperMeta};
+use crate::paper::{LoadedPaper, PaperMeta, Provenance};
+use crate::papersignore::IgnorePatterns;
 use crate::primitive::Primitive;
 use crate::tag::Tag;
 
@@ -21,8 +33,60 @@ pub(crate) fn now_naive() -> chrono::NaiveDateTime {
     chrono::NaiveDateTime::from_timestamp_opt(millis, 0).unwrap()
 }
 
+/// Absolute form of a path with `.` and `..` removed without following symlinks, like `cd` does.
+fn logical(path: &Path) -> std::io::Result<PathBuf> {
+    let mut logical = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                logical.pop();
+            }
+            c => logical.push(c),
+        }
+    }
+    Ok(logical)
+}
+
 pub struct Repo {
+    /// Root as it was given, which may go through symlinks.
     root: PathBuf,
+    /// Root with all symlinks resolved.
+    canonical_root: PathBuf,
+    ignore: IgnorePatterns,
+    require_marker: bool,
+    fold_filters: bool,
+    cancel: CancellationToken,
+    journal: Journal,
+}
+
+/// Regular expressions that papers must match to be listed, compiled once up front.
+#[derive(Debug, Clone, Default)]
+pub struct RegexFilters {
+    /// Matches somewhere in the title.
+    pub title: Option<Regex>,
+    /// Matches somewhere in the document filename, papers without documents never match.
+    pub file: Option<Regex>,
+    /// Matches somewhere in the name of at least one author.
+    pub author: Option<Regex>,
+}
+
+impl RegexFilters {
+    /// Whether a paper matches all of the expressions.
+    pub fn matches(&self, meta: &PaperMeta) -> bool {
+        self.title
+            .as_ref()
+            .is_none_or(|re| re.is_match(&meta.title))
+            && self.file.as_ref().is_none_or(|re| {
+                meta.filename
+                    .as_ref()
+                    .is_some_and(|f| re.is_match(&f.to_string_lossy()))
+            })
+            && self
+                .author
+                .as_ref()
+                .is_none_or(|re| meta.authors.iter().any(|a| re.is_match(&a.to_string())))
+    }
 }
 
 impl Repo {
@@ -31,34 +95,91 @@ impl Repo {
     }
 
     pub fn load(root: &Path) -> anyhow::Result<Self> {
+        // avoid verbatim `\\?\` paths on windows, other programs often can't open them
+        let canonical_root = dunce::canonicalize(root)?;
+        // keep the root as given so symlinked repos (e.g. synced folders) still match the paths
+        // users give
+        let root = logical(root)?;
         Ok(Self {
-            root: canonicalize(root)?,
+            journal: Journal::new(&root),
+            ignore: IgnorePatterns::load(&root),
+            require_marker: false,
+            fold_filters: true,
+            root,
+            canonical_root,
+            cancel: CancellationToken::default(),
         })
     }
 
+    /// Use the given token to stop long running operations early.
+    pub fn set_cancellation(&mut self, cancel: CancellationToken) {
+        self.cancel = cancel;
+    }
+
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Only treat notes files with `papers: true` in their frontmatter as papers, marking the
+    /// ones that are written, so the repo can share its root with other notes.
+    pub fn set_require_marker(&mut self, require_marker: bool) {
+        self.require_marker = require_marker;
+    }
+
+    /// Whether filters on tags, labels and authors in [`Repo::list`] ignore case and accents, on
+    /// by default.
+    pub fn set_fold_filters(&mut self, fold_filters: bool) {
+        self.fold_filters = fold_filters;
+    }
+
+    /// Whether the file at `path` is the notes of a paper, rather than another file or notes
+    /// that should be left alone.
+    pub fn is_paper(&self, path: &Path) -> bool {
+        let path = self.resolve(path);
+        if path.extension().and_then(|e| e.to_str()) != Some("md") || self.is_ignored(&path) {
+            return false;
+        }
+        !self.require_marker
+            || File::open(&path)
+                .ok()
+                .and_then(|f| read_frontmatter(BufReader::new(f)).ok())
+                .is_some_and(|f| has_marker(&f))
+    }
+
+    fn check_marker(&self, frontmatter: &str) -> anyhow::Result<()> {
+        if self.require_marker && !has_marker(frontmatter) {
+            anyhow::bail!("Not a paper, the frontmatter has no `papers: true` marker");
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn add<P: AsRef<Path>>(
         &mut self,
         file: Option<P>,
         url: Option<String>,
         title: String,
+        doc_type: DocType,
         authors: Vec<Author>,
         tags: BTreeSet<Tag>,
         labels: BTreeMap<String, Primitive>,
+        read_by: Option<chrono::NaiveDate>,
+        provenance: Provenance,
+        extra: BTreeMap<String, serde_yaml::Value>,
+        notes: &str,
     ) -> anyhow::Result<PaperMeta> {
         let filename = if let Some(file) = file {
-            let file = file.as_ref();
-            let file = canonicalize(file).context("canonicalising the filename")?;
-            let file = file
-                .strip_prefix(&self.root)
-                .context("File does not live in the root")?;
-            Some(file.to_owned())
+            Some(self.in_root(file.as_ref())?)
         } else {
             None
         };
+        let sha256 = filename.as_ref().map(|f| self.update_hash(f)).transpose()?;
         let paper = PaperMeta {
             title,
+            doc_type,
             url,
             filename,
+            sha256,
             tags,
             labels,
             authors,
@@ -66,6 +187,11 @@ impl Repo {
             modified_at: now_naive(),
             last_review: None,
             next_review: None,
+            no_review: false,
+            ratings: Vec::new(),
+            read_by,
+            provenance: Some(provenance),
+            extra,
         };
 
         let paper_path = self.get_path(&paper);
@@ -74,7 +200,7 @@ impl Repo {
         if paper_path.is_file() {
             anyhow::bail!("Paper entry already exists for {:?}", paper_path);
         }
-        self.write_paper(&paper_path, paper.clone(), "")?;
+        self.write_paper(&paper_path, paper.clone(), notes)?;
 
         Ok(paper)
     }
@@ -91,34 +217,94 @@ impl Repo {
         notes: &str,
     ) -> anyhow::Result<()> {
         paper.modified_at = now_naive();
-        let data_string = serde_yaml::to_string(&paper)?;
 
         let path = self.root.join(path);
-        let mut file = File::create(path)?;
-        write!(file, "---\n{data_string}---\n{notes}")?;
+        if path == self.root {
+            anyhow::bail!("Paper has no title to name its notes file");
+        }
+        // what was there before, to record what kind of change this is and to keep anything
+        // written by hand in the frontmatter
+        let old_frontmatter = File::open(&path)
+            .ok()
+            .and_then(|f| read_frontmatter(BufReader::new(f)).ok());
+        let old = old_frontmatter.as_deref().and_then(|f| parse_meta(f).ok());
+
+        let contents = match old_frontmatter.as_deref().filter(|_| old.is_some()) {
+            Some(frontmatter) if !self.require_marker || has_marker(frontmatter) => {
+                match render_merged(frontmatter, &paper, notes) {
+                    Ok(contents) => contents,
+                    Err(err) => {
+                        warn!(?path, %err, "Failed to keep the existing frontmatter, rewriting it");
+                        render(&paper, notes)?
+                    }
+                }
+            }
+            _ if self.require_marker => render_marked(&paper, notes)?,
+            _ => render(&paper, notes)?,
+        };
+
+        // write to a temporary file first so an interruption can't leave a partial paper behind
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        rename(&tmp_path, &path)?;
+
+        let (action, details) = Action::classify(old.as_ref(), &paper);
+        let mut entry = Entry::new(action, self.relative(&path), None);
+        entry.details = details;
+        self.record(entry);
+        Ok(())
+    }
+
+    /// Move a file in the repo, recording it in the journal.
+    pub fn rename(&self, from: &Path, to: &Path) -> anyhow::Result<()> {
+        let from = self.resolve(from);
+        let to = self.resolve(to);
+        rename(&from, &to)?;
+        self.record(Entry::new(
+            Action::Renamed,
+            self.relative(&from),
+            Some(self.relative(&to)),
+        ));
         Ok(())
     }
 
+    /// The journal of changes made to this repo.
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    pub(crate) fn record(&self, entry: Entry) {
+        audit::record(
+            &entry.action.to_string(),
+            &entry.path,
+            entry.other.as_deref(),
+        );
+        // the change has already happened so failing to note it shouldn't fail the operation
+        if let Err(err) = self.journal.append(&entry) {
+            warn!(%err, "Failed to write to the journal");
+        }
+    }
+
     pub fn update(&self, paper: &LoadedPaper, file: Option<&Path>) -> anyhow::Result<()> {
         let filename = if let Some(file) = file {
-            if !canonicalize(file)
-                .with_context(|| format!("Canoncalizing file path {:?}", file))?
-                .parent()
-                .unwrap()
-                .starts_with(&self.root)
-            {
-                anyhow::bail!("File doesn't live in the root {:?}", self.root)
-            }
-
-            Some(file.file_name().unwrap_or_default().into())
+            Some(
+                self.in_root(file)
+                    .with_context(|| format!("File doesn't live in the root {:?}", self.root))?,
+            )
         } else {
             None
         };
 
+        let sha256 = file.map(|f| self.update_hash(f)).transpose()?;
         let mut paper = self
             .get_paper(&paper.path)
             .with_context(|| format!("Opening paper notes at {:?}", paper.path))?;
         paper.meta.filename = filename;
+        paper.meta.sha256 = sha256;
 
         self.write_paper(&paper.path, paper.meta, &paper.notes)
             .with_context(|| format!("Writing paper {:?}", paper.path))?;
@@ -133,11 +319,14 @@ impl Repo {
         match_authors: Vec<Author>,
         match_tags: Vec<Tag>,
         match_labels: Vec<Label>,
+        regexes: &RegexFilters,
     ) -> anyhow::Result<Vec<LoadedPaper>> {
-        let papers = self.all_papers();
+        let papers = self.all_papers()?;
         let mut filtered_papers = Vec::new();
         let match_title = match_title.map(|t| t.to_lowercase());
         let match_file = match_file.map(|t| t.to_lowercase());
+        let fold_filters = self.fold_filters;
+        let same = |a: &str, b: &str| a == b || (fold_filters && fold(a) == fold(b));
         for paper in papers {
             if let Some(match_file) = match_file.as_ref() {
                 if let Some(filename) = paper.meta.filename.as_ref() {
@@ -156,23 +345,34 @@ impl Repo {
                 }
             }
 
+            if !regexes.matches(&paper.meta) {
+                continue;
+            }
+
             // filter papers down
-            if !match_authors.iter().all(|a| paper.meta.authors.contains(a)) {
+            if !match_authors.iter().all(|a| {
+                paper
+                    .meta
+                    .authors
+                    .iter()
+                    .any(|b| same(&a.to_string(), &b.to_string()))
+            }) {
                 continue;
             }
 
             // filter papers down
-            if !match_tags.iter().all(|t| paper.meta.tags.contains(t)) {
+            if !match_tags
+                .iter()
+                .all(|t| paper.meta.tags.iter().any(|u| same(t.key(), u.key())))
+            {
                 continue;
             }
 
             // filter papers down
             if !match_labels.iter().all(|l| {
-                paper
-                    .meta
-                    .labels
-                    .get(l.key())
-                    .map_or(false, |v| v == l.value())
+                paper.meta.field(l.key()).is_some_and(|v| {
+                    v == *l.value() || same(&v.to_string(), &l.value().to_string())
+                })
             }) {
                 continue;
             }
@@ -183,50 +383,212 @@ impl Repo {
     }
 
     pub fn get_path(&self, paper: &PaperMeta) -> PathBuf {
-        let title = paper.title.replace(PROHIBITED_PATH_CHARS, "");
-        PathBuf::from(&title).with_extension("md")
-    }
-
-    pub fn all_papers(&self) -> Vec<LoadedPaper> {
-        let mut papers = Vec::new();
-        let entries = read_dir(&self.root);
-        if let Ok(entries) = entries {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                        if let Ok(paper) = self.get_paper(&path) {
-                            papers.push(paper);
-                        }
-                    }
+        PathBuf::from(sanitize_stem(&paper.title)).with_extension("md")
+    }
+
+    /// Paths of all the paper notes files in the repo, without loading them.
+    pub fn paper_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Ok(entries) = read_dir(&self.root) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("md")
+                    && !self.is_ignored(&path)
+                {
+                    paths.push(path);
                 }
             }
         }
-        papers
+        paths
+    }
+
+    /// Lazily load the metadata of the papers at the given paths, skipping any that fail to parse
+    /// and stopping early if cancelled.
+    pub fn load_papers(&self, paths: Vec<PathBuf>) -> impl Iterator<Item = LoadedPaper> + '_ {
+        paths
+            .into_iter()
+            .take_while(|_| !self.cancel.is_cancelled())
+            .filter_map(|path| self.get_paper_meta(&path).ok())
     }
 
+    /// Load the metadata of all papers in the repo, their notes are left empty.
+    ///
+    /// Fails if cancelled part way, rather than giving only some of the papers.
+    pub fn all_papers(&self) -> anyhow::Result<Vec<LoadedPaper>> {
+        let papers = self.load_papers(self.paper_paths()).collect();
+        self.cancel.check()?;
+        Ok(papers)
+    }
+
+    /// Load a paper along with its notes.
     pub fn get_paper(&self, path: &Path) -> anyhow::Result<LoadedPaper> {
+        let path = self.resolve(path);
         let mut file_content = String::new();
-        let path = if path.is_absolute() {
+        File::open(&path)?.read_to_string(&mut file_content)?;
+        let (frontmatter, notes) = split_frontmatter(&file_content)?;
+        self.check_marker(frontmatter)?;
+        Ok(LoadedPaper {
+            meta: parse_meta(frontmatter)?,
+            notes: notes.to_owned(),
+            path: self.relative(&path),
+        })
+    }
+
+    /// Load just the metadata of a paper, without reading its notes.
+    pub fn get_paper_meta(&self, path: &Path) -> anyhow::Result<LoadedPaper> {
+        let path = self.resolve(path);
+        let frontmatter = read_frontmatter(BufReader::new(File::open(&path)?))?;
+        self.check_marker(&frontmatter)?;
+        Ok(LoadedPaper {
+            meta: parse_meta(&frontmatter)?,
+            notes: String::new(),
+            path: self.relative(&path),
+        })
+    }
+
+    /// Whether a file is ignored by the patterns in the repo's ignore file.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.is_ignored(&self.resolve(path))
+    }
+
+    /// The path relative to the root, if it is within it.
+    pub fn relative(&self, path: &Path) -> PathBuf {
+        if path.is_relative() {
+            return path.to_owned();
+        }
+        self.strip_root(path).unwrap_or_else(|| path.to_owned())
+    }
+
+    /// Path of an existing file relative to the root, failing if it doesn't live in the root.
+    pub fn in_root(&self, file: &Path) -> anyhow::Result<PathBuf> {
+        dunce::canonicalize(file).context("canonicalising the filename")?;
+        self.strip_root(file)
+            .context("File does not live in the root")
+    }
+
+    /// Strip the root from a path, which may reach the root as it was given, e.g. through a
+    /// symlinked directory of attachments, or with all symlinks resolved.
+    fn strip_root(&self, path: &Path) -> Option<PathBuf> {
+        if let Some(relative) = logical(path)
+            .ok()
+            .and_then(|p| p.strip_prefix(&self.root).ok().map(Path::to_owned))
+        {
+            return Some(relative);
+        }
+        let canonical = dunce::canonicalize(path).ok()?;
+        let relative = canonical.strip_prefix(&self.canonical_root).ok()?;
+        Some(relative.to_owned())
+    }
+
+    pub(crate) fn resolve(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() {
             path.to_owned()
         } else {
             self.root.join(path)
-        };
-        let mut file = File::open(&path)?;
-        file.read_to_string(&mut file_content)?;
-        let matter = Matter::<YAML>::new();
-        let file_content = matter.parse(&file_content);
-        if let Some(data) = file_content.data {
-            let paper = data.deserialize::<PaperMeta>()?;
-            let path = path.strip_prefix(&self.root).unwrap().to_owned();
-            let notes = file_content.content;
-            Ok(LoadedPaper {
-                path,
-                meta: paper,
-                notes,
-            })
-        } else {
-            anyhow::bail!("No content for file! Is there any frontmatter?")
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use crate::hashes::hash_bytes;
+    use crate::paper::Source;
+
+    use super::*;
+
+    fn add(repo: &mut Repo, title: &str, file: &Path) -> anyhow::Result<PaperMeta> {
+        repo.add(
+            Some(file),
+            None,
+            title.to_owned(),
+            DocType::Paper,
+            Vec::new(),
+            BTreeSet::new(),
+            BTreeMap::new(),
+            None,
+            Provenance::now(Source::Add, None),
+            BTreeMap::new(),
+            "",
+        )
+    }
+
+    #[test]
+    fn test_symlinked_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        let link = dir.path().join("link");
+        symlink(&real, &link).unwrap();
+        std::fs::write(real.join("a.pdf"), "a").unwrap();
+        std::fs::write(real.join("b.pdf"), "b").unwrap();
+
+        let mut repo = Repo::load(&link).unwrap();
+        assert_eq!(repo.root(), link);
+        let paper = add(&mut repo, "a", &link.join("a.pdf")).unwrap();
+        assert_eq!(paper.filename, Some(PathBuf::from("a.pdf")));
+        // the resolved path is still in the root
+        let paper = add(&mut repo, "b", &real.join("b.pdf")).unwrap();
+        assert_eq!(paper.filename, Some(PathBuf::from("b.pdf")));
+        assert_eq!(
+            repo.relative(&link.join("gone.md")),
+            PathBuf::from("gone.md")
+        );
+    }
+
+    #[test]
+    fn test_symlinked_attachments() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let attachments = dir.path().join("attachments");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::create_dir(&attachments).unwrap();
+        symlink(&attachments, root.join("attachments")).unwrap();
+        std::fs::write(attachments.join("a.pdf"), "a").unwrap();
+        std::fs::write(dir.path().join("outside.pdf"), "outside").unwrap();
+
+        let mut repo = Repo::load(&root).unwrap();
+        let paper = add(
+            &mut repo,
+            "a",
+            &root.join("attachments/../attachments/a.pdf"),
+        )
+        .unwrap();
+        assert_eq!(paper.filename, Some(PathBuf::from("attachments/a.pdf")));
+        let err = add(&mut repo, "outside", &dir.path().join("outside.pdf")).unwrap_err();
+        assert_eq!(err.to_string(), "File does not live in the root");
+    }
+
+    #[test]
+    fn test_all_papers_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.pdf"), "a").unwrap();
+        let mut repo = Repo::load(dir.path()).unwrap();
+        add(&mut repo, "a", &dir.path().join("a.pdf")).unwrap();
+        assert_eq!(repo.all_papers().unwrap().len(), 1);
+
+        // a cancelled load isn't mistaken for a repo with fewer papers
+        let cancel = CancellationToken::new();
+        repo.set_cancellation(cancel.clone());
+        cancel.cancel();
+        assert!(repo.all_papers().is_err());
+    }
+
+    #[test]
+    fn test_update_nested() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("a.pdf"), "a").unwrap();
+        std::fs::write(dir.path().join("nested/b.pdf"), "b").unwrap();
+
+        let mut repo = Repo::load(dir.path()).unwrap();
+        add(&mut repo, "a", &dir.path().join("a.pdf")).unwrap();
+        let paper = repo.get_paper(Path::new("a.md")).unwrap();
+        repo.update(&paper, Some(&dir.path().join("nested/b.pdf")))
+            .unwrap();
+        let paper = repo.get_paper(Path::new("a.md")).unwrap();
+        assert_eq!(paper.meta.filename, Some(PathBuf::from("nested/b.pdf")));
+        assert_eq!(paper.meta.sha256, Some(hash_bytes(b"b")));
+    }
+}
@@ -1,30 +1,171 @@
+use std::{fmt::Display, str::FromStr};
+
 use chrono::{Days, NaiveDateTime};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use crate::{paper::PaperMeta, repo::now_naive};
 
-const REVIEW_POWER: f64 = 2.0;
+/// How much longer than the last interval the next one is after a hard review.
+const HARD_FACTOR: f64 = 1.2;
+
+/// How much longer than usual the next interval is after an easy review.
+const EASY_BONUS: f64 = 1.3;
+
+/// How well a paper was remembered when reviewing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    /// Forgotten, so start the intervals over.
+    Again,
+    /// Remembered with difficulty, so grow the interval slowly.
+    Hard,
+    /// Remembered, so grow the interval as usual.
+    Good,
+    /// Remembered easily, so grow the interval more than usual.
+    Easy,
+}
+
+impl Rating {
+    /// Prompt listing the keys for each rating.
+    pub const PROMPT: &'static str = "Rate it [a]gain, [h]ard, [g]ood, [e]asy, enter to skip";
+
+    /// The rating for a key, `None` for a key with no rating.
+    pub fn from_key(key: Option<char>) -> Option<Self> {
+        match key.map(|k| k.to_ascii_lowercase()) {
+            Some('a') => Some(Self::Again),
+            Some('h') => Some(Self::Hard),
+            Some('g') => Some(Self::Good),
+            Some('e') => Some(Self::Easy),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Again => "again",
+            Self::Hard => "hard",
+            Self::Good => "good",
+            Self::Easy => "easy",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for Rating {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "again" => Ok(Self::Again),
+            "hard" => Ok(Self::Hard),
+            "good" => Ok(Self::Good),
+            "easy" => Ok(Self::Easy),
+            _ => Err(format!(
+                "Unknown rating {s:?}, expected one of again, hard, good or easy"
+            )),
+        }
+    }
+}
+
+/// A rating given at a review, kept so the history can be looked back over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewRating {
+    /// When the review happened.
+    pub at: NaiveDateTime,
+    /// How well the paper was remembered.
+    pub rating: Rating,
+}
+
+/// How reviews are spaced out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewConfig {
+    /// Power the interval between reviews is raised to for the next one.
+    #[serde(default = "default_power")]
+    pub power: f64,
+
+    /// Days until the first review of a paper.
+    #[serde(default = "default_initial_interval_days")]
+    pub initial_interval_days: u64,
+
+    /// Most days between reviews, unlimited if not set.
+    #[serde(default)]
+    pub max_interval_days: Option<u64>,
+}
+
+fn default_power() -> f64 {
+    2.0
+}
+
+fn default_initial_interval_days() -> u64 {
+    1
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            power: default_power(),
+            initial_interval_days: default_initial_interval_days(),
+            max_interval_days: None,
+        }
+    }
+}
+
+impl ReviewConfig {
+    /// Days to wait for the next review, after waiting `last_interval` days for this one and
+    /// rating it, a review without a rating counting as good.
+    fn next_interval(&self, last_interval: Option<i64>, rating: Option<Rating>) -> u64 {
+        let initial = self.initial_interval_days.max(1);
+        let grown = match last_interval {
+            None => initial,
+            Some(days) if days > 1 => {
+                let grown = (days as f64).powf(self.power).floor() as u64;
+                // always growing, even with powers that barely change short intervals
+                grown.max(days as u64 + 1)
+            }
+            Some(_) => initial + 1,
+        };
+        let wait_days = match (rating, last_interval) {
+            (None | Some(Rating::Good), _) => grown,
+            (Some(Rating::Again), _) | (Some(Rating::Hard), None) => initial,
+            (Some(Rating::Hard), Some(days)) => {
+                let days = days.max(1) as u64;
+                ((days as f64 * HARD_FACTOR).floor() as u64)
+                    .max(days + 1)
+                    .min(grown)
+            }
+            (Some(Rating::Easy), _) => (grown as f64 * EASY_BONUS).ceil() as u64,
+        };
+        self.max_interval_days
+            .map_or(wait_days, |max| wait_days.min(max.max(1)))
+    }
+}
 
 impl PaperMeta {
-    fn calculate_next_review_date(&self) -> NaiveDateTime {
+    fn calculate_next_review_date(
+        &self,
+        config: &ReviewConfig,
+        rating: Option<Rating>,
+    ) -> NaiveDateTime {
         let now = now_naive();
-        let wait_days = match (self.last_review, self.next_review) {
-            (None, None) => 1,
-            (None, Some(_next)) => 1,
-            (Some(_last), None) => 1,
-            (Some(last), Some(next)) => {
-                let days_since_last = (next - last).num_days();
-                if days_since_last > 1 {
-                    (days_since_last as f64).powf(REVIEW_POWER).floor() as u64
-                } else {
-                    2
-                }
-            }
+        let last_interval = match (self.last_review, self.next_review) {
+            (Some(last), Some(next)) => Some((next - last).num_days()),
+            _ => None,
         };
-        now + Days::new(wait_days)
+        now + Days::new(config.next_interval(last_interval, rating))
     }
 
-    pub fn update_review(&mut self) {
-        let next_review_date = self.calculate_next_review_date();
+    /// Record a review done now, scheduling the next one sooner or later depending on the rating.
+    pub fn update_review(&mut self, config: &ReviewConfig, rating: Option<Rating>) {
+        let next_review_date = self.calculate_next_review_date(config, rating);
+        if let Some(rating) = rating {
+            self.ratings.push(ReviewRating {
+                at: now_naive(),
+                rating,
+            });
+        }
         self.last_review = if self.next_review.is_none() {
             Some(next_review_date)
         } else {
@@ -36,6 +177,76 @@ impl PaperMeta {
     pub fn is_reviewable(&self) -> bool {
         let now = now_naive();
         // reviewable if next review date is in the past
-        self.next_review.map_or(true, |r| r < now)
+        !self.no_review && self.next_review.is_none_or(|r| r < now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Intervals of successive reviews, each done on the day it was due and rated in turn.
+    fn rated_intervals(config: &ReviewConfig, ratings: &[Option<Rating>]) -> Vec<u64> {
+        let mut last = None;
+        let mut intervals = Vec::new();
+        for rating in ratings {
+            let interval = config.next_interval(last, *rating);
+            intervals.push(interval);
+            // the first review records no gap, as update_review does
+            last = Some(if last.is_none() { 0 } else { interval as i64 });
+        }
+        intervals
+    }
+
+    /// Intervals of successive unrated reviews.
+    fn intervals(config: &ReviewConfig, reviews: usize) -> Vec<u64> {
+        rated_intervals(config, &vec![None; reviews])
+    }
+
+    #[test]
+    fn test_next_interval() {
+        assert_eq!(
+            intervals(&ReviewConfig::default(), 5),
+            vec![1, 2, 4, 16, 256]
+        );
+        let gentle = ReviewConfig {
+            power: 1.5,
+            initial_interval_days: 3,
+            max_interval_days: Some(30),
+        };
+        assert_eq!(intervals(&gentle, 6), vec![3, 4, 8, 22, 30, 30]);
+    }
+
+    #[test]
+    fn test_rated_interval() {
+        use Rating::*;
+        let config = ReviewConfig::default();
+        let good = rated_intervals(&config, &[Some(Good); 5]);
+        assert_eq!(good, intervals(&config, 5));
+        assert_eq!(
+            rated_intervals(
+                &config,
+                &[None, Some(Good), Some(Good), Some(Again), Some(Good)]
+            ),
+            vec![1, 2, 4, 1, 2]
+        );
+        assert_eq!(
+            rated_intervals(&config, &[Some(Hard); 6]),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(
+            rated_intervals(&config, &[Some(Easy); 4]),
+            vec![2, 3, 12, 188]
+        );
+        let capped = ReviewConfig {
+            max_interval_days: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            rated_intervals(&capped, &[Some(Easy); 4]),
+            vec![2, 3, 12, 30]
+        );
+        assert_eq!("Hard".parse(), Ok(Hard));
+        assert!("meh".parse::<Rating>().is_err());
     }
 }
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read_dir, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::filename::sanitize_stem;
+use crate::paper::PaperMeta;
+use crate::repo::{now_naive, Repo};
+
+/// Where snapshots live, relative to the root of the repo.
+pub const SNAPSHOTS_DIR: &str = ".papers/snapshots";
+
+/// The metadata of every paper in the repo at a point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Name to restore the snapshot by.
+    pub name: String,
+    /// When the snapshot was taken.
+    pub created_at: NaiveDateTime,
+    /// Metadata of each paper, by the path of its notes.
+    pub papers: BTreeMap<PathBuf, PaperMeta>,
+}
+
+/// What restoring a snapshot did to a paper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Restored {
+    /// The metadata was put back.
+    Reverted,
+    /// The notes file was missing so was written again, without notes.
+    Recreated,
+}
+
+/// Whether two sets of metadata match, ignoring when they were last written.
+fn same_meta(a: &PaperMeta, b: &PaperMeta) -> bool {
+    PaperMeta {
+        modified_at: b.modified_at,
+        ..a.clone()
+    } == *b
+}
+
+impl Repo {
+    fn snapshots_dir(&self) -> PathBuf {
+        self.root().join(SNAPSHOTS_DIR)
+    }
+
+    /// Save the metadata of all papers under the given name, or the current time if not given.
+    pub fn create_snapshot(&self, name: Option<String>) -> anyhow::Result<Snapshot> {
+        let created_at = now_naive();
+        let name = name.unwrap_or_else(|| created_at.format("%Y-%m-%dT%H-%M-%S").to_string());
+        if name.is_empty() || name.starts_with('.') || sanitize_stem(&name) != name {
+            anyhow::bail!("Invalid snapshot name {name:?}, it must be usable as a filename");
+        }
+        let path = self.snapshots_dir().join(&name).with_extension("json");
+        if path.exists() {
+            anyhow::bail!("Snapshot {name:?} already exists");
+        }
+
+        let papers = self
+            .all_papers()?
+            .into_iter()
+            .map(|p| (p.path, p.meta))
+            .collect();
+        self.cancellation().check()?;
+        let snapshot = Snapshot {
+            name,
+            created_at,
+            papers,
+        };
+        create_dir_all(self.snapshots_dir())?;
+        serde_json::to_writer(BufWriter::new(File::create(path)?), &snapshot)?;
+        Ok(snapshot)
+    }
+
+    /// All saved snapshots, oldest first.
+    pub fn snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
+        let mut snapshots = Vec::new();
+        let Ok(entries) = read_dir(self.snapshots_dir()) else {
+            return Ok(snapshots);
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                let snapshot: Snapshot =
+                    serde_json::from_reader(BufReader::new(File::open(&path)?))
+                        .map_err(|err| anyhow::anyhow!("Reading snapshot {path:?}: {err}"))?;
+                snapshots.push(snapshot);
+            }
+        }
+        snapshots.sort_by(|a, b| (a.created_at, &a.name).cmp(&(b.created_at, &b.name)));
+        Ok(snapshots)
+    }
+
+    /// Load a single snapshot by name.
+    pub fn get_snapshot(&self, name: &str) -> anyhow::Result<Snapshot> {
+        self.snapshots()?
+            .into_iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| anyhow::anyhow!("No snapshot called {name:?}"))
+    }
+
+    /// Put the metadata of papers back to how it was in the snapshot, keeping their notes.
+    ///
+    /// Papers added since the snapshot are left alone. Returns the papers that were changed.
+    pub fn restore_snapshot(&self, name: &str) -> anyhow::Result<Vec<(PathBuf, Restored)>> {
+        let snapshot = self.get_snapshot(name)?;
+        let mut restored = Vec::new();
+        for (path, meta) in snapshot.papers {
+            self.cancellation().check()?;
+            match self.get_paper(&path) {
+                Ok(current) if same_meta(&current.meta, &meta) => {}
+                Ok(current) => {
+                    self.write_paper(&path, meta, &current.notes)?;
+                    restored.push((path, Restored::Reverted));
+                }
+                // a notes file that can't be read still has notes in it, so only recreate missing ones
+                Err(err) if self.resolve(&path).exists() => {
+                    return Err(err.context(format!("Restoring {path:?}")));
+                }
+                Err(_) => {
+                    self.write_paper(&path, meta, "")?;
+                    restored.push((path, Restored::Recreated));
+                }
+            }
+        }
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        doc_type::DocType,
+        paper::{Provenance, Source},
+        tag::Tag,
+    };
+
+    use super::*;
+
+    fn add(repo: &mut Repo, title: &str, tag: &str) {
+        repo.add::<PathBuf>(
+            None,
+            None,
+            title.to_owned(),
+            DocType::Paper,
+            Vec::new(),
+            BTreeSet::from([Tag::new(tag)]),
+            BTreeMap::new(),
+            None,
+            Provenance::now(Source::Add, None),
+            BTreeMap::new(),
+            "my notes",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repo::load(dir.path()).unwrap();
+        add(&mut repo, "first", "a");
+        add(&mut repo, "second", "a");
+        repo.create_snapshot(Some("before".to_owned())).unwrap();
+        assert!(repo.create_snapshot(Some("before".to_owned())).is_err());
+
+        let mut first = repo.get_paper(&PathBuf::from("first.md")).unwrap();
+        first.meta.tags = BTreeSet::from([Tag::new("b")]);
+        repo.write_paper(&first.path, first.meta, &first.notes)
+            .unwrap();
+        std::fs::remove_file(dir.path().join("second.md")).unwrap();
+        add(&mut repo, "third", "a");
+
+        let restored = repo.restore_snapshot("before").unwrap();
+        assert_eq!(
+            restored,
+            vec![
+                ("first.md".into(), Restored::Reverted),
+                ("second.md".into(), Restored::Recreated),
+            ]
+        );
+        let first = repo.get_paper(&PathBuf::from("first.md")).unwrap();
+        assert_eq!(first.meta.tags, BTreeSet::from([Tag::new("a")]));
+        assert_eq!(first.notes, "my notes");
+        assert!(dir.path().join("third.md").is_file());
+        assert!(repo.restore_snapshot("before").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_corrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repo::load(dir.path()).unwrap();
+        add(&mut repo, "first", "a");
+        repo.create_snapshot(Some("before".to_owned())).unwrap();
+
+        let corrupted = "---\ntitle: first\nunterminated notes\n";
+        std::fs::write(dir.path().join("first.md"), corrupted).unwrap();
+        assert!(repo.restore_snapshot("before").is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("first.md")).unwrap(),
+            corrupted
+        );
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repo::load(dir.path()).unwrap();
+        assert!(repo.create_snapshot(Some("../escape".to_owned())).is_err());
+        assert!(repo.create_snapshot(Some(".hidden".to_owned())).is_err());
+        assert_eq!(repo.snapshots().unwrap(), Vec::new());
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::tag::Tag;
+
+/// File in the root of the repo describing the tags that are meant to be used.
+pub const TAG_REGISTRY_FILE: &str = ".paperstags.yaml";
+
+/// What a tag in the registry is for.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagInfo {
+    /// What the tag means.
+    pub description: Option<String>,
+    /// When to put the tag on a paper.
+    pub usage: Option<String>,
+}
+
+/// The tags registered for use in the repo, keyed by tag.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TagRegistry(pub BTreeMap<String, TagInfo>);
+
+impl TagRegistry {
+    /// Load the registry from `root`, empty if there isn't one.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let path = root.join(TAG_REGISTRY_FILE);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        // an empty file is an empty registry rather than an error
+        let registry: Option<Self> = serde_yaml::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to read tag registry {path:?}"))?;
+        Ok(registry.unwrap_or_default())
+    }
+
+    /// What the tag is for, if it is registered.
+    pub fn get(&self, tag: &Tag) -> Option<&TagInfo> {
+        self.0.get(tag.key())
+    }
+
+    /// Whether the tag is registered.
+    pub fn contains(&self, tag: &Tag) -> bool {
+        self.0.contains_key(tag.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            TagRegistry::load(dir.path()).unwrap(),
+            TagRegistry::default()
+        );
+
+        std::fs::write(
+            dir.path().join(TAG_REGISTRY_FILE),
+            "to-read:\n  description: Not read yet\n  usage: Added to new papers\ndb: {}\n",
+        )
+        .unwrap();
+        let registry = TagRegistry::load(dir.path()).unwrap();
+        assert!(registry.contains(&Tag::new("db")));
+        assert!(!registry.contains(&Tag::new("ml")));
+        assert_eq!(
+            registry.get(&Tag::new("to-read")),
+            Some(&TagInfo {
+                description: Some("Not read yet".to_owned()),
+                usage: Some("Added to new papers".to_owned()),
+            })
+        );
+
+        std::fs::write(dir.path().join(TAG_REGISTRY_FILE), "db: [oops]\n").unwrap();
+        assert!(TagRegistry::load(dir.path()).is_err());
+    }
+}
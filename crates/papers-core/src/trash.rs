@@ -0,0 +1,257 @@
+use std::fs::{create_dir_all, read_dir, remove_dir_all, rename, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::filename::sanitize_stem;
+use crate::journal::{Action, Entry};
+use crate::repo::{now_naive, Repo};
+
+/// Where removed papers are kept until restored, relative to the root of the repo.
+pub const TRASH_DIR: &str = ".papers-trash";
+
+/// File in each trashed paper's directory saying where its files came from.
+const MANIFEST_FILE: &str = "trashed.json";
+
+/// Directory in each trashed paper's directory holding its files, by their paths in the repo.
+const FILES_DIR: &str = "files";
+
+/// A paper that was moved to the trash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trashed {
+    /// Name to restore the paper by.
+    pub name: String,
+    /// When the paper was removed.
+    pub trashed_at: NaiveDateTime,
+    /// Where the notes were, relative to the root.
+    pub path: PathBuf,
+    /// Where the document was, relative to the root, if it was removed too.
+    pub file: Option<PathBuf>,
+}
+
+/// Where a file from `original`, relative to the root, is kept in the trash directory `dir`.
+///
+/// The whole path is kept so that the notes and document can't be trashed over each other.
+fn in_trash(dir: &Path, original: &Path) -> PathBuf {
+    dir.join(FILES_DIR).join(original)
+}
+
+/// Move `from` to `to`, making the directories leading to `to`.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        create_dir_all(parent)?;
+    }
+    rename(from, to)
+}
+
+impl Repo {
+    fn trash_dir(&self) -> PathBuf {
+        self.root().join(TRASH_DIR)
+    }
+
+    /// Move the paper at `path` to the trash, along with its document if `with_file`.
+    pub fn trash(&self, path: &Path, with_file: bool) -> anyhow::Result<Trashed> {
+        let paper = self.get_paper_meta(path)?;
+        let trashed_at = now_naive();
+        let stem = paper.path.file_stem().unwrap_or_default().to_string_lossy();
+        let base = format!(
+            "{}-{}",
+            trashed_at.format("%Y-%m-%dT%H-%M-%S"),
+            sanitize_stem(&stem)
+        );
+        let mut name = base.clone();
+        let mut n = 1;
+        while self.trash_dir().join(&name).exists() {
+            n += 1;
+            name = format!("{base}-{n}");
+        }
+        let file = paper
+            .meta
+            .filename
+            .filter(|f| with_file && self.root().join(f).is_file());
+        let trashed = Trashed {
+            name,
+            trashed_at,
+            path: paper.path,
+            file,
+        };
+
+        let dir = self.trash_dir().join(&trashed.name);
+        create_dir_all(&dir)?;
+        // written first so that whatever gets moved can be found again
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(dir.join(MANIFEST_FILE))?),
+            &trashed,
+        )?;
+        if let Some(file) = &trashed.file {
+            move_file(&self.root().join(file), &in_trash(&dir, file))
+                .with_context(|| format!("Moving {file:?} to the trash"))?;
+        }
+        move_file(
+            &self.root().join(&trashed.path),
+            &in_trash(&dir, &trashed.path),
+        )
+        .with_context(|| format!("Moving {:?} to the trash", trashed.path))?;
+
+        self.record(Entry::new(
+            Action::Removed,
+            trashed.path.clone(),
+            Some(self.relative(&dir)),
+        ));
+        Ok(trashed)
+    }
+
+    /// All papers in the trash, oldest first.
+    pub fn trashed(&self) -> anyhow::Result<Vec<Trashed>> {
+        let mut trashed = Vec::new();
+        let Ok(entries) = read_dir(self.trash_dir()) else {
+            return Ok(trashed);
+        };
+        for entry in entries {
+            let manifest = entry?.path().join(MANIFEST_FILE);
+            if manifest.is_file() {
+                let paper: Trashed =
+                    serde_json::from_reader(BufReader::new(File::open(&manifest)?))
+                        .with_context(|| format!("Reading trashed paper {manifest:?}"))?;
+                trashed.push(paper);
+            }
+        }
+        trashed.sort_by(|a, b| (a.trashed_at, &a.name).cmp(&(b.trashed_at, &b.name)));
+        Ok(trashed)
+    }
+
+    /// Move a paper in the trash back to where it was, failing rather than overwriting anything.
+    pub fn restore_trashed(&self, name: &str) -> anyhow::Result<Trashed> {
+        let trashed = self
+            .trashed()?
+            .into_iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| anyhow::anyhow!("No paper in the trash called {name:?}"))?;
+        let dir = self.trash_dir().join(&trashed.name);
+        let moves = std::iter::once(&trashed.path)
+            .chain(&trashed.file)
+            .map(|original| (in_trash(&dir, original), self.root().join(original)))
+            .collect::<Vec<_>>();
+        for (from, to) in &moves {
+            if to.exists() {
+                anyhow::bail!("Not restoring {name:?} as {to:?} already exists");
+            }
+            if !from.exists() {
+                anyhow::bail!("Trashed file {from:?} is missing");
+            }
+        }
+        for (from, to) in &moves {
+            move_file(from, to)?;
+        }
+        remove_dir_all(&dir)?;
+
+        self.record(Entry::new(
+            Action::Restored,
+            trashed.path.clone(),
+            Some(self.relative(&dir)),
+        ));
+        Ok(trashed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crate::{
+        doc_type::DocType,
+        paper::{Provenance, Source},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut repo = Repo::load(root).unwrap();
+        std::fs::write(root.join("raft.pdf"), "pdf").unwrap();
+        repo.add(
+            Some(root.join("raft.pdf")),
+            None,
+            "raft".to_owned(),
+            DocType::Paper,
+            Vec::new(),
+            BTreeSet::new(),
+            BTreeMap::new(),
+            None,
+            Provenance::now(Source::Add, None),
+            BTreeMap::new(),
+            "my notes",
+        )
+        .unwrap();
+
+        let trashed = repo.trash(Path::new("raft.md"), true).unwrap();
+        assert_eq!(trashed.file, Some(PathBuf::from("raft.pdf")));
+        assert!(!root.join("raft.md").exists());
+        assert!(!root.join("raft.pdf").exists());
+        assert!(repo.all_papers().unwrap().is_empty());
+        assert_eq!(repo.trashed().unwrap(), vec![trashed.clone()]);
+
+        // something new in the way stops the restore without losing anything
+        std::fs::write(root.join("raft.pdf"), "other").unwrap();
+        assert!(repo.restore_trashed(&trashed.name).is_err());
+        std::fs::remove_file(root.join("raft.pdf")).unwrap();
+
+        assert_eq!(repo.restore_trashed(&trashed.name).unwrap(), trashed);
+        assert_eq!(
+            repo.get_paper(Path::new("raft.md")).unwrap().notes,
+            "my notes"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("raft.pdf")).unwrap(),
+            "pdf"
+        );
+        assert!(repo.trashed().unwrap().is_empty());
+        assert!(repo.restore_trashed(&trashed.name).is_err());
+
+        // the document stays unless asked for
+        let trashed = repo.trash(Path::new("raft.md"), false).unwrap();
+        assert_eq!(trashed.file, None);
+        assert!(root.join("raft.pdf").exists());
+    }
+
+    #[test]
+    fn test_trash_same_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut repo = Repo::load(root).unwrap();
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        // a document with the same name as the notes, in another directory
+        std::fs::write(root.join("notes/raft.md"), "document").unwrap();
+        repo.add(
+            Some(root.join("notes/raft.md")),
+            None,
+            "raft".to_owned(),
+            DocType::Paper,
+            Vec::new(),
+            BTreeSet::new(),
+            BTreeMap::new(),
+            None,
+            Provenance::now(Source::Add, None),
+            BTreeMap::new(),
+            "my notes",
+        )
+        .unwrap();
+
+        let trashed = repo.trash(Path::new("raft.md"), true).unwrap();
+        assert_eq!(trashed.file, Some(PathBuf::from("notes/raft.md")));
+        repo.restore_trashed(&trashed.name).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("notes/raft.md")).unwrap(),
+            "document"
+        );
+        assert_eq!(
+            repo.get_paper(Path::new("raft.md")).unwrap().notes,
+            "my notes"
+        );
+    }
+}